@@ -0,0 +1,62 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Sibling temp path for `path`, unique per process and call so concurrent
+/// writers never share one.
+fn tmp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let n = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{name}.{}.{n}.tmp", std::process::id()))
+}
+
+/// Replace `path` with `contents` so readers see the old file or the new
+/// one, never a partial write.
+///
+/// Writes and fsyncs a temp file in the same directory, then renames it
+/// over `path` (atomic on one filesystem). The temp file is removed if any
+/// step fails.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let tmp = tmp_path(path);
+    let result = File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(contents.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_file_and_leaves_no_temp() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("swarm-ui-meta.json");
+        write_atomic(&path, r#"{"schema_version":1}"#).unwrap();
+        // A crash mid-write only ever leaves a stray temp file behind
+        let stray = tmp_path(&path);
+        fs::write(&stray, r#"{"schema_ver"#).unwrap();
+
+        write_atomic(&path, r#"{"schema_version":2}"#).unwrap();
+
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["schema_version"], 2);
+        let mut names: Vec<PathBuf> =
+            fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().path()).collect();
+        names.sort();
+        assert_eq!(names, [stray, path]);
+
+        let missing = dir.path().join("no-such-dir").join("meta.json");
+        assert!(write_atomic(&missing, "{}").is_err());
+    }
+}
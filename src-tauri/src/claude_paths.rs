@@ -0,0 +1,121 @@
+use serde::Deserialize;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+/// Env var Claude itself honours for its config directory.
+pub const CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+/// The subset of the app config this module reads.
+#[derive(Debug, Default, Deserialize)]
+struct AppConfig {
+    #[serde(default)]
+    claude_config_dir: Option<PathBuf>,
+}
+
+fn app_config_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("swarm-ui").join("config.json"))
+}
+
+/// `claude_config_dir` from the app config; a missing or malformed file
+/// means no override.
+fn app_config_override() -> Option<PathBuf> {
+    let content = fs::read_to_string(app_config_path()?).ok()?;
+    match serde_json::from_str::<AppConfig>(&content) {
+        Ok(config) => config.claude_config_dir,
+        Err(e) => {
+            tracing::warn!("Ignoring malformed app config: {e}");
+            None
+        }
+    }
+}
+
+/// Pick the Claude directory from its possible sources; empty values are
+/// treated as unset.
+fn resolve_claude_dir(
+    app_override: Option<PathBuf>,
+    env_dir: Option<OsString>,
+    home: Option<PathBuf>,
+) -> Option<PathBuf> {
+    app_override
+        .filter(|p| !p.as_os_str().is_empty())
+        .or_else(|| env_dir.filter(|d| !d.is_empty()).map(PathBuf::from))
+        .or_else(|| home.map(|h| h.join(".claude")))
+}
+
+/// Where Claude keeps its state, normally `~/.claude`.
+///
+/// Overridden by `claude_config_dir` in the app config
+/// (`~/.local/share/swarm-ui/config.json`), then by `CLAUDE_CONFIG_DIR`.
+/// Paths on sprites are unaffected and stay the remote `~/.claude`.
+pub fn claude_dir() -> Option<PathBuf> {
+    resolve_claude_dir(
+        app_config_override(),
+        std::env::var_os(CONFIG_DIR_ENV),
+        dirs::home_dir(),
+    )
+}
+
+/// The app config override, which a spawned `claude` only sees if it's
+/// passed on as `CLAUDE_CONFIG_DIR` (the env var is inherited anyway).
+pub fn child_config_dir() -> Option<PathBuf> {
+    app_config_override().filter(|p| !p.as_os_str().is_empty())
+}
+
+/// Session JSONL files, one subdirectory per project.
+pub fn projects_dir() -> Option<PathBuf> {
+    claude_dir().map(|d| d.join("projects"))
+}
+
+/// Team configs: `teams/{name}/config.json`.
+pub fn teams_dir() -> Option<PathBuf> {
+    claude_dir().map(|d| d.join("teams"))
+}
+
+/// Team task lists: `tasks/{team}/{id}.json`.
+pub fn tasks_dir() -> Option<PathBuf> {
+    claude_dir().map(|d| d.join("tasks"))
+}
+
+/// Agent definitions: `agents/{name}.md`.
+pub fn agents_dir() -> Option<PathBuf> {
+    claude_dir().map(|d| d.join("agents"))
+}
+
+/// OAuth credentials copied to sprites during provisioning.
+pub fn credentials_path() -> Option<PathBuf> {
+    claude_dir().map(|d| d.join(".credentials.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_override_changes_projects_path() {
+        let home = Some(PathBuf::from("/home/u"));
+        let default = resolve_claude_dir(None, None, home.clone()).unwrap();
+        assert_eq!(default.join("projects"), PathBuf::from("/home/u/.claude/projects"));
+
+        let env = Some(OsString::from("/work/claude-profile-b"));
+        let from_env = resolve_claude_dir(None, env.clone(), home.clone()).unwrap();
+        assert_eq!(from_env.join("projects"), PathBuf::from("/work/claude-profile-b/projects"));
+
+        // The app config wins over the env var; empty values are ignored
+        let app = Some(PathBuf::from("/srv/claude"));
+        assert_eq!(resolve_claude_dir(app, env, home.clone()), Some(PathBuf::from("/srv/claude")));
+        assert_eq!(
+            resolve_claude_dir(Some(PathBuf::new()), Some(OsString::new()), home),
+            Some(default)
+        );
+    }
+
+    #[test]
+    fn test_app_config_parses_override() {
+        let config: AppConfig =
+            serde_json::from_str(r#"{"claude_config_dir": "/srv/claude", "other": 1}"#).unwrap();
+        assert_eq!(config.claude_config_dir, Some(PathBuf::from("/srv/claude")));
+        let config: AppConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.claude_config_dir, None);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::atomic_file::write_atomic;
+use crate::claude_paths::{agents_dir, claude_dir};
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -27,8 +29,7 @@ pub async fn list_agents() -> Result<Vec<AgentDef>, AppError> {
         description: Some("Default Claude Code session".to_string()),
     }];
 
-    let home = dirs::home_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
-    let agents_dir = home.join(".claude").join("agents");
+    let agents_dir = agents_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
 
     if agents_dir.exists() {
         for entry in fs::read_dir(&agents_dir)? {
@@ -115,8 +116,9 @@ pub async fn save_smith_override(
     enabled: bool,
     instructions: String,
 ) -> Result<(), AppError> {
-    let home = dirs::home_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
-    let dir = home.join(".claude").join("smith-overrides");
+    let dir = claude_dir()
+        .ok_or_else(|| AppError::Internal("No home dir".into()))?
+        .join("smith-overrides");
     fs::create_dir_all(&dir)?;
 
     let path = dir.join(format!("{}.json", session_id));
@@ -126,7 +128,7 @@ pub async fn save_smith_override(
     };
     let json =
         serde_json::to_string_pretty(&data).map_err(|e| AppError::Internal(e.to_string()))?;
-    fs::write(&path, json)?;
+    write_atomic(&path, json)?;
 
     Ok(())
 }
@@ -134,9 +136,8 @@ pub async fn save_smith_override(
 /// Load Smith override configuration for a session
 #[tauri::command]
 pub async fn load_smith_override(session_id: String) -> Result<SmithOverride, AppError> {
-    let home = dirs::home_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
-    let path = home
-        .join(".claude")
+    let path = claude_dir()
+        .ok_or_else(|| AppError::Internal("No home dir".into()))?
         .join("smith-overrides")
         .join(format!("{}.json", session_id));
 
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use tauri::State;
+
+use crate::error::AppError;
+use crate::state::{lock_recover, IndexHandle};
+
+/// Write a diagnostics zip (index stats, meta, sizes, background health and
+/// recent logs; no session content) to `dest_zip`. Returns the entry names.
+#[tauri::command]
+pub async fn generate_diagnostics(
+    dest_zip: String,
+    index: State<'_, IndexHandle>,
+) -> Result<Vec<String>, AppError> {
+    let index_path = crate::index_path()
+        .ok_or_else(|| AppError::NotFound("index directory".into()))?;
+    let reader = index.reader.clone();
+    let schema = index.schema.clone();
+    let health = index.health.report();
+    let watcher_mode = *lock_recover(&index.watcher_mode, "watcher mode");
+    let dest = PathBuf::from(dest_zip);
+
+    tokio::task::spawn_blocking(move || {
+        crate::diagnostics::write_diagnostics_zip(
+            &reader,
+            &schema,
+            &health,
+            watcher_mode,
+            &index_path,
+            &dest,
+        )
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .map_err(AppError::Internal)
+}
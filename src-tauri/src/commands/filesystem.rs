@@ -1,5 +1,10 @@
 use crate::error::AppError;
-use std::fs;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::ipc::Channel;
 
 /// Read a file's contents (for diff viewer)
 #[tauri::command]
@@ -20,3 +25,322 @@ pub async fn read_file_range(
     let end = end_line.min(lines.len());
     Ok(lines[start..end].join("\n"))
 }
+
+/// How often `tail_file` checks for appended data.
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Quiet polls after which `tail_file` sends a heartbeat (about 15s), so a
+/// closed channel is noticed on a file that stopped growing.
+const TAIL_HEARTBEAT_POLLS: u32 = 30;
+
+/// Largest file `tail_file` will replay from the start; bigger ones need `from_end`.
+const MAX_TAIL_REPLAY_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Longest line `tail_file` buffers; a longer one is sent in pieces of this size.
+const MAX_TAIL_LINE_BYTES: usize = 1024 * 1024;
+
+/// Most `tail_file` reads per poll, so a burst or a replay after rotation
+/// is taken in bounded steps.
+const TAIL_READ_CHUNK_BYTES: u64 = 1024 * 1024;
+
+/// One `tail_file` channel message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TailEvent {
+    /// A complete line, without its newline
+    Line { text: String },
+    /// Nothing was appended for a while
+    Heartbeat,
+}
+
+/// Follow a file like `tail -f`, sending each complete line as it is
+/// appended. With `from_end`, existing content is skipped.
+///
+/// The file is reopened from the start when it shrinks (truncation) or, on
+/// Unix, when the path points at a new file (log rotation). Runs until a send
+/// fails; a heartbeat every `TAIL_HEARTBEAT_POLLS` quiet polls makes sure
+/// that happens soon after the channel closed even if the file stays quiet.
+///
+/// `path` must resolve inside the home directory or the Claude projects dir.
+#[tauri::command]
+pub async fn tail_file(
+    path: String,
+    on_event: Channel<TailEvent>,
+    from_end: bool,
+) -> Result<(), AppError> {
+    let roots: Vec<PathBuf> = [dirs::home_dir(), crate::claude_paths::projects_dir()]
+        .into_iter()
+        .flatten()
+        .collect();
+    let path = confined_tail_path(Path::new(&path), &roots)?;
+    tokio::task::spawn_blocking(move || {
+        follow_file(
+            &path,
+            from_end,
+            TAIL_POLL_INTERVAL,
+            TAIL_HEARTBEAT_POLLS,
+            |event| on_event.send(event).is_ok(),
+        )
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// `path` canonicalized, if it lies under one of `roots`.
+fn confined_tail_path(path: &Path, roots: &[PathBuf]) -> Result<PathBuf, AppError> {
+    let resolved = path.canonicalize()?;
+    let inside = roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| resolved.starts_with(root));
+    if !inside {
+        return Err(AppError::Internal(format!(
+            "{} is outside the directories tail_file may read",
+            path.display()
+        )));
+    }
+    Ok(resolved)
+}
+
+/// Identity of the file behind a path, to notice rotation.
+#[cfg(unix)]
+fn file_identity(meta: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_meta: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// `tail_file` loop. `emit` returns false to stop.
+fn follow_file(
+    path: &Path,
+    from_end: bool,
+    interval: Duration,
+    heartbeat_polls: u32,
+    mut emit: impl FnMut(TailEvent) -> bool,
+) -> Result<(), AppError> {
+    let mut file = File::open(path)?;
+    let meta = file.metadata()?;
+    if !meta.is_file() {
+        return Err(AppError::NotFound(format!("Not a regular file: {}", path.display())));
+    }
+    if !from_end && meta.len() > MAX_TAIL_REPLAY_BYTES {
+        return Err(AppError::Internal(format!(
+            "{} is {} bytes, too large to replay; tail it from the end",
+            path.display(),
+            meta.len()
+        )));
+    }
+    let mut identity = file_identity(&meta);
+    let mut pos = if from_end { meta.len() } else { 0 };
+    let mut pending: Vec<u8> = Vec::new();
+    let mut buf = Vec::new();
+    let mut quiet_polls = 0;
+
+    loop {
+        // A missing path mid-rotation just means "try again next poll"
+        if let Ok(current) = fs::metadata(path) {
+            let rotated = file_identity(&current) != identity;
+            if rotated || current.len() < pos {
+                if let Ok(reopened) = File::open(path) {
+                    file = reopened;
+                    identity = file_identity(&current);
+                    pos = 0;
+                    pending.clear();
+                }
+            }
+        }
+
+        buf.clear();
+        file.seek(SeekFrom::Start(pos))?;
+        let read = (&mut file).take(TAIL_READ_CHUNK_BYTES).read_to_end(&mut buf)? as u64;
+        pos += read;
+        pending.extend_from_slice(&buf);
+        let mut emitted = false;
+        loop {
+            let newline = pending.iter().take(MAX_TAIL_LINE_BYTES + 1).position(|&b| b == b'\n');
+            let text = match newline {
+                Some(newline) => {
+                    let line: Vec<u8> = pending.drain(..=newline).collect();
+                    let text = String::from_utf8_lossy(&line[..newline]);
+                    text.trim_end_matches('\r').to_string()
+                }
+                None if pending.len() > MAX_TAIL_LINE_BYTES => {
+                    let piece: Vec<u8> = pending.drain(..MAX_TAIL_LINE_BYTES).collect();
+                    String::from_utf8_lossy(&piece).into_owned()
+                }
+                None => break,
+            };
+            if !emit(TailEvent::Line { text }) {
+                return Ok(());
+            }
+            emitted = true;
+        }
+
+        quiet_polls = if emitted { 0 } else { quiet_polls + 1 };
+        if quiet_polls >= heartbeat_polls {
+            if !emit(TailEvent::Heartbeat) {
+                return Ok(());
+            }
+            quiet_polls = 0;
+        }
+
+        // A full chunk means more is probably waiting; read it right away
+        if read < TAIL_READ_CHUNK_BYTES {
+            std::thread::sleep(interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Run `follow_file` on its own thread, failing the test if it hasn't
+    /// returned within `timeout` after `drive` finished.
+    fn follow_until(
+        path: &Path,
+        heartbeat_polls: u32,
+        emit: impl FnMut(TailEvent) -> bool + Send + 'static,
+        drive: impl FnOnce(),
+    ) -> Result<(), AppError> {
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let path = path.to_path_buf();
+        std::thread::spawn(move || {
+            let interval = Duration::from_millis(10);
+            let result = follow_file(&path, true, interval, heartbeat_polls, emit);
+            let _ = done_tx.send(result);
+        });
+        drive();
+        done_rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("follow_file did not stop")
+    }
+
+    fn line(text: &str) -> TailEvent {
+        TailEvent::Line { text: text.into() }
+    }
+
+    #[test]
+    fn test_follow_file_emits_appended_lines() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("build.log");
+        fs::write(&path, "already there\n").unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut seen = 0;
+        let emit = move |event| {
+            tx.send(event).unwrap();
+            seen += 1;
+            seen < 2
+        };
+        let result = follow_until(&path, u32::MAX, emit, || {
+            let mut log = fs::OpenOptions::new().append(true).open(&path).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            log.write_all(b"step 1 ok\nstep 2 ").unwrap();
+            log.flush().unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            log.write_all(b"ok\r\n").unwrap();
+        });
+
+        result.unwrap();
+        let lines: Vec<TailEvent> = rx.try_iter().collect();
+        assert_eq!(lines, [line("step 1 ok"), line("step 2 ok")]);
+    }
+
+    #[test]
+    fn test_follow_file_stops_on_quiet_file_once_channel_closes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("quiet.log");
+        fs::write(&path, "").unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel::<TailEvent>();
+        let emit = move |event| tx.send(event).is_ok();
+        // Nothing is ever appended; only the heartbeat can notice the closed receiver
+        let result = follow_until(&path, 3, emit, || drop(rx));
+        result.unwrap();
+    }
+
+    #[test]
+    fn test_follow_file_splits_overlong_lines() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("minified.log");
+        fs::write(&path, "").unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let emit = move |event| {
+            let more = !matches!(event, TailEvent::Line { ref text } if text == "tail");
+            tx.send(event).unwrap();
+            more
+        };
+        let result = follow_until(&path, u32::MAX, emit, || {
+            let mut log = fs::OpenOptions::new().append(true).open(&path).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            log.write_all(&vec![b'x'; MAX_TAIL_LINE_BYTES + 10]).unwrap();
+            log.write_all(b"\ntail\n").unwrap();
+        });
+
+        result.unwrap();
+        let lengths: Vec<usize> = rx
+            .try_iter()
+            .map(|event| match event {
+                TailEvent::Line { text } => text.len(),
+                TailEvent::Heartbeat => 0,
+            })
+            .collect();
+        assert_eq!(lengths, [MAX_TAIL_LINE_BYTES, 10, 4]);
+    }
+
+    #[test]
+    fn test_follow_file_reads_large_burst_in_chunks() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("burst.log");
+        fs::write(&path, "").unwrap();
+        let total = 3 * TAIL_READ_CHUNK_BYTES as usize / 1000;
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut seen = 0;
+        let emit = move |event| {
+            tx.send(event).unwrap();
+            seen += 1;
+            seen < total
+        };
+        let result = follow_until(&path, u32::MAX, emit, || {
+            let mut log = fs::OpenOptions::new().append(true).open(&path).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            let line = format!("{}\n", "y".repeat(999));
+            log.write_all(line.repeat(total).as_bytes()).unwrap();
+        });
+
+        result.unwrap();
+        let lines: Vec<TailEvent> = rx.try_iter().collect();
+        assert_eq!(lines.len(), total);
+        assert!(lines.iter().all(|l| matches!(l, TailEvent::Line { text } if text.len() == 999)));
+    }
+
+    #[test]
+    fn test_confined_tail_path_rejects_paths_outside_roots() {
+        let root = tempfile::TempDir::new().unwrap();
+        let elsewhere = tempfile::TempDir::new().unwrap();
+        let inside = root.path().join("build.log");
+        let outside = elsewhere.path().join("secret.log");
+        fs::write(&inside, "").unwrap();
+        fs::write(&outside, "").unwrap();
+        let roots = [root.path().to_path_buf()];
+
+        assert_eq!(confined_tail_path(&inside, &roots).unwrap(), inside.canonicalize().unwrap());
+        assert!(confined_tail_path(&outside, &roots).is_err());
+        let dotdot = root.path().join("..").join(elsewhere.path().file_name().unwrap());
+        assert!(confined_tail_path(&dotdot.join("secret.log"), &roots).is_err());
+        #[cfg(unix)]
+        {
+            let link = root.path().join("link.log");
+            std::os::unix::fs::symlink(&outside, &link).unwrap();
+            assert!(confined_tail_path(&link, &roots).is_err(), "symlink out of the root");
+        }
+    }
+}
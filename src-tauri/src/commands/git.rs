@@ -1,8 +1,12 @@
 use crate::error::AppError;
+use crate::search::queries::get_session_detail_query;
+use crate::state::IndexHandle;
 use serde::Serialize;
+use std::path::Path;
 use std::process::Command;
+use tauri::State;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct WorktreeInfo {
     pub path: String,
     pub branch: String,
@@ -12,13 +16,21 @@ pub struct WorktreeInfo {
 /// Detect git worktrees for a given repository path
 #[tauri::command]
 pub async fn detect_worktree(repo_path: String) -> Result<Vec<WorktreeInfo>, AppError> {
+    list_worktrees(&repo_path)
+}
+
+fn list_worktrees(repo_path: &str) -> Result<Vec<WorktreeInfo>, AppError> {
     let output = Command::new("git")
         .args(["worktree", "list", "--porcelain"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .output()
         .map_err(|e| AppError::Internal(format!("git worktree list failed: {e}")))?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_worktree_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `git worktree list --porcelain` output; the main worktree comes first.
+pub fn parse_worktree_list(stdout: &str) -> Vec<WorktreeInfo> {
     let mut worktrees = Vec::new();
     let mut current_path = String::new();
     let mut current_branch = String::new();
@@ -51,18 +63,207 @@ pub async fn detect_worktree(repo_path: String) -> Result<Vec<WorktreeInfo>, App
         });
     }
 
-    Ok(worktrees)
+    worktrees
+}
+
+/// The worktree checked out on `branch`, else the main (first non-bare) one.
+pub fn worktree_for_branch<'a>(
+    worktrees: &'a [WorktreeInfo],
+    branch: &str,
+) -> Option<&'a WorktreeInfo> {
+    worktrees
+        .iter()
+        .find(|w| !branch.is_empty() && w.branch == branch)
+        .or_else(|| worktrees.iter().find(|w| !w.is_bare))
+}
+
+/// Resolve the working directory a session ran in.
+///
+/// Lists the worktrees of the session's project_path and picks the one on
+/// the session's git_branch, falling back to the main worktree. `None` when
+/// the project isn't a git repository.
+#[tauri::command]
+pub async fn get_session_worktree(
+    session_id: String,
+    index: State<'_, IndexHandle>,
+) -> Result<Option<WorktreeInfo>, AppError> {
+    let reader = index.reader.clone();
+    let schema = index.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        let session = get_session_detail_query(&reader, &schema, &session_id)
+            .map_err(AppError::NotFound)?;
+        if !Path::new(&session.project_path).is_dir() {
+            return Err(AppError::NotFound(format!(
+                "Project directory no longer exists: {}",
+                session.project_path
+            )));
+        }
+        let worktrees = list_worktrees(&session.project_path)?;
+        Ok(worktree_for_branch(&worktrees, &session.git_branch).cloned())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
 }
 
 /// Get git diff (staged + unstaged) for a repository path
 #[tauri::command]
 pub async fn get_git_diff(repo_path: String) -> Result<Vec<GitFileChange>, AppError> {
+    git_changes(&repo_path)
+}
+
+/// Stage files (`git add`); returns the updated change list
+#[tauri::command]
+pub async fn git_stage(
+    repo_path: String,
+    paths: Vec<String>,
+) -> Result<Vec<GitFileChange>, AppError> {
+    let paths = paths_within_repo(&repo_path, &paths)?;
+    run_git_on_paths(&repo_path, &["add"], &paths)?;
+    git_changes(&repo_path)
+}
+
+/// Unstage files (`git restore --staged`, or `git rm --cached` before the
+/// first commit, when there is no HEAD to restore from); returns the
+/// updated change list
+#[tauri::command]
+pub async fn git_unstage(
+    repo_path: String,
+    paths: Vec<String>,
+) -> Result<Vec<GitFileChange>, AppError> {
+    let paths = paths_within_repo(&repo_path, &paths)?;
+    let has_head = Command::new("git")
+        .args(["rev-parse", "--verify", "-q", "HEAD"])
+        .current_dir(&repo_path)
+        .output()
+        .map_err(|e| AppError::Internal(format!("git rev-parse failed: {e}")))?
+        .status
+        .success();
+    if has_head {
+        run_git_on_paths(&repo_path, &["restore", "--staged"], &paths)?;
+    } else {
+        run_git_on_paths(&repo_path, &["rm", "--cached", "-q"], &paths)?;
+    }
+    git_changes(&repo_path)
+}
+
+/// Commit the staged changes (`git commit -m`); returns the new HEAD hash
+#[tauri::command]
+pub async fn git_commit(repo_path: String, message: String) -> Result<String, AppError> {
+    if message.trim().is_empty() {
+        return Err(AppError::Internal("Commit message is empty".into()));
+    }
+
+    // `--quiet` exits 1 when the index differs from HEAD, 0 when it doesn't
+    let staged = Command::new("git")
+        .args(["diff", "--cached", "--quiet"])
+        .current_dir(&repo_path)
+        .status()
+        .map_err(|e| AppError::Internal(format!("git diff --cached failed: {e}")))?;
+    match staged.code() {
+        Some(0) => return Err(AppError::NothingToCommit),
+        Some(1) => {}
+        _ => return Err(AppError::Internal(format!("Not a git repository: {repo_path}"))),
+    }
+
+    let output = Command::new("git")
+        .args(["commit", "-m", &message])
+        .current_dir(&repo_path)
+        .output()
+        .map_err(|e| AppError::Internal(format!("git commit failed: {e}")))?;
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "git commit failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(&repo_path)
+        .output()
+        .map_err(|e| AppError::Internal(format!("git rev-parse failed: {e}")))?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Normalize `paths` to paths relative to `repo_path`, rejecting any that
+/// would resolve outside it (absolute paths elsewhere, `..` escapes).
+fn paths_within_repo(repo_path: &str, paths: &[String]) -> Result<Vec<String>, AppError> {
+    if paths.is_empty() {
+        return Err(AppError::Internal("No paths given".into()));
+    }
+    let root = Path::new(repo_path)
+        .canonicalize()
+        .map_err(|e| AppError::NotFound(format!("Cannot resolve {repo_path}: {e}")))?;
+    paths
+        .iter()
+        .map(|raw| {
+            let path = Path::new(raw);
+            // Absolute paths must point into the repo; deleted files can't be
+            // canonicalized, so compare their parent
+            let rel = if path.is_absolute() {
+                let parent = path.parent().and_then(|p| p.canonicalize().ok());
+                parent
+                    .and_then(|p| p.strip_prefix(&root).ok().map(Path::to_path_buf))
+                    .map(|p| p.join(path.file_name().unwrap_or_default()))
+            } else {
+                Some(path.to_path_buf())
+            };
+            rel.and_then(|rel| normalize_relative(&rel))
+                .ok_or_else(|| AppError::Internal(format!("Path is outside the repository: {raw}")))
+        })
+        .collect()
+}
+
+/// Resolve `.` and `..` in a relative path without touching the filesystem;
+/// `None` if it climbs above its start or is empty.
+fn normalize_relative(path: &Path) -> Option<String> {
+    use std::path::Component;
+    let mut parts: Vec<&std::ffi::OsStr> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    let joined: std::path::PathBuf = parts.iter().collect();
+    Some(joined.to_string_lossy().into_owned())
+}
+
+/// Run `git <args> -- <paths>`, with `paths` taken literally rather than as
+/// pathspec globs, so staging `*.rs` touches only a file named `*.rs`.
+fn run_git_on_paths(repo_path: &str, args: &[&str], paths: &[String]) -> Result<(), AppError> {
+    let output = Command::new("git")
+        .arg("--literal-pathspecs")
+        .args(args)
+        .arg("--")
+        .args(paths)
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| AppError::Internal(format!("git {} failed: {e}", args.join(" "))))?;
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn git_changes(repo_path: &str) -> Result<Vec<GitFileChange>, AppError> {
     let mut changes = Vec::new();
 
     // Get unstaged changes
     let output = Command::new("git")
         .args(["diff", "--name-status"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .output()
         .map_err(|e| AppError::Internal(format!("git diff failed: {e}")))?;
 
@@ -80,7 +281,7 @@ pub async fn get_git_diff(repo_path: String) -> Result<Vec<GitFileChange>, AppEr
     // Get staged changes
     let output = Command::new("git")
         .args(["diff", "--cached", "--name-status"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .output()
         .map_err(|e| AppError::Internal(format!("git diff --cached failed: {e}")))?;
 
@@ -98,7 +299,7 @@ pub async fn get_git_diff(repo_path: String) -> Result<Vec<GitFileChange>, AppEr
     // Get untracked files
     let output = Command::new("git")
         .args(["ls-files", "--others", "--exclude-standard"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .output()
         .map_err(|e| AppError::Internal(format!("git ls-files failed: {e}")))?;
 
@@ -116,6 +317,78 @@ pub async fn get_git_diff(repo_path: String) -> Result<Vec<GitFileChange>, AppEr
     Ok(changes)
 }
 
+/// Line counts of one changed file, as `git diff --numstat` reports them
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GitDiffStat {
+    pub path: String,
+    pub insertions: u64,
+    pub deletions: u64,
+    pub staged: bool,
+    /// Binary files have no line counts (both are 0)
+    pub is_binary: bool,
+}
+
+/// Get insertions/deletions per changed file (unstaged, then staged)
+#[tauri::command]
+pub async fn get_git_diff_stat(repo_path: String) -> Result<Vec<GitDiffStat>, AppError> {
+    let mut stats = git_numstat(&repo_path, false)?;
+    stats.extend(git_numstat(&repo_path, true)?);
+    Ok(stats)
+}
+
+fn git_numstat(repo_path: &str, staged: bool) -> Result<Vec<GitDiffStat>, AppError> {
+    let args: &[&str] = if staged {
+        &["diff", "--cached", "--numstat", "-z"]
+    } else {
+        &["diff", "--numstat", "-z"]
+    };
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| AppError::Internal(format!("git diff --numstat failed: {e}")))?;
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "git diff --numstat failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(parse_numstat(&String::from_utf8_lossy(&output.stdout), staged))
+}
+
+/// Parse `git diff --numstat -z` output: `added<TAB>deleted<TAB>path<NUL>`,
+/// with `-` for both counts of a binary file. A rename leaves the path empty
+/// and follows with `old<NUL>new<NUL>`; it is reported under the new path.
+/// Paths are verbatim (no C-quoting under `-z`).
+pub fn parse_numstat(stdout: &str, staged: bool) -> Vec<GitDiffStat> {
+    let mut fields = stdout.split('\0');
+    let mut stats = Vec::new();
+    while let Some(record) = fields.next() {
+        let mut parts = record.splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let path = if path.is_empty() {
+            match (fields.next(), fields.next()) {
+                (Some(_old), Some(new)) => new,
+                _ => break,
+            }
+        } else {
+            path
+        };
+        stats.push(GitDiffStat {
+            path: path.to_string(),
+            insertions: added.parse().unwrap_or(0),
+            deletions: deleted.parse().unwrap_or(0),
+            staged,
+            is_binary: added == "-" && deleted == "-",
+        });
+    }
+    stats
+}
+
 /// Get recent git commits for a repository
 #[tauri::command]
 pub async fn get_git_log(
@@ -364,3 +637,234 @@ pub async fn get_git_branch(path: String) -> Result<Option<String>, AppError> {
         _ => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKTREE_LIST: &str = "\
+worktree /home/user/app
+HEAD 1111111111111111111111111111111111111111
+branch refs/heads/main
+
+worktree /home/user/app-feature
+HEAD 2222222222222222222222222222222222222222
+branch refs/heads/feature/login
+
+worktree /home/user/app-detached
+HEAD 3333333333333333333333333333333333333333
+detached
+";
+
+    #[test]
+    fn test_worktree_for_branch_matches_session_branch() {
+        let worktrees = parse_worktree_list(WORKTREE_LIST);
+        assert_eq!(worktrees.len(), 3);
+        assert_eq!(worktrees[2].branch, "");
+
+        let hit = worktree_for_branch(&worktrees, "feature/login").unwrap();
+        assert_eq!(hit.path, "/home/user/app-feature");
+
+        // Unknown or missing branch falls back to the main worktree
+        let main = worktree_for_branch(&worktrees, "gone").unwrap();
+        assert_eq!(main.path, "/home/user/app");
+        assert_eq!(worktree_for_branch(&worktrees, "").unwrap().path, "/home/user/app");
+        assert_eq!(worktree_for_branch(&[], "main"), None);
+    }
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(["-c", "commit.gpgsign=false"])
+            .args(args)
+            .current_dir(repo)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[tokio::test]
+    async fn test_git_diff_stat_counts_lines_and_flags_binary() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let path = repo.path();
+        git(path, &["init", "-q"]);
+        std::fs::write(path.join("notes.txt"), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(path.join("logo.bin"), b"\x00\x01\x02").unwrap();
+        git(path, &["add", "."]);
+        git(path, &["commit", "-q", "-m", "init"]);
+
+        // One line changed, two added; the binary change is staged
+        std::fs::write(path.join("notes.txt"), "one\n2\nthree\nfour\nfive\n").unwrap();
+        std::fs::write(path.join("logo.bin"), b"\x00\x03\x02\x04").unwrap();
+        git(path, &["add", "logo.bin"]);
+
+        let stats = get_git_diff_stat(path.to_string_lossy().into_owned()).await.unwrap();
+
+        assert_eq!(
+            stats,
+            [
+                GitDiffStat {
+                    path: "notes.txt".into(),
+                    insertions: 3,
+                    deletions: 1,
+                    staged: false,
+                    is_binary: false,
+                },
+                GitDiffStat {
+                    path: "logo.bin".into(),
+                    insertions: 0,
+                    deletions: 0,
+                    staged: true,
+                    is_binary: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_numstat_handles_renames_and_raw_paths() {
+        let stdout = concat!(
+            "3\t1\tnotes.txt\0",
+            "0\t0\t\0src/old.rs\0src/new.rs\0",
+            "2\t0\t\0dir/a/mod.rs\0dir/b/mod.rs\0",
+            "1\t1\tweird \"name\"\twith tab.txt\0",
+            "-\t-\tlogo.bin\0",
+        );
+
+        let stats = parse_numstat(stdout, true);
+
+        let paths: Vec<&str> = stats.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["notes.txt", "src/new.rs", "dir/b/mod.rs", "weird \"name\"\twith tab.txt", "logo.bin"]
+        );
+        assert_eq!((stats[2].insertions, stats[2].deletions), (2, 0));
+        assert!(stats[4].is_binary && !stats[0].is_binary);
+        assert!(stats.iter().all(|s| s.staged));
+    }
+
+    #[tokio::test]
+    async fn test_git_diff_stat_reports_renames_and_fails_outside_a_repo() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let path = repo.path();
+        git(path, &["init", "-q"]);
+        std::fs::create_dir(path.join("old")).unwrap();
+        let lib = "fn a() {}\nfn b() {}\nfn c() {}\n";
+        std::fs::write(path.join("old").join("lib.rs"), lib).unwrap();
+        git(path, &["add", "."]);
+        git(path, &["commit", "-q", "-m", "init"]);
+        git(path, &["mv", "old", "new dir"]);
+
+        let stats = get_git_diff_stat(path.to_string_lossy().into_owned()).await.unwrap();
+        let paths: Vec<&str> = stats.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, ["new dir/lib.rs"]);
+
+        let not_repo = tempfile::TempDir::new().unwrap();
+        let err = get_git_diff_stat(not_repo.path().to_string_lossy().into_owned()).await;
+        assert!(err.is_err(), "a failing git diff is an error, not an empty list");
+    }
+
+    #[tokio::test]
+    async fn test_git_stage_and_unstage_move_file() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let path = repo.path();
+        git(path, &["init", "-q"]);
+        std::fs::write(path.join("notes.txt"), "one\n").unwrap();
+        git(path, &["add", "."]);
+        git(path, &["commit", "-q", "-m", "init"]);
+        std::fs::write(path.join("notes.txt"), "one\ntwo\n").unwrap();
+        let repo_path = path.to_string_lossy().into_owned();
+        let staged_state = |changes: &[GitFileChange]| {
+            changes.iter().filter(|c| c.path == "notes.txt").map(|c| c.staged).collect::<Vec<_>>()
+        };
+
+        let changes = git_stage(repo_path.clone(), vec!["./notes.txt".into()]).await.unwrap();
+        assert_eq!(staged_state(&changes), [true]);
+
+        let absolute = path.join("notes.txt").to_string_lossy().into_owned();
+        let changes = git_unstage(repo_path.clone(), vec![absolute]).await.unwrap();
+        assert_eq!(staged_state(&changes), [false]);
+
+        for outside in ["../escape.txt", "sub/../../escape.txt", "/etc/passwd", "."] {
+            let err = git_stage(repo_path.clone(), vec![outside.into()]).await.unwrap_err();
+            assert!(err.to_string().contains("outside the repository"), "{outside}: {err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_git_stage_takes_paths_literally() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let path = repo.path();
+        git(path, &["init", "-q"]);
+        for name in ["*.rs", "main.rs", "lib.rs"] {
+            std::fs::write(path.join(name), "fn main() {}\n").unwrap();
+        }
+        let repo_path = path.to_string_lossy().into_owned();
+
+        let changes = git_stage(repo_path, vec!["*.rs".into()]).await.unwrap();
+
+        let staged: Vec<&str> =
+            changes.iter().filter(|c| c.staged).map(|c| c.path.as_str()).collect();
+        assert_eq!(staged, ["*.rs"]);
+    }
+
+    #[tokio::test]
+    async fn test_git_unstage_works_before_first_commit() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let path = repo.path();
+        git(path, &["init", "-q"]);
+        std::fs::write(path.join("notes.txt"), "one\n").unwrap();
+        git(path, &["add", "notes.txt"]);
+        let repo_path = path.to_string_lossy().into_owned();
+
+        let changes = git_unstage(repo_path, vec!["notes.txt".into()]).await.unwrap();
+
+        assert!(changes.iter().all(|c| !c.staged), "got {changes:?}");
+        let index = Command::new("git").arg("ls-files").current_dir(path).output().unwrap();
+        assert!(index.stdout.is_empty(), "notes.txt left the index");
+        assert!(path.join("notes.txt").exists(), "the working copy stays");
+    }
+
+    #[tokio::test]
+    async fn test_git_commit_adds_commit_to_log() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let path = repo.path();
+        git(path, &["init", "-q"]);
+        git(path, &["config", "user.name", "Test"]);
+        git(path, &["config", "user.email", "test@example.com"]);
+        git(path, &["config", "commit.gpgsign", "false"]);
+        std::fs::write(path.join("notes.txt"), "one\n").unwrap();
+        git(path, &["add", "."]);
+        git(path, &["commit", "-q", "-m", "init"]);
+        let repo_path = path.to_string_lossy().into_owned();
+
+        std::fs::write(path.join("notes.txt"), "one\ntwo\n").unwrap();
+        assert!(matches!(
+            git_commit(repo_path.clone(), "unstaged only".into()).await,
+            Err(AppError::NothingToCommit)
+        ));
+        git(path, &["add", "notes.txt"]);
+        assert!(git_commit(repo_path.clone(), "  \n".into()).await.is_err());
+
+        let hash = git_commit(repo_path.clone(), "Add second line".into()).await.unwrap();
+
+        let log = get_git_log(repo_path.clone(), Some(5)).await.unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].hash, hash);
+        assert_eq!(log[0].subject, "Add second line");
+        assert!(matches!(
+            git_commit(repo_path, "again".into()).await,
+            Err(AppError::NothingToCommit)
+        ));
+    }
+
+    #[test]
+    fn test_worktree_fallback_skips_bare_repo() {
+        let worktrees = parse_worktree_list(
+            "worktree /srv/app.git\nbare\n\nworktree /srv/app-main\nHEAD 1111\nbranch refs/heads/main\n",
+        );
+        assert!(worktrees[0].is_bare);
+        assert_eq!(worktree_for_branch(&worktrees, "dev").unwrap().path, "/srv/app-main");
+    }
+}
@@ -0,0 +1,289 @@
+use crate::error::AppError;
+use crate::search::queries::search_sessions_query;
+use crate::search::types::{MatchSnippet, SearchFilter, SearchResult};
+use crate::shell::shell_quote;
+use crate::state::{AppState, IndexHandle};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::State;
+
+/// Max JSONL files reported back per sprite.
+const REMOTE_MATCH_CAP: usize = 20;
+/// Per-sprite budget for the remote grep, so one slow VM can't stall the search.
+const REMOTE_SEARCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A session match from either the local index or a sprite.
+#[derive(Debug, Clone, Serialize)]
+pub struct GlobalSearchResult {
+    /// "local" for the Tantivy index, otherwise the sprite name
+    pub location: String,
+    pub session_id: String,
+    pub project_path: Option<String>,
+    pub summary: Option<String>,
+    /// BM25 score (local hits only)
+    pub score: Option<f32>,
+    /// Matching line count in the JSONL (remote hits only)
+    pub match_count: Option<u64>,
+    pub snippets: Vec<MatchSnippet>,
+    pub jsonl_path: Option<String>,
+}
+
+/// A JSONL file on a sprite that matched the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteSearchHit {
+    pub session_id: String,
+    pub project_dir: String,
+    pub jsonl_path: String,
+    pub match_count: u64,
+}
+
+/// Search local sessions and the Claude sessions on each named sprite.
+///
+/// Local hits come from the Tantivy index; remote hits come from a
+/// case-insensitive fixed-string grep over `~/.claude/projects` on each sprite.
+/// A sprite that fails or times out is skipped rather than failing the search.
+#[tauri::command]
+pub async fn search_all(
+    query: String,
+    sprite_names: Vec<String>,
+    limit: Option<usize>,
+    index: State<'_, IndexHandle>,
+    state: State<'_, AppState>,
+) -> Result<Vec<GlobalSearchResult>, AppError> {
+    let reader = index.reader.clone();
+    let schema = index.schema.clone();
+    let local_query = query.clone();
+    let filter = SearchFilter {
+        limit,
+        ..Default::default()
+    };
+    let local = tokio::task::spawn_blocking(move || {
+        search_sessions_query(&reader, &schema, &local_query, Some(&filter))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .map_err(AppError::Internal)?;
+
+    let mut remote = Vec::new();
+    if !sprite_names.is_empty() {
+        let client = state.get_sprites_client()?;
+        let cmd = remote_search_command(&query, REMOTE_MATCH_CAP);
+        let searches = sprite_names.iter().map(|name| {
+            let client = &client;
+            let cmd = &cmd;
+            async move {
+                let result =
+                    tokio::time::timeout(REMOTE_SEARCH_TIMEOUT, client.exec_http(name, cmd)).await;
+                match result {
+                    Ok(Ok(out)) => Some((name.clone(), parse_remote_hits(&out.stdout))),
+                    Ok(Err(e)) => {
+                        tracing::warn!("search_all: remote search on '{name}' failed: {e}");
+                        None
+                    }
+                    Err(_) => {
+                        tracing::warn!("search_all: remote search on '{name}' timed out");
+                        None
+                    }
+                }
+            }
+        });
+        remote = futures_util::future::join_all(searches)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+    }
+
+    Ok(merge_global_results(local, remote, REMOTE_MATCH_CAP))
+}
+
+/// Build the shell command that greps a sprite's session JSONL files.
+/// Prefers ripgrep, falls back to grep; output is `<path>:<count>` per file,
+/// highest count first and capped after sorting, so the best files survive.
+fn remote_search_command(query: &str, cap: usize) -> String {
+    let q = shell_quote(query);
+    // The count is the last `:` field; prefix it so `sort -n` sees it first
+    format!(
+        "cd ~/.claude/projects 2>/dev/null || exit 0; \
+         if command -v rg >/dev/null 2>&1; then \
+         timeout 10 rg -c -i -F -g '*.jsonl' -- {q} .; \
+         else timeout 10 grep -rciF --include='*.jsonl' -- {q} . | grep -v ':0$'; \
+         fi 2>/dev/null | awk -F: '{{print $NF \"\\t\" $0}}' | sort -rn | cut -f2- \
+         | head -n {cap}"
+    )
+}
+
+/// Parse `<path>:<count>` lines from the remote grep into hits.
+pub fn parse_remote_hits(stdout: &str) -> Vec<RemoteSearchHit> {
+    let mut hits = Vec::new();
+    for line in stdout.lines() {
+        let line = line.trim();
+        let (path, count) = match line.rsplit_once(':') {
+            Some(p) => p,
+            None => continue,
+        };
+        let match_count = match count.trim().parse::<u64>() {
+            Ok(n) if n > 0 => n,
+            _ => continue,
+        };
+        let path = path.trim_start_matches("./");
+        let session_id = match path.rsplit('/').next() {
+            Some(f) if f.ends_with(".jsonl") => f.trim_end_matches(".jsonl").to_string(),
+            _ => continue,
+        };
+        let project_dir = path.rsplit('/').nth(1).unwrap_or("").to_string();
+        hits.push(RemoteSearchHit {
+            session_id,
+            project_dir,
+            jsonl_path: format!("~/.claude/projects/{path}"),
+            match_count,
+        });
+    }
+    hits
+}
+
+/// Merge local and per-sprite hits into one tagged list.
+///
+/// Local results keep their BM25 order and come first; BM25 scores and grep
+/// counts aren't comparable, so remote hits follow in sprite order, each
+/// sprite's hits sorted by match count and capped at `remote_cap`.
+pub fn merge_global_results(
+    local: Vec<SearchResult>,
+    remote: Vec<(String, Vec<RemoteSearchHit>)>,
+    remote_cap: usize,
+) -> Vec<GlobalSearchResult> {
+    let mut merged: Vec<GlobalSearchResult> = local
+        .into_iter()
+        .map(|r| GlobalSearchResult {
+            location: "local".to_string(),
+            session_id: r.session_id,
+            project_path: r.project_path,
+            summary: r.summary,
+            score: Some(r.score),
+            match_count: None,
+            snippets: r.snippets,
+            jsonl_path: None,
+        })
+        .collect();
+
+    for (sprite, mut hits) in remote {
+        hits.sort_by_key(|h| std::cmp::Reverse(h.match_count));
+        hits.truncate(remote_cap);
+        merged.extend(hits.into_iter().map(|h| GlobalSearchResult {
+            location: sprite.clone(),
+            session_id: h.session_id,
+            project_path: Some(h.project_dir),
+            summary: None,
+            score: None,
+            match_count: Some(h.match_count),
+            snippets: Vec::new(),
+            jsonl_path: Some(h.jsonl_path),
+        }));
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_result(session_id: &str, score: f32) -> SearchResult {
+        SearchResult {
+            session_id: session_id.into(),
+            score,
+            snippets: vec![],
+            project_path: Some("/home/user/proj".into()),
+            summary: Some("local summary".into()),
+            model: None,
+            modified_at: None,
+            file_exists: true,
+            best_turn_index: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_remote_hits_extracts_session_and_project() {
+        let stdout = "./-home-sprite-app/abc-123.jsonl:4\n./-home-sprite-app/def-456.jsonl:0\nnot a hit\n";
+        let hits = parse_remote_hits(stdout);
+
+        assert_eq!(hits.len(), 1, "zero-count and malformed lines are skipped");
+        assert_eq!(hits[0].session_id, "abc-123");
+        assert_eq!(hits[0].project_dir, "-home-sprite-app");
+        assert_eq!(hits[0].match_count, 4);
+        assert_eq!(
+            hits[0].jsonl_path,
+            "~/.claude/projects/-home-sprite-app/abc-123.jsonl"
+        );
+    }
+
+    #[test]
+    fn test_merge_tags_local_and_remote_results() {
+        let local = vec![local_result("l1", 2.5), local_result("l2", 1.0)];
+        let remote = vec![
+            (
+                "sprite-a".to_string(),
+                parse_remote_hits("./p/r1.jsonl:1\n./p/r2.jsonl:7\n"),
+            ),
+            ("sprite-b".to_string(), parse_remote_hits("./q/r3.jsonl:2\n")),
+        ];
+
+        let merged = merge_global_results(local, remote, 10);
+
+        let tagged: Vec<(&str, &str)> = merged
+            .iter()
+            .map(|r| (r.location.as_str(), r.session_id.as_str()))
+            .collect();
+        assert_eq!(
+            tagged,
+            vec![
+                ("local", "l1"),
+                ("local", "l2"),
+                ("sprite-a", "r2"),
+                ("sprite-a", "r1"),
+                ("sprite-b", "r3"),
+            ]
+        );
+        assert_eq!(merged[0].score, Some(2.5));
+        assert!(merged[0].match_count.is_none());
+        assert_eq!(merged[2].match_count, Some(7));
+        assert!(merged[2].score.is_none());
+    }
+
+    #[test]
+    fn test_merge_caps_remote_hits_per_sprite() {
+        let stdout: String = (0..5).map(|i| format!("./p/s{i}.jsonl:{}\n", i + 1)).collect();
+        let remote = vec![("sprite-a".to_string(), parse_remote_hits(&stdout))];
+
+        let merged = merge_global_results(vec![], remote, 2);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].session_id, "s4", "highest match count first");
+        assert_eq!(merged[1].session_id, "s3");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remote_search_command_caps_after_ranking() {
+        let home = tempfile::TempDir::new().unwrap();
+        let project = home.path().join(".claude/projects/-home-sprite-app");
+        std::fs::create_dir_all(&project).unwrap();
+        // Files sort by name; the best matches come last in file order
+        for i in 0..6 {
+            let lines = "needle\n".repeat(i + 1);
+            std::fs::write(project.join(format!("s{i}.jsonl")), lines).unwrap();
+        }
+
+        let out = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(remote_search_command("NEEDLE", 2))
+            .env("HOME", home.path())
+            .output()
+            .unwrap();
+        let hits = parse_remote_hits(&String::from_utf8_lossy(&out.stdout));
+
+        let ranked: Vec<(&str, u64)> =
+            hits.iter().map(|h| (h.session_id.as_str(), h.match_count)).collect();
+        assert_eq!(ranked, [("s5", 6), ("s4", 5)]);
+    }
+}
@@ -1,8 +1,11 @@
 pub mod agent;
+pub mod diagnostics;
 pub mod filesystem;
 pub mod git;
+pub mod global_search;
 pub mod process;
 pub mod pty;
 pub mod session;
 pub mod sprite;
 pub mod team;
+pub mod terminal;
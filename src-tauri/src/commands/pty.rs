@@ -1,8 +1,13 @@
 use crate::error::AppError;
-use crate::state::{AppState, PtyInfo, PtyInstance, PtySpawnConfig};
+use crate::state::{lock_recover, AppState, PtyInfo, PtyInstance, PtyLog, PtySpawnConfig};
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
-use std::io::Read;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, State};
 use uuid::Uuid;
 
@@ -61,7 +66,7 @@ pub async fn pty_spawn(
         .map_err(|e| AppError::Pty(e.to_string()))?;
 
     // Get reader for reading output from the PTY
-    let mut reader = pair
+    let reader = pair
         .master
         .try_clone_reader()
         .map_err(|e| AppError::Pty(e.to_string()))?;
@@ -74,6 +79,8 @@ pub async fn pty_spawn(
         rows,
     };
 
+    let log = PtyLog::default();
+
     // Store the PTY instance
     {
         let mut ptys = state.ptys.lock().unwrap();
@@ -86,24 +93,38 @@ pub async fn pty_spawn(
                 child,
                 cols,
                 rows,
+                cwd: config.cwd.clone(),
+                log: Arc::clone(&log),
             },
         );
     }
 
-    // Spawn a thread to read PTY output and emit events
+    // Spawn a thread to read PTY output; a second thread batches it into events
     let event_name = format!("pty:data:{}", pty_id);
     let exit_event = format!("pty:exit:{}", pty_id);
+    let (tx, rx) = mpsc::channel::<Vec<u8>>();
+    thread::spawn(move || pump_output(reader, &log, |chunk| tx.send(chunk.to_vec()).is_ok()));
     thread::spawn(move || {
-        let mut buf = [0u8; 8192];
-        loop {
-            match reader.read(&mut buf) {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    // Emit raw bytes as base64 to preserve binary data
-                    let data = base64_encode(&buf[..n]);
-                    let _ = app.emit(&event_name, data);
-                }
-                Err(_) => break,
+        let mut batcher = OutputBatcher::new(BATCH_BYTES, MAX_PENDING_BYTES, FLUSH_INTERVAL);
+        let mut open = true;
+        while open {
+            let received = match batcher.next_flush_in(Instant::now()) {
+                Some(wait) => rx.recv_timeout(wait),
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(chunk) => batcher.push(&chunk),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => open = false,
+            }
+            // Drain whatever else already arrived before deciding to emit
+            while let Ok(chunk) = rx.try_recv() {
+                batcher.push(&chunk);
+            }
+            let now = Instant::now();
+            while let Some(batch) = batcher.take_ready(now, !open) {
+                // Emit raw bytes as base64 to preserve binary data
+                let _ = app.emit(&event_name, base64_encode(&batch));
             }
         }
         let _ = app.emit(&exit_event, ());
@@ -126,7 +147,6 @@ pub async fn pty_write(
 
     // Data comes as base64 from frontend
     let bytes = base64_decode(&data)?;
-    use std::io::Write;
     pty.writer
         .write_all(&bytes)
         .map_err(|e| AppError::Pty(e.to_string()))?;
@@ -175,6 +195,89 @@ pub async fn pty_kill(id: String, state: State<'_, AppState>) -> Result<(), AppE
     Ok(())
 }
 
+/// Start appending a PTY's raw output to `log_path` (created if missing),
+/// alongside the usual events. Replaces any log already active.
+///
+/// `log_path` must resolve inside the home directory.
+#[tauri::command]
+pub async fn pty_start_logging(
+    id: String,
+    log_path: String,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let home = dirs::home_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
+    let path = confined_log_path(Path::new(&log_path), &home)?;
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+    let ptys = state.ptys.lock().unwrap();
+    let pty = ptys
+        .get(&id)
+        .ok_or_else(|| AppError::NotFound(format!("PTY {id} not found")))?;
+    *lock_recover(&pty.log, "pty log") = Some(file);
+    Ok(())
+}
+
+/// Stop logging a PTY's output; a no-op when it isn't logging.
+#[tauri::command]
+pub async fn pty_stop_logging(id: String, state: State<'_, AppState>) -> Result<(), AppError> {
+    let ptys = state.ptys.lock().unwrap();
+    let pty = ptys
+        .get(&id)
+        .ok_or_else(|| AppError::NotFound(format!("PTY {id} not found")))?;
+    if let Some(file) = lock_recover(&pty.log, "pty log").take() {
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+/// `path` with its parent canonicalized, if that parent lies under `root`.
+/// Refuses existing symlinks, which could point anywhere.
+fn confined_log_path(path: &Path, root: &Path) -> Result<PathBuf, AppError> {
+    let invalid = |why: &str| AppError::Internal(format!("Log path {} {why}", path.display()));
+    let outside = || invalid(&format!("is outside {}", root.display()));
+    let name = path.file_name().ok_or_else(|| invalid("has no file name"))?;
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).ok_or_else(outside)?;
+    let parent = parent.canonicalize()?;
+    if !parent.starts_with(root.canonicalize()?) {
+        return Err(outside());
+    }
+    let resolved = parent.join(name);
+    if resolved.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()) {
+        return Err(invalid("is a symlink"));
+    }
+    Ok(resolved)
+}
+
+/// Read PTY output to EOF, handing each chunk to `on_chunk` (which returns
+/// false to stop) after appending it to `log` if logging is on.
+///
+/// A failed log write turns logging off rather than ending the terminal.
+pub(crate) fn pump_output(
+    mut reader: impl Read,
+    log: &PtyLog,
+    mut on_chunk: impl FnMut(&[u8]) -> bool,
+) {
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break, // EOF, or EIO once the child is gone
+            Ok(n) => n,
+        };
+        {
+            let mut log = lock_recover(log, "pty log");
+            if let Some(file) = log.as_mut() {
+                if let Err(e) = file.write_all(&buf[..n]) {
+                    tracing::warn!("PTY log write failed, logging stopped: {e}");
+                    *log = None;
+                }
+            }
+        }
+        if !on_chunk(&buf[..n]) {
+            break;
+        }
+    }
+}
+
 /// List all active PTY instances
 #[tauri::command]
 pub async fn pty_list(state: State<'_, AppState>) -> Result<Vec<PtyInfo>, AppError> {
@@ -191,6 +294,82 @@ pub async fn pty_list(state: State<'_, AppState>) -> Result<Vec<PtyInfo>, AppErr
     Ok(infos)
 }
 
+/// Largest payload of one `pty:data` event.
+const BATCH_BYTES: usize = 16 * 1024;
+/// Minimum gap between `pty:data` events, capping UI throughput at ~4 MB/s.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(4);
+/// Output buffered beyond this is dropped, oldest first, with a marker.
+const MAX_PENDING_BYTES: usize = 1024 * 1024;
+const TRUNCATED_MARKER: &[u8] = b"\r\n[output truncated]\r\n";
+
+/// Coalesces PTY reads into rate-limited batches so that a command dumping
+/// hundreds of MB doesn't flood the event channel and freeze the webview.
+///
+/// At most one batch of `batch_bytes` is released per `interval`. When more
+/// than `max_pending` bytes pile up, the oldest are discarded (keeping the
+/// latest output on screen) and the next batch starts with a truncation
+/// marker. A cut can land mid escape sequence, which xterm.js shrugs off.
+struct OutputBatcher {
+    pending: Vec<u8>,
+    batch_bytes: usize,
+    max_pending: usize,
+    interval: Duration,
+    last_flush: Option<Instant>,
+    truncated: bool,
+}
+
+impl OutputBatcher {
+    fn new(batch_bytes: usize, max_pending: usize, interval: Duration) -> Self {
+        Self {
+            pending: Vec::new(),
+            batch_bytes,
+            max_pending,
+            interval,
+            last_flush: None,
+            truncated: false,
+        }
+    }
+
+    fn push(&mut self, data: &[u8]) {
+        self.pending.extend_from_slice(data);
+        if self.pending.len() > self.max_pending {
+            let excess = self.pending.len() - self.max_pending;
+            self.pending.drain(..excess);
+            self.truncated = true;
+        }
+    }
+
+    /// How long until a batch may be released; None when nothing is pending.
+    fn next_flush_in(&self, now: Instant) -> Option<Duration> {
+        if self.pending.is_empty() && !self.truncated {
+            return None;
+        }
+        Some(match self.last_flush {
+            Some(last) => (last + self.interval).saturating_duration_since(now),
+            None => Duration::ZERO,
+        })
+    }
+
+    /// Take the next batch if the interval has elapsed. `drain` (EOF) ignores
+    /// the interval so that the tail is delivered before `pty:exit`.
+    fn take_ready(&mut self, now: Instant, drain: bool) -> Option<Vec<u8>> {
+        if self.pending.is_empty() && !self.truncated {
+            return None;
+        }
+        if !drain && self.next_flush_in(now).is_some_and(|wait| !wait.is_zero()) {
+            return None;
+        }
+        let take = self.pending.len().min(self.batch_bytes);
+        let mut batch = Vec::with_capacity(take + TRUNCATED_MARKER.len());
+        if std::mem::take(&mut self.truncated) {
+            batch.extend_from_slice(TRUNCATED_MARKER);
+        }
+        batch.extend(self.pending.drain(..take));
+        self.last_flush = Some(now);
+        Some(batch)
+    }
+}
+
 /// Public base64 encode for use by other command modules
 pub fn base64_encode_pub(data: &[u8]) -> String {
     base64_encode(data)
@@ -267,3 +446,111 @@ fn base64_decode(data: &str) -> Result<Vec<u8>, AppError> {
     }
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pump_output_logs_the_emitted_bytes() {
+        let pair = NativePtySystem::default()
+            .openpty(PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .unwrap();
+        let mut cmd = CommandBuilder::new("sh");
+        cmd.args(["-c", "printf 'hello\\nworld\\n'"]);
+        let mut child = pair.slave.spawn_command(cmd).unwrap();
+        drop(pair.slave);
+        let reader = pair.master.try_clone_reader().unwrap();
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let log_path = confined_log_path(&tmp.path().join("pty.log"), tmp.path()).unwrap();
+        std::fs::write(&log_path, b"earlier\n").unwrap();
+        let file = OpenOptions::new().append(true).open(&log_path).unwrap();
+        let log: PtyLog = Arc::new(std::sync::Mutex::new(Some(file)));
+
+        let mut emitted = Vec::new();
+        pump_output(reader, &log, |chunk| {
+            emitted.extend_from_slice(chunk);
+            true
+        });
+        child.wait().unwrap();
+        drop(log);
+
+        assert!(String::from_utf8_lossy(&emitted).contains("world"));
+        let logged = std::fs::read(&log_path).unwrap();
+        assert_eq!(logged, [b"earlier\n".as_slice(), &emitted].concat(), "appends, same bytes");
+
+        let outside = tempfile::TempDir::new().unwrap();
+        assert!(confined_log_path(&outside.path().join("x.log"), tmp.path()).is_err());
+        assert!(confined_log_path(Path::new("relative.log"), tmp.path()).is_err());
+    }
+
+    fn batcher() -> OutputBatcher {
+        OutputBatcher::new(16, 64, Duration::from_millis(4))
+    }
+
+    #[test]
+    fn test_batcher_coalesces_rapid_reads() {
+        let mut b = batcher();
+        let start = Instant::now();
+        assert_eq!(b.next_flush_in(start), None, "idle batcher waits indefinitely");
+
+        for chunk in [b"ab".as_slice(), b"cd", b"ef"] {
+            b.push(chunk);
+        }
+        assert_eq!(b.take_ready(start, false).as_deref(), Some(b"abcdef".as_slice()));
+
+        // Within the interval, new output waits for the next flush
+        b.push(b"gh");
+        b.push(b"ij");
+        let soon = start + Duration::from_millis(1);
+        assert_eq!(b.next_flush_in(soon), Some(Duration::from_millis(3)));
+        assert_eq!(b.take_ready(soon, false), None);
+        let later = start + Duration::from_millis(4);
+        assert_eq!(b.take_ready(later, false).as_deref(), Some(b"ghij".as_slice()));
+        assert_eq!(b.take_ready(later, false), None);
+    }
+
+    #[test]
+    fn test_batcher_splits_large_output_into_batches() {
+        let mut b = batcher();
+        let start = Instant::now();
+        b.push(&[b'x'; 40]);
+
+        let first = b.take_ready(start, false).unwrap();
+        assert_eq!(first.len(), 16);
+        assert_eq!(b.take_ready(start, false), None, "one batch per interval");
+
+        // EOF drains the rest regardless of the interval
+        let rest: Vec<usize> = std::iter::from_fn(|| b.take_ready(start, true))
+            .map(|batch| batch.len())
+            .collect();
+        assert_eq!(rest, [16, 8]);
+    }
+
+    #[test]
+    fn test_batcher_drops_oldest_with_marker_when_backlogged() {
+        let mut b = batcher();
+        let start = Instant::now();
+        for i in 0..10u8 {
+            b.push(&[b'0' + i; 10]);
+        }
+        assert_eq!(b.pending.len(), 64, "backlog capped at max_pending");
+
+        let first = b.take_ready(start, false).unwrap();
+        assert!(first.starts_with(TRUNCATED_MARKER));
+        assert_eq!(&first[TRUNCATED_MARKER.len()..], b"3333444444444455");
+
+        let mut tail = Vec::new();
+        while let Some(batch) = b.take_ready(start, true) {
+            tail.extend(batch);
+        }
+        assert!(!tail.starts_with(TRUNCATED_MARKER), "marker is emitted once");
+        assert!(tail.ends_with(&[b'9'; 10]), "latest output is kept");
+    }
+}
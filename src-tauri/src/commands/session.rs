@@ -1,7 +1,9 @@
+use crate::claude_paths::{child_config_dir, CONFIG_DIR_ENV};
 use crate::error::AppError;
-use crate::state::{AppState, PtyInfo, PtyInstance};
+use crate::search::queries::latest_session_for_project_query;
+use crate::state::{AppState, IndexHandle, PtyInfo, PtyInstance, PtyLog};
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
-use std::io::Read;
+use std::sync::Arc;
 use std::thread;
 use tauri::{AppHandle, Emitter, State};
 use uuid::Uuid;
@@ -16,6 +18,61 @@ pub async fn inject_session_message(
     cwd: String,
     state: State<'_, AppState>,
     app: AppHandle,
+) -> Result<PtyInfo, AppError> {
+    let info = spawn_resume_pty(&session_id, &cwd, true, &state, app.clone())?;
+
+    // After a delay for Claude to load, emit an event telling the frontend
+    // to send the steering message via pty_write. This avoids ownership issues
+    // with the writer (which is stored in PtyInstance).
+    let steering_event = format!("pty:inject:{}", info.id);
+    thread::spawn(move || {
+        thread::sleep(std::time::Duration::from_millis(3000));
+        let _ = app.emit(&steering_event, message);
+    });
+
+    Ok(info)
+}
+
+/// Resume the newest non-archived session of `project_path` whose JSONL
+/// still exists, in a local PTY running `claude --resume <id>`.
+#[tauri::command]
+pub async fn resume_latest_session(
+    project_path: String,
+    handle: State<'_, IndexHandle>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<PtyInfo, AppError> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let project = project_path.clone();
+    let latest = tokio::task::spawn_blocking(move || {
+        latest_session_for_project_query(&reader, &schema, &project)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .map_err(AppError::Internal)?
+    .ok_or_else(|| {
+        AppError::NotFound(format!("No resumable session for project {project_path}"))
+    })?;
+
+    let cwd = if latest.project_path.is_empty() {
+        project_path
+    } else {
+        latest.project_path
+    };
+    spawn_resume_pty(&latest.session_id, &cwd, false, &state, app)
+}
+
+/// Spawn `claude --resume <session_id>` in `cwd` in a new PTY, registered in
+/// `state` and streaming `pty:data:{id}` / `pty:exit:{id}` events.
+/// `skip_permissions` adds `--dangerously-skip-permissions`; only the
+/// unattended steering-message injection asks for it.
+fn spawn_resume_pty(
+    session_id: &str,
+    cwd: &str,
+    skip_permissions: bool,
+    state: &AppState,
+    app: AppHandle,
 ) -> Result<PtyInfo, AppError> {
     let id = Uuid::new_v4().to_string();
     let cols: u16 = 120;
@@ -34,10 +91,16 @@ pub async fn inject_session_message(
     // Build the claude --resume command
     let mut cmd = CommandBuilder::new("claude");
     cmd.arg("--resume");
-    cmd.arg(&session_id);
-    cmd.arg("--dangerously-skip-permissions");
-    cmd.cwd(&cwd);
+    cmd.arg(session_id);
+    if skip_permissions {
+        cmd.arg("--dangerously-skip-permissions");
+    }
+    cmd.cwd(cwd);
     cmd.env("TERM", "xterm-256color");
+    // Resume from the same Claude dir the session was indexed from
+    if let Some(dir) = child_config_dir() {
+        cmd.env(CONFIG_DIR_ENV, dir);
+    }
 
     let child = pair
         .slave
@@ -51,7 +114,7 @@ pub async fn inject_session_message(
         .take_writer()
         .map_err(|e| AppError::Pty(e.to_string()))?;
 
-    let mut reader = pair
+    let reader = pair
         .master
         .try_clone_reader()
         .map_err(|e| AppError::Pty(e.to_string()))?;
@@ -63,6 +126,8 @@ pub async fn inject_session_message(
         rows,
     };
 
+    let log = PtyLog::default();
+
     // Store the PTY instance
     {
         let mut ptys = state.ptys.lock().unwrap();
@@ -75,40 +140,24 @@ pub async fn inject_session_message(
                 child,
                 cols,
                 rows,
+                cwd: Some(cwd.to_string()),
+                log: Arc::clone(&log),
             },
         );
     }
 
-    // Clone app handle before moving into threads
-    let app_for_inject = app.clone();
-
     // Spawn reader thread to stream PTY output as events
     let pty_id = id.clone();
     let event_name = format!("pty:data:{}", pty_id);
     let exit_event = format!("pty:exit:{}", pty_id);
     thread::spawn(move || {
-        let mut buf = [0u8; 8192];
-        loop {
-            match reader.read(&mut buf) {
-                Ok(0) => break,
-                Ok(n) => {
-                    let data = crate::commands::pty::base64_encode_pub(&buf[..n]);
-                    let _ = app.emit(&event_name, data);
-                }
-                Err(_) => break,
-            }
-        }
+        crate::commands::pty::pump_output(reader, &log, |chunk| {
+            let data = crate::commands::pty::base64_encode_pub(chunk);
+            let _ = app.emit(&event_name, data);
+            true
+        });
         let _ = app.emit(&exit_event, ());
     });
 
-    // After a delay for Claude to load, emit an event telling the frontend
-    // to send the steering message via pty_write. This avoids ownership issues
-    // with the writer (which is stored in PtyInstance).
-    let steering_event = format!("pty:inject:{}", id);
-    thread::spawn(move || {
-        thread::sleep(std::time::Duration::from_millis(3000));
-        let _ = app_for_inject.emit(&steering_event, message);
-    });
-
     Ok(info)
 }
@@ -1,4 +1,8 @@
+use crate::claude_paths::credentials_path;
+use crate::commands::team::TeamConfig;
 use crate::error::AppError;
+use crate::shell::shell_quote;
+use crate::sprite::metadata;
 use crate::sprites_api;
 use crate::state::{AppState, PtyInfo};
 use serde::Serialize;
@@ -15,7 +19,37 @@ pub async fn sprite_list(
     state: State<'_, AppState>,
 ) -> Result<Vec<sprites_api::SpriteInfo>, AppError> {
     let client = state.get_sprites_client()?;
-    client.list_sprites().await
+    let mut sprites = client.list_sprites().await?;
+    if let Some(path) = metadata::metadata_path() {
+        metadata::merge_into(&mut sprites, &metadata::load_all(&path)?);
+    }
+    Ok(sprites)
+}
+
+fn sprite_metadata_path() -> Result<std::path::PathBuf, AppError> {
+    metadata::metadata_path()
+        .ok_or_else(|| AppError::Internal("Could not determine sprite metadata location".into()))
+}
+
+/// Set a sprite's local label, color and notes (blank or absent fields are
+/// cleared). Returns what was stored.
+#[tauri::command]
+pub async fn sprite_set_metadata(
+    name: String,
+    label: Option<String>,
+    color: Option<String>,
+    notes: Option<String>,
+) -> Result<metadata::SpriteLocalMetadata, AppError> {
+    let path = sprite_metadata_path()?;
+    metadata::set(&path, &name, metadata::SpriteLocalMetadata { label, color, notes })
+}
+
+/// A sprite's local metadata, or `None` if none was set.
+#[tauri::command]
+pub async fn sprite_get_metadata(
+    name: String,
+) -> Result<Option<metadata::SpriteLocalMetadata>, AppError> {
+    metadata::get(&sprite_metadata_path()?, &name)
 }
 
 /// Get sprite details
@@ -28,14 +62,39 @@ pub async fn sprite_get(
     client.get_sprite(&name).await
 }
 
-/// Create a new sprite
+/// Get a sprite's shareable URL and whether it's public
+#[tauri::command]
+pub async fn sprite_get_url(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<sprites_api::SpriteUrl, AppError> {
+    let client = state.get_sprites_client()?;
+    client.get_sprite(&name).await?.url_info()
+}
+
+/// Get CPU/memory/disk usage of a sprite (cached for a few seconds)
+#[tauri::command]
+pub async fn sprite_get_metrics(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<sprites_api::SpriteMetrics, AppError> {
+    let client = state.get_sprites_client()?;
+    client.get_sprite_metrics(&name).await
+}
+
+/// Create a new sprite; with `ensure`, an existing one is returned instead of an error
 #[tauri::command]
 pub async fn sprite_create(
     name: String,
+    ensure: Option<bool>,
     state: State<'_, AppState>,
 ) -> Result<sprites_api::SpriteInfo, AppError> {
     let client = state.get_sprites_client()?;
-    client.create_sprite(&name).await
+    if ensure.unwrap_or(false) {
+        client.create_sprite_if_absent(&name).await
+    } else {
+        client.create_sprite(&name).await
+    }
 }
 
 /// Update sprite settings (url_settings.auth)
@@ -60,24 +119,31 @@ pub async fn sprite_delete(name: String, state: State<'_, AppState>) -> Result<(
 // Exec
 // ==========================================
 
-/// Execute a command on a sprite via HTTP POST (non-interactive)
+/// Execute a command on a sprite via HTTP POST (non-interactive), returning
+/// stdout and stderr merged. A non-zero exit code is an error unless
+/// `error_on_failure` is false.
 #[tauri::command]
 pub async fn sprite_exec(
     name: String,
     command: String,
+    error_on_failure: Option<bool>,
     state: State<'_, AppState>,
 ) -> Result<String, AppError> {
     let client = state.get_sprites_client()?;
     let result = client.exec_http(&name, &command).await?;
+    result.merged_output(error_on_failure.unwrap_or(true))
+}
 
-    let mut output = result.stdout;
-    if !result.stderr.is_empty() {
-        if !output.is_empty() {
-            output.push('\n');
-        }
-        output.push_str(&result.stderr);
-    }
-    Ok(output)
+/// Execute a command on a sprite via HTTP POST, returning the structured result
+/// (separate stdout/stderr, exit code and round-trip duration)
+#[tauri::command]
+pub async fn sprite_exec_result(
+    name: String,
+    command: String,
+    state: State<'_, AppState>,
+) -> Result<sprites_api::ExecResult, AppError> {
+    let client = state.get_sprites_client()?;
+    client.exec_http(&name, &command).await
 }
 
 /// Execute a shell command on a sprite via query-param API (returns raw output)
@@ -101,6 +167,71 @@ pub async fn sprite_list_exec_sessions(
     client.list_exec_sessions(&name).await
 }
 
+/// Poll interval of `sprite_watch_exec_sessions` when none is given.
+const DEFAULT_WATCH_INTERVAL_MS: u64 = 2000;
+/// Floor on the watch interval, so a bad argument can't hammer the API.
+const MIN_WATCH_INTERVAL_MS: u64 = 250;
+/// Longest stretch without a send; a closed channel only shows up on send.
+const WATCH_HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Watch a sprite's exec sessions, sending only what changed since the last
+/// poll (all current sessions arrive as `started` first). Runs until the
+/// channel is closed; while nothing changes a `heartbeat` goes out every
+/// `WATCH_HEARTBEAT_INTERVAL` so that is noticed on an idle sprite too.
+#[tauri::command]
+pub async fn sprite_watch_exec_sessions(
+    name: String,
+    on_event: Channel<sprites_api::ExecSessionEvent>,
+    interval_ms: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let client = state.get_sprites_client()?;
+    let interval = std::time::Duration::from_millis(
+        interval_ms
+            .unwrap_or(DEFAULT_WATCH_INTERVAL_MS)
+            .max(MIN_WATCH_INTERVAL_MS),
+    );
+
+    let mut known = Vec::new();
+    let mut last_send = std::time::Instant::now();
+    loop {
+        let mut events = match client.list_exec_sessions(&name).await {
+            Ok(sessions) => {
+                let events = sprites_api::diff_exec_sessions(&known, &sessions);
+                known = sessions;
+                events
+            }
+            Err(e) => vec![sprites_api::ExecSessionEvent::Error {
+                message: e.to_string(),
+            }],
+        };
+        if events.is_empty() && last_send.elapsed() >= WATCH_HEARTBEAT_INTERVAL {
+            events.push(sprites_api::ExecSessionEvent::Heartbeat);
+        }
+        for event in events {
+            if on_event.send(event).is_err() {
+                return Ok(());
+            }
+            last_send = std::time::Instant::now();
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Reattach to a running exec session: stream its buffered and live output.
+/// `session_id` may be the string or number `sprite_list_exec_sessions` returned.
+#[tauri::command]
+pub async fn sprite_read_exec_session(
+    name: String,
+    session_id: serde_json::Value,
+    on_event: Channel<sprites_api::ExecOutputEvent>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let client = state.get_sprites_client()?;
+    let resp = client.exec_session_output_stream(&name, &session_id).await?;
+    sprites_api::pipe_ndjson_stream(resp, &on_event, sprites_api::ExecOutputEvent::is_terminal).await
+}
+
 /// Kill an exec session via NDJSON streaming
 #[tauri::command]
 pub async fn sprite_kill_exec_session(
@@ -147,6 +278,17 @@ pub async fn sprite_list_checkpoints(
     client.list_checkpoints(&name).await
 }
 
+/// List checkpoints newest first, with parsed timestamps and readable sizes
+/// (sizes only when the list API reports them; no per-checkpoint requests)
+#[tauri::command]
+pub async fn sprite_list_checkpoints_detailed(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<sprites_api::CheckpointDetail>, AppError> {
+    let client = state.get_sprites_client()?;
+    Ok(sprites_api::detail_checkpoints(client.list_checkpoints(&name).await?))
+}
+
 /// Restore a checkpoint with NDJSON streaming progress
 #[tauri::command]
 pub async fn sprite_restore_checkpoint(
@@ -221,13 +363,59 @@ pub async fn sprite_get_service_logs(
         .await
 }
 
+/// Follow several services' logs at once, merged into one tagged stream
+#[tauri::command]
+pub async fn sprite_follow_all_logs(
+    name: String,
+    service_names: Vec<String>,
+    lines: Option<u32>,
+    on_event: Channel<sprites_api::TaggedServiceEvent>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let client = state.get_sprites_client()?;
+    let opened = futures_util::future::join_all(
+        service_names
+            .iter()
+            .map(|svc| client.get_service_logs_stream(&name, svc, lines)),
+    )
+    .await;
+
+    let mut streams = Vec::with_capacity(opened.len());
+    for (svc, resp) in service_names.into_iter().zip(opened) {
+        match resp {
+            Ok(resp) => streams.push((svc, resp.bytes_stream())),
+            Err(e) => {
+                // Report the failed service but keep following the others
+                let _ = on_event.send(sprites_api::TaggedServiceEvent {
+                    service: svc,
+                    event: sprites_api::ServiceStreamEvent::Error {
+                        data: Some(e.to_string()),
+                        timestamp: None,
+                    },
+                });
+            }
+        }
+    }
+
+    sprites_api::merge_service_log_streams(streams, &on_event).await
+}
+
 // ==========================================
 // Sprite introspection (shell commands on VM)
 // ==========================================
 
-#[derive(Debug, Serialize)]
+/// Process names `sprite_list_sessions` reports when no filter is given.
+const DEFAULT_PROCESS_FILTER: &[&str] = &["claude", "node", "bash"];
+
+/// One row of `ps aux` on a sprite.
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SpriteSessionInfo {
-    pub pid: String,
+    pub user: String,
+    pub pid: u32,
+    pub cpu: f32,
+    pub mem: f32,
+    pub start: String,
+    pub time: String,
     pub command: String,
     pub status: String,
 }
@@ -246,38 +434,69 @@ pub struct SpriteTeamInfo {
 }
 
 /// List exec sessions running on a sprite (via ps aux on the VM)
+///
+/// `command_filter` lists executable names to keep (matched against the
+/// basename of the first command word, login shells' `-` prefix dropped);
+/// defaults to claude/node/bash.
 #[tauri::command]
 pub async fn sprite_list_sessions(
     name: String,
+    command_filter: Option<Vec<String>>,
     state: State<'_, AppState>,
 ) -> Result<Vec<SpriteSessionInfo>, AppError> {
     let client = state.get_sprites_client()?;
     let result = client
-        .exec_http(
-            &name,
-            "ps aux --no-headers 2>/dev/null | grep -v 'grep' | head -20 || echo ''",
-        )
+        .exec_http(&name, "ps aux --no-headers 2>/dev/null || echo ''")
         .await?;
+    let filter = command_filter.unwrap_or_else(|| {
+        DEFAULT_PROCESS_FILTER.iter().map(|s| s.to_string()).collect()
+    });
+    Ok(parse_ps_aux(&result.stdout, &filter))
+}
+
+/// Parse `ps aux` rows, keeping processes whose executable is in `filter`.
+///
+/// Columns are USER PID %CPU %MEM VSZ RSS TTY STAT START TIME COMMAND; the
+/// command is everything after TIME, internal spaces included. An empty
+/// filter keeps every process.
+pub fn parse_ps_aux(stdout: &str, filter: &[String]) -> Vec<SpriteSessionInfo> {
     let mut sessions = Vec::new();
-    for line in result.stdout.lines() {
-        let line = line.trim();
-        if line.is_empty() {
+    for line in stdout.lines() {
+        let mut cols = line.split_whitespace();
+        let fields: Vec<&str> = cols.by_ref().take(10).collect();
+        if fields.len() < 10 {
             continue;
         }
-        let parts: Vec<&str> = line.splitn(11, char::is_whitespace).collect();
-        if parts.len() >= 11 {
-            let cmd = parts[10].to_string();
-            let pid = parts[1].to_string();
-            if cmd.contains("claude") || cmd.contains("node") || cmd.contains("bash") {
-                sessions.push(SpriteSessionInfo {
-                    pid,
-                    command: cmd,
-                    status: "running".to_string(),
-                });
-            }
+        let command = cols.collect::<Vec<_>>().join(" ");
+        let pid = match fields[1].parse::<u32>() {
+            Ok(p) => p,
+            // Header row or garbage
+            Err(_) => continue,
+        };
+
+        // Login shells show as `-bash`
+        let exe = command
+            .split_whitespace()
+            .next()
+            .map(|argv0| argv0.strip_prefix('-').unwrap_or(argv0))
+            .and_then(|argv0| argv0.rsplit('/').next())
+            .unwrap_or("");
+        if !filter.is_empty() && !filter.iter().any(|f| f == exe) {
+            continue;
         }
+
+        sessions.push(SpriteSessionInfo {
+            user: fields[0].to_string(),
+            pid,
+            cpu: fields[2].parse().unwrap_or(0.0),
+            mem: fields[3].parse().unwrap_or(0.0),
+            start: fields[8].to_string(),
+            time: fields[9].to_string(),
+            command,
+            status: "running".to_string(),
+        });
     }
-    Ok(sessions)
+    sessions
 }
 
 /// List Claude Code sessions found on a sprite
@@ -350,6 +569,35 @@ pub async fn sprite_list_teams(
     Ok(teams)
 }
 
+/// Read one team's config.json on a sprite and parse it with the real schema
+#[tauri::command]
+pub async fn sprite_get_team(
+    name: String,
+    team_name: String,
+    state: State<'_, AppState>,
+) -> Result<TeamConfig, AppError> {
+    if team_name.is_empty() || team_name.contains('/') || team_name == ".." {
+        return Err(AppError::Internal(format!("Invalid team name: {team_name:?}")));
+    }
+    let client = state.get_sprites_client()?;
+    let cmd = format!("cat ~/.claude/teams/{}/config.json", shell_quote(&team_name));
+    let result = client.exec_http(&name, &cmd).await?;
+    if result.exit_code.unwrap_or(0) != 0 || result.stdout.trim().is_empty() {
+        return Err(AppError::NotFound(format!(
+            "team '{team_name}' config.json on '{name}': {}",
+            result.stderr.trim()
+        )));
+    }
+    parse_team_config(&team_name, &result.stdout)
+}
+
+/// Parse a team config.json, naming the team in the error if it's malformed.
+pub fn parse_team_config(team_name: &str, json: &str) -> Result<TeamConfig, AppError> {
+    serde_json::from_str(json).map_err(|e| {
+        AppError::Internal(format!("team '{team_name}' config.json is malformed: {e}"))
+    })
+}
+
 // ==========================================
 // WebSocket terminal
 // ==========================================
@@ -374,6 +622,28 @@ pub async fn sprite_ws_spawn(
         .await
 }
 
+/// Open a sprite WebSocket terminal that runs `claude --resume <session_id>`
+#[tauri::command]
+pub async fn sprite_ws_resume(
+    sprite_name: String,
+    session_id: String,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<PtyInfo, AppError> {
+    tracing::info!("sprite_ws_resume called for '{sprite_name}' session '{session_id}'");
+    let client = state.get_sprites_client()?;
+    let c = cols.unwrap_or(80);
+    let r = rows.unwrap_or(24);
+    let cmd = sprites_api::claude_resume_command(&session_id)?;
+    let ws_url = client.ws_exec_url_with_cmd(&sprite_name, &cmd, c, r);
+    let token = client.token().to_string();
+
+    crate::sprites_ws::sprite_ws_connect(&sprite_name, &ws_url, &token, c, r, app, &state.ws_state)
+        .await
+}
+
 /// Write to a sprite WebSocket terminal
 #[tauri::command]
 pub async fn sprite_ws_write(
@@ -416,9 +686,8 @@ pub async fn sprite_provision_claude(
 ) -> Result<(), AppError> {
     tracing::info!("sprite_provision_claude called for '{name}'");
     // Read local credentials
-    let home = dirs::home_dir()
+    let creds_path = credentials_path()
         .ok_or_else(|| AppError::Internal("Cannot determine home directory".into()))?;
-    let creds_path = home.join(".claude").join(".credentials.json");
 
     let creds_content = std::fs::read_to_string(&creds_path).map_err(|e| {
         AppError::Internal(format!(
@@ -450,22 +719,132 @@ pub async fn sprite_provision_claude(
 // Config
 // ==========================================
 
+/// Check a pasted API base URL and return its canonical form with the fixups applied
+#[tauri::command]
+pub fn normalize_sprites_base_url(
+    input: String,
+) -> Result<sprites_api::NormalizedBaseUrl, AppError> {
+    sprites_api::normalize_sprites_base_url(&input)
+}
+
 /// Configure the Sprites API client from settings
+///
+/// `base_url` is normalized first (see `normalize_sprites_base_url`).
+/// `max_concurrent_requests` caps in-flight API calls (default
+/// `sprites_api::DEFAULT_MAX_CONCURRENT_REQUESTS`).
 #[tauri::command]
 pub async fn sprite_configure(
     base_url: String,
     token: String,
+    max_concurrent_requests: Option<usize>,
     state: State<'_, AppState>,
 ) -> Result<String, AppError> {
-    state.set_sprites_client(base_url.clone(), token.clone());
+    let base_url = sprites_api::normalize_sprites_base_url(&base_url)?.url;
+    state.set_sprites_client(base_url, token, max_concurrent_requests);
 
     let client = state.get_sprites_client()?;
     client.test_connection().await
 }
 
+/// Drop the configured client and sprite caches, forcing re-entry of the token
+#[tauri::command]
+pub fn sprite_clear_client(state: State<'_, AppState>) {
+    state.clear_sprites_client();
+}
+
 /// Test connection to Sprites API
 #[tauri::command]
 pub async fn sprite_test_connection(state: State<'_, AppState>) -> Result<String, AppError> {
     let client = state.get_sprites_client()?;
     client.test_connection().await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PS_FIXTURE: &str = "\
+USER         PID %CPU %MEM    VSZ   RSS TTY      STAT START   TIME COMMAND
+root           1  0.0  0.1   4364  3328 ?        Ss   09:12   0:00 /bin/bash /entrypoint.sh
+sprite       812 12.5  3.4 987654 65432 pts/0    Sl+  09:15   1:42 node /usr/local/bin/claude --resume abc-123
+sprite       901  0.3  0.2  10240  4096 pts/1    Ss   09:20   0:01 -bash
+sprite       907  0.0  0.1   8192  2048 pts/1    S+   09:21   0:00 vim notes/bash
+sprite      1020 98.0 10.0 123456 99999 ?        R    Feb18 120:05 claude -p summarize the logs
+";
+
+    fn filter(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_ps_aux_extracts_columns() {
+        let rows = parse_ps_aux(PS_FIXTURE, &[]);
+
+        assert_eq!(rows.len(), 5, "header row is skipped");
+        let node = &rows[1];
+        assert_eq!(node.user, "sprite");
+        assert_eq!(node.pid, 812);
+        assert_eq!(node.cpu, 12.5);
+        assert_eq!(node.mem, 3.4);
+        assert_eq!(node.start, "09:15");
+        assert_eq!(node.time, "1:42");
+        assert_eq!(node.command, "node /usr/local/bin/claude --resume abc-123");
+        assert_eq!(rows[4].start, "Feb18");
+        assert_eq!(rows[4].command, "claude -p summarize the logs");
+    }
+
+    #[test]
+    fn test_parse_ps_aux_filters_by_executable_name() {
+        let default = filter(DEFAULT_PROCESS_FILTER);
+        let pids: Vec<u32> = parse_ps_aux(PS_FIXTURE, &default).iter().map(|r| r.pid).collect();
+
+        // The "-bash" login shell matches; vim editing "notes/bash" does not
+        assert_eq!(pids, vec![1, 812, 901, 1020]);
+
+        let pids: Vec<u32> = parse_ps_aux(PS_FIXTURE, &filter(&["vim"]))
+            .iter()
+            .map(|r| r.pid)
+            .collect();
+        assert_eq!(pids, vec![907]);
+    }
+
+    #[test]
+    fn test_parse_team_config_counts_real_members() {
+        let json = r#"{
+            "name": "refactor-squad",
+            "description": "Split the monolith",
+            "createdAt": 1760000000,
+            "leadAgentId": "lead-1",
+            "settings": { "name": "not-a-member", "theme": { "name": "dark" } },
+            "members": [
+                {
+                    "agentId": "lead-1",
+                    "name": "team-lead",
+                    "agentType": "general-purpose",
+                    "model": "claude-opus-4-6",
+                    "cwd": "/home/sprite/app"
+                },
+                {
+                    "agentId": "w-2",
+                    "name": "tester",
+                    "agentType": "qa",
+                    "tools": [{ "name": "Bash" }, { "name": "Read" }]
+                }
+            ]
+        }"#;
+
+        let config = parse_team_config("refactor-squad", json).unwrap();
+
+        assert_eq!(config.name, "refactor-squad");
+        assert_eq!(config.lead_agent_id.as_deref(), Some("lead-1"));
+        let names: Vec<&str> = config.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["team-lead", "tester"]);
+        assert_eq!(config.members[0].model.as_deref(), Some("claude-opus-4-6"));
+    }
+
+    #[test]
+    fn test_parse_team_config_malformed() {
+        let err = parse_team_config("broken", "{\"name\": ").unwrap_err();
+        assert!(err.to_string().contains("team 'broken' config.json is malformed"));
+    }
+}
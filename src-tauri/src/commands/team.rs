@@ -1,6 +1,12 @@
+use crate::atomic_file::write_atomic;
+use crate::claude_paths::{claude_dir, tasks_dir, teams_dir};
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
 
 /// A member of an agent team
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +61,59 @@ pub struct TeamTask {
     pub blocks: Vec<String>,
     #[serde(default, rename = "blockedBy")]
     pub blocked_by: Vec<String>,
+    /// `status` mapped onto `TaskStatus`; derived, never read from the file
+    #[serde(default, skip_deserializing)]
+    pub normalized_status: TaskStatus,
+}
+
+impl TeamTask {
+    /// Parse a task file, filling in `normalized_status`.
+    pub fn from_json(content: &str) -> serde_json::Result<Self> {
+        let mut task: TeamTask = serde_json::from_str(content)?;
+        task.normalized_status = TaskStatus::parse(task.status.as_deref());
+        Ok(task)
+    }
+}
+
+/// Canonical task status. Task files spell it in many ways ("done",
+/// "In-Progress", "canceled"), so counts go through `TaskStatus::parse`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// Also a task with no status at all
+    #[default]
+    Pending,
+    InProgress,
+    Completed,
+    Blocked,
+    Cancelled,
+    /// A spelling none of the others match; only counted in `total`
+    Unknown,
+}
+
+impl TaskStatus {
+    /// Case-insensitive, with `-` and spaces treated as `_`.
+    pub fn parse(status: Option<&str>) -> Self {
+        let Some(raw) = status.map(str::trim).filter(|s| !s.is_empty()) else {
+            return TaskStatus::Pending;
+        };
+        let key = raw.to_lowercase().replace(['-', ' '], "_");
+        match key.as_str() {
+            "pending" | "todo" | "to_do" | "open" | "not_started" | "queued" => {
+                TaskStatus::Pending
+            }
+            "in_progress" | "inprogress" | "active" | "started" | "running" | "doing"
+            | "wip" => TaskStatus::InProgress,
+            "completed" | "complete" | "done" | "finished" | "closed" | "resolved" => {
+                TaskStatus::Completed
+            }
+            "blocked" | "waiting" | "on_hold" => TaskStatus::Blocked,
+            "cancelled" | "canceled" | "abandoned" | "skipped" | "wontfix" | "won't_do" => {
+                TaskStatus::Cancelled
+            }
+            _ => TaskStatus::Unknown,
+        }
+    }
 }
 
 /// Full team info returned to the frontend
@@ -73,21 +132,35 @@ pub struct TeamInfo {
 }
 
 /// Summary of task states
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TaskSummary {
     pub total: usize,
     pub pending: usize,
     pub in_progress: usize,
     pub completed: usize,
+    pub blocked: usize,
+    pub cancelled: usize,
+}
+
+impl TaskSummary {
+    /// Count `tasks` by `normalized_status`.
+    pub fn from_tasks(tasks: &[TeamTask]) -> Self {
+        let count = |status| tasks.iter().filter(|t| t.normalized_status == status).count();
+        TaskSummary {
+            total: tasks.len(),
+            pending: count(TaskStatus::Pending),
+            in_progress: count(TaskStatus::InProgress),
+            completed: count(TaskStatus::Completed),
+            blocked: count(TaskStatus::Blocked),
+            cancelled: count(TaskStatus::Cancelled),
+        }
+    }
 }
 
 /// List all agent teams from ~/.claude/teams/
 #[tauri::command]
 pub async fn list_teams() -> Result<Vec<TeamInfo>, AppError> {
-    let teams_dir = dirs::home_dir()
-        .ok_or_else(|| AppError::Internal("No home dir".into()))?
-        .join(".claude")
-        .join("teams");
+    let teams_dir = teams_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
 
     if !teams_dir.exists() {
         return Ok(vec![]);
@@ -133,12 +206,317 @@ pub async fn get_team(name: String) -> Result<TeamInfo, AppError> {
     read_team(&name)
 }
 
+/// Copy every task of `source_team` into `dest_team`'s task list, leaving
+/// the source list as is. Returns old id -> new id.
+///
+/// With `renumber`, the copied tasks get fresh ids after dest's highest one
+/// (in source id order) and their blocks/blockedBy are rewritten to match.
+/// Without it, any id dest already uses aborts the merge before anything is
+/// written.
+#[tauri::command]
+pub async fn merge_team_tasks(
+    app: AppHandle,
+    source_team: String,
+    dest_team: String,
+    renumber: bool,
+) -> Result<BTreeMap<String, String>, AppError> {
+    validate_team_name(&source_team)?;
+    validate_team_name(&dest_team)?;
+    if source_team == dest_team {
+        return Err(AppError::Internal("Cannot merge a team into itself".into()));
+    }
+    let tasks_dir = tasks_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
+
+    let mapping =
+        merge_task_dirs(&tasks_dir.join(&source_team), &tasks_dir.join(&dest_team), renumber)?;
+
+    match read_team(&dest_team) {
+        Ok(info) => {
+            let _ = app.emit("team:updated", &info);
+        }
+        Err(AppError::NotFound(_)) => {}
+        Err(e) => tracing::warn!("Failed to read team {dest_team} after merge: {e}"),
+    }
+    Ok(mapping)
+}
+
+/// Reject names that would escape ~/.claude/teams or ~/.claude/tasks.
+fn validate_team_name(name: &str) -> Result<(), AppError> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(AppError::Internal(format!("Invalid name: {name:?}")));
+    }
+    Ok(())
+}
+
+/// A team's config.json and task files in one shareable document.
+///
+/// Both are kept as raw JSON so fields we don't model survive the trip.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamBundle {
+    pub config: Value,
+    pub tasks: Vec<Value>,
+}
+
+/// Write `team_name`'s config and tasks to `dest_path` as a `TeamBundle`.
+#[tauri::command]
+pub async fn export_team(team_name: String, dest_path: String) -> Result<(), AppError> {
+    validate_team_name(&team_name)?;
+    let claude_dir = claude_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
+    let bundle = export_team_bundle(&claude_dir, &team_name)?;
+    write_atomic(Path::new(&dest_path), serde_json::to_string_pretty(&bundle)?)?;
+    Ok(())
+}
+
+/// Recreate a team from a `TeamBundle` file, as `new_name` if given.
+///
+/// The bundle is validated in full before anything is written, and an
+/// existing team or task list of that name is never overwritten.
+#[tauri::command]
+pub async fn import_team(
+    bundle_path: String,
+    new_name: Option<String>,
+) -> Result<TeamInfo, AppError> {
+    let bundle: TeamBundle = serde_json::from_str(&fs::read_to_string(&bundle_path)?)?;
+    let claude_dir = claude_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
+    let name = import_team_bundle(&claude_dir, bundle, new_name.as_deref())?;
+    read_team(&name)
+}
+
+fn export_team_bundle(claude_dir: &Path, team_name: &str) -> Result<TeamBundle, AppError> {
+    let config_path = claude_dir.join("teams").join(team_name).join("config.json");
+    if !config_path.exists() {
+        return Err(AppError::NotFound(format!("Team {team_name} not found")));
+    }
+    let config: Value = serde_json::from_str(&fs::read_to_string(&config_path)?)?;
+    let tasks = read_task_values(&claude_dir.join("tasks").join(team_name))?
+        .into_iter()
+        .map(|(_, task)| task)
+        .collect();
+    Ok(TeamBundle { config, tasks })
+}
+
+/// Write `bundle` under `claude_dir`/teams and `claude_dir`/tasks and
+/// return the team name used.
+fn import_team_bundle(
+    claude_dir: &Path,
+    mut bundle: TeamBundle,
+    new_name: Option<&str>,
+) -> Result<String, AppError> {
+    let config: TeamConfig = serde_json::from_value(bundle.config.clone())
+        .map_err(|e| AppError::Internal(format!("Invalid team config in bundle: {e}")))?;
+    let name = new_name.unwrap_or(&config.name).to_string();
+    validate_team_name(&name)?;
+
+    let mut ids = HashSet::new();
+    for task in &bundle.tasks {
+        let task: TeamTask = serde_json::from_value(task.clone())
+            .map_err(|e| AppError::Internal(format!("Invalid task in bundle: {e}")))?;
+        validate_team_name(&task.id)
+            .map_err(|_| AppError::Internal(format!("Invalid task id in bundle: {:?}", task.id)))?;
+        if !ids.insert(task.id.clone()) {
+            return Err(AppError::Internal(format!("Duplicate task id in bundle: {}", task.id)));
+        }
+    }
+
+    let team_dir = claude_dir.join("teams").join(&name);
+    let tasks_dir = claude_dir.join("tasks").join(&name);
+    if team_dir.exists() || tasks_dir.exists() {
+        return Err(AppError::Internal(format!("Team {name} already exists")));
+    }
+
+    bundle.config["name"] = Value::String(name.clone());
+    fs::create_dir_all(&team_dir)?;
+    write_atomic(&team_dir.join("config.json"), serde_json::to_string_pretty(&bundle.config)?)?;
+    fs::create_dir_all(&tasks_dir)?;
+    for task in &bundle.tasks {
+        let id = task["id"].as_str().unwrap_or_default();
+        write_atomic(&tasks_dir.join(format!("{id}.json")), serde_json::to_string_pretty(task)?)?;
+    }
+    Ok(name)
+}
+
+/// A ~/.claude/tasks/{name} directory with no ~/.claude/teams/{name}/config.json
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanTaskDir {
+    pub name: String,
+    /// Task .json files in the directory
+    pub task_count: usize,
+    /// Every file in the directory, locks and watermarks included
+    pub bytes: u64,
+    pub deleted: bool,
+}
+
+/// List task directories whose team config is gone; with `delete`, also
+/// remove them.
+#[tauri::command]
+pub async fn find_orphan_tasks(delete: bool) -> Result<Vec<OrphanTaskDir>, AppError> {
+    let claude_dir = claude_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
+    find_orphan_task_dirs(&claude_dir, delete)
+}
+
+fn find_orphan_task_dirs(claude_dir: &Path, delete: bool) -> Result<Vec<OrphanTaskDir>, AppError> {
+    let tasks_dir = claude_dir.join("tasks");
+    if !tasks_dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut orphans = Vec::new();
+    for entry in fs::read_dir(&tasks_dir)?.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if claude_dir.join("teams").join(&name).join("config.json").exists() {
+            continue;
+        }
+
+        let (mut task_count, mut bytes) = (0, 0);
+        for file in fs::read_dir(&path)?.flatten() {
+            let file_path = file.path();
+            bytes += file.metadata().map(|m| m.len()).unwrap_or(0);
+            if file_path.extension().and_then(|e| e.to_str()) == Some("json") {
+                task_count += 1;
+            }
+        }
+        if delete {
+            fs::remove_dir_all(&path)?;
+            tracing::info!("Deleted orphan task dir {}", path.display());
+        }
+        orphans.push(OrphanTaskDir { name, task_count, bytes, deleted: delete });
+    }
+    orphans.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(orphans)
+}
+
+/// Task files in `dir` as raw JSON (so fields we don't model survive a
+/// copy), keyed by id and sorted like `read_team_tasks`.
+fn read_task_values(dir: &Path) -> Result<Vec<(String, Value)>, AppError> {
+    let mut tasks = Vec::new();
+    if !dir.is_dir() {
+        return Ok(tasks);
+    }
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let value: Value = match serde_json::from_str(&fs::read_to_string(&path)?) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("Failed to parse task {}: {e}", path.display());
+                continue;
+            }
+        };
+        match value.get("id").and_then(Value::as_str) {
+            Some(id) => tasks.push((id.to_string(), value)),
+            None => tracing::warn!("Task {} has no id", path.display()),
+        }
+    }
+    tasks.sort_by(|(a, _), (b, _)| {
+        let a_num: u32 = a.parse().unwrap_or(u32::MAX);
+        let b_num: u32 = b.parse().unwrap_or(u32::MAX);
+        a_num.cmp(&b_num).then_with(|| a.cmp(b))
+    });
+    Ok(tasks)
+}
+
+/// Copy the tasks in `source` into `dest` (see `merge_team_tasks`).
+///
+/// Renumbering also bumps dest's `.highwatermark`, if it has one, so new
+/// tasks created there don't reuse a merged id.
+fn merge_task_dirs(
+    source: &Path,
+    dest: &Path,
+    renumber: bool,
+) -> Result<BTreeMap<String, String>, AppError> {
+    if !source.is_dir() {
+        return Err(AppError::NotFound(format!("Task list {} not found", source.display())));
+    }
+    let tasks = read_task_values(source)?;
+    // Ids become file names in dest, so they get the same checks as a bundle's
+    let mut source_ids = HashSet::new();
+    for (id, _) in &tasks {
+        validate_team_name(id).map_err(|_| {
+            AppError::Internal(format!("Invalid task id in {}: {id:?}", source.display()))
+        })?;
+        if !source_ids.insert(id.as_str()) {
+            return Err(AppError::Internal(format!(
+                "Duplicate task id in {}: {id}",
+                source.display()
+            )));
+        }
+    }
+    let dest_ids: HashSet<String> = read_task_values(dest)?.into_iter().map(|(id, _)| id).collect();
+
+    let highwatermark_path = dest.join(".highwatermark");
+    let highwatermark: Option<u64> = fs::read_to_string(&highwatermark_path)
+        .ok()
+        .and_then(|s| s.trim().parse().ok());
+
+    let mapping: BTreeMap<String, String> = if renumber {
+        let mut next = dest_ids
+            .iter()
+            .filter_map(|id| id.parse::<u64>().ok())
+            .chain(highwatermark)
+            .max()
+            .unwrap_or(0);
+        tasks
+            .iter()
+            .map(|(id, _)| {
+                next += 1;
+                (id.clone(), next.to_string())
+            })
+            .collect()
+    } else {
+        let mut collisions: Vec<&str> = tasks
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .filter(|id| dest_ids.contains(*id))
+            .collect();
+        if !collisions.is_empty() {
+            collisions.sort_unstable();
+            return Err(AppError::Internal(format!(
+                "Task ids {} already exist in {}; merge with renumber",
+                collisions.join(", "),
+                dest.display()
+            )));
+        }
+        tasks.iter().map(|(id, _)| (id.clone(), id.clone())).collect()
+    };
+
+    fs::create_dir_all(dest)?;
+    for (id, mut task) in tasks {
+        let new_id = &mapping[&id];
+        task["id"] = Value::String(new_id.clone());
+        for key in ["blocks", "blockedBy"] {
+            if let Some(Value::Array(refs)) = task.get_mut(key) {
+                for r in refs.iter_mut() {
+                    if let Some(mapped) = r.as_str().and_then(|old| mapping.get(old)) {
+                        *r = Value::String(mapped.clone());
+                    }
+                }
+            }
+        }
+        write_atomic(&dest.join(format!("{new_id}.json")), serde_json::to_string_pretty(&task)?)?;
+    }
+
+    if renumber {
+        if let (Some(_), Some(max)) =
+            (highwatermark, mapping.values().filter_map(|id| id.parse::<u64>().ok()).max())
+        {
+            write_atomic(&highwatermark_path, max.to_string())?;
+        }
+    }
+    Ok(mapping)
+}
+
 /// Read team config + tasks for a given team name
 fn read_team(team_name: &str) -> Result<TeamInfo, AppError> {
-    let home = dirs::home_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
+    let claude_dir = claude_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
 
-    let config_path = home
-        .join(".claude")
+    let config_path = claude_dir
         .join("teams")
         .join(team_name)
         .join("config.json");
@@ -151,31 +529,15 @@ fn read_team(team_name: &str) -> Result<TeamInfo, AppError> {
     let config: TeamConfig = serde_json::from_str(&content)?;
 
     // Read tasks from ~/.claude/tasks/{team_name}/
-    let tasks = read_team_tasks(team_name, &home);
+    let tasks = read_team_tasks(team_name, &claude_dir);
 
     // Check for inboxes directory
-    let inboxes_dir = home
-        .join(".claude")
+    let inboxes_dir = claude_dir
         .join("teams")
         .join(team_name)
         .join("inboxes");
     let has_inboxes = inboxes_dir.exists() && inboxes_dir.is_dir();
-
-    let total = tasks.len();
-    let pending = tasks
-        .iter()
-        .filter(|t| t.status.as_deref() == Some("pending"))
-        .count();
-    let in_progress = tasks
-        .iter()
-        .filter(|t| {
-            t.status.as_deref() == Some("in_progress") || t.status.as_deref() == Some("in-progress")
-        })
-        .count();
-    let completed = tasks
-        .iter()
-        .filter(|t| t.status.as_deref() == Some("completed"))
-        .count();
+    let task_summary = TaskSummary::from_tasks(&tasks);
 
     Ok(TeamInfo {
         name: config.name,
@@ -185,19 +547,14 @@ fn read_team(team_name: &str) -> Result<TeamInfo, AppError> {
         lead_session_id: config.lead_session_id,
         members: config.members,
         tasks,
-        task_summary: TaskSummary {
-            total,
-            pending,
-            in_progress,
-            completed,
-        },
+        task_summary,
         has_inboxes,
     })
 }
 
 /// Read tasks from ~/.claude/tasks/{team_name}/*.json
-fn read_team_tasks(team_name: &str, home: &std::path::Path) -> Vec<TeamTask> {
-    let tasks_dir = home.join(".claude").join("tasks").join(team_name);
+fn read_team_tasks(team_name: &str, claude_dir: &Path) -> Vec<TeamTask> {
+    let tasks_dir = claude_dir.join("tasks").join(team_name);
 
     if !tasks_dir.exists() {
         // Also try matching by UUID-named dirs that might correspond
@@ -216,7 +573,7 @@ fn read_team_tasks(team_name: &str, home: &std::path::Path) -> Vec<TeamTask> {
             }
 
             if let Ok(content) = fs::read_to_string(&path) {
-                match serde_json::from_str::<TeamTask>(&content) {
+                match TeamTask::from_json(&content) {
                     Ok(task) => tasks.push(task),
                     Err(e) => {
                         tracing::warn!("Failed to parse task {}: {e}", path.display());
@@ -235,3 +592,179 @@ fn read_team_tasks(team_name: &str, home: &std::path::Path) -> Vec<TeamTask> {
 
     tasks
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write_task(dir: &Path, task: Value) {
+        fs::create_dir_all(dir).unwrap();
+        let id = task["id"].as_str().unwrap().to_string();
+        fs::write(dir.join(format!("{id}.json")), task.to_string()).unwrap();
+    }
+
+    fn read_task(dir: &Path, id: &str) -> Value {
+        serde_json::from_str(&fs::read_to_string(dir.join(format!("{id}.json"))).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_task_status_spellings_bucket_into_summary() {
+        let spellings = [
+            (Some("pending"), TaskStatus::Pending),
+            (None, TaskStatus::Pending),
+            (Some("TODO"), TaskStatus::Pending),
+            (Some("in_progress"), TaskStatus::InProgress),
+            (Some("In-Progress"), TaskStatus::InProgress),
+            (Some("in progress"), TaskStatus::InProgress),
+            (Some("completed"), TaskStatus::Completed),
+            (Some("Done"), TaskStatus::Completed),
+            (Some("complete"), TaskStatus::Completed),
+            (Some(" BLOCKED "), TaskStatus::Blocked),
+            (Some("cancelled"), TaskStatus::Cancelled),
+            (Some("Canceled"), TaskStatus::Cancelled),
+            (Some("frobnicated"), TaskStatus::Unknown),
+        ];
+        let tasks: Vec<TeamTask> = spellings
+            .iter()
+            .enumerate()
+            .map(|(i, (status, expected))| {
+                let file = json!({"id": i.to_string(), "status": status}).to_string();
+                let task = TeamTask::from_json(&file).unwrap();
+                assert_eq!(task.normalized_status, *expected, "{status:?}");
+                task
+            })
+            .collect();
+
+        let summary = TaskSummary::from_tasks(&tasks);
+        assert_eq!(
+            summary,
+            TaskSummary {
+                total: 13,
+                pending: 3,
+                in_progress: 3,
+                completed: 3,
+                blocked: 1,
+                cancelled: 2,
+            }
+        );
+        // Serialized for the frontend in snake_case, raw status untouched
+        let json = serde_json::to_value(&tasks[4]).unwrap();
+        assert_eq!(json["normalizedStatus"], "in_progress");
+        assert_eq!(json["status"], "In-Progress");
+    }
+
+    #[test]
+    fn test_merge_task_dirs_renumbers_and_rewrites_dependencies() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (source, dest) = (tmp.path().join("team-b"), tmp.path().join("team-a"));
+        write_task(&source, json!({"id": "1", "subject": "schema", "blocks": ["2", "3"]}));
+        write_task(&source, json!({"id": "2", "subject": "api", "blockedBy": ["1"]}));
+        write_task(
+            &source,
+            json!({"id": "3", "subject": "ui", "blockedBy": ["1", "2"], "metadata": {"k": 1}}),
+        );
+        write_task(&dest, json!({"id": "1", "subject": "dest one"}));
+        write_task(&dest, json!({"id": "2", "subject": "dest two", "blocks": ["1"]}));
+        fs::write(dest.join(".highwatermark"), "2").unwrap();
+
+        let collision = merge_task_dirs(&source, &dest, false).unwrap_err();
+        assert!(collision.to_string().contains("1, 2"), "{collision}");
+        assert!(!dest.join("3.json").exists(), "nothing written on collision");
+
+        let mapping = merge_task_dirs(&source, &dest, true).unwrap();
+        let expected: BTreeMap<String, String> =
+            [("1", "3"), ("2", "4"), ("3", "5")].map(|(a, b)| (a.into(), b.into())).into();
+        assert_eq!(mapping, expected);
+
+        assert_eq!(read_task(&dest, "3")["blocks"], json!(["4", "5"]));
+        assert_eq!(read_task(&dest, "4")["blockedBy"], json!(["3"]));
+        let ui = read_task(&dest, "5");
+        assert_eq!(ui["id"], "5");
+        assert_eq!(ui["blockedBy"], json!(["3", "4"]));
+        assert_eq!(ui["metadata"], json!({"k": 1}), "unmodelled fields survive");
+        // Dest's own tasks and the source list are untouched
+        assert_eq!(read_task(&dest, "2")["blocks"], json!(["1"]));
+        assert_eq!(read_task(&dest, "1")["subject"], "dest one");
+        assert_eq!(read_task(&source, "3")["blockedBy"], json!(["1", "2"]));
+        assert_eq!(fs::read_to_string(dest.join(".highwatermark")).unwrap(), "5");
+    }
+
+    #[test]
+    fn test_merge_task_dirs_rejects_path_like_ids() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (source, dest) = (tmp.path().join("team-b"), tmp.path().join("team-a"));
+        write_task(&source, json!({"id": "1", "subject": "fine"}));
+        fs::write(source.join("2.json"), json!({"id": "../../escape", "subject": "x"}).to_string())
+            .unwrap();
+
+        for renumber in [false, true] {
+            let err = merge_task_dirs(&source, &dest, renumber).unwrap_err();
+            assert!(err.to_string().contains("Invalid task id"), "{err}");
+        }
+        assert!(!tmp.path().join("escape.json").exists());
+        assert!(!dest.exists(), "nothing written");
+    }
+
+    #[test]
+    fn test_team_bundle_round_trips_under_new_name() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let claude = tmp.path();
+        let config = json!({
+            "name": "alpha",
+            "description": "first team",
+            "members": [{"agentId": "a1", "name": "lead", "agentType": "general"}],
+            "extra": true
+        });
+        fs::create_dir_all(claude.join("teams").join("alpha")).unwrap();
+        fs::write(claude.join("teams").join("alpha").join("config.json"), config.to_string())
+            .unwrap();
+        let tasks_dir = claude.join("tasks").join("alpha");
+        write_task(&tasks_dir, json!({"id": "1", "subject": "plan", "blocks": ["2"]}));
+        write_task(&tasks_dir, json!({"id": "2", "subject": "build", "blockedBy": ["1"]}));
+
+        let bundle = export_team_bundle(claude, "alpha").unwrap();
+        let bundle: TeamBundle =
+            serde_json::from_str(&serde_json::to_string(&bundle).unwrap()).unwrap();
+        assert_eq!(import_team_bundle(claude, bundle.clone(), Some("beta")).unwrap(), "beta");
+
+        let imported = export_team_bundle(claude, "beta").unwrap();
+        let mut expected = bundle.clone();
+        expected.config["name"] = json!("beta");
+        assert_eq!(imported, expected);
+        assert_eq!(imported.tasks.len(), 2);
+
+        // Existing teams are never overwritten, and bad bundles write nothing
+        assert!(import_team_bundle(claude, bundle.clone(), None).is_err());
+        let mut bad = bundle;
+        bad.tasks.push(json!({"subject": "no id"}));
+        assert!(import_team_bundle(claude, bad, Some("gamma")).is_err());
+        assert!(!claude.join("teams").join("gamma").exists());
+    }
+
+    #[test]
+    fn test_find_orphan_task_dirs_skips_live_teams() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let claude = tmp.path();
+        fs::create_dir_all(claude.join("teams").join("live")).unwrap();
+        fs::write(claude.join("teams").join("live").join("config.json"), r#"{"name":"live"}"#)
+            .unwrap();
+        write_task(&claude.join("tasks").join("live"), json!({"id": "1"}));
+        let orphan_dir = claude.join("tasks").join("gone");
+        write_task(&orphan_dir, json!({"id": "1"}));
+        write_task(&orphan_dir, json!({"id": "2"}));
+        fs::write(orphan_dir.join(".lock"), "").unwrap();
+
+        let found = find_orphan_task_dirs(claude, false).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].name.as_str(), found[0].task_count), ("gone", 2));
+        assert!(found[0].bytes > 0);
+        assert!(!found[0].deleted && orphan_dir.exists());
+
+        let deleted = find_orphan_task_dirs(claude, true).unwrap();
+        assert!(deleted[0].deleted);
+        assert!(!orphan_dir.exists());
+        assert!(claude.join("tasks").join("live").join("1.json").exists());
+        assert!(find_orphan_task_dirs(claude, false).unwrap().is_empty());
+    }
+}
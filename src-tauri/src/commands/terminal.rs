@@ -0,0 +1,141 @@
+use crate::error::AppError;
+use crate::state::AppState;
+use serde::Serialize;
+use tauri::State;
+
+/// Where a terminal runs: a local PTY or a sprite exec WebSocket.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind")]
+pub enum TerminalLocation {
+    Local { pid: u32, cwd: Option<String> },
+    Remote { sprite_name: String },
+}
+
+/// One entry of the unified terminal switcher.
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalInfo {
+    /// The id used with pty_* / sprite_ws_* commands and `pty:data:{id}` events
+    pub id: String,
+    pub cols: u16,
+    pub rows: u16,
+    #[serde(flatten)]
+    pub location: TerminalLocation,
+}
+
+/// Snapshot local PTYs and sprite WS sessions, locals first, each sorted by id.
+pub async fn all_terminals(state: &AppState) -> Vec<TerminalInfo> {
+    let mut local: Vec<TerminalInfo> = {
+        let ptys = state.ptys.lock().unwrap();
+        ptys.values()
+            .map(|p| TerminalInfo {
+                id: p.id.clone(),
+                cols: p.cols,
+                rows: p.rows,
+                location: TerminalLocation::Local {
+                    pid: p.child.process_id().unwrap_or(0),
+                    cwd: p.cwd.clone(),
+                },
+            })
+            .collect()
+    };
+    let mut remote: Vec<TerminalInfo> = state
+        .ws_state
+        .sessions
+        .lock()
+        .await
+        .values()
+        .map(|s| TerminalInfo {
+            id: s.id.clone(),
+            cols: s.cols,
+            rows: s.rows,
+            location: TerminalLocation::Remote {
+                sprite_name: s.sprite_name.clone(),
+            },
+        })
+        .collect();
+
+    local.sort_by(|a, b| a.id.cmp(&b.id));
+    remote.sort_by(|a, b| a.id.cmp(&b.id));
+    local.extend(remote);
+    local
+}
+
+/// List every active terminal, local PTY and remote sprite session alike.
+#[tauri::command]
+pub async fn list_all_terminals(
+    state: State<'_, AppState>,
+) -> Result<Vec<TerminalInfo>, AppError> {
+    Ok(all_terminals(&state).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::PtyInstance;
+    use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
+
+    fn local_pty(id: &str, cwd: &str) -> PtyInstance {
+        let pair = NativePtySystem::default()
+            .openpty(PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .unwrap();
+        let mut cmd = CommandBuilder::new("sleep");
+        cmd.arg("5");
+        cmd.cwd(cwd);
+        let child = pair.slave.spawn_command(cmd).unwrap();
+        PtyInstance {
+            id: id.to_string(),
+            writer: pair.master.take_writer().unwrap(),
+            master: pair.master,
+            child,
+            cols: 80,
+            rows: 24,
+            cwd: Some(cwd.to_string()),
+            log: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_all_terminals_tags_local_and_remote() {
+        let state = AppState::new();
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cwd = tmp.path().to_string_lossy().to_string();
+        let pty = local_pty("local-1", &cwd);
+        let pid = pty.child.process_id().unwrap();
+        state.ptys.lock().unwrap().insert("local-1".into(), pty);
+
+        let ws = crate::sprites_ws::connect_test_session("remote-1", "sprite-a").await;
+        state.ws_state.sessions.lock().await.insert("remote-1".into(), ws);
+
+        let terminals = all_terminals(&state).await;
+
+        assert_eq!(terminals.len(), 2);
+        assert_eq!(terminals[0].id, "local-1");
+        match &terminals[0].location {
+            TerminalLocation::Local { pid: p, cwd: c } => {
+                assert_eq!(*p, pid);
+                assert_eq!(c.as_deref(), Some(cwd.as_str()));
+            }
+            other => panic!("expected local, got {other:?}"),
+        }
+        assert_eq!(terminals[1].id, "remote-1");
+        assert_eq!(
+            terminals[1].location,
+            TerminalLocation::Remote {
+                sprite_name: "sprite-a".into()
+            }
+        );
+
+        let json = serde_json::to_value(&terminals[1]).unwrap();
+        assert_eq!(json["kind"], "Remote");
+        assert_eq!(json["sprite_name"], "sprite-a");
+
+        for p in state.ptys.lock().unwrap().values_mut() {
+            let _ = p.child.kill();
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use tantivy::IndexReader;
+use tracing_subscriber::fmt::MakeWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::search::indexer::read_index_meta;
+use crate::search::queries::{get_index_stats_query, index_size_breakdown};
+use crate::search::schema::IndexSchema;
+use crate::search::types::{BackgroundHealth, WatcherMode};
+use crate::state::lock_recover;
+
+/// Log lines kept in memory for diagnostics bundles.
+const LOG_TAIL_LINES: usize = 500;
+
+static LOG_TAIL: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// `MakeWriter` for a `tracing_subscriber` fmt layer that keeps the last
+/// `LOG_TAIL_LINES` formatted lines in memory (see `recent_log_lines`).
+pub struct LogTailWriter;
+
+/// One formatted event, pushed to the tail when the layer drops it.
+pub struct LogTailLine(Vec<u8>);
+
+impl<'a> MakeWriter<'a> for LogTailWriter {
+    type Writer = LogTailLine;
+
+    fn make_writer(&'a self) -> LogTailLine {
+        LogTailLine(Vec::new())
+    }
+}
+
+impl Write for LogTailLine {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogTailLine {
+    fn drop(&mut self) {
+        let text = String::from_utf8_lossy(&self.0);
+        let mut tail = lock_recover(&LOG_TAIL, "log tail");
+        for line in text.lines().filter(|l| !l.is_empty()) {
+            if tail.len() == LOG_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line.to_string());
+        }
+    }
+}
+
+/// The most recent log lines, oldest first.
+pub fn recent_log_lines() -> Vec<String> {
+    lock_recover(&LOG_TAIL, "log tail").iter().cloned().collect()
+}
+
+fn pretty<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| e.to_string())
+}
+
+/// Write a support bundle to `dest`: index stats, swarm-ui-meta.json, the
+/// index size breakdown, background health and the log tail.
+///
+/// Only aggregate metadata goes in, never message content or JSONL files.
+/// `index_path` is the on-disk index directory (meta file and sizes).
+/// Returns the entry names written.
+pub fn write_diagnostics_zip(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    health: &BackgroundHealth,
+    watcher_mode: Option<WatcherMode>,
+    index_path: &Path,
+    dest: &Path,
+) -> Result<Vec<String>, String> {
+    let mut stats = get_index_stats_query(reader, schema)?;
+    stats.watcher_mode = watcher_mode;
+
+    let mut entries: Vec<(&str, Vec<u8>)> = vec![
+        ("index_stats.json", pretty(&stats)?),
+        ("index_size_breakdown.json", pretty(&index_size_breakdown(index_path))?),
+        ("background_health.json", pretty(health)?),
+    ];
+    if let Some(meta) = read_index_meta(index_path) {
+        entries.push(("swarm-ui-meta.json", pretty(&meta)?));
+    }
+    entries.push(("log_tail.txt", recent_log_lines().join("\n").into_bytes()));
+
+    let file = File::create(dest).map_err(|e| format!("Cannot create {}: {e}", dest.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+    for (name, bytes) in &entries {
+        zip.start_file(*name, options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+
+    Ok(entries.into_iter().map(|(name, _)| name.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::indexer::{write_index_meta, IndexOptions};
+    use crate::state::IndexHandle;
+    use tantivy::{Index, TantivyDocument};
+
+    #[test]
+    fn test_write_diagnostics_zip_contains_expected_entries() {
+        let schema = IndexSchema::new();
+        let index = Index::create_in_ram(schema.schema.clone());
+        let handle = IndexHandle::new(index, schema, 50_000_000, IndexOptions::default()).unwrap();
+        {
+            let mut writer = lock_recover(&handle.writer, "index writer");
+            let mut doc = TantivyDocument::new();
+            doc.add_text(handle.schema.session_id, "s-1");
+            doc.add_text(handle.schema.doc_type, "session");
+            writer.add_document(doc).unwrap();
+            let mut doc = TantivyDocument::new();
+            doc.add_text(handle.schema.session_id, "s-1");
+            doc.add_text(handle.schema.doc_type, "message");
+            doc.add_text(handle.schema.content, "private session content");
+            doc.add_text(handle.schema.content_stored, "private session content");
+            writer.add_document(doc).unwrap();
+            writer.commit().unwrap();
+        }
+        handle.reader.reload().unwrap();
+        let index_dir = tempfile::TempDir::new().unwrap();
+        write_index_meta(index_dir.path(), 1, None).unwrap();
+        LogTailLine(b"INFO swarm_ui: diagnostics test line\n".to_vec());
+
+        let dest = index_dir.path().join("diagnostics.zip");
+        write_diagnostics_zip(
+            &handle.reader,
+            &handle.schema,
+            &handle.health.report(),
+            None,
+            index_dir.path(),
+            &dest,
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&dest).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            [
+                "background_health.json",
+                "index_size_breakdown.json",
+                "index_stats.json",
+                "log_tail.txt",
+                "swarm-ui-meta.json",
+            ]
+        );
+
+        let mut read = |name: &str| {
+            let mut out = String::new();
+            std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut out).unwrap();
+            out
+        };
+        let stats: serde_json::Value = serde_json::from_str(&read("index_stats.json")).unwrap();
+        assert_eq!(stats["total_sessions"], 1);
+        assert_eq!(stats["total_messages"], 1);
+        assert!(read("log_tail.txt").contains("diagnostics test line"));
+        for name in ["index_stats.json", "background_health.json", "swarm-ui-meta.json"] {
+            assert!(!read(name).contains("private session content"));
+        }
+    }
+}
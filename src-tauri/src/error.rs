@@ -10,6 +10,8 @@ pub enum AppError {
     Json(#[from] serde_json::Error),
     #[error("Not found: {0}")]
     NotFound(String),
+    #[error("Nothing to commit: no changes are staged")]
+    NothingToCommit,
     #[error("Internal: {0}")]
     Internal(String),
 }
@@ -1,33 +1,54 @@
+mod atomic_file;
+mod claude_paths;
 mod commands;
+mod diagnostics;
 mod error;
 mod search;
+mod shell;
 mod sprite;
 mod sprites_api;
 mod sprites_ws;
 mod state;
 mod watchers;
 
+use claude_paths::projects_dir;
 use search::indexer;
 use search::schema::IndexSchema;
-use state::{AppState, IndexHandle};
+use state::{lock_recover, AppState, IndexHandle};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 use tauri::Manager;
 use tantivy::Index;
 
+/// How long app exit waits for sprite WebSockets to take their Close frames.
+const WS_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Determine the on-disk index directory: `~/.local/share/swarm-ui/tantivy/`
-fn index_path() -> Option<PathBuf> {
+pub(crate) fn index_path() -> Option<PathBuf> {
     dirs::data_local_dir().map(|d| d.join("swarm-ui").join("tantivy"))
 }
 
-/// Determine the Claude projects directory: `~/.claude/projects/`
-fn projects_dir() -> Option<PathBuf> {
-    dirs::home_dir().map(|d| d.join(".claude").join("projects"))
+/// Home of the watcher ping thread's probe file: `~/.local/share/swarm-ui/watch-probe/`
+fn watch_probe_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("swarm-ui").join("watch-probe"))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tracing_subscriber::fmt::init();
+    {
+        use tracing_subscriber::prelude::*;
+        // stdout as before, plus an uncolored copy for diagnostics bundles
+        tracing_subscriber::registry()
+            .with(tracing_subscriber::filter::LevelFilter::INFO)
+            .with(tracing_subscriber::fmt::layer())
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(diagnostics::LogTailWriter),
+            )
+            .init();
+    }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
@@ -48,41 +69,95 @@ pub fn run() {
             commands::pty::pty_resize,
             commands::pty::pty_kill,
             commands::pty::pty_list,
+            commands::pty::pty_start_logging,
+            commands::pty::pty_stop_logging,
+            commands::terminal::list_all_terminals,
             // Session commands (Tantivy-backed)
             search::queries::list_sessions,
+            search::queries::resync_sessions,
+            search::queries::list_sessions_by_branch,
             search::queries::search_sessions,
+            search::queries::search_sessions_stream,
+            search::queries::search_project_sessions,
+            search::queries::count_search_matches,
+            search::queries::cancel_search,
             search::queries::get_session_detail,
+            search::queries::get_session_details,
+            search::queries::record_session_open,
+            search::queries::get_recent_sessions,
+            search::queries::compare_sessions,
+            search::queries::get_context_fill,
+            search::queries::get_session_message_breakdown,
             search::queries::get_conversation,
+            search::queries::get_conversation_raw_messages,
+            search::queries::get_message,
+            search::queries::find_duplicate_sessions,
+            search::queries::find_sessions_with_gaps,
+            search::queries::get_daily_token_spend,
+            search::queries::get_last_activity,
+            search::queries::get_dashboard_summary,
             search::queries::get_index_stats,
+            search::queries::get_background_health,
+            search::queries::repair_index_meta,
+            search::queries::get_index_size_breakdown,
+            search::queries::diagnose_session_file,
+            search::queries::reveal_session_file,
+            commands::diagnostics::generate_diagnostics,
+            search::queries::estimate_reindex_time,
             search::queries::reindex_all,
+            search::queries::get_extra_session_dirs,
+            search::queries::set_extra_session_dirs,
+            search::queries::reindex_project,
+            search::queries::archive_project,
+            search::queries::archive_project_preview,
+            search::queries::archive_older_than,
+            search::queries::archive_older_than_preview,
+            search::queries::prune_old_messages,
+            search::queries::prune_old_messages_preview,
+            search::queries::export_index_ndjson,
+            search::queries::import_index_ndjson,
+            // Global search (local index + sprite-hosted sessions)
+            commands::global_search::search_all,
             // Legacy session command (PTY-based injection, not search)
             commands::session::inject_session_message,
+            commands::session::resume_latest_session,
             // Process commands
             commands::process::find_claude_processes,
             commands::process::kill_process,
             // Sprite REST API commands
             commands::sprite::sprite_list,
             commands::sprite::sprite_get,
+            commands::sprite::sprite_get_url,
+            commands::sprite::sprite_set_metadata,
+            commands::sprite::sprite_get_metadata,
+            commands::sprite::sprite_get_metrics,
             commands::sprite::sprite_create,
             commands::sprite::sprite_update,
             commands::sprite::sprite_delete,
             commands::sprite::sprite_exec,
+            commands::sprite::sprite_exec_result,
             commands::sprite::sprite_exec_command,
             commands::sprite::sprite_list_exec_sessions,
+            commands::sprite::sprite_watch_exec_sessions,
+            commands::sprite::sprite_read_exec_session,
             commands::sprite::sprite_kill_exec_session,
             commands::sprite::sprite_checkpoint_create,
             commands::sprite::sprite_list_checkpoints,
+            commands::sprite::sprite_list_checkpoints_detailed,
             commands::sprite::sprite_restore_checkpoint,
             commands::sprite::sprite_list_services,
             commands::sprite::sprite_start_service,
             commands::sprite::sprite_stop_service,
             commands::sprite::sprite_get_service_logs,
+            commands::sprite::sprite_follow_all_logs,
             // Sprite introspection commands
             commands::sprite::sprite_list_sessions,
             commands::sprite::sprite_list_claude_sessions,
             commands::sprite::sprite_list_teams,
+            commands::sprite::sprite_get_team,
             // Sprite WebSocket terminal commands
             commands::sprite::sprite_ws_spawn,
+            commands::sprite::sprite_ws_resume,
             commands::sprite::sprite_ws_write,
             commands::sprite::sprite_ws_resize,
             commands::sprite::sprite_ws_kill,
@@ -90,11 +165,18 @@ pub fn run() {
             commands::sprite::sprite_provision_claude,
             // Sprite config commands
             commands::sprite::sprite_configure,
+            commands::sprite::sprite_clear_client,
+            commands::sprite::normalize_sprites_base_url,
             commands::sprite::sprite_test_connection,
             // Git commands
             commands::git::detect_worktree,
             commands::git::get_git_branch,
+            commands::git::get_session_worktree,
             commands::git::get_git_diff,
+            commands::git::get_git_diff_stat,
+            commands::git::git_stage,
+            commands::git::git_unstage,
+            commands::git::git_commit,
             commands::git::get_git_log,
             commands::git::get_file_diff,
             commands::git::get_commit_files,
@@ -102,6 +184,7 @@ pub fn run() {
             // Filesystem commands
             commands::filesystem::read_file,
             commands::filesystem::read_file_range,
+            commands::filesystem::tail_file,
             // Agent commands
             commands::agent::list_agents,
             commands::agent::list_sprite_agents,
@@ -110,11 +193,26 @@ pub fn run() {
             // Team commands
             commands::team::list_teams,
             commands::team::get_team,
+            commands::team::merge_team_tasks,
+            commands::team::export_team,
+            commands::team::import_team,
+            commands::team::find_orphan_tasks,
             // Watcher commands
             watchers::team_watcher::start_team_watcher,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running Swarm-UI");
+        .build(tauri::generate_context!())
+        .expect("error while building Swarm-UI")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Close sprite WebSockets so their server-side execs end too
+                let ws_state = &app.state::<AppState>().ws_state;
+                tauri::async_runtime::block_on(ws_state.shutdown_all(WS_SHUTDOWN_TIMEOUT));
+                let index = app.try_state::<IndexHandle>();
+                if let (Some(index), Some(dir)) = (index, watch_probe_dir()) {
+                    search::watcher::stop_watcher_pinger(&index.health, &dir);
+                }
+            }
+        });
 }
 
 /// Initialize the Tantivy search index and start background indexing.
@@ -122,9 +220,13 @@ pub fn run() {
 /// Startup sequence:
 /// 1. Build schema and determine index_path
 /// 2. Check schema_version_mismatch — if mismatch or missing, delete and recreate
+///    (unless an interrupted bulk index of this schema can be resumed)
 /// 3. Create IndexHandle (512MB buffer if bulk needed, 50MB otherwise)
 /// 4. Manage IndexHandle as Tauri state
-/// 5. Spawn background thread: bulk_index if needed, then start watcher
+/// 5. Spawn background thread: warm up (or bulk_index if needed), then start watcher
+///
+/// Both the bulk index and the watcher cover `~/.claude/projects` plus the
+/// extra session directories in `EXTRA_DIRS_FILE`.
 fn setup_tantivy_index(app_handle: tauri::AppHandle) {
     let idx_path = match index_path() {
         Some(p) => p,
@@ -142,11 +244,17 @@ fn setup_tantivy_index(app_handle: tauri::AppHandle) {
         }
     };
 
+    // Read before a schema mismatch can drop the index directory holding it
+    let extra_dirs = indexer::read_extra_dirs(&idx_path);
+
     let schema = IndexSchema::new();
+    let options = indexer::IndexOptions::from_env();
     let needs_bulk = !idx_path.exists() || indexer::schema_version_mismatch(&idx_path);
+    // An interrupted bulk index has no meta yet, but its committed docs are kept
+    let resume_bulk = indexer::bulk_index_resumable(&idx_path);
 
     // If schema mismatch, drop the old index entirely
-    if idx_path.exists() && indexer::schema_version_mismatch(&idx_path) {
+    if idx_path.exists() && indexer::schema_version_mismatch(&idx_path) && !resume_bulk {
         tracing::info!("Schema version mismatch — dropping old index");
         if let Err(e) = fs::remove_dir_all(&idx_path) {
             tracing::error!("Failed to remove old index: {e}");
@@ -158,6 +266,20 @@ fn setup_tantivy_index(app_handle: tauri::AppHandle) {
         tracing::error!("Failed to create index directory: {e}");
         return;
     }
+    if !extra_dirs.is_empty() && !idx_path.join(indexer::EXTRA_DIRS_FILE).exists() {
+        if let Err(e) = indexer::write_extra_dirs(&idx_path, &extra_dirs) {
+            tracing::error!("Failed to restore extra session directories: {e}");
+        }
+    }
+    // The startup probe only ever goes into ~/.claude/projects, not an extra directory
+    let watch_probes = search::watcher::WatchProbes {
+        startup_dir: proj_dir.exists().then(|| proj_dir.clone()),
+        heartbeat_dir: watch_probe_dir(),
+    };
+    let session_dirs: Vec<PathBuf> = indexer::session_dirs(proj_dir, extra_dirs)
+        .into_iter()
+        .filter(|dir| dir.exists())
+        .collect();
 
     // Open or create the Tantivy index
     let index = match Index::open_in_dir(&idx_path) {
@@ -178,7 +300,7 @@ fn setup_tantivy_index(app_handle: tauri::AppHandle) {
         50 * 1024 * 1024
     };
 
-    let handle = match IndexHandle::new(index, schema, heap_bytes) {
+    let handle = match IndexHandle::new(index, schema, heap_bytes, options) {
         Ok(h) => h,
         Err(e) => {
             tracing::error!("Failed to create IndexHandle: {e}");
@@ -191,6 +313,7 @@ fn setup_tantivy_index(app_handle: tauri::AppHandle) {
     let reader = handle.reader.clone();
     let schema_clone = handle.schema.clone();
     let paused = handle.paused.clone();
+    let health = handle.health.clone();
 
     // Register IndexHandle as Tauri managed state
     app_handle.manage(handle);
@@ -200,24 +323,50 @@ fn setup_tantivy_index(app_handle: tauri::AppHandle) {
     std::thread::Builder::new()
         .name("tantivy-startup".into())
         .spawn(move || {
-            if needs_bulk && proj_dir.exists() {
-                tracing::info!("Starting bulk index of {}", proj_dir.display());
-                let session_count = {
-                    let mut w = writer.lock().unwrap();
-                    match indexer::bulk_index(&mut w, &schema_clone, &proj_dir, Some(&app_for_bg)) {
-                        Ok(count) => {
-                            tracing::info!("Bulk indexed {count} sessions");
-                            count
+            // Bulk indexing touches every segment anyway; only warm reopened indexes
+            if !needs_bulk && !options.skip_warm_up {
+                let started = std::time::Instant::now();
+                match search::queries::warm_up_index(&reader, &schema_clone) {
+                    Ok(n) => tracing::info!(
+                        "Index warm-up touched {n} sessions in {:?}",
+                        started.elapsed()
+                    ),
+                    Err(e) => tracing::warn!("Index warm-up failed: {e}"),
+                }
+            }
+
+            if needs_bulk && !session_dirs.is_empty() {
+                tracing::info!("Starting bulk index of {:?}", session_dirs);
+                let (session_count, bytes_per_sec) = {
+                    let mut w = lock_recover(&writer, "index writer");
+                    match indexer::bulk_index(
+                        &mut w,
+                        &schema_clone,
+                        &session_dirs,
+                        &options,
+                        Some(&app_for_bg),
+                        Some(&idx_path),
+                        Some(&health.bulk),
+                    ) {
+                        Ok(summary) => {
+                            tracing::info!(
+                                "Bulk indexed {} sessions ({} files failed)",
+                                summary.session_count,
+                                summary.failed_files
+                            );
+                            (summary.session_count, summary.bytes_per_sec)
                         }
                         Err(e) => {
                             tracing::error!("Bulk index failed: {e}");
-                            0
+                            (0, None)
                         }
                     }
                 };
 
                 // Write index metadata
-                if let Err(e) = indexer::write_index_meta(&idx_path, session_count) {
+                if let Err(e) =
+                    indexer::write_index_meta(&idx_path, session_count, bytes_per_sec)
+                {
                     tracing::error!("Failed to write index meta: {e}");
                 }
 
@@ -225,7 +374,7 @@ fn setup_tantivy_index(app_handle: tauri::AppHandle) {
                 // IndexHandle already holds the Arc<Mutex<IndexWriter>>, so we replace
                 // the inner writer with a smaller-buffer one.
                 if let Some(app_state) = app_for_bg.try_state::<IndexHandle>() {
-                    let mut w = app_state.writer.lock().unwrap();
+                    let mut w = lock_recover(&app_state.writer, "index writer");
                     // Commit any pending docs before dropping
                     let _ = w.commit();
                     // Drop current writer and create new 50MB writer
@@ -238,17 +387,23 @@ fn setup_tantivy_index(app_handle: tauri::AppHandle) {
             }
 
             // Start filesystem watcher for incremental indexing
-            if proj_dir.exists() {
+            if !session_dirs.is_empty() {
                 match search::watcher::start_index_watcher(
-                    proj_dir,
+                    session_dirs,
                     writer,
                     reader,
                     schema_clone,
                     paused,
-                    Some(app_for_bg),
+                    health,
+                    options,
+                    watch_probes,
+                    Some(app_for_bg.clone()),
                 ) {
-                    Ok((_watcher, _merge_handle)) => {
-                        tracing::info!("Tantivy watcher started");
+                    Ok((_watcher, mode, _merge_handle)) => {
+                        tracing::info!("Tantivy watcher started ({mode:?})");
+                        if let Some(handle) = app_for_bg.try_state::<IndexHandle>() {
+                            *lock_recover(&handle.watcher_mode, "watcher mode") = Some(mode);
+                        }
                         // Watcher and merge thread must stay alive — leak them
                         // since they run for the lifetime of the application.
                         std::mem::forget(_watcher);
@@ -282,7 +437,6 @@ mod tests {
         let path = projects_dir();
         assert!(path.is_some());
         let p = path.unwrap();
-        assert!(p.to_string_lossy().contains(".claude"));
-        assert!(p.to_string_lossy().contains("projects"));
+        assert!(p.ends_with("projects"));
     }
 }
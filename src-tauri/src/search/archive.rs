@@ -0,0 +1,555 @@
+use crate::atomic_file::write_atomic;
+use crate::search::doc_ext::DocExt;
+use crate::search::schema::IndexSchema;
+use crate::search::types::AffectedSession;
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use tantivy::collector::DocSetCollector;
+use tantivy::query::{BooleanQuery, Occur, Query, RangeQuery, TermQuery};
+use tantivy::schema::{IndexRecordOption, Term};
+use tantivy::{IndexReader, IndexWriter, TantivyDocument};
+
+/// Sidecar list of session ids archived by hand.
+///
+/// The index is rebuilt from JSONL files (reindex, schema bump), which always
+/// yields archived=false, so user archiving is persisted outside the index
+/// directory and re-applied via `apply_archived` whenever sessions are parsed.
+pub fn archived_ids_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("swarm-ui").join("archived-sessions.json"))
+}
+
+/// Load archived session ids; a missing or unreadable file means none.
+pub fn load_archived_ids(path: &Path) -> HashSet<String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Archived session ids from the default sidecar location.
+pub fn persisted_archived_ids() -> HashSet<String> {
+    archived_ids_path()
+        .map(|p| load_archived_ids(&p))
+        .unwrap_or_default()
+}
+
+/// Add ids to the sidecar file, keeping it sorted for stable diffs.
+fn persist_archived_ids(path: &Path, ids: &[String]) -> Result<(), String> {
+    let mut all: Vec<String> = load_archived_ids(path).into_iter().collect();
+    all.extend(ids.iter().cloned());
+    all.sort();
+    all.dedup();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&all).map_err(|e| e.to_string())?;
+    write_atomic(path, json).map_err(|e| e.to_string())
+}
+
+/// Copy a session doc with archived=true, preserving all its metadata.
+pub fn archived_session_doc(
+    existing: &TantivyDocument,
+    schema: &IndexSchema,
+    file_exists: bool,
+) -> TantivyDocument {
+    let pruned = existing.get_bool_val(schema.messages_pruned).unwrap_or(false);
+    session_doc_with_flags(existing, schema, true, file_exists, pruned)
+}
+
+/// Copy a session doc with the given flags, preserving all its other metadata.
+pub fn session_doc_with_flags(
+    existing: &TantivyDocument,
+    schema: &IndexSchema,
+    archived: bool,
+    file_exists: bool,
+    messages_pruned: bool,
+) -> TantivyDocument {
+    let mut doc = TantivyDocument::new();
+    doc.add_text(schema.session_id, existing.get_str(schema.session_id).unwrap_or(""));
+    doc.add_text(schema.doc_type, "session");
+
+    // Copy text fields
+    for field in [
+        schema.project_path,
+        schema.project_raw,
+        schema.summary,
+        schema.first_prompt,
+        schema.first_reply,
+        schema.git_branch,
+        schema.model,
+        schema.status,
+        schema.jsonl_path,
+    ] {
+        doc.add_text(field, existing.get_str(field).unwrap_or(""));
+    }
+
+    // Copy numeric fields
+    for field in [
+        schema.message_count,
+        schema.input_tokens,
+        schema.output_tokens,
+        schema.total_tokens,
+        schema.context_tokens,
+        schema.turn_depth,
+    ] {
+        doc.add_u64(field, existing.get_u64_val(field).unwrap_or(0));
+    }
+
+    // Copy date fields
+    if let Some(dt) = existing.get_date_val(schema.created_at) {
+        doc.add_date(schema.created_at, dt);
+    }
+    if let Some(dt) = existing.get_date_val(schema.modified_at) {
+        doc.add_date(schema.modified_at, dt);
+    }
+
+    // Set flags
+    doc.add_bool(schema.archived, archived);
+    doc.add_bool(schema.file_exists, file_exists);
+    doc.add_bool(
+        schema.has_tool_use,
+        existing.get_bool_val(schema.has_tool_use).unwrap_or(false),
+    );
+    doc.add_bool(schema.messages_pruned, messages_pruned);
+    doc
+}
+
+/// Flip the session doc of freshly parsed `docs` to archived if its id is in `archived`.
+pub fn apply_archived(
+    docs: &mut [TantivyDocument],
+    schema: &IndexSchema,
+    archived: &HashSet<String>,
+) {
+    let session_doc = match docs.first_mut() {
+        Some(d) if d.get_str(schema.doc_type) == Some("session") => d,
+        _ => return,
+    };
+    let is_archived = session_doc
+        .get_str(schema.session_id)
+        .is_some_and(|sid| archived.contains(sid));
+    if is_archived {
+        let file_exists = session_doc.get_bool_val(schema.file_exists).unwrap_or(true);
+        *session_doc = archived_session_doc(session_doc, schema, file_exists);
+    }
+}
+
+/// Whether a session doc belongs to `project`, given as a project path
+/// (`/home/user/app`) or a `~/.claude/projects` directory name (`-home-user-app`).
+fn session_in_project(doc: &TantivyDocument, schema: &IndexSchema, project: &str) -> bool {
+    if doc.get_str(schema.project_raw) == Some(project)
+        || doc.get_str(schema.project_path) == Some(project)
+    {
+        return true;
+    }
+    doc.get_str(schema.jsonl_path)
+        .and_then(|p| Path::new(p).parent())
+        .and_then(|d| d.file_name())
+        .is_some_and(|name| name == project)
+}
+
+/// Archive every not-yet-archived session of a project in a single commit.
+///
+/// Message docs are kept (the JSONL files still exist), only each session doc
+/// is swapped for its archived copy. The ids are then persisted to `ids_path`
+/// so reindexing keeps them archived. Returns the number of sessions archived.
+pub fn archive_project_sessions(
+    writer: &mut IndexWriter,
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    project: &str,
+    ids_path: &Path,
+) -> Result<u64, String> {
+    let docs = select_project_sessions(reader, schema, project)?;
+    archive_docs(writer, schema, docs, ids_path)
+}
+
+/// The sessions `archive_project_sessions` would archive, without touching
+/// the index.
+pub fn preview_project_archive(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    project: &str,
+) -> Result<Vec<AffectedSession>, String> {
+    let docs = select_project_sessions(reader, schema, project)?;
+    Ok(affected_sessions(&docs, schema))
+}
+
+/// Archive every not-yet-archived session last modified before `cutoff_secs`
+/// (unix seconds), in a single commit and persisted like
+/// `archive_project_sessions`. Returns the number of sessions archived.
+pub fn archive_sessions_older_than(
+    writer: &mut IndexWriter,
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    cutoff_secs: i64,
+    ids_path: &Path,
+) -> Result<u64, String> {
+    let docs = select_sessions_older_than(reader, schema, cutoff_secs)?;
+    archive_docs(writer, schema, docs, ids_path)
+}
+
+/// The sessions `archive_sessions_older_than` would archive, without touching
+/// the index.
+pub fn preview_archive_older_than(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    cutoff_secs: i64,
+) -> Result<Vec<AffectedSession>, String> {
+    let docs = select_sessions_older_than(reader, schema, cutoff_secs)?;
+    Ok(affected_sessions(&docs, schema))
+}
+
+/// Non-archived session docs of `project`.
+fn select_project_sessions(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    project: &str,
+) -> Result<Vec<TantivyDocument>, String> {
+    if project.is_empty() {
+        return Err("Project must not be empty".to_string());
+    }
+    let session_docs = TermQuery::new(
+        Term::from_field_text(schema.doc_type, "session"),
+        IndexRecordOption::Basic,
+    );
+    select_archivable(reader, schema, &session_docs, |doc| {
+        session_in_project(doc, schema, project)
+    })
+}
+
+/// Non-archived session docs last modified before `cutoff_secs`.
+fn select_sessions_older_than(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    cutoff_secs: i64,
+) -> Result<Vec<TantivyDocument>, String> {
+    let query = BooleanQuery::new(vec![
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(schema.doc_type, "session"),
+                IndexRecordOption::Basic,
+            )) as Box<dyn Query>,
+        ),
+        (
+            Occur::Must,
+            Box::new(RangeQuery::new_date_bounds(
+                "modified_at".to_string(),
+                Bound::Unbounded,
+                Bound::Excluded(tantivy::DateTime::from_timestamp_secs(cutoff_secs)),
+            )),
+        ),
+    ]);
+    select_archivable(reader, schema, &query, |_| true)
+}
+
+/// Non-archived session docs with an id matching `query` and `accept`,
+/// sorted by session id.
+fn select_archivable(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    query: &dyn Query,
+    accept: impl Fn(&TantivyDocument) -> bool,
+) -> Result<Vec<TantivyDocument>, String> {
+    let searcher = reader.searcher();
+    let addrs = searcher
+        .search(query, &DocSetCollector)
+        .map_err(|e| e.to_string())?;
+
+    let mut docs = Vec::new();
+    for addr in addrs {
+        let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+        if doc.get_bool_val(schema.archived).unwrap_or(false)
+            || doc.get_str(schema.session_id).unwrap_or("").is_empty()
+            || !accept(&doc)
+        {
+            continue;
+        }
+        docs.push(doc);
+    }
+    docs.sort_by(|a, b| a.get_str(schema.session_id).cmp(&b.get_str(schema.session_id)));
+    Ok(docs)
+}
+
+pub fn affected_sessions(docs: &[TantivyDocument], schema: &IndexSchema) -> Vec<AffectedSession> {
+    docs.iter()
+        .map(|doc| AffectedSession {
+            session_id: doc.get_str(schema.session_id).unwrap_or("").to_string(),
+            summary: doc.get_str(schema.summary).unwrap_or("").to_string(),
+            project_path: doc.get_str(schema.project_path).unwrap_or("").to_string(),
+        })
+        .collect()
+}
+
+/// Swap each selected session doc for its archived copy, commit once and
+/// persist the ids to `ids_path`.
+fn archive_docs(
+    writer: &mut IndexWriter,
+    schema: &IndexSchema,
+    docs: Vec<TantivyDocument>,
+    ids_path: &Path,
+) -> Result<u64, String> {
+    let mut archived_ids = Vec::new();
+    for doc in docs {
+        let session_id = doc.get_str(schema.session_id).unwrap_or("").to_string();
+
+        // Delete only the session doc; its message docs stay searchable
+        writer
+            .delete_query(Box::new(BooleanQuery::new(vec![
+                (
+                    Occur::Must,
+                    Box::new(TermQuery::new(
+                        Term::from_field_text(schema.session_id, &session_id),
+                        IndexRecordOption::Basic,
+                    )),
+                ),
+                (
+                    Occur::Must,
+                    Box::new(TermQuery::new(
+                        Term::from_field_text(schema.doc_type, "session"),
+                        IndexRecordOption::Basic,
+                    )),
+                ),
+            ])))
+            .map_err(|e| e.to_string())?;
+        let file_exists = doc.get_bool_val(schema.file_exists).unwrap_or(true);
+        writer
+            .add_document(archived_session_doc(&doc, schema, file_exists))
+            .map_err(|e| e.to_string())?;
+        archived_ids.push(session_id);
+    }
+
+    if archived_ids.is_empty() {
+        return Ok(0);
+    }
+    writer.commit().map_err(|e| e.to_string())?;
+    persist_archived_ids(ids_path, &archived_ids)?;
+    Ok(archived_ids.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::Count;
+    use tantivy::Index;
+
+    fn add_session(
+        writer: &IndexWriter,
+        schema: &IndexSchema,
+        sid: &str,
+        project: &str,
+        modified_secs: i64,
+    ) {
+        let dir = project.replace('/', "-");
+        let mut doc = TantivyDocument::new();
+        doc.add_text(schema.session_id, sid);
+        doc.add_text(schema.doc_type, "session");
+        doc.add_text(schema.project_path, project);
+        doc.add_text(schema.project_raw, project);
+        doc.add_text(schema.summary, format!("summary of {sid}"));
+        doc.add_text(schema.jsonl_path, format!("/home/user/.claude/projects/{dir}/{sid}.jsonl"));
+        doc.add_u64(schema.message_count, 4);
+        doc.add_date(schema.modified_at, tantivy::DateTime::from_timestamp_secs(modified_secs));
+        doc.add_bool(schema.archived, false);
+        doc.add_bool(schema.file_exists, true);
+        doc.add_bool(schema.has_tool_use, false);
+        writer.add_document(doc).unwrap();
+
+        let mut msg = TantivyDocument::new();
+        msg.add_text(schema.session_id, sid);
+        msg.add_text(schema.doc_type, "message");
+        msg.add_text(schema.content, "hello");
+        writer.add_document(msg).unwrap();
+    }
+
+    fn session_doc(reader: &IndexReader, schema: &IndexSchema, sid: &str) -> TantivyDocument {
+        let searcher = reader.searcher();
+        let query = BooleanQuery::new(vec![
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(schema.session_id, sid),
+                    IndexRecordOption::Basic,
+                )) as Box<dyn tantivy::query::Query>,
+            ),
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(schema.doc_type, "session"),
+                    IndexRecordOption::Basic,
+                )),
+            ),
+        ]);
+        let addrs = searcher.search(&query, &DocSetCollector).unwrap();
+        assert_eq!(addrs.len(), 1, "exactly one session doc for {sid}");
+        searcher.doc(*addrs.iter().next().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_archive_project_flips_only_that_project() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let ids_path = tmp.path().join("archived-sessions.json");
+        let schema = IndexSchema::new();
+        let index = Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        for sid in ["a1", "a2", "a3"] {
+            add_session(&writer, &schema, sid, "/home/user/abandoned", 1000);
+        }
+        add_session(&writer, &schema, "k1", "/home/user/keeper", 1000);
+        writer.commit().unwrap();
+        let reader: IndexReader = index
+            .reader_builder()
+            .reload_policy(tantivy::ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+
+        let count = archive_project_sessions(
+            &mut writer,
+            &reader,
+            &schema,
+            "/home/user/abandoned",
+            &ids_path,
+        )
+        .unwrap();
+        reader.reload().unwrap();
+
+        assert_eq!(count, 3);
+        for sid in ["a1", "a2", "a3"] {
+            let doc = session_doc(&reader, &schema, sid);
+            assert_eq!(doc.get_bool_val(schema.archived), Some(true));
+            assert_eq!(doc.get_str(schema.summary), Some(format!("summary of {sid}").as_str()));
+            assert_eq!(doc.get_u64_val(schema.message_count), Some(4));
+        }
+        let keeper = session_doc(&reader, &schema, "k1");
+        assert_eq!(keeper.get_bool_val(schema.archived), Some(false));
+
+        // Message docs survive: 4 sessions + 4 messages
+        let total = reader
+            .searcher()
+            .search(&tantivy::query::AllQuery, &Count)
+            .unwrap();
+        assert_eq!(total, 8);
+
+        // Persisted for reindex, and a second call is a no-op
+        let persisted = load_archived_ids(&ids_path);
+        assert_eq!(persisted.len(), 3);
+        assert!(persisted.contains("a2"));
+        let again = archive_project_sessions(
+            &mut writer,
+            &reader,
+            &schema,
+            "-home-user-abandoned",
+            &ids_path,
+        )
+        .unwrap();
+        assert_eq!(again, 0, "directory-name form matches, but all are already archived");
+    }
+
+    #[test]
+    fn test_archive_older_than_skips_recent_and_archived() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let ids_path = tmp.path().join("archived-sessions.json");
+        let schema = IndexSchema::new();
+        let index = Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let day = 86_400;
+        let now = 200 * day;
+        add_session(&writer, &schema, "old-1", "/home/user/app", now - 120 * day);
+        add_session(&writer, &schema, "old-2", "/home/user/lib", now - 91 * day);
+        add_session(&writer, &schema, "recent-1", "/home/user/app", now - 3 * day);
+        add_session(&writer, &schema, "edge-1", "/home/user/app", now - 90 * day);
+        writer.commit().unwrap();
+        let reader: IndexReader = index
+            .reader_builder()
+            .reload_policy(tantivy::ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+
+        let cutoff = now - 90 * day;
+        let count =
+            archive_sessions_older_than(&mut writer, &reader, &schema, cutoff, &ids_path).unwrap();
+        reader.reload().unwrap();
+
+        assert_eq!(count, 2);
+        for (sid, archived) in
+            [("old-1", true), ("old-2", true), ("recent-1", false), ("edge-1", false)]
+        {
+            let doc = session_doc(&reader, &schema, sid);
+            assert_eq!(doc.get_bool_val(schema.archived), Some(archived), "{sid}");
+        }
+
+        // Persisted for reindex; already-archived sessions are not redone
+        let persisted = load_archived_ids(&ids_path);
+        assert_eq!(persisted, HashSet::from(["old-1".to_string(), "old-2".to_string()]));
+        let again =
+            archive_sessions_older_than(&mut writer, &reader, &schema, cutoff, &ids_path).unwrap();
+        assert_eq!(again, 0);
+    }
+
+    #[test]
+    fn test_archive_previews_match_archived_ids() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let schema = IndexSchema::new();
+        let index = Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let day = 86_400;
+        let now = 200 * day;
+        add_session(&writer, &schema, "old-1", "/home/user/app", now - 120 * day);
+        add_session(&writer, &schema, "old-2", "/home/user/lib", now - 91 * day);
+        add_session(&writer, &schema, "recent-1", "/home/user/app", now - 3 * day);
+        add_session(&writer, &schema, "recent-2", "/home/user/lib", now - day);
+        writer.commit().unwrap();
+        let reader: IndexReader = index
+            .reader_builder()
+            .reload_policy(tantivy::ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let ids = |preview: Vec<AffectedSession>| -> HashSet<String> {
+            preview.into_iter().map(|s| s.session_id).collect()
+        };
+
+        let cutoff = now - 90 * day;
+        let preview = preview_archive_older_than(&reader, &schema, cutoff).unwrap();
+        assert_eq!(preview[0].summary, "summary of old-1");
+        let preview = ids(preview);
+        let untouched = session_doc(&reader, &schema, "old-1");
+        assert_eq!(untouched.get_bool_val(schema.archived), Some(false), "preview is read-only");
+        let older_path = tmp.path().join("older.json");
+        archive_sessions_older_than(&mut writer, &reader, &schema, cutoff, &older_path).unwrap();
+        reader.reload().unwrap();
+        assert_eq!(preview, load_archived_ids(&older_path));
+        assert_eq!(preview.len(), 2);
+
+        // Already-archived sessions drop out of the next preview as well
+        let preview = ids(preview_project_archive(&reader, &schema, "/home/user/lib").unwrap());
+        let project_path = tmp.path().join("project.json");
+        archive_project_sessions(&mut writer, &reader, &schema, "/home/user/lib", &project_path)
+            .unwrap();
+        assert_eq!(preview, load_archived_ids(&project_path));
+        assert_eq!(preview, HashSet::from(["recent-2".to_string()]));
+        assert!(preview_project_archive(&reader, &schema, "").is_err());
+    }
+
+    #[test]
+    fn test_apply_archived_marks_reparsed_session() {
+        let schema = IndexSchema::new();
+        let mut session = TantivyDocument::new();
+        session.add_text(schema.session_id, "a1");
+        session.add_text(schema.doc_type, "session");
+        session.add_text(schema.summary, "kept");
+        session.add_bool(schema.archived, false);
+        session.add_bool(schema.file_exists, true);
+        let mut docs = vec![session];
+
+        apply_archived(&mut docs, &schema, &HashSet::from(["a1".to_string()]));
+
+        assert_eq!(docs[0].get_bool_val(schema.archived), Some(true));
+        assert_eq!(docs[0].get_bool_val(schema.file_exists), Some(true));
+        assert_eq!(docs[0].get_str(schema.summary), Some("kept"));
+    }
+}
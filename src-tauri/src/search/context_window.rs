@@ -0,0 +1,28 @@
+/// Context window sizes in tokens by model-id substring, most specific first.
+const CONTEXT_WINDOWS: &[(&str, u64)] = &[
+    ("opus", 200_000),
+    ("sonnet", 200_000),
+    ("haiku", 200_000),
+];
+
+/// Context window of a model id such as `claude-opus-4-6`; `None` for
+/// unknown models.
+pub fn context_window(model: &str) -> Option<u64> {
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(needle, _)| model.contains(needle))
+        .map(|&(_, size)| size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_window_by_model_family() {
+        assert_eq!(context_window("claude-opus-4-6"), Some(200_000));
+        assert_eq!(context_window("claude-haiku-4-5"), Some(200_000));
+        assert_eq!(context_window("gpt-4o"), None);
+        assert_eq!(context_window(""), None);
+    }
+}
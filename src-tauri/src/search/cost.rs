@@ -0,0 +1,46 @@
+/// USD per million tokens for one model family.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelRate {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+/// Rates by model-id substring, most specific first (`opus-4-5` before `opus`).
+const MODEL_RATES: &[(&str, ModelRate)] = &[
+    ("opus-4-6", ModelRate { input_per_mtok: 5.0, output_per_mtok: 25.0 }),
+    ("opus-4-5", ModelRate { input_per_mtok: 5.0, output_per_mtok: 25.0 }),
+    ("opus", ModelRate { input_per_mtok: 15.0, output_per_mtok: 75.0 }),
+    ("sonnet", ModelRate { input_per_mtok: 3.0, output_per_mtok: 15.0 }),
+    ("haiku-4", ModelRate { input_per_mtok: 1.0, output_per_mtok: 5.0 }),
+    ("haiku", ModelRate { input_per_mtok: 0.8, output_per_mtok: 4.0 }),
+];
+
+/// Rate for a model id such as `claude-opus-4-6`; `None` for unknown models.
+pub fn model_rate(model: &str) -> Option<ModelRate> {
+    MODEL_RATES
+        .iter()
+        .find(|(needle, _)| model.contains(needle))
+        .map(|&(_, rate)| rate)
+}
+
+/// Estimated USD cost of a session's tokens. Unknown models cost 0.
+pub fn estimate_cost(model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+    model_rate(model).map_or(0.0, |r| {
+        (input_tokens as f64 * r.input_per_mtok + output_tokens as f64 * r.output_per_mtok)
+            / 1_000_000.0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_rate_prefers_specific_entries() {
+        assert_eq!(model_rate("claude-opus-4-6").unwrap().input_per_mtok, 5.0);
+        assert_eq!(model_rate("claude-opus-4-1-20250805").unwrap().input_per_mtok, 15.0);
+        assert_eq!(model_rate("claude-haiku-4-5").unwrap().output_per_mtok, 5.0);
+        assert_eq!(model_rate("gpt-4o"), None);
+        assert_eq!(estimate_cost("claude-sonnet-4-5", 1_000_000, 100_000), 4.5);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::search::doc_ext::DocExt;
+use crate::search::schema::IndexSchema;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use tantivy::{DocAddress, Document, IndexReader, IndexWriter, TantivyDocument};
+
+/// Export every live doc (session and message) as one JSON object per line.
+///
+/// Only stored fields survive: `content` is not stored, so message bodies are
+/// exported via `content_stored`, which is the 500-char preview — a dump is
+/// lossy for long messages. Returns the number of docs written.
+pub fn export_ndjson(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    dest: &Path,
+) -> Result<u64, String> {
+    let searcher = reader.searcher();
+    let file = File::create(dest).map_err(|e| format!("Failed to create {}: {e}", dest.display()))?;
+    let mut out = BufWriter::new(file);
+    let mut count = 0;
+
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        for doc_id in segment_reader.doc_ids_alive() {
+            let doc: TantivyDocument = searcher
+                .doc(DocAddress::new(segment_ord as u32, doc_id))
+                .map_err(|e| e.to_string())?;
+            writeln!(out, "{}", doc.to_json(&schema.schema)).map_err(|e| e.to_string())?;
+            count += 1;
+        }
+    }
+
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// Replace the index contents with the docs in an `export_ndjson` dump.
+///
+/// Unstored fields are rebuilt where possible: `content` from
+/// `content_stored`, and `msg_project` from the owning session's
+/// `project_raw`. Everything lands in one commit. Returns the number of docs
+/// imported.
+///
+/// The whole dump is parsed before anything is deleted, and any later failure
+/// rolls the writer back, so a bad dump never leaves a pending delete-all on
+/// the shared writer for the next commit to apply.
+pub fn import_ndjson(
+    writer: &mut IndexWriter,
+    schema: &IndexSchema,
+    src: &Path,
+) -> Result<u64, String> {
+    // Pass 1: session_id -> project_raw, since message lines may precede their
+    // session; also rejects a bad line before the index is touched
+    let mut projects: HashMap<String, String> = HashMap::new();
+    for_each_dump_doc(schema, src, |doc| {
+        if doc.get_str(schema.doc_type) == Some("session") {
+            if let (Some(sid), Some(project)) =
+                (doc.get_str(schema.session_id), doc.get_str(schema.project_raw))
+            {
+                projects.insert(sid.to_string(), project.to_string());
+            }
+        }
+        Ok(())
+    })?;
+
+    let result = replace_with_dump(writer, schema, src, &projects);
+    if result.is_err() {
+        if let Err(e) = writer.rollback() {
+            tracing::error!("Failed to roll back dump import: {e}");
+        }
+    }
+    result
+}
+
+/// Pass 2 of `import_ndjson`: delete everything, add the dump's docs with the
+/// unstored message fields filled in, and commit.
+fn replace_with_dump(
+    writer: &mut IndexWriter,
+    schema: &IndexSchema,
+    src: &Path,
+    projects: &HashMap<String, String>,
+) -> Result<u64, String> {
+    writer.delete_all_documents().map_err(|e| e.to_string())?;
+
+    let mut count = 0;
+    for_each_dump_doc(schema, src, |mut doc| {
+        if doc.get_str(schema.doc_type) == Some("message") {
+            let text = doc.get_str(schema.content_stored).unwrap_or("").to_string();
+            doc.add_text(schema.content, &text);
+            doc.add_text(schema.content_cased, &text);
+            let project = doc
+                .get_str(schema.session_id)
+                .and_then(|sid| projects.get(sid))
+                .cloned();
+            if let Some(project) = project {
+                doc.add_text(schema.msg_project, &project);
+            }
+        }
+        writer.add_document(doc).map_err(|e| e.to_string())?;
+        count += 1;
+        Ok(())
+    })?;
+
+    writer.commit().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// Parse each non-empty line of a dump, stopping at the first bad line.
+fn for_each_dump_doc(
+    schema: &IndexSchema,
+    src: &Path,
+    mut f: impl FnMut(TantivyDocument) -> Result<(), String>,
+) -> Result<(), String> {
+    let file = File::open(src).map_err(|e| format!("Failed to open {}: {e}", src.display()))?;
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let doc = TantivyDocument::parse_json(&schema.schema, &line)
+            .map_err(|e| format!("Invalid dump line {}: {e}", i + 1))?;
+        f(doc)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::indexer::{parse_jsonl_to_documents, IndexOptions};
+    use crate::search::queries::{get_conversation_query, search_sessions_query};
+    use crate::search::types::SearchFilter;
+    use std::fs;
+    use tantivy::collector::Count;
+    use tantivy::query::AllQuery;
+    use tantivy::Index;
+
+    fn indexed(schema: &IndexSchema) -> (Index, IndexReader) {
+        let index = Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let reader = index
+            .reader_builder()
+            .reload_policy(tantivy::ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        (index, reader)
+    }
+
+    #[test]
+    fn test_ndjson_round_trip_preserves_docs() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let jsonl = tmp.path().join("dump-1.jsonl");
+        fs::write(
+            &jsonl,
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"migrate the zebra database"},"sessionId":"dump-1","cwd":"/home/user/zoo","timestamp":"2026-02-18T10:00:00Z"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"done migrating"}]},"sessionId":"dump-1","cwd":"/home/user/zoo","timestamp":"2026-02-18T10:01:00Z"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let schema = IndexSchema::new();
+        let (src_index, src_reader) = indexed(&schema);
+        let mut writer = src_index.writer::<TantivyDocument>(50_000_000).unwrap();
+        for doc in parse_jsonl_to_documents(&jsonl, &schema, None, &IndexOptions::default()) {
+            writer.add_document(doc).unwrap();
+        }
+        writer.commit().unwrap();
+        src_reader.reload().unwrap();
+
+        let dump = tmp.path().join("index.ndjson");
+        let exported = export_ndjson(&src_reader, &schema, &dump).unwrap();
+        assert_eq!(exported, 3, "one session doc + two message docs");
+
+        let (dst_index, dst_reader) = indexed(&schema);
+        let mut dst_writer = dst_index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let imported = import_ndjson(&mut dst_writer, &schema, &dump).unwrap();
+        dst_reader.reload().unwrap();
+
+        assert_eq!(imported, exported);
+        assert_eq!(dst_reader.searcher().search(&AllQuery, &Count).unwrap(), 3);
+
+        // content is rebuilt from content_stored, so full-text search still works
+        let filter = SearchFilter {
+            project: Some("/home/user/zoo".into()),
+            ..Default::default()
+        };
+        let hits = search_sessions_query(&dst_reader, &schema, "zebra", Some(&filter)).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "dump-1");
+
+        let conversation = get_conversation_query(&dst_reader, &schema, "dump-1").unwrap();
+        assert_eq!(conversation.len(), 2);
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_line() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dump = tmp.path().join("bad.ndjson");
+        fs::write(&dump, "{\"session_id\":[\"x\"]}\nnot json\n").unwrap();
+
+        let schema = IndexSchema::new();
+        let (index, _) = indexed(&schema);
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        let err = import_ndjson(&mut writer, &schema, &dump).unwrap_err();
+        assert!(err.contains("line 2"), "got: {err}");
+    }
+
+    #[test]
+    fn test_failed_import_keeps_existing_docs() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dump = tmp.path().join("bad.ndjson");
+        fs::write(
+            &dump,
+            "{\"session_id\":[\"x\"]}\nnot json\n{\"session_id\":[\"y\"]}\n",
+        )
+        .unwrap();
+
+        let schema = IndexSchema::new();
+        let (index, reader) = indexed(&schema);
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        for sid in ["keep-1", "keep-2"] {
+            let mut doc = TantivyDocument::new();
+            doc.add_text(schema.session_id, sid);
+            doc.add_text(schema.doc_type, "session");
+            writer.add_document(doc).unwrap();
+        }
+        writer.commit().unwrap();
+
+        assert!(import_ndjson(&mut writer, &schema, &dump).is_err());
+        // Any later commit on the shared writer must not apply a half-done import
+        writer.commit().unwrap();
+        reader.reload().unwrap();
+        assert_eq!(reader.searcher().search(&AllQuery, &Count).unwrap(), 2);
+    }
+}
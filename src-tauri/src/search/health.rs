@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+
+use crate::search::types::BackgroundHealth;
+
+/// How often background threads report in.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Missed heartbeats after which a thread counts as dead.
+const MISSED_BEATS: u64 = 3;
+
+fn now_secs() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn format_secs(secs: i64) -> Option<String> {
+    (secs > 0)
+        .then(|| chrono::DateTime::from_timestamp(secs, 0))
+        .flatten()
+        .map(|d| d.to_rfc3339())
+}
+
+/// Liveness flag of one background thread.
+///
+/// `started` flips once the thread runs; `last_beat` (unix seconds) is
+/// refreshed every `HEARTBEAT_INTERVAL`. A thread that died or hangs simply
+/// stops beating, so liveness is "started and beat recently".
+#[derive(Default)]
+pub struct Heartbeat {
+    started: AtomicBool,
+    last_beat: AtomicI64,
+}
+
+impl Heartbeat {
+    pub fn beat(&self) {
+        self.beat_at(now_secs());
+    }
+
+    fn beat_at(&self, secs: i64) {
+        self.last_beat.store(secs, Ordering::Relaxed);
+        self.started.store(true, Ordering::Relaxed);
+    }
+
+    /// Mark a thread that ended on purpose, so it no longer reports at all.
+    pub fn finish(&self) {
+        self.started.store(false, Ordering::Relaxed);
+    }
+
+    fn is_alive_at(&self, now: i64, max_age: Duration) -> bool {
+        self.started.load(Ordering::Relaxed)
+            && now - self.last_beat.load(Ordering::Relaxed) <= max_age.as_secs() as i64
+    }
+}
+
+/// Heartbeats of the index watcher, merge thread and bulk index, shared via
+/// `IndexHandle`.
+#[derive(Default)]
+pub struct BackgroundHeartbeats {
+    pub watcher: Heartbeat,
+    pub merge: Heartbeat,
+    /// Beats per file while a bulk index runs; finished once it returns
+    pub bulk: Heartbeat,
+    /// Set on shutdown so the watcher ping thread stops rewriting its probe
+    pinger_stopped: AtomicBool,
+    /// Extra slack for the watcher: a poll watcher only notices the probe on its next scan
+    watcher_grace: Duration,
+    last_merge_commit: AtomicI64,
+    last_watcher_event: AtomicI64,
+}
+
+impl BackgroundHeartbeats {
+    pub fn new(watcher_grace: Duration) -> Self {
+        Self {
+            watcher_grace,
+            ..Default::default()
+        }
+    }
+
+    pub fn record_merge_commit(&self) {
+        self.last_merge_commit.store(now_secs(), Ordering::Relaxed);
+    }
+
+    pub fn record_watcher_event(&self) {
+        self.last_watcher_event.store(now_secs(), Ordering::Relaxed);
+    }
+
+    pub fn stop_pinger(&self) {
+        self.pinger_stopped.store(true, Ordering::SeqCst);
+    }
+
+    pub fn pinger_stopped(&self) -> bool {
+        self.pinger_stopped.load(Ordering::SeqCst)
+    }
+
+    /// Snapshot for `get_background_health`.
+    pub fn report(&self) -> BackgroundHealth {
+        self.report_at(now_secs())
+    }
+
+    fn report_at(&self, now: i64) -> BackgroundHealth {
+        let max_age = HEARTBEAT_INTERVAL * MISSED_BEATS as u32;
+        BackgroundHealth {
+            watcher_alive: self.watcher.is_alive_at(now, max_age + self.watcher_grace),
+            merge_thread_alive: self.merge.is_alive_at(now, max_age),
+            bulk_index_alive: self
+                .bulk
+                .started
+                .load(Ordering::Relaxed)
+                .then(|| self.bulk.is_alive_at(now, max_age)),
+            last_merge_commit: format_secs(self.last_merge_commit.load(Ordering::Relaxed)),
+            last_watcher_event: format_secs(self.last_watcher_event.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_heartbeats_are_reported_dead() {
+        let health = BackgroundHeartbeats::new(Duration::from_secs(10));
+        let t0 = 1_771_408_800;
+        assert!(!health.report_at(t0).merge_thread_alive, "never started");
+
+        health.watcher.beat_at(t0);
+        health.merge.beat_at(t0);
+        let fresh = health.report_at(t0 + 60);
+        assert!(fresh.watcher_alive && fresh.merge_thread_alive);
+
+        // Past 3 missed beats the merge thread is dead; the watcher gets its grace
+        let stale = health.report_at(t0 + 95);
+        assert!(!stale.merge_thread_alive);
+        assert!(stale.watcher_alive);
+        assert!(!health.report_at(t0 + 101).watcher_alive);
+        assert_eq!(stale.last_merge_commit, None);
+    }
+
+    #[test]
+    fn test_bulk_heartbeat_reports_only_while_running() {
+        let health = BackgroundHeartbeats::default();
+        let t0 = 1_771_408_800;
+        assert_eq!(health.report_at(t0).bulk_index_alive, None, "no bulk index yet");
+
+        health.bulk.beat_at(t0);
+        assert_eq!(health.report_at(t0 + 60).bulk_index_alive, Some(true));
+        assert_eq!(health.report_at(t0 + 95).bulk_index_alive, Some(false), "stalled");
+
+        health.bulk.finish();
+        assert_eq!(health.report_at(t0 + 95).bulk_index_alive, None);
+    }
+}
@@ -1,10 +1,15 @@
+use crate::atomic_file::write_atomic;
+use crate::search::archive::{apply_archived, persisted_archived_ids};
+use crate::search::doc_ext::DocExt;
+use crate::search::health::Heartbeat;
 use crate::search::schema::{IndexSchema, SCHEMA_VERSION};
-use crate::search::types::IndexMeta;
-use serde::Deserialize;
-use std::collections::HashMap;
+use crate::search::types::{IndexMeta, ReindexEstimate};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use tantivy::schema::Term;
 use tantivy::TantivyDocument;
 
 /// Metadata entry from sessions-index.json.
@@ -27,17 +32,17 @@ pub struct SessionIndexEntry {
     pub project_path: Option<String>,
 }
 
-/// sessions-index.json file structure.
-#[derive(Debug, Deserialize)]
-struct SessionsIndexFile {
-    entries: Vec<SessionIndexEntry>,
-}
-
 /// A single extracted content block from a JSONL message.
 #[derive(Debug, Clone)]
 pub struct ContentBlock {
     pub content_type: String,
     pub text: String,
+    /// `id` of a tool_use block, or the `tool_use_id` a tool_result answers
+    pub tool_use_id: Option<String>,
+}
+
+fn str_field(block: &serde_json::Value, key: &str) -> Option<String> {
+    block.get(key).and_then(|v| v.as_str()).map(str::to_string)
 }
 
 /// Extract content blocks (text, tool_use, tool_result, thinking) from a message content value.
@@ -49,6 +54,7 @@ pub fn extract_content_blocks(content: &serde_json::Value) -> Vec<ContentBlock>
             blocks.push(ContentBlock {
                 content_type: "text".to_string(),
                 text: s.to_string(),
+                tool_use_id: None,
             });
         }
         return blocks;
@@ -64,6 +70,7 @@ pub fn extract_content_blocks(content: &serde_json::Value) -> Vec<ContentBlock>
                         blocks.push(ContentBlock {
                             content_type: "text".to_string(),
                             text: text.to_string(),
+                            tool_use_id: None,
                         });
                     }
                 }
@@ -80,6 +87,7 @@ pub fn extract_content_blocks(content: &serde_json::Value) -> Vec<ContentBlock>
                     blocks.push(ContentBlock {
                         content_type: "tool_use".to_string(),
                         text,
+                        tool_use_id: str_field(block, "id"),
                     });
                 }
                 "tool_result" => {
@@ -98,6 +106,7 @@ pub fn extract_content_blocks(content: &serde_json::Value) -> Vec<ContentBlock>
                         blocks.push(ContentBlock {
                             content_type: "tool_result".to_string(),
                             text,
+                            tool_use_id: str_field(block, "tool_use_id"),
                         });
                     }
                 }
@@ -111,6 +120,7 @@ pub fn extract_content_blocks(content: &serde_json::Value) -> Vec<ContentBlock>
                         blocks.push(ContentBlock {
                             content_type: "thinking".to_string(),
                             text: text.to_string(),
+                            tool_use_id: None,
                         });
                     }
                 }
@@ -122,6 +132,89 @@ pub fn extract_content_blocks(content: &serde_json::Value) -> Vec<ContentBlock>
     blocks
 }
 
+/// Env var that switches indexing to text/thinking blocks only.
+pub const TEXT_ONLY_ENV: &str = "SWARM_UI_INDEX_TEXT_ONLY";
+/// Env var that disables the startup index warm-up.
+pub const SKIP_WARM_UP_ENV: &str = "SWARM_UI_SKIP_INDEX_WARMUP";
+/// Env var selecting the watcher backend: `auto` (default), `native`, `poll`.
+pub const WATCHER_ENV: &str = "SWARM_UI_INDEX_WATCHER";
+/// Env var overriding the poll watcher interval, in seconds.
+pub const POLL_SECS_ENV: &str = "SWARM_UI_INDEX_POLL_SECS";
+/// Env var that coalesces watcher updates into `session:batch_updated`.
+pub const BATCH_UPDATES_ENV: &str = "SWARM_UI_BATCH_UPDATES";
+
+/// Indexing options, read once at startup.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IndexOptions {
+    /// Only index `text` and `thinking` blocks. Tool blocks are skipped, which
+    /// keeps large tool output out of the index; the session's `has_tool_use`
+    /// flag is still set from them.
+    pub text_only: bool,
+    /// Skip priming the page cache after the index opens (see `warm_up_index`).
+    pub skip_warm_up: bool,
+    /// Which filesystem watcher backs incremental indexing.
+    pub watcher: WatcherChoice,
+    /// Poll interval for the poll watcher; `None` uses `DEFAULT_POLL_INTERVAL`.
+    pub poll_interval_secs: Option<u64>,
+    /// Emit watcher updates as `session:batch_updated` every `UPDATE_BATCH_WINDOW`
+    /// instead of one `session:updated` per session.
+    pub batch_updates: bool,
+}
+
+/// Watcher backend selection (`SWARM_UI_INDEX_WATCHER=auto|native|poll`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WatcherChoice {
+    /// Native watcher, falling back to polling if a probe event never arrives.
+    #[default]
+    Auto,
+    /// inotify/FSEvents/ReadDirectoryChanges only.
+    Native,
+    /// Poll the directory tree; works on network mounts and odd containers.
+    Poll,
+}
+
+/// Poll interval when `SWARM_UI_INDEX_POLL_SECS` is unset.
+pub const DEFAULT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+impl IndexOptions {
+    /// Read options from the environment (`SWARM_UI_INDEX_TEXT_ONLY=1`,
+    /// `SWARM_UI_SKIP_INDEX_WARMUP=1`, `SWARM_UI_INDEX_WATCHER=poll`,
+    /// `SWARM_UI_INDEX_POLL_SECS=10`, `SWARM_UI_BATCH_UPDATES=1`).
+    ///
+    /// Toggling text-only on an existing index only affects newly (re)indexed
+    /// sessions — run `reindex_all` to apply it everywhere.
+    pub fn from_env() -> Self {
+        let watcher = match std::env::var(WATCHER_ENV).as_deref() {
+            Ok("native") => WatcherChoice::Native,
+            Ok("poll") => WatcherChoice::Poll,
+            _ => WatcherChoice::Auto,
+        };
+        Self {
+            text_only: env_flag(TEXT_ONLY_ENV),
+            skip_warm_up: env_flag(SKIP_WARM_UP_ENV),
+            watcher,
+            poll_interval_secs: std::env::var(POLL_SECS_ENV)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&secs| secs > 0),
+            batch_updates: env_flag(BATCH_UPDATES_ENV),
+        }
+    }
+
+    /// Effective poll watcher interval.
+    pub fn poll_interval(&self) -> std::time::Duration {
+        self.poll_interval_secs
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(DEFAULT_POLL_INTERVAL)
+    }
+}
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
 /// JSONL entry — minimal fields needed for indexing.
 #[derive(Debug, Deserialize)]
 struct IndexJsonlEntry {
@@ -148,20 +241,87 @@ struct IndexJsonlMessage {
 struct IndexJsonlUsage {
     input_tokens: Option<u64>,
     output_tokens: Option<u64>,
+    cache_creation_input_tokens: Option<u64>,
+    cache_read_input_tokens: Option<u64>,
+}
+
+/// Stable permalink id for a message block: FNV-1a 64 of
+/// `session_id:turn_index:block_index`, as 16 hex chars.
+///
+/// Deterministic across runs and builds (unlike `DefaultHasher`), so ids
+/// survive a reindex as long as the JSONL ordering is unchanged.
+pub fn message_id(session_id: &str, turn_index: u64, block_index: u64) -> String {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let key = format!("{session_id}:{turn_index}:{block_index}");
+    let hash = key
+        .bytes()
+        .fold(OFFSET, |h, b| (h ^ b as u64).wrapping_mul(PRIME));
+    format!("{hash:016x}")
 }
 
 /// Parse a single JSONL file into a Vec of TantivyDocuments.
 /// The first document is always the session document; the rest are message documents.
 ///
 /// `meta` is optional pre-populated metadata from sessions-index.json.
+/// With `options.text_only`, tool blocks produce no message documents.
 pub fn parse_jsonl_to_documents(
     path: &Path,
     schema: &IndexSchema,
     meta: Option<&SessionIndexEntry>,
+    options: &IndexOptions,
 ) -> Vec<TantivyDocument> {
+    parse_jsonl_with_report(path, schema, meta, options).0
+}
+
+/// Line counts of one JSONL parse, for `diagnose_session_file`.
+#[derive(Debug, Default)]
+pub struct ParseReport {
+    /// Non-blank lines
+    pub lines_total: u64,
+    /// Lines that could not be read or are not a JSON entry
+    pub lines_skipped: u64,
+    pub first_error: Option<String>,
+    /// No entry carried a `sessionId` before the first doc, so the file
+    /// stem was used
+    pub session_id_from_stem: bool,
+}
+
+impl ParseReport {
+    fn skip(&mut self, line_no: usize, error: impl std::fmt::Display) {
+        self.lines_skipped += 1;
+        if self.first_error.is_none() {
+            self.first_error = Some(format!("line {line_no}: {error}"));
+        }
+    }
+
+    /// Fall back to the file stem (the session UUID in Claude's layout, as
+    /// `sprite_list_claude_sessions` assumes) when no `sessionId` was seen.
+    fn ensure_session_id(&mut self, session_id: &mut String, path: &Path) {
+        if session_id.is_empty() {
+            *session_id = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            self.session_id_from_stem = true;
+        }
+    }
+}
+
+/// `parse_jsonl_to_documents`, also reporting which lines were skipped.
+pub fn parse_jsonl_with_report(
+    path: &Path,
+    schema: &IndexSchema,
+    meta: Option<&SessionIndexEntry>,
+    options: &IndexOptions,
+) -> (Vec<TantivyDocument>, ParseReport) {
+    let mut report = ParseReport::default();
     let file = match File::open(path) {
         Ok(f) => f,
-        Err(_) => return Vec::new(),
+        Err(e) => {
+            report.first_error = Some(format!("Cannot open {}: {e}", path.display()));
+            return (Vec::new(), report);
+        }
     };
     let reader = BufReader::with_capacity(64 * 1024, file);
 
@@ -171,8 +331,10 @@ pub fn parse_jsonl_to_documents(
     let mut model = String::new();
     let mut input_tokens: u64 = 0;
     let mut output_tokens: u64 = 0;
+    let mut context_tokens: u64 = 0;
     let mut has_tool_use = false;
     let mut first_prompt = String::new();
+    let mut first_reply = String::new();
     let mut summary = String::new();
     let mut first_timestamp: Option<String> = None;
     let mut last_timestamp: Option<String> = None;
@@ -198,18 +360,26 @@ pub fn parse_jsonl_to_documents(
 
     let mut message_docs: Vec<TantivyDocument> = Vec::new();
 
-    for line in reader.lines() {
+    for (line_no, line) in (1..).zip(reader.lines()) {
         let line = match line {
             Ok(l) => l,
-            Err(_) => continue,
+            Err(e) => {
+                report.lines_total += 1;
+                report.skip(line_no, e);
+                continue;
+            }
         };
         if line.trim().is_empty() {
             continue;
         }
+        report.lines_total += 1;
 
         let entry: IndexJsonlEntry = match serde_json::from_str(&line) {
             Ok(e) => e,
-            Err(_) => continue,
+            Err(e) => {
+                report.skip(line_no, e);
+                continue;
+            }
         };
 
         // Extract session-level metadata (first occurrence wins for most fields)
@@ -258,6 +428,10 @@ pub fn parse_jsonl_to_documents(
             if let Some(ot) = usage.output_tokens {
                 output_tokens += ot;
             }
+            // context_tokens = the latest full prompt, cached parts included
+            context_tokens = usage.input_tokens.unwrap_or(0)
+                + usage.cache_creation_input_tokens.unwrap_or(0)
+                + usage.cache_read_input_tokens.unwrap_or(0);
         }
 
         let role = msg.role.as_deref().unwrap_or("unknown");
@@ -292,9 +466,26 @@ pub fn parse_jsonl_to_documents(
                 }
             }
 
+            // Likewise the first assistant text, for list previews
+            if first_reply.is_empty() && role == "assistant" {
+                if let Some(b) = blocks
+                    .iter()
+                    .find(|b| b.content_type == "text" && !b.text.trim().is_empty())
+                {
+                    first_reply = truncate_at_char_boundary(&b.text, 500);
+                }
+            }
+
             for block in &blocks {
                 if block.content_type == "tool_use" || block.content_type == "tool_result" {
                     has_tool_use = true;
+                    if options.text_only {
+                        // Keep block_index and message_count independent of
+                        // the option so positions and metrics match the JSONL
+                        block_index += 1;
+                        message_count += 1;
+                        continue;
+                    }
                 }
 
                 let content_stored = if block.text.len() > 500 {
@@ -305,11 +496,13 @@ pub fn parse_jsonl_to_documents(
 
                 let timestamp_str = entry.timestamp.as_deref().unwrap_or("");
 
+                report.ensure_session_id(&mut session_id, path);
                 let mut doc = TantivyDocument::new();
                 doc.add_text(schema.session_id, &session_id);
                 doc.add_text(schema.doc_type, "message");
                 doc.add_text(schema.role, role);
                 doc.add_text(schema.content, &block.text);
+                doc.add_text(schema.content_cased, &block.text);
                 doc.add_text(schema.content_stored, &content_stored);
                 doc.add_text(schema.content_type, &block.content_type);
                 if let Some(dt) = parse_timestamp(timestamp_str) {
@@ -318,6 +511,10 @@ pub fn parse_jsonl_to_documents(
                 doc.add_u64(schema.turn_index, turn_index);
                 doc.add_u64(schema.block_index, block_index);
                 doc.add_text(schema.msg_project, &cwd);
+                doc.add_text(
+                    schema.message_id,
+                    message_id(&session_id, turn_index, block_index),
+                );
 
                 message_docs.push(doc);
                 block_index += 1;
@@ -337,6 +534,7 @@ pub fn parse_jsonl_to_documents(
     let jsonl_path = path.to_string_lossy().to_string();
 
     // Build session document
+    report.ensure_session_id(&mut session_id, path);
     let mut session_doc = TantivyDocument::new();
     session_doc.add_text(schema.session_id, &session_id);
     session_doc.add_text(schema.doc_type, "session");
@@ -344,6 +542,7 @@ pub fn parse_jsonl_to_documents(
     session_doc.add_text(schema.project_raw, &cwd);
     session_doc.add_text(schema.summary, &summary);
     session_doc.add_text(schema.first_prompt, &first_prompt);
+    session_doc.add_text(schema.first_reply, &first_reply);
     session_doc.add_text(schema.git_branch, &git_branch);
     session_doc.add_text(schema.model, &model);
     session_doc.add_text(schema.status, &status);
@@ -352,6 +551,7 @@ pub fn parse_jsonl_to_documents(
     session_doc.add_u64(schema.input_tokens, input_tokens);
     session_doc.add_u64(schema.output_tokens, output_tokens);
     session_doc.add_u64(schema.total_tokens, total_tokens);
+    session_doc.add_u64(schema.context_tokens, context_tokens);
 
     // Parse timestamps
     if let Some(ref ts) = first_timestamp {
@@ -380,13 +580,14 @@ pub fn parse_jsonl_to_documents(
     session_doc.add_bool(schema.archived, false);
     session_doc.add_bool(schema.file_exists, true);
     session_doc.add_bool(schema.has_tool_use, has_tool_use);
+    session_doc.add_bool(schema.messages_pruned, false);
     session_doc.add_u64(schema.turn_depth, turn_index);
 
     // Session doc first, then message docs
     let mut docs = Vec::with_capacity(1 + message_docs.len());
     docs.push(session_doc);
     docs.append(&mut message_docs);
-    docs
+    (docs, report)
 }
 
 /// Load all sessions-index.json files under a projects directory.
@@ -401,27 +602,138 @@ pub fn load_all_index_files(projects_dir: &Path) -> HashMap<String, SessionIndex
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if !path.is_dir() {
-            continue;
+        if path.is_dir() {
+            map.extend(load_index_file(&path));
         }
-        let index_path = path.join("sessions-index.json");
-        if !index_path.exists() {
-            continue;
+    }
+
+    map
+}
+
+/// Load one project directory's sessions-index.json, keyed by session_id.
+///
+/// Streams the file through a `BufReader`, so a large index is never held
+/// as a whole string alongside its parsed entries.
+pub fn load_index_file(project_dir: &Path) -> HashMap<String, SessionIndexEntry> {
+    let index_path = project_dir.join("sessions-index.json");
+    let file = match File::open(&index_path) {
+        Ok(f) => f,
+        Err(_) => return HashMap::new(),
+    };
+    read_index_entries(BufReader::new(file), &index_path)
+        .into_iter()
+        .map(|entry| (entry.session_id.clone(), entry))
+        .collect()
+}
+
+/// One element of `entries`: a well-formed entry, or anything else (skipped).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LenientEntry {
+    Entry(Box<SessionIndexEntry>),
+    Malformed(serde::de::IgnoredAny),
+}
+
+#[derive(Deserialize)]
+struct IndexFile {
+    entries: Option<Vec<LenientEntry>>,
+}
+
+/// Parse the `entries` array of a sessions-index.json leniently, while reading.
+///
+/// Each entry is deserialized on its own, so one malformed entry only drops
+/// that entry instead of the whole file's curated metadata. Dropped entries
+/// are counted and logged.
+fn read_index_entries(reader: impl std::io::Read, index_path: &Path) -> Vec<SessionIndexEntry> {
+    let raw_entries = match serde_json::from_reader::<_, IndexFile>(reader) {
+        Ok(IndexFile { entries: Some(e) }) => e,
+        Ok(IndexFile { entries: None }) => {
+            tracing::warn!("{} has no entries array", index_path.display());
+            return Vec::new();
         }
-        let content = match fs::read_to_string(&index_path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-        let index_file: SessionsIndexFile = match serde_json::from_str(&content) {
-            Ok(f) => f,
-            Err(_) => continue,
+        Err(e) => {
+            tracing::warn!("Unreadable {}: {e}", index_path.display());
+            return Vec::new();
+        }
+    };
+
+    let total = raw_entries.len();
+    let entries: Vec<SessionIndexEntry> = raw_entries
+        .into_iter()
+        .filter_map(|raw| match raw {
+            LenientEntry::Entry(entry) => Some(*entry),
+            LenientEntry::Malformed(_) => None,
+        })
+        .collect();
+    let dropped = total - entries.len();
+    if dropped > 0 {
+        tracing::warn!(
+            "Dropped {dropped} malformed entries (of {total}) in {}",
+            index_path.display()
+        );
+    }
+    entries
+}
+
+/// Re-index every JSONL file in one project directory, in a single commit.
+///
+/// Every indexed session whose `jsonl_path` lies under `project_dir` is
+/// deleted first, so sessions whose files were since removed or renamed drop
+/// out; then each file on disk is parsed and added. Sessions of other projects
+/// are untouched. Returns the number of sessions reindexed.
+pub fn reindex_project_dir(
+    writer: &mut tantivy::IndexWriter,
+    reader: &tantivy::IndexReader,
+    schema: &IndexSchema,
+    project_dir: &Path,
+    options: &IndexOptions,
+) -> tantivy::Result<u64> {
+    reader.reload()?;
+    for session_id in indexed_sessions_under(&reader.searcher(), schema, project_dir)? {
+        writer.delete_term(Term::from_field_text(schema.session_id, &session_id));
+    }
+
+    let meta = load_index_file(project_dir);
+    let archived = persisted_archived_ids();
+    let mut count = 0;
+    for path in discover_jsonl_files(project_dir) {
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+        let mut docs = parse_jsonl_to_documents(&path, schema, meta.get(stem), options);
+        apply_archived(&mut docs, schema, &archived);
+        let session_id = match docs.first().and_then(|d| d.get_str(schema.session_id)) {
+            Some(sid) if !sid.is_empty() => sid.to_string(),
+            _ => continue,
         };
-        for entry in index_file.entries {
-            map.insert(entry.session_id.clone(), entry);
+        writer.delete_term(tantivy::Term::from_field_text(schema.session_id, &session_id));
+        for doc in docs {
+            writer.add_document(doc)?;
         }
+        count += 1;
     }
+    writer.commit()?;
+    Ok(count)
+}
 
-    map
+/// Ids of indexed sessions whose `jsonl_path` is inside `dir`.
+fn indexed_sessions_under(
+    searcher: &tantivy::Searcher,
+    schema: &IndexSchema,
+    dir: &Path,
+) -> tantivy::Result<Vec<String>> {
+    let sessions = tantivy::query::TermQuery::new(
+        Term::from_field_text(schema.doc_type, "session"),
+        tantivy::schema::IndexRecordOption::Basic,
+    );
+    let mut ids = Vec::new();
+    for addr in searcher.search(&sessions, &tantivy::collector::DocSetCollector)? {
+        let doc: TantivyDocument = searcher.doc(addr)?;
+        let under = doc.get_str(schema.jsonl_path).is_some_and(|p| Path::new(p).starts_with(dir));
+        match doc.get_str(schema.session_id) {
+            Some(sid) if under && !sid.is_empty() => ids.push(sid.to_string()),
+            _ => {}
+        }
+    }
+    Ok(ids)
 }
 
 /// Recursively discover all .jsonl files under a projects directory.
@@ -447,62 +759,288 @@ fn discover_jsonl_recursive(dir: &Path, files: &mut Vec<PathBuf>) {
     }
 }
 
+/// Discover .jsonl files under each of `session_dirs`, one per session: a
+/// session id (file stem) found in several directories keeps its most recently
+/// modified file.
+pub fn discover_session_files(session_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut newest: HashMap<String, (Option<std::time::SystemTime>, PathBuf)> = HashMap::new();
+    for path in session_dirs.iter().flat_map(|dir| discover_jsonl_files(dir)) {
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_string();
+        let mtime = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        match newest.get(&stem) {
+            Some((seen, _)) if *seen >= mtime => {}
+            _ => {
+                newest.insert(stem, (mtime, path));
+            }
+        }
+    }
+    newest.into_values().map(|(_, path)| path).collect()
+}
+
+/// Throughput `estimate_reindex` assumes until a bulk index has been timed.
+pub const DEFAULT_INDEX_BYTES_PER_SEC: u64 = 20 * 1024 * 1024;
+
+/// How long a full reindex of `session_dirs` should take at `bytes_per_sec`,
+/// from the files `bulk_index` would parse and their sizes.
+pub fn estimate_reindex(session_dirs: &[PathBuf], bytes_per_sec: u64) -> ReindexEstimate {
+    let files = discover_session_files(session_dirs);
+    let total_bytes: u64 = files
+        .iter()
+        .filter_map(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum();
+    ReindexEstimate {
+        file_count: files.len() as u64,
+        total_bytes,
+        estimated_seconds: total_bytes as f64 / bytes_per_sec.max(1) as f64,
+    }
+}
+
+/// Newest modification time among all .jsonl files under a projects directory.
+pub fn newest_jsonl_mtime(projects_dir: &Path) -> Option<std::time::SystemTime> {
+    discover_jsonl_files(projects_dir)
+        .iter()
+        .filter_map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+/// Read swarm-ui-meta.json, or `None` if missing or unparseable.
+pub fn read_index_meta(index_path: &Path) -> Option<IndexMeta> {
+    let content = fs::read_to_string(index_path.join("swarm-ui-meta.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
 /// Check whether the on-disk index has a schema version mismatch.
 /// Returns `true` if a reindex is needed (missing file or version mismatch).
 pub fn schema_version_mismatch(index_path: &Path) -> bool {
-    let meta_path = index_path.join("swarm-ui-meta.json");
-    let content = match fs::read_to_string(&meta_path) {
-        Ok(c) => c,
-        Err(_) => return true,
-    };
-    let meta: IndexMeta = match serde_json::from_str(&content) {
-        Ok(m) => m,
-        Err(_) => return true,
-    };
-    meta.schema_version != SCHEMA_VERSION
+    match read_index_meta(index_path) {
+        Some(meta) => meta.schema_version != SCHEMA_VERSION,
+        None => true,
+    }
 }
 
 /// Write index metadata to swarm-ui-meta.json.
-pub fn write_index_meta(index_path: &Path, session_count: u64) -> std::io::Result<()> {
+pub fn write_index_meta(
+    index_path: &Path,
+    session_count: u64,
+    bytes_per_sec: Option<u64>,
+) -> std::io::Result<()> {
     let meta = IndexMeta {
         schema_version: SCHEMA_VERSION,
         indexed_at: chrono::Utc::now().to_rfc3339(),
         session_count,
+        bytes_per_sec,
     };
     let json = serde_json::to_string_pretty(&meta)
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-    fs::write(index_path.join("swarm-ui-meta.json"), json)
+    write_atomic(&index_path.join("swarm-ui-meta.json"), json)
+}
+
+/// Config in the index directory listing session directories to index and
+/// watch besides `~/.claude/projects` (e.g. a shared mount).
+pub const EXTRA_DIRS_FILE: &str = "extra-dirs.json";
+
+/// The extra session directories from `EXTRA_DIRS_FILE`; empty if the file is
+/// missing or malformed.
+pub fn read_extra_dirs(index_path: &Path) -> Vec<PathBuf> {
+    let path = index_path.join(EXTRA_DIRS_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring malformed {}: {e}", path.display());
+        Vec::new()
+    })
+}
+
+pub fn write_extra_dirs(index_path: &Path, dirs: &[PathBuf]) -> std::io::Result<()> {
+    let json = serde_json::to_vec_pretty(dirs).map_err(std::io::Error::other)?;
+    write_atomic(&index_path.join(EXTRA_DIRS_FILE), json)
+}
+
+/// `projects_dir` followed by the extra directories, without repeats.
+pub fn session_dirs(projects_dir: PathBuf, extra_dirs: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut dirs = vec![projects_dir];
+    for dir in extra_dirs {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// Sidecar in the index directory recording an unfinished bulk index.
+pub const BULK_PROGRESS_FILE: &str = "bulk-progress.json";
+
+/// Files between intermediate bulk commits (each followed by a sidecar write).
+const BULK_COMMIT_EVERY: u64 = 2_000;
+
+/// What an interrupted `bulk_index` had committed, so a restart can skip it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BulkProgress {
+    schema_version: u64,
+    session_count: u64,
+    #[serde(default)]
+    failed_files: u64,
+    /// JSONL paths whose docs are committed (including ones that yielded none
+    /// or failed, which would only fail again)
+    completed: Vec<String>,
+}
+
+/// What `bulk_index` indexed, including earlier interrupted runs it resumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkIndexSummary {
+    pub session_count: u64,
+    /// Files whose parse panicked; logged and skipped
+    pub failed_files: u64,
+    /// JSONL bytes indexed per second by this run; `None` if it had no files
+    pub bytes_per_sec: Option<u64>,
 }
 
-/// Bulk index all JSONL files using rayon for parallel parsing
-/// and crossbeam_channel for feeding documents to the writer.
+fn read_bulk_progress(index_path: &Path) -> Option<BulkProgress> {
+    let content = fs::read_to_string(index_path.join(BULK_PROGRESS_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Write via a temp file and rename, so a crash never leaves a torn sidecar.
+fn write_bulk_progress(index_path: &Path, progress: &BulkProgress) -> std::io::Result<()> {
+    let json = serde_json::to_vec(progress).map_err(std::io::Error::other)?;
+    write_atomic(&index_path.join(BULK_PROGRESS_FILE), json)
+}
+
+/// Whether `index_path` holds an interrupted bulk index of the current
+/// schema, which `bulk_index` can resume instead of starting over.
+pub fn bulk_index_resumable(index_path: &Path) -> bool {
+    read_bulk_progress(index_path).is_some_and(|p| p.schema_version == SCHEMA_VERSION)
+}
+
+/// Bulk index all JSONL files under `session_dirs` using rayon for parallel
+/// parsing and crossbeam_channel for feeding documents to the writer.
+///
+/// A session in several directories is indexed once (see
+/// `discover_session_files`); should two files still carry the same session
+/// id, the later one replaces the earlier.
 ///
 /// `app_handle` is optional — when provided, emits `index:progress` events.
+///
+/// With `index_path`, the run is resumable: it commits every
+/// `BULK_COMMIT_EVERY` files and records the committed files in
+/// `BULK_PROGRESS_FILE`. A later call skips those files (re-adding a session
+/// replaces its docs, in case a commit landed without its sidecar write) and
+/// removes the sidecar once everything is committed.
+///
+/// A file whose parse panics is logged and skipped instead of aborting the
+/// run; the summary counts them.
+///
+/// `heartbeat`, when given, beats once per indexed file and is finished when
+/// the run returns, so `get_background_health` can tell a stalled bulk index
+/// from a slow one.
 pub fn bulk_index(
     writer: &mut tantivy::IndexWriter,
     schema: &IndexSchema,
-    projects_dir: &Path,
+    session_dirs: &[PathBuf],
+    options: &IndexOptions,
+    app_handle: Option<&tauri::AppHandle>,
+    index_path: Option<&Path>,
+    heartbeat: Option<&Heartbeat>,
+) -> Result<BulkIndexSummary, Box<dyn std::error::Error + Send + Sync>> {
+    bulk_index_with(
+        writer,
+        schema,
+        session_dirs,
+        options,
+        app_handle,
+        index_path,
+        heartbeat,
+        parse_jsonl_to_documents,
+    )
+}
+
+/// Finishes the bulk heartbeat however `bulk_index_with` returns.
+struct FinishOnDrop<'a>(Option<&'a Heartbeat>);
+
+impl FinishOnDrop<'_> {
+    fn beat(&self) {
+        if let Some(heartbeat) = self.0 {
+            heartbeat.beat();
+        }
+    }
+}
+
+impl Drop for FinishOnDrop<'_> {
+    fn drop(&mut self) {
+        if let Some(heartbeat) = self.0 {
+            heartbeat.finish();
+        }
+    }
+}
+
+/// Per-file parser of `bulk_index`; swapped out by tests.
+type ParseFn = fn(&Path, &IndexSchema, Option<&SessionIndexEntry>, &IndexOptions)
+    -> Vec<TantivyDocument>;
+
+#[allow(clippy::too_many_arguments)]
+fn bulk_index_with(
+    writer: &mut tantivy::IndexWriter,
+    schema: &IndexSchema,
+    session_dirs: &[PathBuf],
+    options: &IndexOptions,
     app_handle: Option<&tauri::AppHandle>,
-) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    index_path: Option<&Path>,
+    heartbeat: Option<&Heartbeat>,
+    parse: ParseFn,
+) -> Result<BulkIndexSummary, Box<dyn std::error::Error + Send + Sync>> {
     use crossbeam_channel::bounded;
     use rayon::prelude::*;
 
-    // Phase 1: Discover JSONL files
+    let started = std::time::Instant::now();
+    let heartbeat = FinishOnDrop(heartbeat);
+    heartbeat.beat();
+    let previous = index_path
+        .and_then(read_bulk_progress)
+        .filter(|p| p.schema_version == SCHEMA_VERSION);
+    let resuming = previous.is_some();
+    let mut progress = previous.unwrap_or(BulkProgress {
+        schema_version: SCHEMA_VERSION,
+        ..Default::default()
+    });
+
+    // Phase 1: Discover JSONL files, minus the ones an earlier run committed
     emit_progress(app_handle, "discovering", 0, 0);
-    let jsonl_files = discover_jsonl_files(projects_dir);
+    let done: HashSet<&str> = progress.completed.iter().map(String::as_str).collect();
+    let jsonl_files: Vec<PathBuf> = discover_session_files(session_dirs)
+        .into_iter()
+        .filter(|p| !p.to_str().is_some_and(|s| done.contains(s)))
+        .collect();
+    drop(done);
     let total = jsonl_files.len() as u64;
+    if resuming {
+        tracing::info!(
+            "Resuming bulk index: {} files done, {total} remaining",
+            progress.completed.len()
+        );
+    }
 
     if total == 0 {
-        return Ok(0);
+        if let Some(path) = index_path {
+            let _ = fs::remove_file(path.join(BULK_PROGRESS_FILE));
+        }
+        return Ok(progress.summary());
     }
 
     // Phase 2: Load sessions-index.json metadata
     emit_progress(app_handle, "loading_metadata", 0, total);
-    let index_meta = load_all_index_files(projects_dir);
+    let index_meta: HashMap<String, SessionIndexEntry> = session_dirs
+        .iter()
+        .flat_map(|dir| load_all_index_files(dir))
+        .collect();
+    let archived = persisted_archived_ids();
 
     // Phase 3: Parallel parse + channel -> writer
-    let (sender, receiver) = bounded::<Vec<TantivyDocument>>(64);
+    // `None` docs: the parse panicked
+    let (sender, receiver) = bounded::<(PathBuf, Option<Vec<TantivyDocument>>)>(64);
     let schema_clone = schema.clone();
+    let options = *options;
 
     let producer = std::thread::spawn(move || {
         jsonl_files.par_iter().for_each(|path| {
@@ -511,26 +1049,57 @@ pub fn bulk_index(
                 .and_then(|s| s.to_str())
                 .unwrap_or("");
             let meta = index_meta.get(file_stem);
-            let docs = parse_jsonl_to_documents(path, &schema_clone, meta);
-            if !docs.is_empty() {
-                let _ = sender.send(docs);
-            }
+            let parsed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                parse(path, &schema_clone, meta, &options)
+            }));
+            let docs = parsed.ok().map(|mut docs| {
+                apply_archived(&mut docs, &schema_clone, &archived);
+                docs
+            });
+            // Empty and failed results are sent too, so the file counts as completed
+            let _ = sender.send((path.clone(), docs));
         });
         drop(sender);
     });
 
     // Consumer: write docs to index
-    let mut session_count: u64 = 0;
     let mut processed: u64 = 0;
-    for docs in receiver {
-        for doc in docs {
-            writer.add_document(doc)?;
+    let mut bytes: u64 = 0;
+    let mut seen: HashSet<String> = HashSet::new();
+    for (path, docs) in receiver {
+        heartbeat.beat();
+        bytes += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let Some(docs) = docs else {
+            tracing::warn!("Skipping {}: parsing panicked", path.display());
+            progress.failed_files += 1;
+            progress.completed.push(path.to_string_lossy().into_owned());
+            processed += 1;
+            continue;
+        };
+        if let Some(first) = docs.first() {
+            let sid = first.get_str(schema.session_id);
+            let repeat = sid.is_some_and(|sid| !seen.insert(sid.to_string()));
+            if let Some(sid) = sid.filter(|_| resuming || repeat) {
+                writer.delete_term(Term::from_field_text(schema.session_id, sid));
+            }
+            for doc in docs {
+                writer.add_document(doc)?;
+            }
+            if !repeat {
+                progress.session_count += 1;
+            }
         }
-        session_count += 1;
+        progress.completed.push(path.to_string_lossy().into_owned());
         processed += 1;
         if processed % 500 == 0 {
             emit_progress(app_handle, "indexing", processed, total);
         }
+        if let Some(path) = index_path {
+            if processed.is_multiple_of(BULK_COMMIT_EVERY) {
+                writer.commit()?;
+                write_bulk_progress(path, &progress)?;
+            }
+        }
     }
 
     producer.join().map_err(|_| "Producer thread panicked")?;
@@ -538,8 +1107,28 @@ pub fn bulk_index(
     // Phase 4: Commit
     emit_progress(app_handle, "committing", total, total);
     writer.commit()?;
+    if let Some(path) = index_path {
+        let _ = fs::remove_file(path.join(BULK_PROGRESS_FILE));
+    }
+    if progress.failed_files > 0 {
+        tracing::warn!("Bulk index skipped {} unparseable files", progress.failed_files);
+    }
+
+    let elapsed = started.elapsed().as_secs_f64();
+    Ok(BulkIndexSummary {
+        bytes_per_sec: (elapsed > 0.0).then(|| (bytes as f64 / elapsed) as u64),
+        ..progress.summary()
+    })
+}
 
-    Ok(session_count)
+impl BulkProgress {
+    fn summary(&self) -> BulkIndexSummary {
+        BulkIndexSummary {
+            session_count: self.session_count,
+            failed_files: self.failed_files,
+            bytes_per_sec: None,
+        }
+    }
 }
 
 fn emit_progress(app_handle: Option<&tauri::AppHandle>, phase: &str, current: u64, total: u64) {
@@ -598,7 +1187,6 @@ fn truncate_at_char_boundary(s: &str, max_bytes: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::search::doc_ext::DocExt;
     use std::io::Write;
     use tempfile::TempDir;
 
@@ -628,7 +1216,7 @@ mod tests {
         let path = write_fixture(tmp.path(), "test-uuid-1.jsonl", fixture_jsonl());
         let schema = IndexSchema::new();
 
-        let docs = parse_jsonl_to_documents(&path, &schema, None);
+        let docs = parse_jsonl_to_documents(&path, &schema, None, &IndexOptions::default());
 
         // 1 session doc + message docs (1 text user + 2 assistant text+tool_use + 1 tool_result + 1 thinking)
         assert!(!docs.is_empty(), "Should produce at least 1 doc");
@@ -654,7 +1242,7 @@ mod tests {
         let path = write_fixture(tmp.path(), "test-uuid-1.jsonl", fixture_jsonl());
         let schema = IndexSchema::new();
 
-        let docs = parse_jsonl_to_documents(&path, &schema, None);
+        let docs = parse_jsonl_to_documents(&path, &schema, None, &IndexOptions::default());
 
         let content_types: Vec<&str> = docs
             .iter()
@@ -674,7 +1262,7 @@ mod tests {
         let path = write_fixture(tmp.path(), "test-uuid-1.jsonl", fixture_jsonl());
         let schema = IndexSchema::new();
 
-        let docs = parse_jsonl_to_documents(&path, &schema, None);
+        let docs = parse_jsonl_to_documents(&path, &schema, None, &IndexOptions::default());
         let session_doc = &docs[0];
 
         assert_eq!(
@@ -684,13 +1272,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_captures_first_reply() {
+        let tmp = TempDir::new().unwrap();
+        let path = write_fixture(
+            tmp.path(),
+            "reply-1.jsonl",
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"What is in this repo?"},"sessionId":"reply-1","timestamp":"2026-02-18T10:00:00Z"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"thinking","thinking":"look around"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]},"sessionId":"reply-1","timestamp":"2026-02-18T10:00:01Z"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"It is a Tauri app."}]},"sessionId":"reply-1","timestamp":"2026-02-18T10:00:02Z"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Anything else?"}]},"sessionId":"reply-1","timestamp":"2026-02-18T10:00:03Z"}"#,
+                "\n",
+            ),
+        );
+        let schema = IndexSchema::new();
+
+        let docs = parse_jsonl_to_documents(&path, &schema, None, &IndexOptions::default());
+
+        // Thinking and tool_use blocks are skipped in favour of the first text
+        assert_eq!(docs[0].get_str(schema.first_reply), Some("It is a Tauri app."));
+        assert_eq!(docs[0].get_str(schema.first_prompt), Some("What is in this repo?"));
+    }
+
+    #[test]
+    fn test_parse_text_only_skips_tool_blocks() {
+        let tmp = TempDir::new().unwrap();
+        let path = write_fixture(tmp.path(), "test-uuid-1.jsonl", fixture_jsonl());
+        let schema = IndexSchema::new();
+        let options = IndexOptions {
+            text_only: true,
+            ..Default::default()
+        };
+
+        let docs = parse_jsonl_to_documents(&path, &schema, None, &options);
+
+        let content_types: Vec<&str> = docs
+            .iter()
+            .skip(1)
+            .filter_map(|d| d.get_str(schema.content_type))
+            .collect();
+        assert_eq!(content_types, vec!["text", "text", "thinking"]);
+        assert_eq!(
+            docs[0].get_bool_val(schema.has_tool_use),
+            Some(true),
+            "has_tool_use should still come from the skipped tool blocks"
+        );
+    }
+
+    #[test]
+    fn test_message_id_is_stable_and_distinct() {
+        assert_eq!(message_id("s1", 2, 0), message_id("s1", 2, 0));
+        assert_ne!(message_id("s1", 2, 0), message_id("s1", 2, 1));
+        assert_ne!(message_id("s1", 12, 0), message_id("s11", 2, 0));
+        assert_eq!(message_id("s1", 0, 0).len(), 16);
+    }
+
     #[test]
     fn test_parse_aggregates_tokens() {
         let tmp = TempDir::new().unwrap();
         let path = write_fixture(tmp.path(), "test-uuid-1.jsonl", fixture_jsonl());
         let schema = IndexSchema::new();
 
-        let docs = parse_jsonl_to_documents(&path, &schema, None);
+        let docs = parse_jsonl_to_documents(&path, &schema, None, &IndexOptions::default());
         let session_doc = &docs[0];
 
         // input_tokens should be the LATEST: 250 (from last entry)
@@ -703,6 +1350,28 @@ mod tests {
         assert_eq!(session_doc.get_u64_val(schema.total_tokens), Some(405));
     }
 
+    #[test]
+    fn test_parse_context_tokens_include_cache() {
+        let tmp = TempDir::new().unwrap();
+        let path = write_fixture(
+            tmp.path(),
+            "ctx-1.jsonl",
+            concat!(
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"First."}],"usage":{"input_tokens":10,"output_tokens":5,"cache_read_input_tokens":90000}},"sessionId":"ctx-1","timestamp":"2026-02-18T10:00:00Z"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Second."}],"usage":{"input_tokens":20,"output_tokens":5,"cache_creation_input_tokens":3000,"cache_read_input_tokens":120000}},"sessionId":"ctx-1","timestamp":"2026-02-18T10:00:01Z"}"#,
+                "\n",
+            ),
+        );
+        let schema = IndexSchema::new();
+
+        let docs = parse_jsonl_to_documents(&path, &schema, None, &IndexOptions::default());
+
+        // Latest response only: 20 + 3000 + 120000
+        assert_eq!(docs[0].get_u64_val(schema.context_tokens), Some(123_020));
+        assert_eq!(docs[0].get_u64_val(schema.input_tokens), Some(20));
+    }
+
     #[test]
     fn test_parse_uses_index_metadata() {
         let tmp = TempDir::new().unwrap();
@@ -721,7 +1390,7 @@ mod tests {
             project_path: None,
         };
 
-        let docs = parse_jsonl_to_documents(&path, &schema, Some(&meta));
+        let docs = parse_jsonl_to_documents(&path, &schema, Some(&meta), &IndexOptions::default());
         let session_doc = &docs[0];
 
         assert_eq!(
@@ -736,6 +1405,367 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_load_index_file_skips_only_malformed_entries() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("sessions-index.json"),
+            r#"{"entries":[
+                {"sessionId":"good-1","summary":"Kept summary","gitBranch":"main"},
+                {"sessionId":42,"summary":["not","a","string"]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let map = load_index_file(tmp.path());
+
+        assert_eq!(map.len(), 1);
+        let good = &map["good-1"];
+        assert_eq!(good.summary.as_deref(), Some("Kept summary"));
+        assert_eq!(good.git_branch.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_load_index_file_streaming_matches_value_parse() {
+        let tmp = TempDir::new().unwrap();
+        let content = r#"{"version":1,"entries":[
+            {"sessionId":"a","summary":"First","messageCount":3,"gitBranch":"main"},
+            {"sessionId":"b","firstPrompt":"Fix the build","projectPath":"/p","extra":{"x":1}},
+            "not an entry",
+            {"sessionId":"c","created":"2026-02-18T10:00:00Z","modified":"2026-02-18T11:00:00Z"},
+            {"summary":"no id"}
+        ]}"#;
+        fs::write(tmp.path().join("sessions-index.json"), content).unwrap();
+
+        // The previous approach: whole-file Value, then entry by entry
+        let value: serde_json::Value = serde_json::from_str(content).unwrap();
+        let expected: Vec<SessionIndexEntry> = value["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|raw| SessionIndexEntry::deserialize(raw).ok())
+            .collect();
+
+        let map = load_index_file(tmp.path());
+
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(map.len(), 3);
+        for entry in expected {
+            let got = &map[&entry.session_id];
+            assert_eq!(format!("{got:?}"), format!("{entry:?}"));
+        }
+    }
+
+    fn session_line(session_id: &str, text: &str) -> String {
+        format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"{text}"}},"timestamp":"2026-02-18T10:00:00Z","sessionId":"{session_id}"}}"#
+        ) + "\n"
+    }
+
+    fn message_count(index: &tantivy::Index, schema: &IndexSchema, session_id: &str) -> usize {
+        use tantivy::collector::Count;
+        use tantivy::query::TermQuery;
+        let reader = index.reader().unwrap();
+        let query = TermQuery::new(
+            tantivy::Term::from_field_text(schema.session_id, session_id),
+            tantivy::schema::IndexRecordOption::Basic,
+        );
+        // Subtract the session doc itself
+        reader.searcher().search(&query, &Count).unwrap() - 1
+    }
+
+    #[test]
+    fn test_reindex_project_dir_leaves_other_projects_untouched() {
+        let tmp = TempDir::new().unwrap();
+        let proj_a = tmp.path().join("-home-u-a");
+        let proj_b = tmp.path().join("-home-u-b");
+        fs::create_dir_all(&proj_a).unwrap();
+        fs::create_dir_all(&proj_b).unwrap();
+        let file_a = write_fixture(&proj_a, "sa.jsonl", &session_line("sa", "first"));
+        let file_b = write_fixture(&proj_b, "sb.jsonl", &session_line("sb", "first"));
+
+        let schema = IndexSchema::new();
+        let index = tantivy::Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer(50_000_000).unwrap();
+        let options = IndexOptions::default();
+        let dirs = [tmp.path().to_path_buf()];
+        bulk_index(&mut writer, &schema, &dirs, &options, None, None, None).unwrap();
+        assert_eq!(message_count(&index, &schema, "sa"), 1);
+        assert_eq!(message_count(&index, &schema, "sb"), 1);
+
+        // Both files grow on disk, but only project A is reindexed
+        let grow = |path: &Path, sid: &str| {
+            let mut f = fs::OpenOptions::new().append(true).open(path).unwrap();
+            f.write_all(session_line(sid, "second").as_bytes()).unwrap();
+        };
+        grow(&file_a, "sa");
+        grow(&file_b, "sb");
+
+        let reader = index.reader().unwrap();
+        let count = reindex_project_dir(&mut writer, &reader, &schema, &proj_a, &options).unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(message_count(&index, &schema, "sa"), 2, "project A picks up the new message");
+        assert_eq!(message_count(&index, &schema, "sb"), 1, "project B is untouched");
+    }
+
+    #[test]
+    fn test_reindex_project_dir_drops_docs_of_removed_files() {
+        use tantivy::collector::Count;
+        use tantivy::query::TermQuery;
+
+        let tmp = TempDir::new().unwrap();
+        let proj = tmp.path().join("-home-u-a");
+        fs::create_dir_all(&proj).unwrap();
+        write_fixture(&proj, "keep.jsonl", &session_line("keep", "first"));
+        let gone = write_fixture(&proj, "gone.jsonl", &session_line("gone", "first"));
+
+        let schema = IndexSchema::new();
+        let index = tantivy::Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer(50_000_000).unwrap();
+        let options = IndexOptions::default();
+        let dirs = [tmp.path().to_path_buf()];
+        bulk_index(&mut writer, &schema, &dirs, &options, None, None, None).unwrap();
+        assert_eq!(message_count(&index, &schema, "gone"), 1);
+
+        // Deleted on disk, e.g. by Claude's cleanup or a rename
+        fs::remove_file(&gone).unwrap();
+        let reader = index.reader().unwrap();
+        let count = reindex_project_dir(&mut writer, &reader, &schema, &proj, &options).unwrap();
+
+        assert_eq!(count, 1);
+        reader.reload().unwrap();
+        let docs_of = |sid: &str| {
+            let query = TermQuery::new(
+                tantivy::Term::from_field_text(schema.session_id, sid),
+                tantivy::schema::IndexRecordOption::Basic,
+            );
+            reader.searcher().search(&query, &Count).unwrap()
+        };
+        assert_eq!(docs_of("gone"), 0, "session and message docs are removed");
+        assert_eq!(docs_of("keep"), 2);
+    }
+
+    #[test]
+    fn test_bulk_index_over_two_directories() {
+        let home = TempDir::new().unwrap();
+        let shared = TempDir::new().unwrap();
+        let proj_home = home.path().join("-home-u-a");
+        let proj_shared = shared.path().join("-mnt-team-b");
+        fs::create_dir_all(&proj_home).unwrap();
+        fs::create_dir_all(&proj_shared).unwrap();
+        write_fixture(&proj_home, "local-1.jsonl", &session_line("local-1", "first"));
+        write_fixture(&proj_shared, "shared-1.jsonl", &session_line("shared-1", "first"));
+        write_fixture(&proj_shared, "shared-2.jsonl", &session_line("shared-2", "first"));
+        // Mirrored into both; the newer copy (two messages) wins
+        write_fixture(&proj_home, "both-1.jsonl", &session_line("both-1", "first"));
+        let newer = session_line("both-1", "first") + &session_line("both-1", "second");
+        let mirror = write_fixture(&proj_shared, "both-1.jsonl", &newer);
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        File::options().write(true).open(&mirror).unwrap().set_modified(later).unwrap();
+
+        let schema = IndexSchema::new();
+        let index = tantivy::Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer(50_000_000).unwrap();
+        let dirs = session_dirs(home.path().to_path_buf(), vec![shared.path().to_path_buf()]);
+        let summary = bulk_index(
+            &mut writer,
+            &schema,
+            &dirs,
+            &IndexOptions::default(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(summary.session_count, 4);
+        for sid in ["local-1", "shared-1", "shared-2"] {
+            assert_eq!(message_count(&index, &schema, sid), 1, "{sid}");
+        }
+        assert_eq!(message_count(&index, &schema, "both-1"), 2, "one copy, the newer one");
+        assert_eq!(index.reader().unwrap().searcher().num_docs(), 9);
+    }
+
+    #[test]
+    fn test_session_id_falls_back_to_file_stem() {
+        let projects = TempDir::new().unwrap();
+        let proj = projects.path().join("-home-u-a");
+        fs::create_dir_all(&proj).unwrap();
+        let stem = "5b1f0c3e-9a2d-4e7b-8c61-0f2d3a4b5c6d";
+        let content = concat!(
+            r#"{"type":"user","message":{"role":"user","content":"early format"},"cwd":"/home/u/a"}"#,
+            "\n",
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"ok"}]}}"#,
+            "\n",
+        );
+        write_fixture(&proj, &format!("{stem}.jsonl"), content);
+
+        let schema = IndexSchema::new();
+        let index = tantivy::Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer(50_000_000).unwrap();
+        let dirs = [projects.path().to_path_buf()];
+        let summary = bulk_index(
+            &mut writer,
+            &schema,
+            &dirs,
+            &IndexOptions::default(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(summary.session_count, 1);
+        assert_eq!(message_count(&index, &schema, stem), 2);
+        let searcher = index.reader().unwrap().searcher();
+        let term = tantivy::Term::from_field_text(schema.session_id, stem);
+        let query = tantivy::query::TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
+        let hits = searcher.search(&query, &tantivy::collector::TopDocs::with_limit(10)).unwrap();
+        let session_doc = hits
+            .iter()
+            .map(|(_, addr)| searcher.doc::<TantivyDocument>(*addr).unwrap())
+            .find(|d| d.get_str(schema.doc_type) == Some("session"))
+            .expect("session doc is keyed by the file stem");
+        assert_eq!(session_doc.get_str(schema.project_path), Some("/home/u/a"));
+    }
+
+    #[test]
+    fn test_estimate_reindex_scales_with_files_and_bytes() {
+        let projects = TempDir::new().unwrap();
+        let proj = projects.path().join("-home-u-a");
+        fs::create_dir_all(&proj).unwrap();
+        let line = session_line("est-0", "x".repeat(1000).as_str());
+        write_fixture(&proj, "est-0.jsonl", &line);
+        let dirs = [projects.path().to_path_buf()];
+
+        let one = estimate_reindex(&dirs, 1000);
+        assert_eq!(one.file_count, 1);
+        assert_eq!(one.total_bytes, line.len() as u64);
+        assert_eq!(one.estimated_seconds, line.len() as f64 / 1000.0);
+
+        for n in 1..4 {
+            write_fixture(&proj, &format!("est-{n}.jsonl"), &line);
+        }
+        let four = estimate_reindex(&dirs, 1000);
+        assert_eq!(four.file_count, 4);
+        assert_eq!(four.total_bytes, 4 * one.total_bytes);
+        assert_eq!(four.estimated_seconds, 4.0 * one.estimated_seconds);
+        assert_eq!(estimate_reindex(&dirs, 2000).estimated_seconds, 2.0 * one.estimated_seconds);
+    }
+
+    #[test]
+    fn test_extra_dirs_round_trip() {
+        let index_dir = TempDir::new().unwrap();
+        assert!(read_extra_dirs(index_dir.path()).is_empty());
+        let extra = vec![PathBuf::from("/mnt/team/claude-projects")];
+        write_extra_dirs(index_dir.path(), &extra).unwrap();
+        assert_eq!(read_extra_dirs(index_dir.path()), extra);
+
+        // The primary directory is never listed twice
+        let projects = PathBuf::from("/home/u/.claude/projects");
+        let dirs = session_dirs(projects.clone(), vec![projects.clone(), extra[0].clone()]);
+        assert_eq!(dirs, [projects, extra[0].clone()]);
+    }
+
+    #[test]
+    fn test_bulk_index_skips_file_whose_parse_panics() {
+        let projects = TempDir::new().unwrap();
+        let proj = projects.path().join("-home-u-a");
+        fs::create_dir_all(&proj).unwrap();
+        write_fixture(&proj, "good-1.jsonl", &session_line("good-1", "first"));
+        write_fixture(&proj, "cursed.jsonl", &session_line("cursed", "first"));
+        write_fixture(&proj, "good-2.jsonl", &session_line("good-2", "first"));
+
+        fn fragile_parse(
+            path: &Path,
+            schema: &IndexSchema,
+            meta: Option<&SessionIndexEntry>,
+            options: &IndexOptions,
+        ) -> Vec<TantivyDocument> {
+            if path.ends_with("cursed.jsonl") {
+                panic!("byte index 3 is not a char boundary");
+            }
+            parse_jsonl_to_documents(path, schema, meta, options)
+        }
+
+        let schema = IndexSchema::new();
+        let index = tantivy::Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer(50_000_000).unwrap();
+        let options = IndexOptions::default();
+        let summary = bulk_index_with(
+            &mut writer,
+            &schema,
+            &[projects.path().to_path_buf()],
+            &options,
+            None,
+            None,
+            None,
+            fragile_parse,
+        )
+        .unwrap();
+
+        assert_eq!((summary.session_count, summary.failed_files), (2, 1));
+        assert_eq!(message_count(&index, &schema, "good-1"), 1);
+        assert_eq!(message_count(&index, &schema, "good-2"), 1);
+        let docs = index.reader().unwrap().searcher().num_docs();
+        assert_eq!(docs, 4, "a session + message doc per good file, none for cursed");
+    }
+
+    #[test]
+    fn test_bulk_index_resumes_from_progress_sidecar() {
+        let projects = TempDir::new().unwrap();
+        let index_dir = TempDir::new().unwrap();
+        let proj = projects.path().join("-home-u-a");
+        fs::create_dir_all(&proj).unwrap();
+        let done = write_fixture(&proj, "done-1.jsonl", &session_line("done-1", "first"));
+        write_fixture(&proj, "todo-1.jsonl", &session_line("todo-1", "first"));
+        write_fixture(&proj, "todo-2.jsonl", &session_line("todo-2", "first"));
+
+        // A previous run committed done-1 (3 sessions so far), then crashed
+        let crashed = BulkProgress {
+            schema_version: SCHEMA_VERSION,
+            session_count: 3,
+            failed_files: 0,
+            completed: vec![done.to_string_lossy().into_owned()],
+        };
+        write_bulk_progress(index_dir.path(), &crashed).unwrap();
+        assert!(bulk_index_resumable(index_dir.path()));
+
+        let schema = IndexSchema::new();
+        let index = tantivy::Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer(50_000_000).unwrap();
+        let options = IndexOptions::default();
+        let dirs = [projects.path().to_path_buf()];
+        let summary =
+            bulk_index(&mut writer, &schema, &dirs, &options, None, Some(index_dir.path()), None)
+                .unwrap();
+
+        assert_eq!(summary.session_count, 5, "3 from the earlier run + 2 now");
+        assert_eq!(message_count(&index, &schema, "todo-1"), 1);
+        assert_eq!(message_count(&index, &schema, "todo-2"), 1);
+        let reader = index.reader().unwrap();
+        let done_docs = reader
+            .searcher()
+            .search(
+                &tantivy::query::TermQuery::new(
+                    Term::from_field_text(schema.session_id, "done-1"),
+                    tantivy::schema::IndexRecordOption::Basic,
+                ),
+                &tantivy::collector::Count,
+            )
+            .unwrap();
+        assert_eq!(done_docs, 0, "completed files are skipped, not re-parsed");
+        assert!(!index_dir.path().join(BULK_PROGRESS_FILE).exists());
+        assert!(!bulk_index_resumable(index_dir.path()));
+    }
+
     #[test]
     fn test_schema_version_mismatch_true_when_missing() {
         let tmp = TempDir::new().unwrap();
@@ -752,6 +1782,7 @@ mod tests {
             schema_version: SCHEMA_VERSION,
             indexed_at: "2026-02-18T12:00:00Z".to_string(),
             session_count: 100,
+            bytes_per_sec: None,
         };
         let json = serde_json::to_string(&meta).unwrap();
         fs::write(tmp.path().join("swarm-ui-meta.json"), json).unwrap();
@@ -1,6 +1,14 @@
+pub mod archive;
+pub mod context_window;
+pub mod cost;
 pub mod doc_ext;
+pub mod dump;
+pub mod health;
 pub mod indexer;
+pub mod prune;
 pub mod queries;
+pub mod recent;
 pub mod schema;
+pub mod tokenizer;
 pub mod types;
 pub mod watcher;
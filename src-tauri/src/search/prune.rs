@@ -0,0 +1,267 @@
+use crate::search::archive::{affected_sessions, session_doc_with_flags};
+use crate::search::doc_ext::DocExt;
+use crate::search::queries::dir_size_recursive;
+use crate::search::schema::IndexSchema;
+use crate::search::types::{PrunePreview, PruneReport};
+use crate::state::lock_recover;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tantivy::collector::{Count, DocSetCollector};
+use tantivy::query::{BooleanQuery, Occur, TermQuery};
+use tantivy::schema::{IndexRecordOption, Term};
+use tantivy::{IndexReader, IndexWriter, TantivyDocument};
+
+/// A session `prune_old_messages` would prune, with its message doc count.
+struct PrunableSession {
+    doc: TantivyDocument,
+    session_id: String,
+    message_count: u64,
+}
+
+/// Session docs last modified before `cutoff` whose messages are still
+/// indexed, sorted by session id. Sessions without `modified_at` or an id are
+/// left out.
+fn select_prunable(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    cutoff: tantivy::DateTime,
+) -> Result<Vec<PrunableSession>, String> {
+    let searcher = reader.searcher();
+    let term = |field, value: &str| -> Box<dyn tantivy::query::Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(field, value),
+            IndexRecordOption::Basic,
+        ))
+    };
+    let addrs = searcher
+        .search(&*term(schema.doc_type, "session"), &DocSetCollector)
+        .map_err(|e| e.to_string())?;
+
+    let mut sessions = Vec::new();
+    for addr in addrs {
+        let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+        let is_old = doc.get_date_val(schema.modified_at).is_some_and(|m| m < cutoff);
+        if !is_old || doc.get_bool_val(schema.messages_pruned).unwrap_or(false) {
+            continue;
+        }
+        let session_id = doc.get_str(schema.session_id).unwrap_or("").to_string();
+        if session_id.is_empty() {
+            continue;
+        }
+
+        let messages = BooleanQuery::new(vec![
+            (Occur::Must, term(schema.session_id, &session_id)),
+            (Occur::Must, term(schema.doc_type, "message")),
+        ]);
+        let message_count = searcher.search(&messages, &Count).map_err(|e| e.to_string())? as u64;
+        sessions.push(PrunableSession {
+            doc,
+            session_id,
+            message_count,
+        });
+    }
+    sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+    Ok(sessions)
+}
+
+/// What `prune_old_messages` would drop for `cutoff`, without touching the
+/// index.
+pub fn prune_old_messages_preview(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    cutoff: tantivy::DateTime,
+) -> Result<PrunePreview, String> {
+    let selected = select_prunable(reader, schema, cutoff)?;
+    let docs: Vec<TantivyDocument> = selected.iter().map(|s| s.doc.clone()).collect();
+    Ok(PrunePreview {
+        sessions: affected_sessions(&docs, schema),
+        messages_removed: selected.iter().map(|s| s.message_count).sum(),
+        cutoff: cutoff.into_timestamp_secs(),
+    })
+}
+
+/// Drop the message docs of sessions last modified before `cutoff`.
+///
+/// Each session doc is kept (re-added with `messages_pruned=true`) so the
+/// session stays listable; `get_conversation` then needs the JSONL file.
+/// Sessions without `modified_at` are left alone. After the commit every
+/// segment is merged and unused files are collected, so `bytes_reclaimed`
+/// (measured on `index_path`) reflects the space actually freed.
+///
+/// The writer lock is released once the prune is committed, so the watcher
+/// isn't blocked for the whole merge. A failed merge only leaves the space
+/// unreclaimed; it is logged rather than failing the committed prune.
+pub fn prune_old_messages(
+    writer: &Arc<Mutex<IndexWriter>>,
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    cutoff: tantivy::DateTime,
+    index_path: &Path,
+) -> Result<PruneReport, String> {
+    let bytes_before = dir_size_recursive(index_path);
+
+    let mut w = lock_recover(writer, "index writer");
+    let mut sessions_pruned = 0;
+    let mut messages_removed = 0;
+    for session in select_prunable(reader, schema, cutoff)? {
+        messages_removed += session.message_count;
+
+        // Delete all docs of the session, then re-add just the flagged session doc
+        let doc = &session.doc;
+        let pruned = session_doc_with_flags(
+            doc,
+            schema,
+            doc.get_bool_val(schema.archived).unwrap_or(false),
+            doc.get_bool_val(schema.file_exists).unwrap_or(true),
+            true,
+        );
+        w.delete_term(Term::from_field_text(schema.session_id, &session.session_id));
+        w.add_document(pruned).map_err(|e| e.to_string())?;
+        sessions_pruned += 1;
+    }
+
+    if sessions_pruned == 0 {
+        return Ok(PruneReport {
+            sessions_pruned,
+            messages_removed,
+            bytes_reclaimed: 0,
+        });
+    }
+    w.commit().map_err(|e| e.to_string())?;
+
+    // Deleted docs only leave the disk once their segments are rewritten
+    let segments = w.index().searchable_segment_ids().map_err(|e| e.to_string())?;
+    let merge = (!segments.is_empty()).then(|| w.merge(&segments));
+    drop(w);
+    if let Some(Err(e)) = merge.map(|m| m.wait()) {
+        tracing::warn!("Merge after prune failed: {e}");
+    }
+    let gc = lock_recover(writer, "index writer").garbage_collect_files();
+    if let Err(e) = gc.wait() {
+        tracing::warn!("Garbage collection after prune failed: {e}");
+    }
+    reader.reload().map_err(|e| e.to_string())?;
+
+    Ok(PruneReport {
+        sessions_pruned,
+        messages_removed,
+        bytes_reclaimed: bytes_before.saturating_sub(dir_size_recursive(index_path)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::queries::list_sessions_query;
+    use tantivy::Index;
+
+    fn add_session(writer: &IndexWriter, schema: &IndexSchema, sid: &str, modified_secs: i64) {
+        let mut doc = TantivyDocument::new();
+        doc.add_text(schema.session_id, sid);
+        doc.add_text(schema.doc_type, "session");
+        doc.add_text(schema.project_raw, "/home/user/app");
+        doc.add_u64(schema.message_count, 40);
+        doc.add_date(schema.modified_at, tantivy::DateTime::from_timestamp_secs(modified_secs));
+        doc.add_bool(schema.archived, false);
+        doc.add_bool(schema.file_exists, false);
+        writer.add_document(doc).unwrap();
+
+        for turn in 0..40u64 {
+            let mut msg = TantivyDocument::new();
+            msg.add_text(schema.session_id, sid);
+            msg.add_text(schema.doc_type, "message");
+            msg.add_text(schema.role, "user");
+            msg.add_text(schema.content_type, "text");
+            let text = format!("message {turn} of {sid} with some padding to take up space");
+            msg.add_text(schema.content, &text);
+            msg.add_text(schema.content_stored, &text);
+            msg.add_u64(schema.turn_index, turn);
+            writer.add_document(msg).unwrap();
+        }
+    }
+
+    fn message_count(reader: &IndexReader, schema: &IndexSchema, sid: &str) -> usize {
+        let query = BooleanQuery::new(vec![
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(schema.session_id, sid),
+                    IndexRecordOption::Basic,
+                )) as Box<dyn tantivy::query::Query>,
+            ),
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(schema.doc_type, "message"),
+                    IndexRecordOption::Basic,
+                )),
+            ),
+        ]);
+        reader.searcher().search(&query, &Count).unwrap()
+    }
+
+    #[test]
+    fn test_prune_drops_old_messages_but_keeps_sessions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let schema = IndexSchema::new();
+        let index = Index::create_in_dir(dir.path(), schema.schema.clone()).unwrap();
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer(50_000_000).unwrap();
+        add_session(&writer, &schema, "old-1", 1_700_000_000);
+        add_session(&writer, &schema, "new-1", 1_771_400_000);
+        writer.commit().unwrap();
+        let writer = Arc::new(Mutex::new(writer));
+        let reader = index.reader().unwrap();
+
+        let cutoff = tantivy::DateTime::from_timestamp_secs(1_771_000_000);
+        let report = prune_old_messages(&writer, &reader, &schema, cutoff, dir.path()).unwrap();
+
+        assert_eq!(report.sessions_pruned, 1);
+        assert_eq!(report.messages_removed, 40);
+        assert!(report.bytes_reclaimed > 0, "compaction should free space");
+        assert_eq!(message_count(&reader, &schema, "old-1"), 0);
+        assert_eq!(message_count(&reader, &schema, "new-1"), 40);
+
+        let listed = list_sessions_query(&reader, &schema, None).unwrap();
+        let mut ids: Vec<&str> = listed.iter().map(|s| s.session_id.as_str()).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, ["new-1", "old-1"]);
+        let old = listed.iter().find(|s| s.session_id == "old-1").unwrap();
+        assert_eq!(old.message_count, 40, "session metadata is preserved");
+
+        // Already-pruned sessions are skipped on the next run
+        let again = prune_old_messages(&writer, &reader, &schema, cutoff, dir.path()).unwrap();
+        assert_eq!(again.sessions_pruned, 0);
+    }
+
+    #[test]
+    fn test_prune_preview_matches_actual_prune() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let schema = IndexSchema::new();
+        let index = Index::create_in_dir(dir.path(), schema.schema.clone()).unwrap();
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer(50_000_000).unwrap();
+        add_session(&writer, &schema, "old-2", 1_700_000_100);
+        add_session(&writer, &schema, "old-1", 1_700_000_000);
+        add_session(&writer, &schema, "new-1", 1_771_400_000);
+        writer.commit().unwrap();
+        let writer = Arc::new(Mutex::new(writer));
+        let reader = index.reader().unwrap();
+        let cutoff = tantivy::DateTime::from_timestamp_secs(1_771_000_000);
+
+        let preview = prune_old_messages_preview(&reader, &schema, cutoff).unwrap();
+        let ids: Vec<&str> = preview.sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["old-1", "old-2"]);
+        assert_eq!(preview.messages_removed, 80);
+        assert_eq!(preview.cutoff, cutoff.into_timestamp_secs());
+        assert_eq!(message_count(&reader, &schema, "old-1"), 40, "preview is read-only");
+
+        let report = prune_old_messages(&writer, &reader, &schema, cutoff, dir.path()).unwrap();
+        assert_eq!(report.sessions_pruned, preview.sessions.len() as u64);
+        assert_eq!(report.messages_removed, preview.messages_removed);
+
+        let after = prune_old_messages_preview(&reader, &schema, cutoff).unwrap();
+        assert!(after.sessions.is_empty());
+        assert_eq!(after.messages_removed, 0);
+    }
+}
@@ -1,22 +1,40 @@
+use crate::claude_paths::projects_dir;
+use crate::search::context_window::context_window;
+use crate::search::cost::estimate_cost;
 use crate::search::doc_ext::DocExt;
-use crate::search::indexer::extract_content_blocks;
+use crate::search::indexer::{
+    estimate_reindex, extract_content_blocks, newest_jsonl_mtime, parse_jsonl_with_report,
+    read_extra_dirs, read_index_meta, session_dirs, write_extra_dirs, write_index_meta,
+    IndexOptions, DEFAULT_INDEX_BYTES_PER_SEC,
+};
+use crate::search::recent::{load_recent_ids, recent_sessions_path};
 use crate::search::schema::IndexSchema;
+use crate::search::tokenizer::{content_analyzer, tokenizer_manager};
 use crate::search::types::{
-    ConversationMessage, IndexStats, MatchSnippet, SearchFilter, SearchResult, SessionDetail,
-    SessionFilter, SessionListItem,
+    AffectedSession, ArchivePreview, BackgroundHealth, ContextFill, ConversationMessage,
+    DailySpend, DashboardSummary, DuplicateCluster, DuplicateSession, IndexMeta,
+    IndexSizeComponent, IndexStats, LastActivity, MatchSnippet, MessageRecord, MessageUsage,
+    MetricsDelta, PrunePreview, PruneReport, ReindexEstimate, SearchFilter, SearchResult,
+    SearchStreamEvent, SessionComparison, SessionDetail, SessionFileDiagnosis, SessionFilter,
+    SessionGap, SessionListItem, SessionMessageBreakdown, SessionMetrics,
 };
-use crate::search::watcher::{format_tantivy_date, session_doc_to_list_item};
-use crate::state::IndexHandle;
+use crate::search::watcher::{format_tantivy_date, session_doc_to_list_item, UpdateSink};
+use crate::state::{lock_recover, IndexHandle};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
-use std::path::Path;
-use std::sync::atomic::Ordering;
-use tantivy::collector::{Count, TopDocs};
-use tantivy::query::{BooleanQuery, Occur, QueryParser, TermQuery};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tantivy::collector::{Count, DocSetCollector, TopDocs};
+use tantivy::query::{
+    BooleanQuery, Occur, PhraseQuery, QueryParser, RangeQuery, TermQuery, TermSetQuery,
+};
 use tantivy::schema::{IndexRecordOption, Term};
-use tantivy::{IndexReader, Order, TantivyDocument};
+use tantivy::{DocAddress, IndexReader, Order, Searcher, TantivyDocument};
 
 // ---------------------------------------------------------------------------
 // Core query functions (synchronous, testable)
@@ -25,8 +43,10 @@ use tantivy::{IndexReader, Order, TantivyDocument};
 /// List sessions with optional filtering.
 ///
 /// BooleanQuery on doc_type=session with optional project/git_branch/model filters.
+/// `modified_within_days` and `around` are RangeQuerys on the modified_at fast field.
 /// Sorted by modified_at DESC via fast field, limit 10,000.
 /// Bool fields (archived) are post-filtered since they are FAST-only (not indexed).
+/// `project_glob` is also a post-filter on `project_raw`.
 pub fn list_sessions_query(
     reader: &IndexReader,
     schema: &IndexSchema,
@@ -34,6 +54,7 @@ pub fn list_sessions_query(
 ) -> Result<Vec<SessionListItem>, String> {
     let searcher = reader.searcher();
     let include_archived = filter.map(|f| f.include_archived).unwrap_or(false);
+    let project_glob = project_glob_matcher(filter.and_then(|f| f.project_glob.as_deref()))?;
 
     // Build query: Must doc_type=session + optional FAST field filters
     let mut clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = vec![(
@@ -72,6 +93,23 @@ pub fn list_sessions_query(
                 )),
             ));
         }
+        if let Some(days) = f.modified_within_days {
+            let now = chrono::Utc::now().timestamp();
+            let days = i64::try_from(days).unwrap_or(i64::MAX);
+            let since = now.saturating_sub(days.saturating_mul(86_400));
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_date_bounds(
+                    "modified_at".to_string(),
+                    Bound::Included(tantivy::DateTime::from_timestamp_secs(since)),
+                    Bound::Included(tantivy::DateTime::from_timestamp_secs(now)),
+                )),
+            ));
+        }
+        if let Some(ref around) = f.around {
+            let offset = parse_utc_offset(f.utc_offset.as_deref())?;
+            clauses.push((Occur::Must, Box::new(around_query("modified_at", around, offset)?)));
+        }
     }
 
     let query = BooleanQuery::new(clauses);
@@ -89,170 +127,338 @@ pub fn list_sessions_query(
         if !include_archived && archived {
             continue;
         }
+        if let Some(ref glob) = project_glob {
+            if !glob.is_match(doc.get_str(schema.project_raw).unwrap_or("")) {
+                continue;
+            }
+        }
         items.push(session_doc_to_list_item(&doc, schema));
     }
 
     Ok(items)
 }
 
+/// Sessions on `branch` in any project, newest `modified_at` first; archived
+/// sessions are left out.
+pub fn list_sessions_by_branch_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    branch: &str,
+) -> Result<Vec<SessionListItem>, String> {
+    if branch.is_empty() {
+        return Err("Branch name is empty".to_string());
+    }
+    let filter = SessionFilter {
+        git_branch: Some(branch.to_string()),
+        ..Default::default()
+    };
+    list_sessions_query(reader, schema, Some(&filter))
+}
+
+/// Push the full `list_sessions` result for `filter` to `sink` as one
+/// `session:snapshot`, which replaces the UI's list so sessions deleted or
+/// archived while it was away drop out. Returns the number of sessions sent.
+pub fn resync_sessions_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    filter: Option<&SessionFilter>,
+    sink: &dyn UpdateSink,
+) -> Result<usize, String> {
+    let items = list_sessions_query(reader, schema, filter)?;
+    sink.snapshot(&items);
+    Ok(items.len())
+}
+
+/// Newest non-archived session of `project` (as in `SessionFilter::project`)
+/// whose JSONL file still exists, i.e. one `claude --resume` can pick up.
+pub fn latest_session_for_project_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    project: &str,
+) -> Result<Option<SessionListItem>, String> {
+    if project.is_empty() {
+        return Err("Project must not be empty".to_string());
+    }
+    let filter = SessionFilter {
+        project: Some(project.to_string()),
+        ..Default::default()
+    };
+    Ok(list_sessions_query(reader, schema, Some(&filter))?
+        .into_iter()
+        .find(|s| s.file_exists))
+}
+
 /// BM25 full-text search across message content with session enrichment.
 ///
-/// Phase 1: BM25 on content field, exclude tool_result by default, over-fetch 3x limit.
+/// Phase 1: BM25 on content field, exclude tool_result by default, over-fetch
+/// (see `overfetch_multiplier`).
 /// Phase 2: Batch OR query for session metadata enrichment (NOT N+1).
+/// `project_glob` and `git_branch` are applied to the enriched session docs
+/// (see `SessionPostFilter`). While post-filters leave fewer than `limit`
+/// sessions, both phases repeat on the next, twice as large page of hits, for
+/// up to `OVERFETCH_ROUNDS` rounds.
+///
+/// `exact_substring` bypasses the query parser for literal, case-sensitive
+/// matching (e.g. `getUserById_v2`). It is slower: a broad token query picks up
+/// to 2,000 candidate messages, each scanned for the literal. Only the stored
+/// 500-char preview is scanned, so matches later in long messages are missed.
 pub fn search_sessions_query(
     reader: &IndexReader,
     schema: &IndexSchema,
     query_text: &str,
     filter: Option<&SearchFilter>,
 ) -> Result<Vec<SearchResult>, String> {
-    let searcher = reader.searcher();
-    let effective_limit = filter.and_then(|f| f.limit).unwrap_or(50);
-    let include_tool_output = filter.map(|f| f.include_tool_output).unwrap_or(false);
+    search_sessions_query_cancellable(reader, schema, query_text, filter, &SearchToken::never())
+}
 
-    // Phase 1: Search message docs
-    let query_parser = QueryParser::new(
-        schema.schema.clone(),
-        vec![schema.content],
-        tantivy::tokenizer::TokenizerManager::default(),
-    );
-    let user_query = query_parser
-        .parse_query(query_text)
-        .map_err(|e| format!("Query parse error: {e}"))?;
+/// Ids of every session doc in `project` on `branch`, archived included.
+fn project_branch_session_ids(
+    searcher: &tantivy::Searcher,
+    schema: &IndexSchema,
+    project: &str,
+    branch: &str,
+) -> Result<Vec<String>, String> {
+    let term = |field, value: &str| -> Box<dyn tantivy::query::Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(field, value),
+            IndexRecordOption::Basic,
+        ))
+    };
+    let query = BooleanQuery::new(vec![
+        (Occur::Must, term(schema.doc_type, "session")),
+        (Occur::Must, term(schema.project_raw, project)),
+        (Occur::Must, term(schema.git_branch, branch)),
+    ]);
+    let addrs = searcher
+        .search(&query, &tantivy::collector::DocSetCollector)
+        .map_err(|e| e.to_string())?;
+    let mut ids = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+        if let Some(sid) = doc.get_str(schema.session_id) {
+            ids.push(sid.to_string());
+        }
+    }
+    Ok(ids)
+}
 
-    let mut clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = vec![
-        (
-            Occur::Must,
-            Box::new(TermQuery::new(
-                Term::from_field_text(schema.doc_type, "message"),
-                IndexRecordOption::Basic,
-            )),
-        ),
-        (Occur::Must, Box::new(user_query)),
-    ];
+/// `search_sessions` within one project and branch.
+///
+/// The project and branch select session docs first; their ids then join the
+/// message query as a `session_ids` Must clause, so the index intersects
+/// content with metadata instead of post-filtering or chaining `list_sessions`.
+/// `project`/`git_branch` in `filter` are superseded.
+pub fn search_project_sessions_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    project: &str,
+    branch: &str,
+    query_text: &str,
+    filter: Option<&SearchFilter>,
+    token: &SearchToken,
+) -> Result<Vec<SearchResult>, String> {
+    if project.is_empty() || branch.is_empty() {
+        return Err("Project and branch must not be empty".to_string());
+    }
+    let session_ids = project_branch_session_ids(&reader.searcher(), schema, project, branch)?;
+    if session_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let scoped = SearchFilter {
+        project: None,
+        git_branch: None,
+        session_ids: Some(session_ids),
+        ..filter.cloned().unwrap_or_default()
+    };
+    search_sessions_query_cancellable(reader, schema, query_text, Some(&scoped), token)
+}
 
-    if !include_tool_output {
+/// Candidate docs scanned for a literal match in `exact_substring` mode.
+const EXACT_SUBSTRING_CANDIDATES: usize = 2_000;
+
+/// Phase-1 rounds before search settles for fewer than `limit` results.
+const OVERFETCH_ROUNDS: usize = 4;
+
+/// Largest `overfetch_multiplier` a filter may ask for.
+const MAX_OVERFETCH_MULTIPLIER: usize = 50;
+
+/// Message hits fetched per requested result in the first round: 3 for a
+/// plain query, 10 when per-doc or session post-filters can drop hits.
+/// A filter's `overfetch_multiplier` overrides both, clamped to
+/// `1..=MAX_OVERFETCH_MULTIPLIER`.
+fn overfetch_multiplier(
+    filter: Option<&SearchFilter>,
+    message_query: &MessageQuery,
+    session_filter: &SessionPostFilter,
+) -> usize {
+    match filter.and_then(|f| f.overfetch_multiplier) {
+        Some(m) => m.clamp(1, MAX_OVERFETCH_MULTIPLIER),
+        None if message_query.has_doc_checks() || !session_filter.is_empty() => 10,
+        None => 3,
+    }
+}
+
+/// Narrow `exact_substring` candidates: every token the content analyzer
+/// (`content_analyzer`, the code-aware tokenizer `content` is indexed with)
+/// produces for `text` must appear in the message content. `None` if `text`
+/// has no indexable tokens.
+fn exact_substring_candidates(
+    schema: &IndexSchema,
+    text: &str,
+) -> Option<Box<dyn tantivy::query::Query>> {
+    let mut analyzer = content_analyzer();
+    let mut stream = analyzer.token_stream(text);
+    let mut clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = Vec::new();
+    while stream.advance() {
         clauses.push((
-            Occur::MustNot,
+            Occur::Must,
             Box::new(TermQuery::new(
-                Term::from_field_text(schema.content_type, "tool_result"),
+                Term::from_field_text(schema.content, &stream.token().text),
                 IndexRecordOption::Basic,
             )),
         ));
     }
-
-    if let Some(f) = filter {
-        if let Some(ref project) = f.project {
-            clauses.push((
-                Occur::Must,
-                Box::new(TermQuery::new(
-                    Term::from_field_text(schema.msg_project, project),
-                    IndexRecordOption::Basic,
-                )),
-            ));
-        }
-        if let Some(ref role) = f.role {
-            clauses.push((
-                Occur::Must,
-                Box::new(TermQuery::new(
-                    Term::from_field_text(schema.role, role),
-                    IndexRecordOption::Basic,
-                )),
-            ));
-        }
+    if clauses.is_empty() {
+        None
+    } else {
+        Some(Box::new(BooleanQuery::new(clauses)))
     }
+}
 
-    let query = BooleanQuery::new(clauses);
-    let overfetch = effective_limit * 3;
-    let top_docs = searcher
-        .search(&query, &TopDocs::with_limit(overfetch))
-        .map_err(|e| e.to_string())?;
+/// Tool output markers `errors_only` looks for unless the filter overrides them.
+pub const DEFAULT_ERROR_SIGNATURES: &[&str] = &[
+    "error",
+    "traceback",
+    "exception",
+    "exit code",
+    "panicked",
+    "enoent",
+    "command not found",
+    "permission denied",
+];
+
+/// Match `signature` the way `content` was tokenized: one token is a term,
+/// several a phrase (compound tokens keep their shared positions). `None`
+/// if it has no indexable tokens.
+fn signature_query(
+    schema: &IndexSchema,
+    signature: &str,
+) -> Option<Box<dyn tantivy::query::Query>> {
+    let mut analyzer = content_analyzer();
+    let mut stream = analyzer.token_stream(signature);
+    let mut terms = Vec::new();
+    while stream.advance() {
+        let token = stream.token();
+        terms.push((token.position, Term::from_field_text(schema.content, &token.text)));
+    }
+    match terms.len() {
+        0 => None,
+        1 => Some(Box::new(TermQuery::new(terms.remove(0).1, IndexRecordOption::WithFreqs))),
+        _ => Some(Box::new(PhraseQuery::new_with_offset(terms))),
+    }
+}
 
-    // Date post-filter and deduplicate by session_id, keeping top 3 snippets
-    let date_from = filter
-        .and_then(|f| f.date_from.as_deref())
-        .and_then(parse_date_filter);
-    let date_to = filter
-        .and_then(|f| f.date_to.as_deref())
-        .and_then(parse_date_filter);
+/// Should-clause of every error signature (the filter's or the defaults).
+/// An error if no signature has an indexable token, since an empty
+/// `BooleanQuery` would silently match nothing.
+fn error_signatures_query(
+    schema: &IndexSchema,
+    filter: &SearchFilter,
+) -> Result<BooleanQuery, String> {
+    let clauses: Vec<_> = match filter.error_signatures {
+        Some(ref custom) => custom.iter().map(String::as_str).collect::<Vec<_>>(),
+        None => DEFAULT_ERROR_SIGNATURES.to_vec(),
+    }
+    .into_iter()
+    .filter_map(|sig| signature_query(schema, sig))
+    .map(|q| (Occur::Should, q))
+    .collect();
+    if clauses.is_empty() {
+        return Err("error_signatures has no searchable signature".to_string());
+    }
+    Ok(BooleanQuery::new(clauses))
+}
 
-    // Group by session_id: (best_score, Vec<MatchSnippet>)
-    let mut session_hits: HashMap<String, (f32, Vec<MatchSnippet>)> = HashMap::new();
+/// Error returned by a search superseded by a newer one.
+pub const SEARCH_CANCELLED: &str = "Search cancelled";
 
-    for (score, addr) in top_docs {
-        let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+/// Cancellation token for one search: cancelled once a newer search (or
+/// `cancel_search`) bumps the shared generation counter past its own.
+pub struct SearchToken {
+    generation: u64,
+    latest: Arc<AtomicU64>,
+}
 
-        // Date post-filter on message timestamp
-        if let Some(ref from) = date_from {
-            if let Some(ts) = doc.get_date_val(schema.timestamp) {
-                if ts.into_timestamp_secs() < from.into_timestamp_secs() {
-                    continue;
-                }
-            }
-        }
-        if let Some(ref to) = date_to {
-            if let Some(ts) = doc.get_date_val(schema.timestamp) {
-                if ts.into_timestamp_secs() > to.into_timestamp_secs() {
-                    continue;
-                }
-            }
+impl SearchToken {
+    /// Start a new search generation, cancelling every older token.
+    pub fn next(latest: &Arc<AtomicU64>) -> Self {
+        let generation = latest.fetch_add(1, Ordering::SeqCst) + 1;
+        Self {
+            generation,
+            latest: latest.clone(),
         }
+    }
 
-        let sid = doc
-            .get_str(schema.session_id)
-            .unwrap_or("")
-            .to_string();
-        if sid.is_empty() {
-            continue;
+    /// A token that is never cancelled.
+    pub fn never() -> Self {
+        Self {
+            generation: 0,
+            latest: Arc::new(AtomicU64::new(0)),
         }
+    }
 
-        let snippet = MatchSnippet {
-            role: doc.get_str(schema.role).unwrap_or("").to_string(),
-            content_type: doc.get_str(schema.content_type).unwrap_or("").to_string(),
-            snippet: doc.get_str(schema.content_stored).unwrap_or("").to_string(),
-            timestamp: doc.get_date_val(schema.timestamp).map(format_tantivy_date),
-            turn_index: doc.get_u64_val(schema.turn_index).unwrap_or(0),
-        };
+    pub fn is_cancelled(&self) -> bool {
+        self.latest.load(Ordering::SeqCst) != self.generation
+    }
 
-        let entry = session_hits
-            .entry(sid)
-            .or_insert_with(|| (score, Vec::new()));
-        if entry.0 < score {
-            entry.0 = score;
-        }
-        if entry.1.len() < 3 {
-            entry.1.push(snippet);
+    fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(SEARCH_CANCELLED.to_string())
+        } else {
+            Ok(())
         }
     }
+}
 
-    if session_hits.is_empty() {
+/// `search_sessions_query` that bails with `SEARCH_CANCELLED` once `token` is
+/// superseded. Checked before phase 1 (the task may have queued behind older
+/// searches) and at the phase-1/phase-2 boundary.
+pub fn search_sessions_query_cancellable(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    query_text: &str,
+    filter: Option<&SearchFilter>,
+    token: &SearchToken,
+) -> Result<Vec<SearchResult>, String> {
+    token.check()?;
+    let searcher = reader.searcher();
+    let effective_limit = filter.and_then(|f| f.limit).unwrap_or(50);
+    let session_filter = SessionPostFilter::new(filter)?;
+    let Some(mut hits) =
+        collect_search_hits(&searcher, schema, query_text, filter, &session_filter, token)?
+    else {
         return Ok(Vec::new());
-    }
+    };
 
     // Phase 2: Batch OR query for session metadata enrichment
-    let session_ids: Vec<String> = session_hits.keys().cloned().collect();
-    let session_meta = batch_fetch_sessions(&searcher, schema, &session_ids)?;
+    let missing: Vec<String> = hits
+        .sessions
+        .keys()
+        .filter(|sid| !hits.looked_up.contains(*sid))
+        .cloned()
+        .collect();
+    hits.meta.extend(batch_fetch_sessions(&searcher, schema, &missing)?);
+
+    let boost = RecencyBoost::new(filter);
 
-    // Build results sorted by best score DESC, limited to effective_limit
-    let mut results: Vec<SearchResult> = session_hits
+    // Build results sorted by (boosted) best score DESC, limited to effective_limit
+    let mut results: Vec<SearchResult> = hits
+        .sessions
         .into_iter()
-        .map(|(sid, (score, snippets))| {
-            let meta = session_meta.get(&sid);
-            SearchResult {
-                session_id: sid,
-                score,
-                snippets,
-                project_path: meta.and_then(|m| m.get_str(schema.project_path))
-                    .map(|s| s.to_string()),
-                summary: meta.and_then(|m| m.get_str(schema.summary))
-                    .map(|s| s.to_string()),
-                model: meta.and_then(|m| m.get_str(schema.model))
-                    .map(|s| s.to_string()),
-                modified_at: meta.and_then(|m| m.get_date_val(schema.modified_at))
-                    .map(format_tantivy_date),
-                file_exists: meta
-                    .and_then(|m| m.get_bool_val(schema.file_exists))
-                    .unwrap_or(true),
-            }
+        .filter(|(sid, _)| session_filter.accepts(hits.meta.get(sid), schema))
+        .map(|(sid, hit)| {
+            let meta = hits.meta.get(&sid);
+            to_search_result(sid, hit, meta, schema, &boost)
         })
         .collect();
 
@@ -261,843 +467,4735 @@ pub fn search_sessions_query(
     Ok(results)
 }
 
-/// Get full session metadata by session_id.
-pub fn get_session_detail_query(
+/// Sessions enriched per batch fetch in `search_sessions_stream_query`.
+const STREAM_CHUNK: usize = 10;
+
+/// `search_sessions_query_cancellable`, emitting each result as soon as its
+/// session metadata is fetched (`STREAM_CHUNK` sessions per batch), then a
+/// terminal event: `Done`, `Cancelled` or `Error`, whichever way it ended.
+///
+/// Results come best BM25 score first, so the set matches `search_sessions`
+/// except under `recency_boost`, which rescores each result but doesn't pick
+/// or reorder them. The token is also checked between chunks.
+pub fn search_sessions_stream_query(
     reader: &IndexReader,
     schema: &IndexSchema,
-    session_id: &str,
-) -> Result<SessionDetail, String> {
-    let searcher = reader.searcher();
-    let query = BooleanQuery::new(vec![
-        (
-            Occur::Must,
-            Box::new(TermQuery::new(
-                Term::from_field_text(schema.session_id, session_id),
-                IndexRecordOption::Basic,
-            )),
-        ),
-        (
-            Occur::Must,
-            Box::new(TermQuery::new(
-                Term::from_field_text(schema.doc_type, "session"),
-                IndexRecordOption::Basic,
-            )),
-        ),
-    ]);
+    query_text: &str,
+    filter: Option<&SearchFilter>,
+    token: &SearchToken,
+    emit: &mut dyn FnMut(SearchStreamEvent),
+) -> Result<usize, String> {
+    let result = stream_search_results(reader, schema, query_text, filter, token, emit);
+    emit(match &result {
+        Ok(count) => SearchStreamEvent::Done { count: *count },
+        Err(e) if e == SEARCH_CANCELLED => SearchStreamEvent::Cancelled,
+        Err(e) => SearchStreamEvent::Error { message: e.clone() },
+    });
+    result
+}
 
-    let top_docs = searcher
-        .search(&query, &TopDocs::with_limit(1))
-        .map_err(|e| e.to_string())?;
+/// `search_sessions_stream_query` up to the terminal event; returns the
+/// number of results emitted.
+fn stream_search_results(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    query_text: &str,
+    filter: Option<&SearchFilter>,
+    token: &SearchToken,
+    emit: &mut dyn FnMut(SearchStreamEvent),
+) -> Result<usize, String> {
+    token.check()?;
+    let searcher = reader.searcher();
+    let effective_limit = filter.and_then(|f| f.limit).unwrap_or(50);
+    let session_filter = SessionPostFilter::new(filter)?;
+    let boost = RecencyBoost::new(filter);
+    let mut hits =
+        collect_search_hits(&searcher, schema, query_text, filter, &session_filter, token)?
+            .unwrap_or_default();
+
+    let mut ranked: Vec<(String, SessionHit)> = hits.sessions.drain().collect();
+    ranked.sort_by(|a, b| b.1 .0.partial_cmp(&a.1 .0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut count = 0;
+    let mut ranked = ranked.into_iter().peekable();
+    while count < effective_limit && ranked.peek().is_some() {
+        let chunk: Vec<(String, SessionHit)> = ranked.by_ref().take(STREAM_CHUNK).collect();
+        let missing: Vec<String> = chunk
+            .iter()
+            .map(|(sid, _)| sid)
+            .filter(|sid| !hits.looked_up.contains(*sid))
+            .cloned()
+            .collect();
+        hits.meta.extend(batch_fetch_sessions(&searcher, schema, &missing)?);
+
+        for (sid, hit) in chunk {
+            let meta = hits.meta.get(&sid);
+            if !session_filter.accepts(meta, schema) {
+                continue;
+            }
+            emit(SearchStreamEvent::Result {
+                result: to_search_result(sid, hit, meta, schema, &boost),
+            });
+            count += 1;
+            if count == effective_limit {
+                break;
+            }
+        }
+        token.check()?;
+    }
 
-    let (_, addr) = top_docs
-        .first()
-        .ok_or_else(|| format!("Session not found: {session_id}"))?;
-    let doc: TantivyDocument = searcher.doc(*addr).map_err(|e| e.to_string())?;
+    Ok(count)
+}
 
-    Ok(session_doc_to_detail(&doc, schema))
+/// One session's matches: (best_score, best_turn_index, snippets)
+type SessionHit = (f32, u64, Vec<MatchSnippet>);
+
+/// Phase-1 hits grouped by session, plus the session docs already fetched to
+/// apply the `SessionPostFilter` while paging.
+#[derive(Default)]
+struct SearchHits {
+    sessions: HashMap<String, SessionHit>,
+    meta: HashMap<String, TantivyDocument>,
+    /// Ids already batch-fetched, found or not
+    looked_up: HashSet<String>,
 }
 
-/// Get conversation messages for a session.
+/// Phase 1: search message docs and group them by session. `None` when the
+/// query can't match anything.
 ///
-/// If file_exists: parse from JSONL (full fidelity).
-/// If file pruned: reconstruct from index (content_stored, truncated=true).
-pub fn get_conversation_query(
-    reader: &IndexReader,
+/// Post-filters can drop most hits, so this keeps paging down the ranking
+/// (doubling the page) until `limit` sessions survive or `OVERFETCH_ROUNDS`
+/// run out. Session docs are only fetched here when a `SessionPostFilter`
+/// needs them to count survivors.
+fn collect_search_hits(
+    searcher: &tantivy::Searcher,
     schema: &IndexSchema,
-    session_id: &str,
-) -> Result<Vec<ConversationMessage>, String> {
-    // First, find the session doc to check file_exists and get jsonl_path
-    let searcher = reader.searcher();
-    let query = BooleanQuery::new(vec![
-        (
-            Occur::Must,
-            Box::new(TermQuery::new(
-                Term::from_field_text(schema.session_id, session_id),
-                IndexRecordOption::Basic,
-            )),
-        ),
-        (
-            Occur::Must,
-            Box::new(TermQuery::new(
-                Term::from_field_text(schema.doc_type, "session"),
-                IndexRecordOption::Basic,
-            )),
-        ),
-    ]);
+    query_text: &str,
+    filter: Option<&SearchFilter>,
+    session_filter: &SessionPostFilter,
+    token: &SearchToken,
+) -> Result<Option<SearchHits>, String> {
+    let effective_limit = filter.and_then(|f| f.limit).unwrap_or(50);
+    let best_snippet_only = filter.is_some_and(|f| f.best_snippet_only);
+    let message_query = match build_message_query(schema, query_text, filter)? {
+        Some(q) => q,
+        None => return Ok(None),
+    };
+    let multiplier = overfetch_multiplier(filter, &message_query, session_filter);
+    let mut page = if message_query.literal.is_some() || message_query.tool_input.is_some() {
+        effective_limit.saturating_mul(multiplier).max(EXACT_SUBSTRING_CANDIDATES)
+    } else {
+        effective_limit.saturating_mul(multiplier)
+    };
 
-    let top_docs = searcher
-        .search(&query, &TopDocs::with_limit(1))
-        .map_err(|e| e.to_string())?;
+    let mut hits = SearchHits::default();
+    let mut fetched = 0;
+    for _ in 0..OVERFETCH_ROUNDS {
+        let top_docs = searcher
+            .search(
+                &message_query.query,
+                &TopDocs::with_limit(page).and_offset(fetched),
+            )
+            .map_err(|e| e.to_string())?;
+        let exhausted = top_docs.len() < page;
+        fetched += top_docs.len();
 
-    let (_, addr) = top_docs
-        .first()
-        .ok_or_else(|| format!("Session not found: {session_id}"))?;
-    let session_doc: TantivyDocument = searcher.doc(*addr).map_err(|e| e.to_string())?;
+        for (score, addr) in top_docs {
+            let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+            if !message_query.accepts(&doc, schema) {
+                continue;
+            }
 
-    let file_exists = session_doc
-        .get_bool_val(schema.file_exists)
-        .unwrap_or(true);
-    let jsonl_path = session_doc
-        .get_str(schema.jsonl_path)
-        .unwrap_or("")
-        .to_string();
+            let sid = doc
+                .get_str(schema.session_id)
+                .unwrap_or("")
+                .to_string();
+            if sid.is_empty() {
+                continue;
+            }
 
-    if file_exists && !jsonl_path.is_empty() && Path::new(&jsonl_path).exists() {
-        parse_conversation_from_jsonl(&jsonl_path)
-    } else {
-        reconstruct_conversation_from_index(session_id, &searcher, schema)
+            let snippet = MatchSnippet {
+                role: doc.get_str(schema.role).unwrap_or("").to_string(),
+                content_type: doc.get_str(schema.content_type).unwrap_or("").to_string(),
+                snippet: doc.get_str(schema.content_stored).unwrap_or("").to_string(),
+                timestamp: doc.get_date_val(schema.timestamp).map(format_tantivy_date),
+                turn_index: doc.get_u64_val(schema.turn_index).unwrap_or(0),
+                score,
+            };
+
+            let entry = hits
+                .sessions
+                .entry(sid)
+                .or_insert_with(|| (score, snippet.turn_index, Vec::new()));
+            if entry.0 < score {
+                entry.0 = score;
+                entry.1 = snippet.turn_index;
+            }
+            if best_snippet_only {
+                if entry.2.first().is_none_or(|best| best.score < score) {
+                    entry.2 = vec![snippet];
+                }
+            } else if entry.2.len() < 3 {
+                entry.2.push(snippet);
+            }
+        }
+        token.check()?;
+
+        let accepted = if session_filter.is_empty() {
+            hits.sessions.len()
+        } else {
+            // Only sessions first seen this round need fetching
+            let new_ids: Vec<String> = hits
+                .sessions
+                .keys()
+                .filter(|sid| hits.looked_up.insert((*sid).clone()))
+                .cloned()
+                .collect();
+            hits.meta.extend(batch_fetch_sessions(searcher, schema, &new_ids)?);
+            hits.sessions
+                .keys()
+                .filter(|sid| session_filter.accepts(hits.meta.get(*sid), schema))
+                .count()
+        };
+        if exhausted || accepted >= effective_limit {
+            break;
+        }
+        page = page.saturating_mul(2);
     }
+    Ok(Some(hits))
 }
 
-/// Get index statistics: session/message counts, segments, disk size.
-pub fn get_index_stats_query(
-    reader: &IndexReader,
-    schema: &IndexSchema,
-) -> Result<IndexStats, String> {
-    let searcher = reader.searcher();
+/// `recency_boost` of a filter, with the current time to age sessions against.
+struct RecencyBoost {
+    half_life_days: Option<f32>,
+    now: i64,
+}
 
-    let session_query = TermQuery::new(
-        Term::from_field_text(schema.doc_type, "session"),
-        IndexRecordOption::Basic,
-    );
-    let total_sessions = searcher
-        .search(&session_query, &Count)
-        .map_err(|e| e.to_string())? as u64;
-
-    let message_query = TermQuery::new(
-        Term::from_field_text(schema.doc_type, "message"),
-        IndexRecordOption::Basic,
-    );
-    let total_messages = searcher
-        .search(&message_query, &Count)
-        .map_err(|e| e.to_string())? as u64;
-
-    // Count archived sessions by collecting session docs and post-filtering
-    let top = TopDocs::with_limit(total_sessions as usize + 1);
-    let all_sessions = searcher
-        .search(&session_query, &top)
-        .map_err(|e| e.to_string())?;
-
-    let mut archived_count = 0u64;
-    for (_, addr) in &all_sessions {
-        let doc: TantivyDocument = searcher.doc(*addr).map_err(|e| e.to_string())?;
-        if doc.get_bool_val(schema.archived).unwrap_or(false) {
-            archived_count += 1;
+impl RecencyBoost {
+    fn new(filter: Option<&SearchFilter>) -> Self {
+        Self {
+            half_life_days: filter
+                .and_then(|f| f.recency_boost)
+                .filter(|h| h.is_finite() && *h > 0.0),
+            now: chrono::Utc::now().timestamp(),
         }
     }
 
-    let active_sessions = total_sessions - archived_count;
-    let segment_count = searcher.segment_readers().len() as u64;
+    fn apply(&self, score: f32, modified: Option<tantivy::DateTime>) -> f32 {
+        match (self.half_life_days, modified) {
+            (Some(h), Some(m)) => score * recency_factor(self.now - m.into_timestamp_secs(), h),
+            _ => score,
+        }
+    }
+}
 
-    // Index size from standard location
-    let index_size_bytes = get_index_dir_size();
+/// Phase 2 for one session: its hit enriched with the session doc's metadata.
+fn to_search_result(
+    session_id: String,
+    (score, best_turn_index, snippets): SessionHit,
+    meta: Option<&TantivyDocument>,
+    schema: &IndexSchema,
+    boost: &RecencyBoost,
+) -> SearchResult {
+    let modified = meta.and_then(|m| m.get_date_val(schema.modified_at));
+    SearchResult {
+        session_id,
+        score: boost.apply(score, modified),
+        snippets,
+        project_path: meta.and_then(|m| m.get_str(schema.project_path))
+            .map(|s| s.to_string()),
+        summary: meta.and_then(|m| m.get_str(schema.summary))
+            .map(|s| s.to_string()),
+        model: meta.and_then(|m| m.get_str(schema.model))
+            .map(|s| s.to_string()),
+        modified_at: modified.map(format_tantivy_date),
+        file_exists: meta
+            .and_then(|m| m.get_bool_val(schema.file_exists))
+            .unwrap_or(true),
+        best_turn_index: Some(best_turn_index),
+    }
+}
 
-    Ok(IndexStats {
-        total_sessions,
-        active_sessions,
-        archived_sessions: archived_count,
-        total_messages,
-        segment_count,
-        index_size_bytes,
-    })
+/// Score multiplier for a session last modified `age_secs` ago: 1.0 now,
+/// 0.5 after one half-life. Future timestamps (clock skew) count as now.
+fn recency_factor(age_secs: i64, half_life_days: f32) -> f32 {
+    let age_days = age_secs.max(0) as f32 / 86_400.0;
+    0.5f32.powf(age_days / half_life_days)
 }
 
-/// Pause watcher, delete all documents, re-index from filesystem, resume watcher.
-pub fn reindex_all_query(handle: &IndexHandle) -> Result<(), String> {
-    handle.paused.store(true, Ordering::SeqCst);
+/// The message-doc query shared by search and count.
+///
+/// Index clauses (doc_type, user query, tool exclusion, project, role,
+/// content_type) live in `query`; date bounds, exact-substring and tool input
+/// matching are per-doc checks in `accepts`, since they need stored values.
+pub struct MessageQuery {
+    pub query: BooleanQuery,
+    date_from: Option<tantivy::DateTime>,
+    date_to: Option<tantivy::DateTime>,
+    /// Set in exact_substring mode: the literal every hit must contain
+    literal: Option<String>,
+    /// Substring the tool_use input JSON must contain
+    tool_input: Option<String>,
+}
 
-    // Delete all documents
-    {
-        let mut writer = handle.writer.lock().map_err(|e| e.to_string())?;
-        writer.delete_all_documents().map_err(|e| e.to_string())?;
-        writer.commit().map_err(|e| e.to_string())?;
+impl MessageQuery {
+    /// Whether `accepts` can reject anything, i.e. hits must be loaded to count.
+    fn has_doc_checks(&self) -> bool {
+        self.date_from.is_some()
+            || self.date_to.is_some()
+            || self.literal.is_some()
+            || self.tool_input.is_some()
     }
 
-    // Re-index all JSONL files
-    let projects_dir = dirs::home_dir()
-        .ok_or("No home directory found")?
-        .join(".claude")
-        .join("projects");
-
-    if projects_dir.exists() {
-        let mut writer = handle.writer.lock().map_err(|e| e.to_string())?;
-        crate::search::indexer::bulk_index(&mut writer, &handle.schema, &projects_dir, None)
-            .map_err(|e| e.to_string())?;
+    /// Apply the per-doc checks to a doc matched by `query`.
+    pub fn accepts(&self, doc: &TantivyDocument, schema: &IndexSchema) -> bool {
+        // Docs without a timestamp pass the date bounds
+        let ts = doc.get_date_val(schema.timestamp).map(|t| t.into_timestamp_secs());
+        if let (Some(from), Some(ts)) = (self.date_from, ts) {
+            if ts < from.into_timestamp_secs() {
+                return false;
+            }
+        }
+        if let (Some(to), Some(ts)) = (self.date_to, ts) {
+            if ts > to.into_timestamp_secs() {
+                return false;
+            }
+        }
+        // Exact mode: the token query over-matches, keep only literal hits
+        if let Some(ref literal) = self.literal {
+            if !doc
+                .get_str(schema.content_stored)
+                .is_some_and(|c| c.contains(literal.as_str()))
+            {
+                return false;
+            }
+        }
+        match self.tool_input {
+            Some(ref needle) => doc
+                .get_str(schema.content_stored)
+                .and_then(tool_use_input)
+                .is_some_and(|input| input.contains(needle.as_str())),
+            None => true,
+        }
     }
-
-    handle.paused.store(false, Ordering::SeqCst);
-    Ok(())
 }
 
-// ---------------------------------------------------------------------------
-// Tauri command wrappers (async, delegates to spawn_blocking)
-// ---------------------------------------------------------------------------
-
-#[tauri::command]
-pub async fn list_sessions(
-    handle: tauri::State<'_, IndexHandle>,
-    filter: Option<SessionFilter>,
-) -> Result<Vec<SessionListItem>, String> {
-    let reader = handle.reader.clone();
-    let schema = handle.schema.clone();
-    tokio::task::spawn_blocking(move || list_sessions_query(&reader, &schema, filter.as_ref()))
-        .await
-        .map_err(|e| e.to_string())?
+/// The input JSON of a stored `"tool_use: {name} {input}"` block (see
+/// `extract_content_blocks`), so a needle can't match the tool name.
+fn tool_use_input(stored: &str) -> Option<&str> {
+    stored
+        .strip_prefix("tool_use: ")
+        .and_then(|rest| rest.split_once(' '))
+        .map(|(_, input)| input)
 }
 
-#[tauri::command]
-pub async fn search_sessions(
-    handle: tauri::State<'_, IndexHandle>,
-    query_text: String,
-    filter: Option<SearchFilter>,
-) -> Result<Vec<SearchResult>, String> {
-    let reader = handle.reader.clone();
-    let schema = handle.schema.clone();
-    tokio::task::spawn_blocking(move || {
-        search_sessions_query(&reader, &schema, &query_text, filter.as_ref())
-    })
-    .await
-    .map_err(|e| e.to_string())?
-}
+/// Build the message query for a search, so every consumer filters identically.
+///
+/// Returns `None` when the query can't match anything (an exact-substring
+/// query with no indexable tokens).
+pub fn build_message_query(
+    schema: &IndexSchema,
+    query_text: &str,
+    filter: Option<&SearchFilter>,
+) -> Result<Option<MessageQuery>, String> {
+    let errors_only = filter.is_some_and(|f| f.errors_only);
+    let include_tool_output =
+        errors_only || filter.map(|f| f.include_tool_output).unwrap_or(false);
+    let exact_substring = filter.map(|f| f.exact_substring).unwrap_or(false);
+    let case_sensitive = filter.map(|f| f.case_sensitive).unwrap_or(false);
+    let tool_input = filter
+        .and_then(|f| f.tool_input_contains.clone())
+        .filter(|t| !t.is_empty());
+
+    let user_query: Box<dyn tantivy::query::Query> = if (tool_input.is_some() || errors_only)
+        && query_text.trim().is_empty()
+    {
+        // "Every session that touched this path / hit an error" needs no query text
+        Box::new(tantivy::query::AllQuery)
+    } else if exact_substring {
+        match exact_substring_candidates(schema, query_text) {
+            Some(q) => q,
+            None => return Ok(None),
+        }
+    } else {
+        let field = if case_sensitive { schema.content_cased } else { schema.content };
+        let query_parser =
+            QueryParser::new(schema.schema.clone(), vec![field], tokenizer_manager());
+        query_parser
+            .parse_query(query_text)
+            .map_err(|e| format!("Query parse error: {e}"))?
+    };
 
-#[tauri::command]
-pub async fn get_session_detail(
-    handle: tauri::State<'_, IndexHandle>,
-    session_id: String,
-) -> Result<SessionDetail, String> {
-    let reader = handle.reader.clone();
-    let schema = handle.schema.clone();
-    tokio::task::spawn_blocking(move || get_session_detail_query(&reader, &schema, &session_id))
-        .await
-        .map_err(|e| e.to_string())?
-}
+    let term = |field, value: &str| -> Box<dyn tantivy::query::Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(field, value),
+            IndexRecordOption::Basic,
+        ))
+    };
 
-#[tauri::command]
-pub async fn get_conversation(
-    handle: tauri::State<'_, IndexHandle>,
-    session_id: String,
-) -> Result<Vec<ConversationMessage>, String> {
-    let reader = handle.reader.clone();
-    let schema = handle.schema.clone();
-    tokio::task::spawn_blocking(move || get_conversation_query(&reader, &schema, &session_id))
-        .await
-        .map_err(|e| e.to_string())?
-}
+    let offset = parse_utc_offset(filter.and_then(|f| f.utc_offset.as_deref()))?;
+    let mut clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = vec![
+        (Occur::Must, term(schema.doc_type, "message")),
+        (Occur::Must, user_query),
+    ];
+    if !include_tool_output {
+        clauses.push((Occur::MustNot, term(schema.content_type, "tool_result")));
+    }
+    if let Some(f) = filter {
+        if let Some(ref project) = f.project {
+            clauses.push((Occur::Must, term(schema.msg_project, project)));
+        }
+        if let Some(ref role) = f.role {
+            clauses.push((Occur::Must, term(schema.role, role)));
+        }
+        if let Some(ref content_type) = f.content_type {
+            clauses.push((Occur::Must, term(schema.content_type, content_type)));
+        }
+        if f.thinking_turns_only {
+            clauses.push((Occur::Must, term(schema.content_type, "thinking")));
+        }
+        if f.errors_only {
+            clauses.push((Occur::Must, term(schema.content_type, "tool_result")));
+            clauses.push((Occur::Must, Box::new(error_signatures_query(schema, f)?)));
+        }
+        if let Some(ref around) = f.around {
+            clauses.push((Occur::Must, Box::new(around_query("timestamp", around, offset)?)));
+        }
+        if let Some(ref ids) = f.session_ids {
+            let terms = ids.iter().map(|sid| Term::from_field_text(schema.session_id, sid));
+            clauses.push((Occur::Must, Box::new(TermSetQuery::new(terms))));
+        }
+    }
+    if let Some(ref needle) = tool_input {
+        clauses.push((Occur::Must, term(schema.content_type, "tool_use")));
+        if let Some(candidates) = exact_substring_candidates(schema, needle) {
+            clauses.push((Occur::Must, candidates));
+        }
+    }
 
-#[tauri::command]
-pub async fn get_index_stats(
-    handle: tauri::State<'_, IndexHandle>,
-) -> Result<IndexStats, String> {
-    let reader = handle.reader.clone();
-    let schema = handle.schema.clone();
-    tokio::task::spawn_blocking(move || get_index_stats_query(&reader, &schema))
-        .await
-        .map_err(|e| e.to_string())?
+    Ok(Some(MessageQuery {
+        query: BooleanQuery::new(clauses),
+        date_from: filter
+            .and_then(|f| f.date_from.as_deref())
+            .and_then(|s| parse_date_filter(s, offset)),
+        date_to: filter
+            .and_then(|f| f.date_to.as_deref())
+            .and_then(|s| parse_date_filter(s, offset)),
+        literal: exact_substring.then(|| query_text.to_string()),
+        tool_input,
+    }))
 }
 
-#[tauri::command]
-pub async fn reindex_all(handle: tauri::State<'_, IndexHandle>) -> Result<(), String> {
-    let reader = handle.reader.clone();
-    let schema = handle.schema.clone();
-    let writer = handle.writer.clone();
-    let paused = handle.paused.clone();
-    let index = handle.index.clone();
-    tokio::task::spawn_blocking(move || {
-        let h = IndexHandle {
-            index,
-            reader,
-            schema,
-            writer,
-            paused,
-        };
-        reindex_all_query(&h)
-    })
-    .await
-    .map_err(|e| e.to_string())?
+/// Session-level filters of a search, checked against enriched session docs
+/// because message docs don't carry these fields.
+struct SessionPostFilter {
+    project_glob: Option<globset::GlobMatcher>,
+    git_branch: Option<String>,
 }
 
-// ---------------------------------------------------------------------------
-// Internal helpers
-// ---------------------------------------------------------------------------
+impl SessionPostFilter {
+    fn new(filter: Option<&SearchFilter>) -> Result<Self, String> {
+        Ok(Self {
+            project_glob: project_glob_matcher(filter.and_then(|f| f.project_glob.as_deref()))?,
+            git_branch: filter.and_then(|f| f.git_branch.clone()),
+        })
+    }
 
-/// Convert a session TantivyDocument to a full SessionDetail.
-fn session_doc_to_detail(doc: &TantivyDocument, schema: &IndexSchema) -> SessionDetail {
-    SessionDetail {
-        session_id: doc.get_str(schema.session_id).unwrap_or("").to_string(),
-        project_path: doc.get_str(schema.project_path).unwrap_or("").to_string(),
-        summary: doc.get_str(schema.summary).unwrap_or("").to_string(),
-        first_prompt: doc.get_str(schema.first_prompt).unwrap_or("").to_string(),
-        git_branch: doc.get_str(schema.git_branch).unwrap_or("").to_string(),
-        model: doc.get_str(schema.model).unwrap_or("").to_string(),
-        status: doc.get_str(schema.status).unwrap_or("").to_string(),
-        jsonl_path: doc.get_str(schema.jsonl_path).unwrap_or("").to_string(),
-        message_count: doc.get_u64_val(schema.message_count).unwrap_or(0),
-        input_tokens: doc.get_u64_val(schema.input_tokens).unwrap_or(0),
-        output_tokens: doc.get_u64_val(schema.output_tokens).unwrap_or(0),
-        total_tokens: doc.get_u64_val(schema.total_tokens).unwrap_or(0),
-        created_at: doc
-            .get_date_val(schema.created_at)
-            .map(format_tantivy_date),
-        modified_at: doc
-            .get_date_val(schema.modified_at)
-            .map(format_tantivy_date),
-        has_tool_use: doc.get_bool_val(schema.has_tool_use).unwrap_or(false),
-        file_exists: doc.get_bool_val(schema.file_exists).unwrap_or(true),
-        archived: doc.get_bool_val(schema.archived).unwrap_or(false),
-        turn_depth: doc.get_u64_val(schema.turn_depth).unwrap_or(0),
+    fn is_empty(&self) -> bool {
+        self.project_glob.is_none() && self.git_branch.is_none()
     }
-}
 
-/// Batch-fetch session documents for a list of session_ids.
-/// Uses a single OR query instead of N+1 individual lookups.
-fn batch_fetch_sessions(
-    searcher: &tantivy::Searcher,
-    schema: &IndexSchema,
-    session_ids: &[String],
-) -> Result<HashMap<String, TantivyDocument>, String> {
-    if session_ids.is_empty() {
-        return Ok(HashMap::new());
+    /// A session whose doc is missing only passes when no filter is set.
+    fn accepts(&self, meta: Option<&TantivyDocument>, schema: &IndexSchema) -> bool {
+        if let Some(ref glob) = self.project_glob {
+            let project = meta.and_then(|m| m.get_str(schema.project_raw));
+            if !glob.is_match(project.unwrap_or("")) {
+                return false;
+            }
+        }
+        match self.git_branch {
+            Some(ref branch) => {
+                meta.and_then(|m| m.get_str(schema.git_branch)) == Some(branch.as_str())
+            }
+            None => true,
+        }
     }
+}
 
-    // Inner OR query: Should(session_id=s1, session_id=s2, ...)
-    let id_clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = session_ids
-        .iter()
-        .map(|sid| {
-            (
-                Occur::Should,
-                Box::new(TermQuery::new(
-                    Term::from_field_text(schema.session_id, sid),
-                    IndexRecordOption::Basic,
-                )) as Box<dyn tantivy::query::Query>,
-            )
-        })
-        .collect();
+/// Count the message docs a search would draw its results from.
+///
+/// Uses the same `build_message_query` clauses and per-doc checks as
+/// `search_sessions_query`, plus its `SessionPostFilter`, so the count always
+/// agrees with what search can show (before the result limit).
+pub fn count_search_matches_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    query_text: &str,
+    filter: Option<&SearchFilter>,
+) -> Result<u64, String> {
+    let searcher = reader.searcher();
+    let session_filter = SessionPostFilter::new(filter)?;
+    let message_query = match build_message_query(schema, query_text, filter)? {
+        Some(q) => q,
+        None => return Ok(0),
+    };
 
-    let query = BooleanQuery::new(vec![
-        (
-            Occur::Must,
-            Box::new(TermQuery::new(
-                Term::from_field_text(schema.doc_type, "session"),
-                IndexRecordOption::Basic,
-            )),
-        ),
-        (
-            Occur::Must,
-            Box::new(BooleanQuery::new(id_clauses)),
-        ),
-    ]);
+    if !message_query.has_doc_checks() && session_filter.is_empty() {
+        let count = searcher
+            .search(&message_query.query, &Count)
+            .map_err(|e| e.to_string())?;
+        return Ok(count as u64);
+    }
 
-    let top_docs = searcher
-        .search(&query, &TopDocs::with_limit(session_ids.len()))
+    let addrs = searcher
+        .search(&message_query.query, &tantivy::collector::DocSetCollector)
         .map_err(|e| e.to_string())?;
-
-    let mut map = HashMap::with_capacity(top_docs.len());
-    for (_, addr) in top_docs {
+    let mut per_session: HashMap<String, u64> = HashMap::new();
+    for addr in addrs {
         let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
-        if let Some(sid) = doc.get_str(schema.session_id) {
-            map.insert(sid.to_string(), doc);
+        if !message_query.accepts(&doc, schema) {
+            continue;
         }
+        let sid = doc.get_str(schema.session_id).unwrap_or("").to_string();
+        *per_session.entry(sid).or_insert(0) += 1;
     }
 
-    Ok(map)
+    if session_filter.is_empty() {
+        return Ok(per_session.values().sum());
+    }
+    let session_ids: Vec<String> = per_session.keys().cloned().collect();
+    let session_meta = batch_fetch_sessions(&searcher, schema, &session_ids)?;
+    Ok(per_session
+        .into_iter()
+        .filter(|(sid, _)| session_filter.accepts(session_meta.get(sid), schema))
+        .map(|(_, n)| n)
+        .sum())
 }
 
-/// Parse a date string (YYYY-MM-DD or ISO 8601) into a tantivy DateTime.
-fn parse_date_filter(s: &str) -> Option<tantivy::DateTime> {
-    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
-        return Some(tantivy::DateTime::from_timestamp_secs(
-            dt.with_timezone(&chrono::Utc).timestamp(),
-        ));
-    }
-    if let Ok(naive) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
-        let dt = naive.and_hms_opt(0, 0, 0)?.and_utc();
-        return Some(tantivy::DateTime::from_timestamp_secs(dt.timestamp()));
+/// All `get_dashboard_summary` aggregates, from one pass over the session docs
+/// of `searcher`. `now_secs` anchors the 7-day token window.
+pub fn dashboard_summary_query(
+    searcher: &tantivy::Searcher,
+    schema: &IndexSchema,
+    now_secs: i64,
+) -> Result<DashboardSummary, String> {
+    let sessions = TermQuery::new(
+        Term::from_field_text(schema.doc_type, "session"),
+        IndexRecordOption::Basic,
+    );
+    let addrs = searcher
+        .search(&sessions, &tantivy::collector::DocSetCollector)
+        .map_err(|e| e.to_string())?;
+
+    let week_start = now_secs - 7 * 86_400;
+    let mut tokens_this_week = 0;
+    let mut projects: HashMap<String, u64> = HashMap::new();
+    let mut models: HashMap<String, u64> = HashMap::new();
+    let mut newest: Option<(tantivy::DateTime, String)> = None;
+    for addr in &addrs {
+        let doc: TantivyDocument = searcher.doc(*addr).map_err(|e| e.to_string())?;
+        let created = doc.get_date_val(schema.created_at).map(|d| d.into_timestamp_secs());
+        if created.is_some_and(|c| c >= week_start) {
+            tokens_this_week += doc.get_u64_val(schema.total_tokens).unwrap_or(0);
+        }
+        if let Some(modified) = doc.get_date_val(schema.modified_at) {
+            if newest.as_ref().is_none_or(|(n, _)| modified > *n) {
+                let sid = doc.get_str(schema.session_id).unwrap_or("").to_string();
+                newest = Some((modified, sid));
+            }
+        }
+        if doc.get_bool_val(schema.archived).unwrap_or(false) {
+            continue;
+        }
+        for (counts, field) in [(&mut projects, schema.project_path), (&mut models, schema.model)] {
+            if let Some(value) = doc.get_str(field).filter(|v| !v.is_empty()) {
+                *counts.entry(value.to_string()).or_default() += 1;
+            }
+        }
     }
-    None
-}
 
-/// JSONL entry for conversation extraction.
-#[derive(Deserialize)]
-struct ConvJsonlEntry {
-    #[serde(rename = "type")]
-    entry_type: Option<String>,
-    message: Option<ConvJsonlMessage>,
-    timestamp: Option<String>,
+    // Most sessions wins; ties go to the alphabetically first
+    let top = |counts: HashMap<String, u64>| {
+        counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+            .map(|(name, _)| name)
+    };
+    Ok(DashboardSummary {
+        total_sessions: addrs.len() as u64,
+        tokens_this_week,
+        top_project: top(projects),
+        top_model: top(models),
+        last_activity: newest.map(|(modified, session_id)| LastActivity {
+            session_id,
+            modified_at: format_tantivy_date(modified),
+        }),
+    })
 }
 
-#[derive(Deserialize)]
-struct ConvJsonlMessage {
-    role: Option<String>,
-    content: Option<serde_json::Value>,
+/// How long a cached `get_dashboard_summary` is served; the index reloading
+/// after a commit invalidates it sooner.
+const DASHBOARD_TTL: Duration = Duration::from_secs(30);
+
+/// Last `get_dashboard_summary`, tagged with the searcher generation it was
+/// computed from (a new one appears whenever the reader picks up a commit).
+#[derive(Default)]
+pub struct DashboardCache(Mutex<Option<(u64, Instant, DashboardSummary)>>);
+
+impl DashboardCache {
+    /// The cached summary if it's from `searcher`'s generation and younger
+    /// than `DASHBOARD_TTL`, else a freshly computed (and cached) one.
+    pub fn get_or_compute(
+        &self,
+        searcher: &tantivy::Searcher,
+        compute: impl FnOnce() -> Result<DashboardSummary, String>,
+    ) -> Result<DashboardSummary, String> {
+        let generation = searcher.generation().generation_id();
+        if let Some((cached_generation, computed_at, ref summary)) =
+            *lock_recover(&self.0, "dashboard cache")
+        {
+            if cached_generation == generation && computed_at.elapsed() < DASHBOARD_TTL {
+                return Ok(summary.clone());
+            }
+        }
+        let summary = compute()?;
+        *lock_recover(&self.0, "dashboard cache") =
+            Some((generation, Instant::now(), summary.clone()));
+        Ok(summary)
+    }
 }
 
-/// Parse conversation messages from a JSONL file (full fidelity).
-fn parse_conversation_from_jsonl(path: &str) -> Result<Vec<ConversationMessage>, String> {
-    let file = File::open(path).map_err(|e| format!("Failed to open {path}: {e}"))?;
-    let reader = BufReader::new(file);
-    let mut messages = Vec::new();
+/// Estimated token spend per day, bucketed by session created_at (UTC).
+///
+/// `date_from`/`date_to` (RFC 3339 or `YYYY-MM-DD`) are inclusive days.
+/// Archived sessions count, since their tokens were spent. Days without
+/// sessions are omitted; results are oldest first. Cache tokens aren't
+/// indexed, so only input and output tokens are priced.
+pub fn daily_token_spend_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    date_from: Option<&str>,
+    date_to: Option<&str>,
+    project: Option<&str>,
+) -> Result<Vec<DailySpend>, String> {
+    let day_of = |dt: tantivy::DateTime| {
+        chrono::DateTime::from_timestamp(dt.into_timestamp_secs(), 0)
+            .map(|d| d.format("%Y-%m-%d").to_string())
+    };
+    let bound = |s: Option<&str>| -> Result<Option<String>, String> {
+        match s {
+            Some(s) => parse_date_filter(s, utc())
+                .and_then(day_of)
+                .map(Some)
+                .ok_or_else(|| format!("Invalid date: {s}")),
+            None => Ok(None),
+        }
+    };
+    let (from_day, to_day) = (bound(date_from)?, bound(date_to)?);
 
-    for line in reader.lines() {
-        let line = line.map_err(|e| e.to_string())?;
-        if line.trim().is_empty() {
+    let mut clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = vec![(
+        Occur::Must,
+        Box::new(TermQuery::new(
+            Term::from_field_text(schema.doc_type, "session"),
+            IndexRecordOption::Basic,
+        )),
+    )];
+    if let Some(project) = project {
+        clauses.push((
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(schema.project_raw, project),
+                IndexRecordOption::Basic,
+            )),
+        ));
+    }
+
+    let searcher = reader.searcher();
+    let top_docs = searcher
+        .search(&BooleanQuery::new(clauses), &TopDocs::with_limit(100_000))
+        .map_err(|e| e.to_string())?;
+
+    let mut days: std::collections::BTreeMap<String, DailySpend> = Default::default();
+    for (_, addr) in top_docs {
+        let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+        let Some(day) = doc.get_date_val(schema.created_at).and_then(day_of) else {
+            continue;
+        };
+        if from_day.as_ref().is_some_and(|f| day < *f)
+            || to_day.as_ref().is_some_and(|t| day > *t)
+        {
             continue;
         }
+        let input = doc.get_u64_val(schema.input_tokens).unwrap_or(0);
+        let output = doc.get_u64_val(schema.output_tokens).unwrap_or(0);
+        let model = doc.get_str(schema.model).unwrap_or("");
+
+        let entry = days.entry(day.clone()).or_insert_with(|| DailySpend {
+            day,
+            total_tokens: 0,
+            estimated_cost: 0.0,
+        });
+        entry.total_tokens += input + output;
+        entry.estimated_cost += estimate_cost(model, input, output);
+    }
+    Ok(days.into_values().collect())
+}
 
-        let entry: ConvJsonlEntry = match serde_json::from_str(&line) {
-            Ok(e) => e,
-            Err(_) => continue,
+/// Group sessions that look like duplicates, archived ones included so an
+/// already-archived copy still shows up next to the live one.
+///
+/// Heuristic, not content comparison: two sessions match when they share a
+/// project, the same first prompt (case- and whitespace-insensitive) and a
+/// message-count bucket (powers of two: 1, 2-3, 4-7, ...), so a retried
+/// scratch prompt clusters while a long session that merely started the same
+/// way doesn't. Sessions without a first prompt are never grouped. Clusters
+/// are ordered largest first, then by newest member.
+pub fn find_duplicate_sessions_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+) -> Result<Vec<DuplicateCluster>, String> {
+    let searcher = reader.searcher();
+    let query = TermQuery::new(
+        Term::from_field_text(schema.doc_type, "session"),
+        IndexRecordOption::Basic,
+    );
+    let addrs = searcher
+        .search(&query, &DocSetCollector)
+        .map_err(|e| e.to_string())?;
+    let mut sessions = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+        let modified = doc.get_date_val(schema.modified_at);
+        sessions.push((modified, session_doc_to_list_item(&doc, schema)));
+    }
+    // Newest first, so every cluster's members come out in modified_at order
+    sessions.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    let mut clusters: Vec<DuplicateCluster> = Vec::new();
+    let mut by_signature: HashMap<(String, String, u32), usize> = HashMap::new();
+    for (_, item) in sessions {
+        let prompt = item.first_prompt.split_whitespace().collect::<Vec<_>>().join(" ");
+        if prompt.is_empty() {
+            continue;
+        }
+        let bucket = u64::BITS - item.message_count.leading_zeros();
+        let signature = (item.project_path.clone(), prompt.to_lowercase(), bucket);
+        let member = DuplicateSession {
+            session_id: item.session_id,
+            message_count: item.message_count,
+            modified_at: item.modified_at,
         };
+        match by_signature.get(&signature) {
+            Some(&i) => clusters[i].sessions.push(member),
+            None => {
+                by_signature.insert(signature, clusters.len());
+                clusters.push(DuplicateCluster {
+                    project_path: item.project_path,
+                    first_prompt: item.first_prompt,
+                    sessions: vec![member],
+                });
+            }
+        }
+    }
 
-        let entry_type = entry.entry_type.as_deref().unwrap_or("");
-        if entry_type != "user" && entry_type != "assistant" && entry_type != "tool" {
+    clusters.retain(|c| c.sessions.len() > 1);
+    // Stable sort keeps newest-first order among equally sized clusters
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.sessions.len()));
+    Ok(clusters)
+}
+
+/// Non-archived sessions whose longest pause between consecutive messages
+/// is at least `min_gap_seconds`, longest pause first.
+///
+/// Message timestamps and session ids are read from fast columns, so no
+/// message doc is loaded; blocks without a timestamp are ignored.
+pub fn find_sessions_with_gaps_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    min_gap_seconds: u64,
+) -> Result<Vec<SessionGap>, String> {
+    let searcher = reader.searcher();
+    let messages = TermQuery::new(
+        Term::from_field_text(schema.doc_type, "message"),
+        IndexRecordOption::Basic,
+    );
+    let addrs = searcher
+        .search(&messages, &DocSetCollector)
+        .map_err(|e| e.to_string())?;
+    let mut by_segment: HashMap<u32, Vec<u32>> = HashMap::new();
+    for addr in addrs {
+        by_segment.entry(addr.segment_ord).or_default().push(addr.doc_id);
+    }
+
+    let mut timestamps: HashMap<String, Vec<i64>> = HashMap::new();
+    let mut session_id = String::new();
+    for (segment_ord, doc_ids) in by_segment {
+        let fast = searcher.segment_reader(segment_ord).fast_fields();
+        let ts_col = fast.date("timestamp").map_err(|e| e.to_string())?;
+        let Some(sid_col) = fast.str("session_id").map_err(|e| e.to_string())? else {
             continue;
+        };
+        for doc_id in doc_ids {
+            let Some(ts) = ts_col.first(doc_id) else {
+                continue;
+            };
+            let Some(ord) = sid_col.term_ords(doc_id).next() else {
+                continue;
+            };
+            session_id.clear();
+            sid_col.ord_to_str(ord, &mut session_id).map_err(|e| e.to_string())?;
+            timestamps
+                .entry(session_id.clone())
+                .or_default()
+                .push(ts.into_timestamp_secs());
+        }
+    }
+
+    // (session, gap, timestamp before the gap), earliest pause on ties
+    let mut gaps: Vec<(String, u64, i64)> = Vec::new();
+    for (sid, mut ts) in timestamps {
+        ts.sort_unstable();
+        let mut longest: Option<(u64, i64)> = None;
+        for w in ts.windows(2) {
+            let gap = (w[1] - w[0]) as u64;
+            if longest.is_none_or(|(g, _)| gap > g) {
+                longest = Some((gap, w[0]));
+            }
+        }
+        if let Some((gap, start)) = longest.filter(|&(g, _)| g >= min_gap_seconds) {
+            gaps.push((sid, gap, start));
+        }
+    }
+
+    let ids: Vec<String> = gaps.iter().map(|(sid, _, _)| sid.clone()).collect();
+    let docs = batch_fetch_sessions(&searcher, schema, &ids)?;
+    let mut results: Vec<SessionGap> = gaps
+        .into_iter()
+        .filter_map(|(sid, gap, start)| {
+            let doc = docs.get(&sid)?;
+            if doc.get_bool_val(schema.archived).unwrap_or(false) {
+                return None;
+            }
+            Some(SessionGap {
+                project_path: doc.get_str(schema.project_path).unwrap_or("").to_string(),
+                summary: doc.get_str(schema.summary).unwrap_or("").to_string(),
+                session_id: sid,
+                max_gap_seconds: gap,
+                gap_started_at: format_tantivy_date(tantivy::DateTime::from_timestamp_secs(start)),
+            })
+        })
+        .collect();
+    results.sort_by(|a, b| {
+        b.max_gap_seconds
+            .cmp(&a.max_gap_seconds)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    Ok(results)
+}
+
+/// Get full session metadata by session_id.
+pub fn get_session_detail_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    session_id: &str,
+) -> Result<SessionDetail, String> {
+    let searcher = reader.searcher();
+    let addr = find_session_doc(&searcher, schema, session_id)?;
+    let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+
+    Ok(session_doc_to_detail(&doc, schema))
+}
+
+/// Details for many sessions in one `batch_fetch_sessions` query, in the
+/// order of `session_ids`. Unknown ids are skipped, not errors.
+pub fn get_session_details_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    session_ids: &[String],
+) -> Result<Vec<SessionDetail>, String> {
+    let searcher = reader.searcher();
+    let docs = batch_fetch_sessions(&searcher, schema, session_ids)?;
+    Ok(session_ids
+        .iter()
+        .filter_map(|sid| docs.get(sid))
+        .map(|doc| session_doc_to_detail(doc, schema))
+        .collect())
+}
+
+/// Sessions from the recents list at `recent_path`, most recently opened
+/// first. Ids no longer in the index (deleted, or not indexed yet) are
+/// skipped, and do not count against `limit`.
+pub fn get_recent_sessions_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    recent_path: &Path,
+    limit: usize,
+) -> Result<Vec<SessionListItem>, String> {
+    let ids = load_recent_ids(recent_path);
+    let searcher = reader.searcher();
+    let docs = batch_fetch_sessions(&searcher, schema, &ids)?;
+    Ok(ids
+        .iter()
+        .filter_map(|sid| docs.get(sid))
+        .take(limit)
+        .map(|doc| session_doc_to_list_item(doc, schema))
+        .collect())
+}
+
+/// Context window fill of a session, from its latest prompt size and the
+/// built-in window table (`context_window`).
+pub fn get_context_fill_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    session_id: &str,
+) -> Result<ContextFill, String> {
+    let searcher = reader.searcher();
+    let addr = find_session_doc(&searcher, schema, session_id)?;
+    let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+
+    let context_tokens = doc.get_u64_val(schema.context_tokens).unwrap_or(0);
+    let model = doc.get_str(schema.model).unwrap_or("").to_string();
+    let window_size = context_window(&model);
+    Ok(ContextFill {
+        context_tokens,
+        window_size,
+        fill_ratio: window_size.map(|w| context_tokens as f64 / w as f64),
+        model,
+    })
+}
+
+/// Count a session's message blocks by role and by content type, one
+/// `Count` per bucket so no docs are loaded.
+pub fn get_session_message_breakdown_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    session_id: &str,
+) -> Result<SessionMessageBreakdown, String> {
+    let searcher = reader.searcher();
+    find_session_doc(&searcher, schema, session_id)?;
+
+    let term = |field, value: &str| -> Box<dyn tantivy::query::Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(field, value),
+            IndexRecordOption::Basic,
+        ))
+    };
+    let count = |field, value: &str| -> Result<u64, String> {
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, term(schema.doc_type, "message")),
+            (Occur::Must, term(schema.session_id, session_id)),
+            (Occur::Must, term(field, value)),
+        ]);
+        searcher
+            .search(&query, &Count)
+            .map(|n| n as u64)
+            .map_err(|e| e.to_string())
+    };
+
+    Ok(SessionMessageBreakdown {
+        user: count(schema.role, "user")?,
+        assistant: count(schema.role, "assistant")?,
+        tool_use: count(schema.content_type, "tool_use")?,
+        tool_result: count(schema.content_type, "tool_result")?,
+        thinking: count(schema.content_type, "thinking")?,
+        text: count(schema.content_type, "text")?,
+    })
+}
+
+/// Compare two sessions' token usage and size.
+///
+/// Reads only the session docs' fast fields (no stored-doc fetch). Deltas are
+/// `b - a`, so a positive value means session B used more.
+pub fn compare_sessions_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    session_id_a: &str,
+    session_id_b: &str,
+) -> Result<SessionComparison, String> {
+    let searcher = reader.searcher();
+    let a = read_session_metrics(&searcher, schema, session_id_a)?;
+    let b = read_session_metrics(&searcher, schema, session_id_b)?;
+
+    let diff = |x: u64, y: u64| y as i64 - x as i64;
+    let delta = MetricsDelta {
+        message_count: diff(a.message_count, b.message_count),
+        input_tokens: diff(a.input_tokens, b.input_tokens),
+        output_tokens: diff(a.output_tokens, b.output_tokens),
+        total_tokens: diff(a.total_tokens, b.total_tokens),
+        turn_depth: diff(a.turn_depth, b.turn_depth),
+    };
+
+    Ok(SessionComparison { a, b, delta })
+}
+
+/// Get conversation messages for a session.
+///
+/// If file_exists: parse from JSONL (full fidelity).
+/// If file pruned: reconstruct from index (content_stored, truncated=true),
+/// unless `prune_old_messages` already dropped the session's message docs.
+pub fn get_conversation_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    session_id: &str,
+) -> Result<Vec<ConversationMessage>, String> {
+    // First, find the session doc to check file_exists and get jsonl_path
+    let searcher = reader.searcher();
+    let query = BooleanQuery::new(vec![
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(schema.session_id, session_id),
+                IndexRecordOption::Basic,
+            )),
+        ),
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(schema.doc_type, "session"),
+                IndexRecordOption::Basic,
+            )),
+        ),
+    ]);
+
+    let top_docs = searcher
+        .search(&query, &TopDocs::with_limit(1))
+        .map_err(|e| e.to_string())?;
+
+    let (_, addr) = top_docs
+        .first()
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+    let session_doc: TantivyDocument = searcher.doc(*addr).map_err(|e| e.to_string())?;
+
+    let file_exists = session_doc
+        .get_bool_val(schema.file_exists)
+        .unwrap_or(true);
+    let jsonl_path = session_doc
+        .get_str(schema.jsonl_path)
+        .unwrap_or("")
+        .to_string();
+
+    if file_exists && !jsonl_path.is_empty() && Path::new(&jsonl_path).exists() {
+        parse_conversation_from_jsonl(&jsonl_path)
+    } else if session_doc.get_bool_val(schema.messages_pruned).unwrap_or(false) {
+        Err(format!(
+            "Conversation unavailable for {session_id}: messages were pruned from the index \
+             and the JSONL file no longer exists"
+        ))
+    } else {
+        reconstruct_conversation_from_index(session_id, &searcher, schema)
+    }
+}
+
+/// Get a conversation as Anthropic Messages API `{role, content: [blocks]}` values.
+///
+/// Blocks (text, tool_use, tool_result, thinking) are passed through untouched
+/// for replay. Needs the JSONL: the index only keeps flattened previews.
+pub fn get_conversation_raw_messages_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    session_id: &str,
+) -> Result<Vec<serde_json::Value>, String> {
+    let searcher = reader.searcher();
+    let addr = find_session_doc(&searcher, schema, session_id)?;
+    let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+
+    let file_exists = doc.get_bool_val(schema.file_exists).unwrap_or(true);
+    let jsonl_path = doc.get_str(schema.jsonl_path).unwrap_or("");
+    if !file_exists || jsonl_path.is_empty() || !Path::new(jsonl_path).exists() {
+        return Err(format!(
+            "Raw messages unavailable for {session_id}: JSONL file no longer exists"
+        ));
+    }
+    parse_raw_messages_from_jsonl(jsonl_path)
+}
+
+/// Look up one message block by its permalink id (see `indexer::message_id`).
+pub fn get_message_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    message_id: &str,
+) -> Result<MessageRecord, String> {
+    let searcher = reader.searcher();
+    let query = TermQuery::new(
+        Term::from_field_text(schema.message_id, message_id),
+        IndexRecordOption::Basic,
+    );
+    let top_docs = searcher
+        .search(&query, &TopDocs::with_limit(1))
+        .map_err(|e| e.to_string())?;
+    let (_, addr) = top_docs
+        .first()
+        .ok_or_else(|| format!("Message not found: {message_id}"))?;
+    let doc: TantivyDocument = searcher.doc(*addr).map_err(|e| e.to_string())?;
+
+    Ok(MessageRecord {
+        message_id: message_id.to_string(),
+        session_id: doc.get_str(schema.session_id).unwrap_or("").to_string(),
+        role: doc.get_str(schema.role).unwrap_or("").to_string(),
+        content_type: doc.get_str(schema.content_type).unwrap_or("").to_string(),
+        text: doc.get_str(schema.content_stored).unwrap_or("").to_string(),
+        timestamp: doc.get_date_val(schema.timestamp).map(format_tantivy_date),
+        turn_index: doc.get_u64_val(schema.turn_index).unwrap_or(0),
+        block_index: doc.get_u64_val(schema.block_index).unwrap_or(0),
+    })
+}
+
+/// Most recent `modified_at` across session docs, with the owning session.
+///
+/// Sorted on the fast field and read back from fast columns, so no stored doc
+/// is loaded. `None` on an empty index or when no session has a timestamp.
+pub fn get_last_activity_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+) -> Result<Option<LastActivity>, String> {
+    let searcher = reader.searcher();
+    let sessions = TermQuery::new(
+        Term::from_field_text(schema.doc_type, "session"),
+        IndexRecordOption::Basic,
+    );
+    let newest = TopDocs::with_limit(1)
+        .order_by_fast_field::<tantivy::DateTime>("modified_at", Order::Desc);
+    let top = searcher
+        .search(&sessions, &newest)
+        .map_err(|e| e.to_string())?;
+    let addr = match top.first() {
+        Some((_, addr)) => *addr,
+        None => return Ok(None),
+    };
+
+    let fast = searcher.segment_reader(addr.segment_ord).fast_fields();
+    let modified_at = match fast
+        .date("modified_at")
+        .map_err(|e| e.to_string())?
+        .first(addr.doc_id)
+    {
+        Some(dt) => dt,
+        None => return Ok(None),
+    };
+    let mut session_id = String::new();
+    if let Some(col) = fast.str("session_id").map_err(|e| e.to_string())? {
+        if let Some(ord) = col.term_ords(addr.doc_id).next() {
+            col.ord_to_str(ord, &mut session_id).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(Some(LastActivity {
+        session_id,
+        modified_at: format_tantivy_date(modified_at),
+    }))
+}
+
+/// Get index statistics: session/message counts, segments, disk size.
+pub fn get_index_stats_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+) -> Result<IndexStats, String> {
+    let searcher = reader.searcher();
+
+    let session_query = TermQuery::new(
+        Term::from_field_text(schema.doc_type, "session"),
+        IndexRecordOption::Basic,
+    );
+    let total_sessions = searcher
+        .search(&session_query, &Count)
+        .map_err(|e| e.to_string())? as u64;
+
+    let message_query = TermQuery::new(
+        Term::from_field_text(schema.doc_type, "message"),
+        IndexRecordOption::Basic,
+    );
+    let total_messages = searcher
+        .search(&message_query, &Count)
+        .map_err(|e| e.to_string())? as u64;
+
+    // Count archived sessions by collecting session docs and post-filtering
+    let top = TopDocs::with_limit(total_sessions as usize + 1);
+    let all_sessions = searcher
+        .search(&session_query, &top)
+        .map_err(|e| e.to_string())?;
+
+    let mut archived_count = 0u64;
+    let mut newest_indexed: Option<i64> = None;
+    for (_, addr) in &all_sessions {
+        let doc: TantivyDocument = searcher.doc(*addr).map_err(|e| e.to_string())?;
+        if doc.get_bool_val(schema.archived).unwrap_or(false) {
+            archived_count += 1;
+        }
+        if let Some(dt) = doc.get_date_val(schema.modified_at) {
+            let secs = dt.into_timestamp_secs();
+            newest_indexed = Some(newest_indexed.map_or(secs, |n| n.max(secs)));
+        }
+    }
+
+    let active_sessions = total_sessions - archived_count;
+    let segment_count = searcher.segment_readers().len() as u64;
+
+    // Index size from standard location
+    let index_size_bytes = get_index_dir_size();
+
+    let meta = index_dir().and_then(|p| read_index_meta(&p));
+    let dirs = known_session_dirs().unwrap_or_default();
+    let (is_stale, newest_on_disk) = index_staleness(meta.as_ref(), newest_indexed, &dirs);
+
+    Ok(IndexStats {
+        total_sessions,
+        active_sessions,
+        archived_sessions: archived_count,
+        total_messages,
+        segment_count,
+        index_size_bytes,
+        is_stale,
+        newest_on_disk,
+        watcher_mode: None,
+    })
+}
+
+/// Slack between a JSONL's mtime and its last entry timestamp before the
+/// index counts as stale (the watcher debounces writes by 2s).
+const STALE_GRACE_SECS: i64 = 30;
+
+/// Compare the newest JSONL mtime under `session_dirs` with what the index
+/// has seen: the later of the meta's `indexed_at` (bulk index time) and the
+/// newest indexed session `modified_at` (kept current by the watcher).
+///
+/// Returns `(is_stale, newest_on_disk)`. Walks every JSONL file, so this scales
+/// with the number of sessions on disk.
+fn index_staleness(
+    meta: Option<&IndexMeta>,
+    newest_indexed_secs: Option<i64>,
+    session_dirs: &[PathBuf],
+) -> (bool, Option<String>) {
+    let newest = match session_dirs.iter().filter_map(|dir| newest_jsonl_mtime(dir)).max() {
+        Some(t) => chrono::DateTime::<chrono::Utc>::from(t),
+        None => return (false, None),
+    };
+    let indexed_at = meta
+        .and_then(|m| chrono::DateTime::parse_from_rfc3339(&m.indexed_at).ok())
+        .map(|dt| dt.timestamp());
+    let seen = match (indexed_at, newest_indexed_secs) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+    let is_stale = match seen {
+        Some(secs) => newest.timestamp() > secs + STALE_GRACE_SECS,
+        None => true,
+    };
+    (is_stale, Some(newest.to_rfc3339()))
+}
+
+/// Prime the OS page cache so the first real query isn't slowed by lazy mmaps.
+///
+/// Runs a Count over session docs (term dictionary + postings) and a
+/// modified_at-sorted TopDocs (the fast field `list_sessions` sorts on).
+/// Returns the number of session docs seen.
+pub fn warm_up_index(reader: &IndexReader, schema: &IndexSchema) -> Result<u64, String> {
+    let searcher = reader.searcher();
+    let sessions = TermQuery::new(
+        Term::from_field_text(schema.doc_type, "session"),
+        IndexRecordOption::Basic,
+    );
+    let count = searcher
+        .search(&sessions, &Count)
+        .map_err(|e| e.to_string())?;
+    let newest = TopDocs::with_limit(1)
+        .order_by_fast_field::<tantivy::DateTime>("modified_at", Order::Desc);
+    searcher
+        .search(&sessions, &newest)
+        .map_err(|e| e.to_string())?;
+    Ok(count as u64)
+}
+
+/// Rewrite swarm-ui-meta.json from the live index: current `SCHEMA_VERSION`,
+/// now as `indexed_at`, and a fresh Count of session docs.
+///
+/// For a deleted or drifted meta file over intact data. The running index
+/// always has the current schema (a mismatch is dropped at startup), so this
+/// can't mask a real version change.
+pub fn repair_index_meta_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    index_path: &Path,
+) -> Result<IndexMeta, String> {
+    let sessions = TermQuery::new(
+        Term::from_field_text(schema.doc_type, "session"),
+        IndexRecordOption::Basic,
+    );
+    let session_count = reader
+        .searcher()
+        .search(&sessions, &Count)
+        .map_err(|e| e.to_string())? as u64;
+    // Keep the measured throughput; repairing doesn't re-time the index
+    let bytes_per_sec = read_index_meta(index_path).and_then(|m| m.bytes_per_sec);
+    write_index_meta(index_path, session_count, bytes_per_sec).map_err(|e| e.to_string())?;
+    read_index_meta(index_path).ok_or_else(|| "Index meta unreadable after repair".to_string())
+}
+
+/// Canonical `jsonl_path`, which must resolve (symlinks included) to a file
+/// under one of `session_dirs`.
+fn confined_session_file(jsonl_path: &str, session_dirs: &[PathBuf]) -> Result<PathBuf, String> {
+    let path = Path::new(jsonl_path)
+        .canonicalize()
+        .map_err(|e| format!("Cannot resolve {jsonl_path}: {e}"))?;
+    let under_session_dir = session_dirs
+        .iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .any(|root| path.starts_with(root));
+    if !under_session_dir || !path.is_file() {
+        return Err(format!("Not a session file under a session directory: {jsonl_path}"));
+    }
+    Ok(path)
+}
+
+/// The JSONL file of an indexed session, for revealing in the file manager.
+///
+/// Errors if the session is missing, its file is gone (`file_exists` false
+/// or deleted since the last index), or the path isn't under one of
+/// `session_dirs`.
+pub fn resolve_session_file_query(
+    reader: &IndexReader,
+    schema: &IndexSchema,
+    session_id: &str,
+    session_dirs: &[PathBuf],
+) -> Result<PathBuf, String> {
+    let searcher = reader.searcher();
+    let addr = find_session_doc(&searcher, schema, session_id)?;
+    let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+
+    let jsonl_path = doc.get_str(schema.jsonl_path).unwrap_or("");
+    let file_exists = doc.get_bool_val(schema.file_exists).unwrap_or(true);
+    if !file_exists || jsonl_path.is_empty() || !Path::new(jsonl_path).exists() {
+        return Err(format!("Session file for {session_id} no longer exists"));
+    }
+    confined_session_file(jsonl_path, session_dirs)
+}
+
+/// Parse a JSONL file exactly as the indexer would, without writing anything,
+/// to explain why a session is missing from search.
+///
+/// `jsonl_path` must resolve (symlinks included) to a file under one of
+/// `session_dirs`.
+pub fn diagnose_session_file_query(
+    jsonl_path: &str,
+    session_dirs: &[PathBuf],
+    schema: &IndexSchema,
+    options: &IndexOptions,
+) -> Result<SessionFileDiagnosis, String> {
+    let path = confined_session_file(jsonl_path, session_dirs)?;
+    let (docs, report) = parse_jsonl_with_report(&path, schema, None, options);
+    let session_id_found = !report.session_id_from_stem
+        && docs
+            .first()
+            .and_then(|d| d.get_str(schema.session_id))
+            .is_some_and(|sid| !sid.is_empty());
+    Ok(SessionFileDiagnosis {
+        session_id_found,
+        message_doc_count: docs.len().saturating_sub(1) as u64,
+        lines_total: report.lines_total,
+        lines_skipped: report.lines_skipped,
+        first_error: report.first_error,
+    })
+}
+
+/// Pause watcher, delete all documents, re-index from filesystem, resume watcher.
+pub fn reindex_all_query(handle: &IndexHandle) -> Result<(), String> {
+    handle.paused.store(true, Ordering::SeqCst);
+
+    // Delete all documents
+    {
+        let mut writer = lock_recover(&handle.writer, "index writer");
+        writer.delete_all_documents().map_err(|e| e.to_string())?;
+        writer.commit().map_err(|e| e.to_string())?;
+    }
+
+    // Re-index all JSONL files, extra session directories included
+    let dirs = known_session_dirs()?;
+    if !dirs.is_empty() {
+        let mut writer = lock_recover(&handle.writer, "index writer");
+        let summary = crate::search::indexer::bulk_index(
+            &mut writer,
+            &handle.schema,
+            &dirs,
+            &handle.options,
+            None,
+            None,
+            Some(&handle.health.bulk),
+        )
+            .map_err(|e| e.to_string())?;
+        // Re-time the throughput `estimate_reindex_time` relies on
+        if let Some(path) = index_dir() {
+            if let Err(e) =
+                write_index_meta(&path, summary.session_count, summary.bytes_per_sec)
+            {
+                tracing::warn!("Failed to write index meta: {e}");
+            }
+        }
+    }
+
+    handle.paused.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Existing session directories: `~/.claude/projects` and the configured
+/// extra directories. A full reindex covers them, and session files are
+/// confined to them.
+fn known_session_dirs() -> Result<Vec<PathBuf>, String> {
+    let projects_dir = projects_dir().ok_or("No home directory found")?;
+    let extra_dirs = index_dir().map(|p| read_extra_dirs(&p)).unwrap_or_default();
+    Ok(session_dirs(projects_dir, extra_dirs)
+        .into_iter()
+        .filter(|dir| dir.exists())
+        .collect())
+}
+
+/// Size of a full reindex and its expected duration at the throughput the
+/// last bulk index measured (`DEFAULT_INDEX_BYTES_PER_SEC` before any).
+pub fn estimate_reindex_time_query(index_path: Option<&Path>) -> Result<ReindexEstimate, String> {
+    let bytes_per_sec = index_path
+        .and_then(read_index_meta)
+        .and_then(|m| m.bytes_per_sec)
+        .filter(|&rate| rate > 0)
+        .unwrap_or(DEFAULT_INDEX_BYTES_PER_SEC);
+    Ok(estimate_reindex(&known_session_dirs()?, bytes_per_sec))
+}
+
+/// Re-index one project directory (e.g. `-home-user-app`) under
+/// ~/.claude/projects or an extra session directory, the first that has it.
+///
+/// The watcher is paused meanwhile, so it can't re-add the project's docs
+/// between the delete and the re-index.
+pub fn reindex_project_query(handle: &IndexHandle, project_dir_name: &str) -> Result<u64, String> {
+    if project_dir_name.is_empty()
+        || project_dir_name.contains('/')
+        || project_dir_name.contains('\\')
+        || project_dir_name == ".."
+    {
+        return Err(format!("Invalid project directory name: {project_dir_name:?}"));
+    }
+    let project_dir = known_session_dirs()?
+        .into_iter()
+        .map(|dir| dir.join(project_dir_name))
+        .find(|dir| dir.is_dir())
+        .ok_or_else(|| format!("Project directory not found: {project_dir_name}"))?;
+
+    handle.paused.store(true, Ordering::SeqCst);
+    let result = crate::search::indexer::reindex_project_dir(
+        &mut lock_recover(&handle.writer, "index writer"),
+        &handle.reader,
+        &handle.schema,
+        &project_dir,
+        &handle.options,
+    )
+    .map_err(|e| e.to_string());
+    handle.paused.store(false, Ordering::SeqCst);
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command wrappers (async, delegates to spawn_blocking)
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn list_sessions(
+    handle: tauri::State<'_, IndexHandle>,
+    filter: Option<SessionFilter>,
+) -> Result<Vec<SessionListItem>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || list_sessions_query(&reader, &schema, filter.as_ref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Re-emit the current session list as one `session:snapshot` event.
+#[tauri::command]
+pub async fn resync_sessions(
+    handle: tauri::State<'_, IndexHandle>,
+    app: tauri::AppHandle,
+    filter: Option<SessionFilter>,
+) -> Result<usize, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        resync_sessions_query(&reader, &schema, filter.as_ref(), &app)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_sessions_by_branch(
+    handle: tauri::State<'_, IndexHandle>,
+    branch: String,
+) -> Result<Vec<SessionListItem>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || list_sessions_by_branch_query(&reader, &schema, &branch))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn search_sessions(
+    handle: tauri::State<'_, IndexHandle>,
+    query_text: String,
+    filter: Option<SearchFilter>,
+) -> Result<Vec<SearchResult>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    // Each search supersedes the previous one, so stale keystrokes stop early
+    let token = SearchToken::next(&handle.search_generation);
+    tokio::task::spawn_blocking(move || {
+        search_sessions_query_cancellable(&reader, &schema, &query_text, filter.as_ref(), &token)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn search_project_sessions(
+    handle: tauri::State<'_, IndexHandle>,
+    project: String,
+    git_branch: String,
+    query_text: String,
+    filter: Option<SearchFilter>,
+) -> Result<Vec<SearchResult>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let token = SearchToken::next(&handle.search_generation);
+    tokio::task::spawn_blocking(move || {
+        search_project_sessions_query(
+            &reader,
+            &schema,
+            &project,
+            &git_branch,
+            &query_text,
+            filter.as_ref(),
+            &token,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// `search_sessions`, streaming each result over `on_event` as it's enriched
+/// and closing with a terminal event; see `search_sessions_stream_query`.
+#[tauri::command]
+pub async fn search_sessions_stream(
+    handle: tauri::State<'_, IndexHandle>,
+    query_text: String,
+    filter: Option<SearchFilter>,
+    on_event: tauri::ipc::Channel<SearchStreamEvent>,
+) -> Result<(), String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let token = SearchToken::next(&handle.search_generation);
+    tokio::task::spawn_blocking(move || {
+        let mut emit = |event| {
+            if let Err(e) = on_event.send(event) {
+                tracing::debug!("Search stream receiver gone: {e}");
+            }
+        };
+        search_sessions_stream_query(
+            &reader,
+            &schema,
+            &query_text,
+            filter.as_ref(),
+            &token,
+            &mut emit,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_dashboard_summary(
+    handle: tauri::State<'_, IndexHandle>,
+) -> Result<DashboardSummary, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let cache = handle.dashboard_cache.clone();
+    tokio::task::spawn_blocking(move || {
+        let searcher = reader.searcher();
+        cache.get_or_compute(&searcher, || {
+            dashboard_summary_query(&searcher, &schema, chrono::Utc::now().timestamp())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn count_search_matches(
+    handle: tauri::State<'_, IndexHandle>,
+    query_text: String,
+    filter: Option<SearchFilter>,
+) -> Result<u64, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        count_search_matches_query(&reader, &schema, &query_text, filter.as_ref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Cancel any in-flight `search_sessions` call (it returns `SEARCH_CANCELLED`).
+#[tauri::command]
+pub async fn cancel_search(handle: tauri::State<'_, IndexHandle>) -> Result<(), String> {
+    handle.search_generation.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_session_detail(
+    handle: tauri::State<'_, IndexHandle>,
+    session_id: String,
+) -> Result<SessionDetail, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || get_session_detail_query(&reader, &schema, &session_id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_session_details(
+    handle: tauri::State<'_, IndexHandle>,
+    session_ids: Vec<String>,
+) -> Result<Vec<SessionDetail>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || get_session_details_query(&reader, &schema, &session_ids))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Mark a session as opened in the app, for `get_recent_sessions`.
+#[tauri::command]
+pub async fn record_session_open(session_id: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let path = recent_sessions_path().ok_or("Could not determine recents list location")?;
+        crate::search::recent::record_session_open(&path, &session_id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Sessions most recently opened in the app (default 10), newest first.
+#[tauri::command]
+pub async fn get_recent_sessions(
+    handle: tauri::State<'_, IndexHandle>,
+    limit: Option<usize>,
+) -> Result<Vec<SessionListItem>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        let path = recent_sessions_path().ok_or("Could not determine recents list location")?;
+        get_recent_sessions_query(&reader, &schema, &path, limit.unwrap_or(10))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_context_fill(
+    handle: tauri::State<'_, IndexHandle>,
+    session_id: String,
+) -> Result<ContextFill, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || get_context_fill_query(&reader, &schema, &session_id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_session_message_breakdown(
+    handle: tauri::State<'_, IndexHandle>,
+    session_id: String,
+) -> Result<SessionMessageBreakdown, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        get_session_message_breakdown_query(&reader, &schema, &session_id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn compare_sessions(
+    handle: tauri::State<'_, IndexHandle>,
+    session_id_a: String,
+    session_id_b: String,
+) -> Result<SessionComparison, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        compare_sessions_query(&reader, &schema, &session_id_a, &session_id_b)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_conversation(
+    handle: tauri::State<'_, IndexHandle>,
+    session_id: String,
+) -> Result<Vec<ConversationMessage>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || get_conversation_query(&reader, &schema, &session_id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_conversation_raw_messages(
+    handle: tauri::State<'_, IndexHandle>,
+    session_id: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        get_conversation_raw_messages_query(&reader, &schema, &session_id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_daily_token_spend(
+    handle: tauri::State<'_, IndexHandle>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+    project: Option<String>,
+) -> Result<Vec<DailySpend>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        daily_token_spend_query(
+            &reader,
+            &schema,
+            date_from.as_deref(),
+            date_to.as_deref(),
+            project.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn find_duplicate_sessions(
+    handle: tauri::State<'_, IndexHandle>,
+) -> Result<Vec<DuplicateCluster>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || find_duplicate_sessions_query(&reader, &schema))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Sessions that paused at least `min_gap_seconds` between two messages.
+#[tauri::command]
+pub async fn find_sessions_with_gaps(
+    handle: tauri::State<'_, IndexHandle>,
+    min_gap_seconds: u64,
+) -> Result<Vec<SessionGap>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        find_sessions_with_gaps_query(&reader, &schema, min_gap_seconds)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_message(
+    handle: tauri::State<'_, IndexHandle>,
+    message_id: String,
+) -> Result<MessageRecord, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || get_message_query(&reader, &schema, &message_id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_last_activity(
+    handle: tauri::State<'_, IndexHandle>,
+) -> Result<Option<LastActivity>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || get_last_activity_query(&reader, &schema))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_index_stats(
+    handle: tauri::State<'_, IndexHandle>,
+) -> Result<IndexStats, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let watcher_mode = *lock_recover(&handle.watcher_mode, "watcher mode");
+    let mut stats = tokio::task::spawn_blocking(move || get_index_stats_query(&reader, &schema))
+        .await
+        .map_err(|e| e.to_string())??;
+    stats.watcher_mode = watcher_mode;
+    Ok(stats)
+}
+
+#[tauri::command]
+pub async fn get_background_health(
+    handle: tauri::State<'_, IndexHandle>,
+) -> Result<BackgroundHealth, String> {
+    Ok(handle.health.report())
+}
+
+#[tauri::command]
+pub async fn repair_index_meta(handle: tauri::State<'_, IndexHandle>) -> Result<IndexMeta, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let path = index_dir().ok_or("Could not determine index directory")?;
+    tokio::task::spawn_blocking(move || repair_index_meta_query(&reader, &schema, &path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn diagnose_session_file(
+    handle: tauri::State<'_, IndexHandle>,
+    jsonl_path: String,
+) -> Result<SessionFileDiagnosis, String> {
+    let schema = handle.schema.clone();
+    let options = handle.options;
+    let session_dirs = known_session_dirs()?;
+    tokio::task::spawn_blocking(move || {
+        diagnose_session_file_query(&jsonl_path, &session_dirs, &schema, &options)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Absolute, existing, distinct directories for `EXTRA_DIRS_FILE`.
+fn validate_extra_dirs(dirs: Vec<PathBuf>) -> Result<Vec<PathBuf>, String> {
+    let mut valid: Vec<PathBuf> = Vec::with_capacity(dirs.len());
+    for dir in dirs {
+        if !dir.is_absolute() {
+            return Err(format!("Not an absolute path: {}", dir.display()));
+        }
+        if !dir.is_dir() {
+            return Err(format!("Not a directory: {}", dir.display()));
+        }
+        if !valid.contains(&dir) {
+            valid.push(dir);
+        }
+    }
+    Ok(valid)
+}
+
+/// Session directories indexed and watched besides `~/.claude/projects`.
+#[tauri::command]
+pub async fn get_extra_session_dirs() -> Result<Vec<PathBuf>, String> {
+    let path = index_dir().ok_or("Could not determine index directory")?;
+    Ok(read_extra_dirs(&path))
+}
+
+/// Replace the extra session directories. `reindex_all` indexes them right
+/// away; the watcher picks them up on the next app start.
+#[tauri::command]
+pub async fn set_extra_session_dirs(dirs: Vec<PathBuf>) -> Result<Vec<PathBuf>, String> {
+    let path = index_dir().ok_or("Could not determine index directory")?;
+    let dirs = validate_extra_dirs(dirs)?;
+    fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+    write_extra_dirs(&path, &dirs).map_err(|e| e.to_string())?;
+    Ok(dirs)
+}
+
+/// Show a session's JSONL file, selected, in Finder/Explorer/the file manager.
+#[tauri::command]
+pub async fn reveal_session_file(
+    handle: tauri::State<'_, IndexHandle>,
+    session_id: String,
+) -> Result<(), String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let session_dirs = known_session_dirs()?;
+    let path = tokio::task::spawn_blocking(move || {
+        resolve_session_file_query(&reader, &schema, &session_id, &session_dirs)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    tauri_plugin_opener::reveal_item_in_dir(&path)
+        .map_err(|e| format!("Cannot reveal {}: {e}", path.display()))
+}
+
+#[tauri::command]
+pub async fn get_index_size_breakdown() -> Result<Vec<IndexSizeComponent>, String> {
+    let path = index_dir().ok_or("Could not determine index directory")?;
+    tokio::task::spawn_blocking(move || index_size_breakdown(&path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Start of the window `prune_old_messages` keeps: `older_than_days` ago.
+fn prune_cutoff(older_than_days: u32) -> Result<tantivy::DateTime, String> {
+    if older_than_days == 0 {
+        return Err("older_than_days must be at least 1".to_string());
+    }
+    Ok(tantivy::DateTime::from_timestamp_secs(
+        chrono::Utc::now().timestamp() - i64::from(older_than_days) * 86_400,
+    ))
+}
+
+/// A `cutoff` a preview returned, checked to lie at least a day back so it
+/// can't take sessions the day-count form would refuse to.
+fn previewed_cutoff(cutoff: i64) -> Result<i64, String> {
+    if cutoff > chrono::Utc::now().timestamp() - 86_400 {
+        return Err(format!("cutoff {cutoff} is less than a day ago"));
+    }
+    Ok(cutoff)
+}
+
+/// Drop message docs of sessions not modified in `older_than_days` days,
+/// keeping their session docs, then compact the index. A `cutoff` from
+/// `prune_old_messages_preview` replaces `older_than_days`, so exactly the
+/// previewed sessions are pruned.
+#[tauri::command]
+pub async fn prune_old_messages(
+    handle: tauri::State<'_, IndexHandle>,
+    older_than_days: u32,
+    cutoff: Option<i64>,
+) -> Result<PruneReport, String> {
+    let cutoff = match cutoff {
+        Some(cutoff) => tantivy::DateTime::from_timestamp_secs(previewed_cutoff(cutoff)?),
+        None => prune_cutoff(older_than_days)?,
+    };
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let writer = handle.writer.clone();
+    let path = index_dir().ok_or("Could not determine index directory")?;
+    tokio::task::spawn_blocking(move || {
+        crate::search::prune::prune_old_messages(
+            &writer,
+            &reader,
+            &schema,
+            cutoff,
+            &path,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The sessions and message count `prune_old_messages` would drop, for the UI
+/// to confirm.
+#[tauri::command]
+pub async fn prune_old_messages_preview(
+    handle: tauri::State<'_, IndexHandle>,
+    older_than_days: u32,
+) -> Result<PrunePreview, String> {
+    let cutoff = prune_cutoff(older_than_days)?;
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        crate::search::prune::prune_old_messages_preview(&reader, &schema, cutoff)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn estimate_reindex_time() -> Result<ReindexEstimate, String> {
+    tokio::task::spawn_blocking(move || estimate_reindex_time_query(index_dir().as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn reindex_all(handle: tauri::State<'_, IndexHandle>) -> Result<(), String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let writer = handle.writer.clone();
+    let paused = handle.paused.clone();
+    let index = handle.index.clone();
+    let options = handle.options;
+    let search_generation = handle.search_generation.clone();
+    let watcher_mode = handle.watcher_mode.clone();
+    let health = handle.health.clone();
+    let dashboard_cache = handle.dashboard_cache.clone();
+    tokio::task::spawn_blocking(move || {
+        let h = IndexHandle {
+            index,
+            reader,
+            schema,
+            writer,
+            paused,
+            options,
+            search_generation,
+            watcher_mode,
+            health,
+            dashboard_cache,
+        };
+        reindex_all_query(&h)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn reindex_project(
+    handle: tauri::State<'_, IndexHandle>,
+    project_dir_name: String,
+) -> Result<u64, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let writer = handle.writer.clone();
+    let paused = handle.paused.clone();
+    let index = handle.index.clone();
+    let options = handle.options;
+    let search_generation = handle.search_generation.clone();
+    let watcher_mode = handle.watcher_mode.clone();
+    let health = handle.health.clone();
+    let dashboard_cache = handle.dashboard_cache.clone();
+    tokio::task::spawn_blocking(move || {
+        let h = IndexHandle {
+            index,
+            reader,
+            schema,
+            writer,
+            paused,
+            options,
+            search_generation,
+            watcher_mode,
+            health,
+            dashboard_cache,
+        };
+        reindex_project_query(&h, &project_dir_name)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Archive every session of a project (path or `~/.claude/projects` dir name).
+/// Returns the number of sessions newly archived.
+#[tauri::command]
+pub async fn archive_project(
+    handle: tauri::State<'_, IndexHandle>,
+    project_dir_name_or_path: String,
+) -> Result<u64, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let writer = handle.writer.clone();
+    tokio::task::spawn_blocking(move || {
+        let ids_path = crate::search::archive::archived_ids_path()
+            .ok_or("Could not determine archive list location")?;
+        let mut w = lock_recover(&writer, "index writer");
+        crate::search::archive::archive_project_sessions(
+            &mut w,
+            &reader,
+            &schema,
+            &project_dir_name_or_path,
+            &ids_path,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The sessions `archive_project` would archive, for the UI to confirm.
+#[tauri::command]
+pub async fn archive_project_preview(
+    handle: tauri::State<'_, IndexHandle>,
+    project_dir_name_or_path: String,
+) -> Result<Vec<AffectedSession>, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        crate::search::archive::preview_project_archive(&reader, &schema, &project_dir_name_or_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Unix seconds `days` before now, saturating for huge values. 0 is refused:
+/// it would take every session, even one written a moment ago.
+fn days_ago_cutoff(days: u64) -> Result<i64, String> {
+    if days == 0 {
+        return Err("days must be at least 1".to_string());
+    }
+    let days = i64::try_from(days).unwrap_or(i64::MAX);
+    Ok(chrono::Utc::now().timestamp().saturating_sub(days.saturating_mul(86_400)))
+}
+
+/// Archive every session untouched for more than `days` days. Returns the
+/// number of sessions newly archived. A `cutoff` from
+/// `archive_older_than_preview` replaces `days`, so exactly the previewed
+/// sessions are archived.
+#[tauri::command]
+pub async fn archive_older_than(
+    handle: tauri::State<'_, IndexHandle>,
+    days: u64,
+    cutoff: Option<i64>,
+) -> Result<u64, String> {
+    let cutoff = match cutoff {
+        Some(cutoff) => previewed_cutoff(cutoff)?,
+        None => days_ago_cutoff(days)?,
+    };
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    let writer = handle.writer.clone();
+    tokio::task::spawn_blocking(move || {
+        let ids_path = crate::search::archive::archived_ids_path()
+            .ok_or("Could not determine archive list location")?;
+        let mut w = lock_recover(&writer, "index writer");
+        crate::search::archive::archive_sessions_older_than(
+            &mut w,
+            &reader,
+            &schema,
+            cutoff,
+            &ids_path,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The sessions `archive_older_than` would archive, for the UI to confirm.
+#[tauri::command]
+pub async fn archive_older_than_preview(
+    handle: tauri::State<'_, IndexHandle>,
+    days: u64,
+) -> Result<ArchivePreview, String> {
+    let cutoff = days_ago_cutoff(days)?;
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        let sessions =
+            crate::search::archive::preview_archive_older_than(&reader, &schema, cutoff)?;
+        Ok(ArchivePreview { sessions, cutoff })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Export the whole index as NDJSON for backup or migration. Returns the doc count.
+#[tauri::command]
+pub async fn export_index_ndjson(
+    handle: tauri::State<'_, IndexHandle>,
+    dest_path: String,
+) -> Result<u64, String> {
+    let reader = handle.reader.clone();
+    let schema = handle.schema.clone();
+    tokio::task::spawn_blocking(move || {
+        crate::search::dump::export_ndjson(&reader, &schema, Path::new(&dest_path))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Replace the index with an `export_index_ndjson` dump. Returns the doc count.
+#[tauri::command]
+pub async fn import_index_ndjson(
+    handle: tauri::State<'_, IndexHandle>,
+    src_path: String,
+) -> Result<u64, String> {
+    let schema = handle.schema.clone();
+    let writer = handle.writer.clone();
+    let paused = handle.paused.clone();
+    tokio::task::spawn_blocking(move || {
+        // Keep the watcher from interleaving docs with the import
+        paused.store(true, Ordering::SeqCst);
+        let result = crate::search::dump::import_ndjson(
+            &mut lock_recover(&writer, "index writer"),
+            &schema,
+            Path::new(&src_path),
+        );
+        paused.store(false, Ordering::SeqCst);
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/// Compile an optional project glob (e.g. `*/frontend-*`).
+///
+/// `*` also matches `/`, so `*/frontend-*` matches any path whose last segment
+/// starts with `frontend-`. Globs can't use the term index: every candidate
+/// session doc is matched in memory, so a broad glob like `*` costs as much as
+/// an unfiltered listing (up to the 10,000-doc cap).
+fn project_glob_matcher(pattern: Option<&str>) -> Result<Option<globset::GlobMatcher>, String> {
+    match pattern {
+        Some(p) if !p.is_empty() => globset::Glob::new(p)
+            .map(|g| Some(g.compile_matcher()))
+            .map_err(|e| format!("Invalid project glob '{p}': {e}")),
+        _ => Ok(None),
+    }
+}
+
+/// Locate the session doc for `session_id`, or a "Session not found" error.
+fn find_session_doc(
+    searcher: &Searcher,
+    schema: &IndexSchema,
+    session_id: &str,
+) -> Result<DocAddress, String> {
+    let query = BooleanQuery::new(vec![
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(schema.session_id, session_id),
+                IndexRecordOption::Basic,
+            )),
+        ),
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(schema.doc_type, "session"),
+                IndexRecordOption::Basic,
+            )),
+        ),
+    ]);
+
+    let top_docs = searcher
+        .search(&query, &TopDocs::with_limit(1))
+        .map_err(|e| e.to_string())?;
+
+    top_docs
+        .first()
+        .map(|(_, addr)| *addr)
+        .ok_or_else(|| format!("Session not found: {session_id}"))
+}
+
+/// Read a session's metrics from its doc's fast-field columns.
+fn read_session_metrics(
+    searcher: &Searcher,
+    schema: &IndexSchema,
+    session_id: &str,
+) -> Result<SessionMetrics, String> {
+    let addr = find_session_doc(searcher, schema, session_id)?;
+    let fast = searcher.segment_reader(addr.segment_ord).fast_fields();
+    let read_u64 = |name: &str| -> Result<u64, String> {
+        let col = fast.u64(name).map_err(|e| e.to_string())?;
+        Ok(col.first(addr.doc_id).unwrap_or(0))
+    };
+
+    let mut model = String::new();
+    if let Some(col) = fast.str("model").map_err(|e| e.to_string())? {
+        if let Some(ord) = col.term_ords(addr.doc_id).next() {
+            col.ord_to_str(ord, &mut model).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(SessionMetrics {
+        session_id: session_id.to_string(),
+        model,
+        message_count: read_u64("message_count")?,
+        input_tokens: read_u64("input_tokens")?,
+        output_tokens: read_u64("output_tokens")?,
+        total_tokens: read_u64("total_tokens")?,
+        turn_depth: read_u64("turn_depth")?,
+    })
+}
+
+/// Convert a session TantivyDocument to a full SessionDetail.
+fn session_doc_to_detail(doc: &TantivyDocument, schema: &IndexSchema) -> SessionDetail {
+    SessionDetail {
+        session_id: doc.get_str(schema.session_id).unwrap_or("").to_string(),
+        project_path: doc.get_str(schema.project_path).unwrap_or("").to_string(),
+        summary: doc.get_str(schema.summary).unwrap_or("").to_string(),
+        first_prompt: doc.get_str(schema.first_prompt).unwrap_or("").to_string(),
+        git_branch: doc.get_str(schema.git_branch).unwrap_or("").to_string(),
+        model: doc.get_str(schema.model).unwrap_or("").to_string(),
+        status: doc.get_str(schema.status).unwrap_or("").to_string(),
+        jsonl_path: doc.get_str(schema.jsonl_path).unwrap_or("").to_string(),
+        message_count: doc.get_u64_val(schema.message_count).unwrap_or(0),
+        input_tokens: doc.get_u64_val(schema.input_tokens).unwrap_or(0),
+        output_tokens: doc.get_u64_val(schema.output_tokens).unwrap_or(0),
+        total_tokens: doc.get_u64_val(schema.total_tokens).unwrap_or(0),
+        created_at: doc
+            .get_date_val(schema.created_at)
+            .map(format_tantivy_date),
+        modified_at: doc
+            .get_date_val(schema.modified_at)
+            .map(format_tantivy_date),
+        has_tool_use: doc.get_bool_val(schema.has_tool_use).unwrap_or(false),
+        file_exists: doc.get_bool_val(schema.file_exists).unwrap_or(true),
+        archived: doc.get_bool_val(schema.archived).unwrap_or(false),
+        turn_depth: doc.get_u64_val(schema.turn_depth).unwrap_or(0),
+    }
+}
+
+/// Batch-fetch session documents for a list of session_ids.
+/// Uses a single OR query instead of N+1 individual lookups.
+///
+/// Over-fetches 2x so duplicate session docs don't crowd others out; any id
+/// the batch still misses is looked up on its own and logged, so every id
+/// with a session doc is enriched.
+fn batch_fetch_sessions(
+    searcher: &tantivy::Searcher,
+    schema: &IndexSchema,
+    session_ids: &[String],
+) -> Result<HashMap<String, TantivyDocument>, String> {
+    if session_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    // Inner OR query: Should(session_id=s1, session_id=s2, ...)
+    let id_clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = session_ids
+        .iter()
+        .map(|sid| {
+            (
+                Occur::Should,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(schema.session_id, sid),
+                    IndexRecordOption::Basic,
+                )) as Box<dyn tantivy::query::Query>,
+            )
+        })
+        .collect();
+
+    let query = BooleanQuery::new(vec![
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(schema.doc_type, "session"),
+                IndexRecordOption::Basic,
+            )),
+        ),
+        (
+            Occur::Must,
+            Box::new(BooleanQuery::new(id_clauses)),
+        ),
+    ]);
+
+    let top_docs = searcher
+        .search(&query, &TopDocs::with_limit(session_ids.len().saturating_mul(2)))
+        .map_err(|e| e.to_string())?;
+
+    let mut map = HashMap::with_capacity(session_ids.len());
+    for (_, addr) in top_docs {
+        let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+        if let Some(sid) = doc.get_str(schema.session_id) {
+            map.entry(sid.to_string()).or_insert(doc);
+        }
+    }
+
+    fetch_missed_sessions(searcher, schema, session_ids, &mut map)?;
+    Ok(map)
+}
+
+/// `batch_fetch_sessions` fallback: look up each id the batch missed on its
+/// own. Returns the ids it filled in.
+fn fetch_missed_sessions(
+    searcher: &tantivy::Searcher,
+    schema: &IndexSchema,
+    session_ids: &[String],
+    map: &mut HashMap<String, TantivyDocument>,
+) -> Result<Vec<String>, String> {
+    let mut filled = Vec::new();
+    for sid in session_ids {
+        if map.contains_key(sid) {
+            continue;
+        }
+        match find_session_doc(searcher, schema, sid) {
+            Ok(addr) => {
+                tracing::warn!("Session batch fetch missed {sid} (duplicate session docs?)");
+                let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+                map.insert(sid.clone(), doc);
+                filled.push(sid.clone());
+            }
+            Err(_) => tracing::debug!("No session doc to enrich {sid}"),
+        }
+    }
+    Ok(filled)
+}
+
+fn utc() -> chrono::FixedOffset {
+    chrono::FixedOffset::east_opt(0).expect("zero offset is valid")
+}
+
+/// Parse a `SearchFilter`/`SessionFilter` `utc_offset` such as `+09:00`
+/// (UTC when absent).
+fn parse_utc_offset(s: Option<&str>) -> Result<chrono::FixedOffset, String> {
+    match s {
+        Some(s) => s
+            .parse::<chrono::FixedOffset>()
+            .map_err(|e| format!("Invalid utc_offset {s:?}: {e}")),
+        None => Ok(utc()),
+    }
+}
+
+/// `[t - window, t + window]` over the date fast field `field`, for an
+/// `around` filter of `(t, window_seconds)`.
+///
+/// `t` is RFC 3339, or `YYYY-MM-DDTHH:MM[:SS]` / `YYYY-MM-DD` at `offset`.
+fn around_query(
+    field: &str,
+    around: &(String, u64),
+    offset: chrono::FixedOffset,
+) -> Result<RangeQuery, String> {
+    let (point, window) = around;
+    let t = parse_date_filter(point, offset)
+        .map(|t| t.into_timestamp_secs())
+        .or_else(|| {
+            ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+                .iter()
+                .find_map(|fmt| chrono::NaiveDateTime::parse_from_str(point, fmt).ok())
+                .and_then(|naive| naive.and_local_timezone(offset).single())
+                .map(|dt| dt.timestamp())
+        })
+        .ok_or_else(|| format!("Invalid around timestamp {point:?}"))?;
+    let window = i64::try_from(*window).unwrap_or(i64::MAX);
+    Ok(RangeQuery::new_date_bounds(
+        field.to_string(),
+        Bound::Included(tantivy::DateTime::from_timestamp_secs(t.saturating_sub(window))),
+        Bound::Included(tantivy::DateTime::from_timestamp_secs(t.saturating_add(window))),
+    ))
+}
+
+/// Parse a date string (YYYY-MM-DD or ISO 8601) into a tantivy DateTime.
+///
+/// A bare date means midnight at `offset`, so `2026-02-18` at `+09:00` starts
+/// at `2026-02-17T15:00:00Z`; RFC 3339 input keeps its own offset.
+fn parse_date_filter(s: &str, offset: chrono::FixedOffset) -> Option<tantivy::DateTime> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Some(tantivy::DateTime::from_timestamp_secs(
+            dt.with_timezone(&chrono::Utc).timestamp(),
+        ));
+    }
+    if let Ok(naive) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let dt = naive.and_hms_opt(0, 0, 0)?.and_local_timezone(offset).single()?;
+        return Some(tantivy::DateTime::from_timestamp_secs(dt.timestamp()));
+    }
+    None
+}
+
+/// JSONL entry for conversation extraction.
+#[derive(Deserialize)]
+struct ConvJsonlEntry {
+    #[serde(rename = "type")]
+    entry_type: Option<String>,
+    message: Option<ConvJsonlMessage>,
+    timestamp: Option<String>,
+    uuid: Option<String>,
+    #[serde(rename = "parentUuid")]
+    parent_uuid: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ConvJsonlMessage {
+    /// API message id, shared by the lines of one split response
+    id: Option<String>,
+    role: Option<String>,
+    content: Option<serde_json::Value>,
+    usage: Option<MessageUsage>,
+}
+
+/// Parse conversation messages from a JSONL file (full fidelity).
+///
+/// Entries are linked by `uuid`/`parentUuid`. When every message entry has a
+/// uuid, the conversation is ordered depth-first along those links (siblings
+/// in file order), so a rewound or edited turn's branch follows its parent
+/// instead of being interleaved with the other branch. Otherwise file order
+/// is kept. See `thread_conversation`.
+fn parse_conversation_from_jsonl(path: &str) -> Result<Vec<ConversationMessage>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {path}: {e}"))?;
+    let reader = BufReader::new(file);
+    // One group per message entry: its blocks share the entry's uuid
+    let mut groups: Vec<Vec<ConversationMessage>> = Vec::new();
+    // uuid -> parentUuid of every entry, messages or not
+    let mut links: HashMap<String, Option<String>> = HashMap::new();
+    // Usage is attached once per API response, not once per line
+    let mut last_usage_id: Option<String> = None;
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry: ConvJsonlEntry = match serde_json::from_str(&line) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        if let Some(ref uuid) = entry.uuid {
+            links.insert(uuid.clone(), entry.parent_uuid.clone());
+        }
+
+        let entry_type = entry.entry_type.as_deref().unwrap_or("");
+        if entry_type != "user" && entry_type != "assistant" && entry_type != "tool" {
+            continue;
+        }
+
+        let msg = match &entry.message {
+            Some(m) => m,
+            None => continue,
+        };
+
+        let role = msg.role.as_deref().unwrap_or("unknown").to_string();
+
+        let usage = match msg.usage {
+            Some(ref usage) if role == "assistant" => {
+                let repeated = msg.id.is_some() && msg.id == last_usage_id;
+                last_usage_id.clone_from(&msg.id);
+                (!repeated).then(|| usage.clone())
+            }
+            _ => None,
+        };
+
+        if let Some(ref content) = msg.content {
+            let mut group: Vec<ConversationMessage> = extract_content_blocks(content)
+                .into_iter()
+                .map(|block| ConversationMessage {
+                    role: role.clone(),
+                    content_type: block.content_type,
+                    text: block.text,
+                    timestamp: entry.timestamp.clone(),
+                    truncated: false,
+                    tool_use_id: block.tool_use_id,
+                    uuid: entry.uuid.clone(),
+                    parent_id: entry.parent_uuid.clone(),
+                    usage: None,
+                })
+                .collect();
+            if let Some(first) = group.first_mut() {
+                first.usage = usage;
+            }
+            if !group.is_empty() {
+                groups.push(group);
+            }
+        }
+    }
+
+    Ok(thread_conversation(groups, &links))
+}
+
+/// Order message groups along their parent links and point each `parent_id`
+/// at the nearest ancestor that is itself a message (parents can be system or
+/// progress lines, which get_conversation drops).
+///
+/// Falls back to file order unless every group has a uuid. Groups caught in
+/// a cycle come last, in file order.
+fn thread_conversation(
+    mut groups: Vec<Vec<ConversationMessage>>,
+    links: &HashMap<String, Option<String>>,
+) -> Vec<ConversationMessage> {
+    let uuid_of = |g: &[ConversationMessage]| g.first().and_then(|m| m.uuid.clone());
+    let by_uuid: HashMap<String, usize> = groups
+        .iter()
+        .enumerate()
+        .filter_map(|(i, g)| Some((uuid_of(g)?, i)))
+        .collect();
+    if by_uuid.len() != groups.len() {
+        return groups.into_iter().flatten().collect();
+    }
+
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); groups.len()];
+    let mut roots = Vec::new();
+    for (i, group) in groups.iter_mut().enumerate() {
+        let mut parent = group.first().and_then(|m| m.parent_id.clone());
+        // Bounded walk: a cyclic chain of non-message entries just ends the search
+        for _ in 0..=links.len() {
+            match parent {
+                Some(ref p) if !by_uuid.contains_key(p) => {
+                    parent = links.get(p).cloned().flatten();
+                }
+                _ => break,
+            }
+        }
+        let parent = parent.filter(|p| by_uuid.contains_key(p));
+        match parent.as_ref().map(|p| by_uuid[p]) {
+            Some(p) if p != i => children[p].push(i),
+            _ => roots.push(i),
+        }
+        for msg in group.iter_mut() {
+            msg.parent_id = parent.clone();
+        }
+    }
+
+    let mut order = Vec::with_capacity(groups.len());
+    let mut visited = vec![false; groups.len()];
+    let mut stack: Vec<usize> = roots.into_iter().rev().collect();
+    while let Some(i) = stack.pop() {
+        if std::mem::replace(&mut visited[i], true) {
+            continue;
+        }
+        order.push(i);
+        stack.extend(children[i].iter().rev());
+    }
+    order.extend((0..groups.len()).filter(|&i| !visited[i]));
+
+    let mut slots: Vec<Option<Vec<ConversationMessage>>> =
+        groups.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|i| slots[i].take())
+        .flatten()
+        .collect()
+}
+
+/// Rebuild API-shaped messages from a JSONL file.
+///
+/// Claude Code writes one line per assistant content block and puts
+/// tool_result blocks in their own user lines, so consecutive lines with the
+/// same role are merged to keep roles alternating as the API requires. String
+/// content becomes a single text block.
+fn parse_raw_messages_from_jsonl(path: &str) -> Result<Vec<serde_json::Value>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {path}: {e}"))?;
+    let reader = BufReader::new(file);
+    let mut messages: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry: ConvJsonlEntry = match serde_json::from_str(&line) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        let (role, content) = match entry.message {
+            Some(ConvJsonlMessage {
+                role: Some(role),
+                content: Some(content),
+                ..
+            }) if role == "user" || role == "assistant" => (role, content),
+            _ => continue,
+        };
+
+        let blocks = match content {
+            serde_json::Value::String(text) => {
+                vec![serde_json::json!({ "type": "text", "text": text })]
+            }
+            serde_json::Value::Array(blocks) => blocks,
+            _ => continue,
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+
+        match messages.last_mut() {
+            Some((last_role, last_blocks)) if *last_role == role => last_blocks.extend(blocks),
+            _ => messages.push((role, blocks)),
+        }
+    }
+
+    Ok(messages
+        .into_iter()
+        .map(|(role, content)| serde_json::json!({ "role": role, "content": content }))
+        .collect())
+}
+
+/// Reconstruct conversation from index when JSONL file is pruned.
+/// Uses content_stored (first 500 chars) with truncated=true.
+fn reconstruct_conversation_from_index(
+    session_id: &str,
+    searcher: &tantivy::Searcher,
+    schema: &IndexSchema,
+) -> Result<Vec<ConversationMessage>, String> {
+    let query = BooleanQuery::new(vec![
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(schema.session_id, session_id),
+                IndexRecordOption::Basic,
+            )),
+        ),
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(schema.doc_type, "message"),
+                IndexRecordOption::Basic,
+            )),
+        ),
+    ]);
+
+    let top_docs = searcher
+        .search(&query, &TopDocs::with_limit(100_000))
+        .map_err(|e| e.to_string())?;
+
+    let mut messages: Vec<(u64, u64, ConversationMessage)> = Vec::with_capacity(top_docs.len());
+    for (_, addr) in top_docs {
+        let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
+        let turn_index = doc.get_u64_val(schema.turn_index).unwrap_or(0);
+        let block_index = doc.get_u64_val(schema.block_index).unwrap_or(0);
+        let msg = ConversationMessage {
+            role: doc.get_str(schema.role).unwrap_or("").to_string(),
+            content_type: doc.get_str(schema.content_type).unwrap_or("").to_string(),
+            text: doc.get_str(schema.content_stored).unwrap_or("").to_string(),
+            timestamp: doc
+                .get_date_val(schema.timestamp)
+                .map(format_tantivy_date),
+            truncated: true,
+            tool_use_id: None,
+            uuid: None,
+            parent_id: None,
+            usage: None,
+        };
+        messages.push((turn_index, block_index, msg));
+    }
+
+    messages.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+    Ok(messages.into_iter().map(|(_, _, m)| m).collect())
+}
+
+/// The on-disk Tantivy index: `<data_local_dir>/swarm-ui/tantivy`.
+fn index_dir() -> Option<std::path::PathBuf> {
+    dirs::data_local_dir().map(|p| p.join("swarm-ui").join("tantivy"))
+}
+
+/// Calculate total size of the index directory.
+fn get_index_dir_size() -> u64 {
+    match index_dir() {
+        Some(path) => dir_size_recursive(&path),
+        None => 0,
+    }
+}
+
+/// Bucket the files under an index directory by Tantivy file kind, largest first.
+pub fn index_size_breakdown(path: &Path) -> Vec<IndexSizeComponent> {
+    let mut buckets: HashMap<String, u64> = HashMap::new();
+    collect_sizes_by_kind(path, &mut buckets);
+    let mut components: Vec<IndexSizeComponent> = buckets
+        .into_iter()
+        .map(|(kind, bytes)| IndexSizeComponent { kind, bytes })
+        .collect();
+    components.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.kind.cmp(&b.kind)));
+    components
+}
+
+fn collect_sizes_by_kind(path: &Path, buckets: &mut HashMap<String, u64>) {
+    const KINDS: &[&str] = &["idx", "pos", "term", "store", "fast", "fieldnorm", "del"];
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let p = entry.path();
+            if p.is_dir() {
+                collect_sizes_by_kind(&p, buckets);
+                continue;
+            }
+            let kind = p
+                .extension()
+                .and_then(|e| e.to_str())
+                .filter(|e| KINDS.contains(e))
+                .unwrap_or("other");
+            let len = entry.metadata().ok().map(|m| m.len()).unwrap_or(0);
+            *buckets.entry(kind.to_string()).or_insert(0) += len;
+        }
+    }
+}
+
+pub fn dir_size_recursive(path: &Path) -> u64 {
+    let mut size = 0;
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let p = entry.path();
+            if p.is_dir() {
+                size += dir_size_recursive(&p);
+            } else {
+                size += entry.metadata().ok().map(|m| m.len()).unwrap_or(0);
+            }
+        }
+    }
+    size
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::schema::IndexSchema;
+    use tantivy::{Index, IndexWriter};
+
+    /// Create an in-memory index with the full schema.
+    fn test_index() -> (Index, IndexSchema) {
+        let schema = IndexSchema::new();
+        let index = Index::create_in_ram(schema.schema.clone());
+        crate::search::tokenizer::register_tokenizers(&index);
+        (index, schema)
+    }
+
+    /// Add a session document to the writer.
+    fn add_session(
+        writer: &IndexWriter,
+        schema: &IndexSchema,
+        session_id: &str,
+        project: &str,
+        branch: &str,
+        model: &str,
+        archived: bool,
+        modified_secs: i64,
+    ) {
+        let mut doc = TantivyDocument::new();
+        doc.add_text(schema.session_id, session_id);
+        doc.add_text(schema.doc_type, "session");
+        doc.add_text(schema.project_path, project);
+        doc.add_text(schema.project_raw, project);
+        doc.add_text(schema.summary, &format!("Summary for {session_id}"));
+        doc.add_text(schema.first_prompt, "Hello");
+        doc.add_text(schema.git_branch, branch);
+        doc.add_text(schema.model, model);
+        doc.add_text(schema.status, "idle");
+        doc.add_text(schema.jsonl_path, &format!("/tmp/{session_id}.jsonl"));
+        doc.add_u64(schema.message_count, 10);
+        doc.add_u64(schema.input_tokens, 100);
+        doc.add_u64(schema.output_tokens, 200);
+        doc.add_u64(schema.total_tokens, 300);
+        doc.add_date(
+            schema.created_at,
+            tantivy::DateTime::from_timestamp_secs(modified_secs - 3600),
+        );
+        doc.add_date(
+            schema.modified_at,
+            tantivy::DateTime::from_timestamp_secs(modified_secs),
+        );
+        doc.add_bool(schema.archived, archived);
+        doc.add_bool(schema.file_exists, true);
+        doc.add_bool(schema.has_tool_use, true);
+        doc.add_u64(schema.turn_depth, 5);
+        writer.add_document(doc).unwrap();
+    }
+
+    /// Add a message document to the writer.
+    fn add_message(
+        writer: &IndexWriter,
+        schema: &IndexSchema,
+        session_id: &str,
+        role: &str,
+        content: &str,
+        content_type: &str,
+        turn_index: u64,
+        block_index: u64,
+        project: &str,
+        timestamp_secs: i64,
+    ) {
+        let stored = if content.len() > 500 {
+            &content[..500]
+        } else {
+            content
+        };
+        let mut doc = TantivyDocument::new();
+        doc.add_text(schema.session_id, session_id);
+        doc.add_text(schema.doc_type, "message");
+        doc.add_text(schema.role, role);
+        doc.add_text(schema.content, content);
+        doc.add_text(schema.content_cased, content);
+        doc.add_text(schema.content_stored, stored);
+        doc.add_text(schema.content_type, content_type);
+        doc.add_date(
+            schema.timestamp,
+            tantivy::DateTime::from_timestamp_secs(timestamp_secs),
+        );
+        doc.add_u64(schema.turn_index, turn_index);
+        doc.add_u64(schema.block_index, block_index);
+        doc.add_text(schema.msg_project, project);
+        writer.add_document(doc).unwrap();
+    }
+
+    fn make_reader(index: &Index) -> IndexReader {
+        index
+            .reader_builder()
+            .reload_policy(tantivy::ReloadPolicy::Manual)
+            .try_into()
+            .unwrap()
+    }
+
+    // -----------------------------------------------------------------------
+    // list_sessions tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_list_sessions_returns_non_archived() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s2", "/proj", "main", "opus", true, 2000);
+        add_session(&writer, &schema, "s3", "/proj", "dev", "opus", false, 3000);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let results = list_sessions_query(&reader, &schema, None).unwrap();
+
+        assert_eq!(results.len(), 2, "Should exclude archived sessions");
+        assert_eq!(results[0].session_id, "s3", "Most recent first");
+        assert_eq!(results[1].session_id, "s1");
+    }
+
+    #[test]
+    fn test_list_sessions_include_archived() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s2", "/proj", "main", "opus", true, 2000);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let filter = SessionFilter {
+            include_archived: true,
+            ..Default::default()
+        };
+        let results = list_sessions_query(&reader, &schema, Some(&filter)).unwrap();
+
+        assert_eq!(results.len(), 2, "Should include archived when requested");
+    }
+
+    #[test]
+    fn test_list_sessions_filter_by_project() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/proj-a", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s2", "/proj-b", "main", "opus", false, 2000);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let filter = SessionFilter {
+            project: Some("/proj-a".into()),
+            ..Default::default()
+        };
+        let results = list_sessions_query(&reader, &schema, Some(&filter)).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].session_id, "s1");
+    }
+
+    #[test]
+    fn test_resync_sessions_emits_one_snapshot_with_all_sessions() {
+        #[derive(Default)]
+        struct RecordingSink {
+            snapshots: Mutex<Vec<Vec<String>>>,
+        }
+        impl UpdateSink for RecordingSink {
+            fn updated(&self, _item: &SessionListItem) {
+                panic!("resync only sends snapshots");
+            }
+            fn batch_updated(&self, _items: &[SessionListItem]) {
+                panic!("resync only sends snapshots");
+            }
+            fn snapshot(&self, items: &[SessionListItem]) {
+                let ids = items.iter().map(|i| i.session_id.clone()).collect();
+                self.snapshots.lock().unwrap().push(ids);
+            }
+        }
+
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        add_session(&writer, &schema, "s1", "/proj-a", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s2", "/proj-b", "main", "opus", false, 3000);
+        add_session(&writer, &schema, "s3", "/proj-a", "main", "opus", false, 2000);
+        add_session(&writer, &schema, "gone", "/proj-a", "main", "opus", true, 4000);
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let sink = RecordingSink::default();
+        assert_eq!(resync_sessions_query(&reader, &schema, None, &sink).unwrap(), 3);
+        let filter = SessionFilter {
+            project: Some("/proj-a".into()),
+            ..Default::default()
+        };
+        resync_sessions_query(&reader, &schema, Some(&filter), &sink).unwrap();
+
+        // Each snapshot is the whole list, so the archived session is absent
+        let snapshots = sink.snapshots.lock().unwrap();
+        assert_eq!(*snapshots, [vec!["s2", "s3", "s1"], vec!["s3", "s1"]]);
+    }
+
+    #[test]
+    fn test_latest_session_for_project_skips_archived_and_other_projects() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "old", "/proj-a", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "newer", "/proj-a", "dev", "opus", false, 2000);
+        add_session(&writer, &schema, "archived", "/proj-a", "main", "opus", true, 3000);
+        add_session(&writer, &schema, "other", "/proj-b", "main", "opus", false, 4000);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let latest = latest_session_for_project_query(&reader, &schema, "/proj-a").unwrap();
+        assert_eq!(latest.map(|s| s.session_id).as_deref(), Some("newer"));
+        assert!(latest_session_for_project_query(&reader, &schema, "/proj-c").unwrap().is_none());
+        assert!(latest_session_for_project_query(&reader, &schema, "").is_err());
+    }
+
+    #[test]
+    fn test_list_sessions_filter_by_project_glob() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/home/u/frontend-web", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s2", "/srv/apps/frontend-admin", "main", "opus", false, 2000);
+        add_session(&writer, &schema, "s3", "/home/u/backend-api", "main", "opus", false, 3000);
+        add_session(&writer, &schema, "s4", "/home/u/frontend-web/docs", "main", "opus", false, 4000);
+        add_session(&writer, &schema, "s5", "/home/u/my-frontend-x", "main", "opus", false, 5000);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let filter = SessionFilter {
+            project_glob: Some("*/frontend-*".into()),
+            ..Default::default()
+        };
+        let results = list_sessions_query(&reader, &schema, Some(&filter)).unwrap();
+        let mut ids: Vec<&str> = results.iter().map(|r| r.session_id.as_str()).collect();
+        ids.sort();
+
+        // s4 matches because `*` spans path separators; s3 and s5 don't match.
+        assert_eq!(ids, vec!["s1", "s2", "s4"]);
+    }
+
+    #[test]
+    fn test_list_sessions_rejects_invalid_project_glob() {
+        let (index, schema) = test_index();
+        let reader = make_reader(&index);
+        let filter = SessionFilter {
+            project_glob: Some("/proj/[".into()),
+            ..Default::default()
+        };
+
+        let err = list_sessions_query(&reader, &schema, Some(&filter)).unwrap_err();
+        assert!(err.contains("Invalid project glob"), "got: {err}");
+    }
+
+    #[test]
+    fn test_list_sessions_filter_by_branch_and_model() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/p", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s2", "/p", "dev", "sonnet", false, 2000);
+        add_session(&writer, &schema, "s3", "/p", "main", "sonnet", false, 3000);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let filter = SessionFilter {
+            git_branch: Some("main".into()),
+            model: Some("opus".into()),
+            ..Default::default()
+        };
+        let results = list_sessions_query(&reader, &schema, Some(&filter)).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].session_id, "s1");
+    }
+
+    #[test]
+    fn test_list_sessions_modified_within_days() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let day = 86_400;
+        add_session(&writer, &schema, "hour", "/p", "main", "opus", false, now - 3600);
+        add_session(&writer, &schema, "three", "/p", "main", "opus", false, now - 3 * day);
+        add_session(&writer, &schema, "ten", "/p", "main", "opus", false, now - 10 * day);
+        add_session(&writer, &schema, "old", "/p", "main", "opus", false, 1000);
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let within = |days| {
+            let filter = SessionFilter { modified_within_days: Some(days), ..Default::default() };
+            let results = list_sessions_query(&reader, &schema, Some(&filter)).unwrap();
+            results.into_iter().map(|r| r.session_id).collect::<Vec<_>>()
+        };
+        assert_eq!(within(1), ["hour"]);
+        assert_eq!(within(7), ["hour", "three"]);
+        assert_eq!(within(30), ["hour", "three", "ten"]);
+        assert_eq!(list_sessions_query(&reader, &schema, None).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_around_filter_matches_only_within_window() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        // 2026-02-18T14:30:00Z
+        let t = 1_771_425_000;
+        for (sid, offset) in [("before", -300), ("after", 500), ("far", -2000), ("later", 7200)] {
+            add_session(&writer, &schema, sid, "/p", "main", "opus", false, t + offset);
+            let ts = t + offset;
+            add_message(&writer, &schema, sid, "user", "deploy failed", "text", 0, 0, "/p", ts);
+        }
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let around = Some(("2026-02-18T14:30".to_string(), 600));
+        let filter = SessionFilter { around: around.clone(), ..Default::default() };
+        let listed: Vec<String> = list_sessions_query(&reader, &schema, Some(&filter))
+            .unwrap()
+            .into_iter()
+            .map(|s| s.session_id)
+            .collect();
+        assert_eq!(listed, ["after", "before"]);
+
+        // The same instant as Tokyo local time
+        let filter = SessionFilter {
+            around: Some(("2026-02-18T23:30".to_string(), 600)),
+            utc_offset: Some("+09:00".into()),
+            ..Default::default()
+        };
+        let listed = list_sessions_query(&reader, &schema, Some(&filter)).unwrap();
+        assert_eq!(listed.len(), 2);
+
+        let filter = SearchFilter { around, ..Default::default() };
+        let mut found: Vec<String> =
+            search_sessions_query(&reader, &schema, "deploy", Some(&filter))
+                .unwrap()
+                .into_iter()
+                .map(|r| r.session_id)
+                .collect();
+        found.sort();
+        assert_eq!(found, ["after", "before"]);
+
+        // An RFC 3339 point at +01:00 is the same instant
+        let filter = SearchFilter {
+            around: Some(("2026-02-18T15:30:00+01:00".to_string(), 400)),
+            ..Default::default()
+        };
+        let found = search_sessions_query(&reader, &schema, "deploy", Some(&filter)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].session_id, "before");
+
+        let bad = SessionFilter { around: Some(("soon".into(), 60)), ..Default::default() };
+        assert!(list_sessions_query(&reader, &schema, Some(&bad)).is_err());
+    }
+
+    #[test]
+    fn test_list_sessions_sorted_by_modified_at_desc() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "old", "/p", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "mid", "/p", "main", "opus", false, 5000);
+        add_session(&writer, &schema, "new", "/p", "main", "opus", false, 9000);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let results = list_sessions_query(&reader, &schema, None).unwrap();
+
+        assert_eq!(results[0].session_id, "new");
+        assert_eq!(results[1].session_id, "mid");
+        assert_eq!(results[2].session_id, "old");
+    }
+
+    // -----------------------------------------------------------------------
+    // search_sessions tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_search_finds_matching_messages() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "s1", "user", "How do I implement authentication?",
+            "text", 0, 0, "/proj", 1000,
+        );
+        add_message(
+            &writer, &schema, "s1", "assistant", "You can use JWT tokens for auth",
+            "text", 1, 0, "/proj", 1001,
+        );
+
+        add_session(&writer, &schema, "s2", "/proj", "main", "opus", false, 2000);
+        add_message(
+            &writer, &schema, "s2", "user", "Fix the CSS layout",
+            "text", 0, 0, "/proj", 2000,
+        );
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let results = search_sessions_query(&reader, &schema, "authentication", None).unwrap();
+
+        assert_eq!(results.len(), 1, "Only s1 should match");
+        assert_eq!(results[0].session_id, "s1");
+        assert!(!results[0].snippets.is_empty());
+    }
+
+    #[test]
+    fn test_search_filter_by_project_glob() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        for (sid, project) in [
+            ("s1", "/work/frontend-shop"),
+            ("s2", "/work/backend-shop"),
+            ("s3", "/other/frontend-blog"),
+        ] {
+            add_session(&writer, &schema, sid, project, "main", "opus", false, 1000);
+            add_message(
+                &writer, &schema, sid, "user", "deploy pipeline broken",
+                "text", 0, 0, project, 1000,
+            );
+        }
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let filter = SearchFilter {
+            project_glob: Some("/work/*".into()),
+            ..Default::default()
+        };
+        let mut ids: Vec<String> = search_sessions_query(&reader, &schema, "deploy", Some(&filter))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.session_id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["s1", "s2"]);
+
+        let filter = SearchFilter {
+            project_glob: Some("*/frontend-*".into()),
+            ..Default::default()
+        };
+        let mut ids: Vec<String> = search_sessions_query(&reader, &schema, "deploy", Some(&filter))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.session_id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["s1", "s3"]);
+    }
+
+    #[test]
+    fn test_search_superseded_token_returns_early() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "s1", "user", "refactor the parser",
+            "text", 0, 0, "/proj", 1000,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let generation = Arc::new(AtomicU64::new(0));
+        let stale = SearchToken::next(&generation);
+        let latest = SearchToken::next(&generation);
+        assert!(stale.is_cancelled());
+        assert!(!latest.is_cancelled());
+
+        let err = search_sessions_query_cancellable(&reader, &schema, "parser", None, &stale)
+            .unwrap_err();
+        assert_eq!(err, SEARCH_CANCELLED);
+
+        let results =
+            search_sessions_query_cancellable(&reader, &schema, "parser", None, &latest).unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_search_exact_substring_matches_identifier_literally() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "exact", "/proj", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "exact", "user", "Why does getUserById_v2 return null?",
+            "text", 0, 0, "/proj", 1000,
+        );
+        // Same tokens after lowercasing/splitting, but not the identifier
+        add_session(&writer, &schema, "loose", "/proj", "main", "opus", false, 2000);
+        add_message(
+            &writer, &schema, "loose", "user", "call getUserByID_v2 from the handler",
+            "text", 0, 0, "/proj", 2000,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let bm25 = search_sessions_query(&reader, &schema, "getUserById_v2", None).unwrap();
+        assert_eq!(bm25.len(), 2, "tokenized search matches both sessions");
+
+        let filter = SearchFilter {
+            exact_substring: true,
+            ..Default::default()
+        };
+        let exact =
+            search_sessions_query(&reader, &schema, "getUserById_v2", Some(&filter)).unwrap();
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].session_id, "exact");
+        assert!(exact[0].snippets[0].snippet.contains("getUserById_v2"));
+    }
+
+    #[test]
+    fn test_search_case_sensitive_distinguishes_acronym() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "upper", "/proj", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "upper", "user", "Deploy the stack to AWS today",
+            "text", 0, 0, "/proj", 1000,
+        );
+        add_session(&writer, &schema, "lower", "/proj", "main", "opus", false, 2000);
+        add_message(
+            &writer, &schema, "lower", "user", "run aws configure first",
+            "text", 0, 0, "/proj", 2000,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let folded = search_sessions_query(&reader, &schema, "AWS", None).unwrap();
+        assert_eq!(folded.len(), 2, "default search folds case");
+
+        let filter = SearchFilter {
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let upper = search_sessions_query(&reader, &schema, "AWS", Some(&filter)).unwrap();
+        let ids: Vec<&str> = upper.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["upper"]);
+        let lower = search_sessions_query(&reader, &schema, "aws", Some(&filter)).unwrap();
+        let ids: Vec<&str> = lower.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["lower"]);
+    }
+
+    #[test]
+    fn test_search_errors_only_matches_failing_tool_output() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "broken", "/proj", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "broken", "user",
+            "Traceback (most recent call last):\n  File \"app.py\", line 3",
+            "tool_result", 0, 0, "/proj", 1000,
+        );
+        add_session(&writer, &schema, "clean", "/proj", "main", "opus", false, 2000);
+        add_message(
+            &writer, &schema, "clean", "user", "all 12 tests passed",
+            "tool_result", 0, 0, "/proj", 2000,
+        );
+        // Talking about errors in prose is not a failing tool
+        add_session(&writer, &schema, "chat", "/proj", "main", "opus", false, 3000);
+        add_message(
+            &writer, &schema, "chat", "user", "how do I handle an error here?",
+            "text", 0, 0, "/proj", 3000,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let filter = SearchFilter {
+            errors_only: true,
+            ..Default::default()
+        };
+        let hits = search_sessions_query(&reader, &schema, "", Some(&filter)).unwrap();
+        let ids: Vec<&str> = hits.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["broken"]);
+
+        let custom = SearchFilter {
+            errors_only: true,
+            error_signatures: Some(vec!["tests passed".to_string()]),
+            ..Default::default()
+        };
+        let hits = search_sessions_query(&reader, &schema, "", Some(&custom)).unwrap();
+        let ids: Vec<&str> = hits.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["clean"]);
+
+        for signatures in [vec![], vec!["".to_string(), " !? ".to_string()]] {
+            let empty = SearchFilter {
+                errors_only: true,
+                error_signatures: Some(signatures),
+                ..Default::default()
+            };
+            assert!(search_sessions_query(&reader, &schema, "", Some(&empty)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_search_excludes_tool_result_by_default() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "s1", "assistant",
+            "tool_result with secret data about tantivy search engine",
+            "tool_result", 0, 0, "/proj", 1000,
+        );
+        // No text messages matching "tantivy"
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let results = search_sessions_query(&reader, &schema, "tantivy", None).unwrap();
+
+        assert_eq!(results.len(), 0, "tool_result should be excluded by default");
+    }
+
+    #[test]
+    fn test_search_includes_tool_result_when_requested() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "s1", "assistant",
+            "tool_result with tantivy search data",
+            "tool_result", 0, 0, "/proj", 1000,
+        );
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let filter = SearchFilter {
+            include_tool_output: true,
+            ..Default::default()
+        };
+        let results =
+            search_sessions_query(&reader, &schema, "tantivy", Some(&filter)).unwrap();
+
+        assert_eq!(results.len(), 1, "tool_result should be included when requested");
+    }
+
+    #[test]
+    fn test_search_filter_by_git_branch() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s-main", "/proj", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s-feat", "/proj", "feature/x", "opus", false, 2000);
+        add_message(
+            &writer, &schema, "s-main", "user", "fix the parser",
+            "text", 0, 0, "/proj", 1000,
+        );
+        add_message(
+            &writer, &schema, "s-feat", "user", "parser rewrite",
+            "text", 0, 0, "/proj", 2000,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let filter = SearchFilter {
+            git_branch: Some("feature/x".into()),
+            ..Default::default()
+        };
+        let results = search_sessions_query(&reader, &schema, "parser", Some(&filter)).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].session_id, "s-feat");
+        let unfiltered = search_sessions_query(&reader, &schema, "parser", None).unwrap();
+        assert_eq!(unfiltered.len(), 2);
+    }
+
+    #[test]
+    fn test_search_project_sessions_intersects_project_branch_and_content() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let sessions = [
+            ("hit", "/app", "main", "fix the parser"),
+            ("other-branch", "/app", "feature/x", "parser rewrite"),
+            ("other-project", "/lib", "main", "parser tests"),
+            ("no-mention", "/app", "main", "update the readme"),
+        ];
+        for (i, (sid, project, branch, text)) in sessions.into_iter().enumerate() {
+            let ts = 1000 + i as i64;
+            add_session(&writer, &schema, sid, project, branch, "opus", false, ts);
+            add_message(&writer, &schema, sid, "user", text, "text", 0, 0, project, ts);
+        }
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+        let never = SearchToken::never();
+
+        let results = search_project_sessions_query(
+            &reader,
+            &schema,
+            "/app",
+            "main",
+            "parser",
+            None,
+            &never,
+        )
+        .unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["hit"]);
+
+        // The filter's own project/branch can't widen the scope
+        let filter = SearchFilter { git_branch: Some("feature/x".into()), ..Default::default() };
+        let results = search_project_sessions_query(
+            &reader,
+            &schema,
+            "/app",
+            "main",
+            "parser",
+            Some(&filter),
+            &never,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        let none = search_project_sessions_query(
+            &reader,
+            &schema,
+            "/app",
+            "release",
+            "parser",
+            None,
+            &never,
+        )
+        .unwrap();
+        assert!(none.is_empty());
+        let no_project = search_project_sessions_query(
+            &reader,
+            &schema,
+            "",
+            "main",
+            "parser",
+            None,
+            &never,
+        );
+        assert!(no_project.is_err());
+    }
+
+    #[test]
+    fn test_search_matches_code_identifier_parts() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        add_session(&writer, &schema, "s-code", "/proj", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "s-code", "assistant",
+            "rename getUserName in src/auth/session_store.rs", "text", 1, 0, "/proj", 1000,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        // "user" sits inside a camelCase identifier: the default tokenizer
+        // indexes only "getusername", so this needs the code tokenizer
+        for query in ["user", "getUserName", "session_store", "src/auth/session_store.rs", "auth"] {
+            let results = search_sessions_query(&reader, &schema, query, None).unwrap();
+            assert_eq!(results.len(), 1, "query {query:?}");
+        }
+        let results = search_sessions_query(&reader, &schema, "getSessionName", None).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_by_tool_input_finds_read_path() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        for sid in ["s-read", "s-prose", "s-name", "s-other"] {
+            add_session(&writer, &schema, sid, "/proj", "main", "opus", false, 1000);
+        }
+        add_message(
+            &writer, &schema, "s-read", "assistant",
+            r#"tool_use: Read {"file_path":"/etc/hosts"}"#, "tool_use", 1, 0, "/proj", 1000,
+        );
+        // Mentioned in prose, not a tool input
+        add_message(
+            &writer, &schema, "s-prose", "user", "why is /etc/hosts wrong",
+            "text", 0, 0, "/proj", 1000,
+        );
+        // Needle only matches the tool name
+        add_message(
+            &writer, &schema, "s-name", "assistant", r#"tool_use: hosts {"mode":"list"}"#,
+            "tool_use", 1, 0, "/proj", 1000,
+        );
+        add_message(
+            &writer, &schema, "s-other", "assistant",
+            r#"tool_use: Read {"file_path":"/etc/passwd"}"#, "tool_use", 1, 0, "/proj", 1000,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let filter = SearchFilter {
+            tool_input_contains: Some("/etc/hosts".into()),
+            ..Default::default()
+        };
+        let results = search_sessions_query(&reader, &schema, "", Some(&filter)).unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["s-read"]);
+        assert_eq!(count_search_matches_query(&reader, &schema, "", Some(&filter)).unwrap(), 1);
+
+        let name_only = SearchFilter {
+            tool_input_contains: Some("hosts".into()),
+            ..Default::default()
+        };
+        let results = search_sessions_query(&reader, &schema, "", Some(&name_only)).unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["s-read"], "tool name and prose don't count as input");
+
+        // Combines with a query over the whole block
+        let results = search_sessions_query(&reader, &schema, "read", Some(&filter)).unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_search_recency_boost_ranks_recent_session_first() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        add_session(&writer, &schema, "s-old", "/proj", "main", "opus", false, now - 365 * 86_400);
+        add_session(&writer, &schema, "s-recent", "/proj", "main", "opus", false, now - 3600);
+        add_message(
+            &writer, &schema, "s-old", "user", "flaky deploy flaky deploy flaky deploy",
+            "text", 0, 0, "/proj", now,
+        );
+        add_message(
+            &writer, &schema, "s-recent", "user",
+            "the deploy looked flaky again after the config change yesterday",
+            "text", 0, 0, "/proj", now,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let plain = search_sessions_query(&reader, &schema, "flaky deploy", None).unwrap();
+        let ids: Vec<&str> = plain.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["s-old", "s-recent"], "BM25 alone prefers the old session");
+
+        let filter = SearchFilter {
+            recency_boost: Some(30.0),
+            ..Default::default()
+        };
+        let boosted =
+            search_sessions_query(&reader, &schema, "flaky deploy", Some(&filter)).unwrap();
+        let ids: Vec<&str> = boosted.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["s-recent", "s-old"]);
+        assert!(boosted[1].score < plain[0].score);
+    }
+
+    #[test]
+    fn test_search_thinking_turns_only_skips_plain_text() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s-think", "/proj", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s-plain", "/proj", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "s-think", "assistant", "weighing the migration order",
+            "thinking", 1, 0, "/proj", 1000,
+        );
+        add_message(
+            &writer, &schema, "s-plain", "assistant", "the migration order is fine",
+            "text", 1, 0, "/proj", 1000,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let all = search_sessions_query(&reader, &schema, "migration", None).unwrap();
+        assert_eq!(all.len(), 2);
+
+        let filter = SearchFilter {
+            thinking_turns_only: true,
+            ..Default::default()
+        };
+        let results = search_sessions_query(&reader, &schema, "migration", Some(&filter)).unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(ids, ["s-think"]);
+        let count = count_search_matches_query(&reader, &schema, "migration", Some(&filter));
+        assert_eq!(count.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_count_matches_what_search_can_show() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/work/api", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s2", "/work/web", "main", "opus", false, 2000);
+        add_message(
+            &writer, &schema, "s1", "user", "deploy the widget",
+            "text", 0, 0, "/work/api", 1000,
+        );
+        add_message(
+            &writer, &schema, "s1", "assistant", "widget deploy log",
+            "tool_result", 1, 0, "/work/api", 1100,
+        );
+        add_message(
+            &writer, &schema, "s1", "assistant", "call widget tool",
+            "tool_use", 1, 1, "/work/api", 1100,
+        );
+        add_message(
+            &writer, &schema, "s2", "assistant", "widget styles updated",
+            "text", 0, 0, "/work/web", 2000,
+        );
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let filters = [
+            SearchFilter::default(),
+            SearchFilter { include_tool_output: true, ..Default::default() },
+            SearchFilter { role: Some("assistant".into()), ..Default::default() },
+            SearchFilter { content_type: Some("tool_use".into()), ..Default::default() },
+            SearchFilter { project: Some("/work/web".into()), ..Default::default() },
+            SearchFilter { project_glob: Some("*/api".into()), ..Default::default() },
+            SearchFilter { git_branch: Some("main".into()), ..Default::default() },
+            SearchFilter {
+                date_from: Some("1970-01-01T00:30:00Z".into()),
+                include_tool_output: true,
+                ..Default::default()
+            },
+        ];
+        let mut counts = Vec::new();
+        for filter in &filters {
+            let results = search_sessions_query(&reader, &schema, "widget", Some(filter)).unwrap();
+            let shown: usize = results.iter().map(|r| r.snippets.len()).sum();
+            let count =
+                count_search_matches_query(&reader, &schema, "widget", Some(filter)).unwrap();
+            assert_eq!(count, shown as u64, "count disagrees with search for {filter:?}");
+            counts.push(count);
+        }
+        assert_eq!(counts, vec![3, 4, 2, 1, 1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_search_enriches_with_session_metadata() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/my/project", "feature", "claude-opus-4-6", false, 5000);
+        add_message(
+            &writer, &schema, "s1", "user", "implement the dashboard component",
+            "text", 0, 0, "/my/project", 5000,
+        );
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let results = search_sessions_query(&reader, &schema, "dashboard", None).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].project_path.as_deref(), Some("/my/project"));
+        assert_eq!(results[0].model.as_deref(), Some("claude-opus-4-6"));
+        assert!(results[0].file_exists);
+    }
+
+    // -----------------------------------------------------------------------
+    // get_session_detail tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_get_session_detail_returns_full_metadata() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "detail-1", "/project", "main", "opus", false, 1000);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let detail = get_session_detail_query(&reader, &schema, "detail-1").unwrap();
+
+        assert_eq!(detail.session_id, "detail-1");
+        assert_eq!(detail.project_path, "/project");
+        assert_eq!(detail.git_branch, "main");
+        assert_eq!(detail.model, "opus");
+        assert_eq!(detail.message_count, 10);
+        assert_eq!(detail.input_tokens, 100);
+        assert_eq!(detail.output_tokens, 200);
+        assert_eq!(detail.total_tokens, 300);
+        assert_eq!(detail.turn_depth, 5);
+        assert!(!detail.archived);
+        assert!(detail.file_exists);
+        assert!(detail.has_tool_use);
+        assert_eq!(detail.jsonl_path, "/tmp/detail-1.jsonl");
+    }
+
+    #[test]
+    fn test_get_session_detail_not_found() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let result = get_session_detail_query(&reader, &schema, "nonexistent");
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn test_get_session_details_keeps_order_and_skips_missing() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        for (i, sid) in ["d-1", "d-2", "d-3", "d-4"].into_iter().enumerate() {
+            add_session(&writer, &schema, sid, "/project", "main", "opus", false, 1000 + i as i64);
+        }
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+
+        let ids: Vec<String> =
+            ["d-3", "gone", "d-1", "d-4"].iter().map(|s| s.to_string()).collect();
+        let details = get_session_details_query(&reader, &schema, &ids).unwrap();
+
+        let got: Vec<&str> = details.iter().map(|d| d.session_id.as_str()).collect();
+        assert_eq!(got, ["d-3", "d-1", "d-4"]);
+        assert!(get_session_details_query(&reader, &schema, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_recent_sessions_skips_missing_ids() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        for (i, sid) in ["r-1", "r-2", "r-3"].into_iter().enumerate() {
+            add_session(&writer, &schema, sid, "/project", "main", "opus", false, 1000 + i as i64);
+        }
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("recent-sessions.json");
+        // Opened order differs from modified_at order; "deleted" is not indexed
+        for sid in ["r-3", "deleted", "r-1", "r-2"] {
+            crate::search::recent::record_session_open(&path, sid).unwrap();
         }
 
-        let msg = match &entry.message {
-            Some(m) => m,
-            None => continue,
-        };
+        let recent = get_recent_sessions_query(&reader, &schema, &path, 10).unwrap();
+        let got: Vec<&str> = recent.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(got, ["r-2", "r-1", "r-3"]);
 
-        let role = msg.role.as_deref().unwrap_or("unknown").to_string();
+        let top = get_recent_sessions_query(&reader, &schema, &path, 2).unwrap();
+        assert_eq!(top.len(), 2);
+        let none = tmp.path().join("never-written.json");
+        assert!(get_recent_sessions_query(&reader, &schema, &none, 10).unwrap().is_empty());
+    }
 
-        if let Some(ref content) = msg.content {
-            let blocks = extract_content_blocks(content);
-            for block in blocks {
-                messages.push(ConversationMessage {
-                    role: role.clone(),
-                    content_type: block.content_type,
-                    text: block.text,
-                    timestamp: entry.timestamp.clone(),
-                    truncated: false,
-                });
-            }
+    #[test]
+    fn test_batch_fetch_sessions_survives_duplicate_session_docs() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        // Equally duplicated ids tie on score, so the 2x over-fetch window
+        // fills up with the first one's copies and the second one is missed
+        for i in 0..10 {
+            add_session(&writer, &schema, "dup-1", "/p", "main", "opus", false, 1000 + i);
         }
-    }
+        for i in 0..10 {
+            add_session(&writer, &schema, "dup-2", "/p", "main", "opus", false, 2000 + i);
+        }
+        writer.commit().unwrap();
+        let searcher = make_reader(&index).searcher();
 
-    Ok(messages)
-}
+        let ids: Vec<String> =
+            ["dup-1", "dup-2", "missing"].iter().map(|s| s.to_string()).collect();
+        let docs = batch_fetch_sessions(&searcher, &schema, &ids).unwrap();
 
-/// Reconstruct conversation from index when JSONL file is pruned.
-/// Uses content_stored (first 500 chars) with truncated=true.
-fn reconstruct_conversation_from_index(
-    session_id: &str,
-    searcher: &tantivy::Searcher,
-    schema: &IndexSchema,
-) -> Result<Vec<ConversationMessage>, String> {
-    let query = BooleanQuery::new(vec![
-        (
-            Occur::Must,
-            Box::new(TermQuery::new(
-                Term::from_field_text(schema.session_id, session_id),
-                IndexRecordOption::Basic,
-            )),
-        ),
-        (
-            Occur::Must,
-            Box::new(TermQuery::new(
-                Term::from_field_text(schema.doc_type, "message"),
-                IndexRecordOption::Basic,
-            )),
-        ),
-    ]);
+        assert_eq!(docs.len(), 2);
+        for sid in &ids[..2] {
+            assert_eq!(docs[sid].get_str(schema.session_id), Some(sid.as_str()));
+            assert!(docs[sid].get_str(schema.summary).is_some(), "{sid} not enriched");
+        }
+    }
 
-    let top_docs = searcher
-        .search(&query, &TopDocs::with_limit(100_000))
-        .map_err(|e| e.to_string())?;
+    #[test]
+    fn test_fetch_missed_sessions_fills_only_absent_ids() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        add_session(&writer, &schema, "seen", "/p", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "missed", "/p", "main", "opus", false, 2000);
+        writer.commit().unwrap();
+        let searcher = make_reader(&index).searcher();
 
-    let mut messages: Vec<(u64, u64, ConversationMessage)> = Vec::with_capacity(top_docs.len());
-    for (_, addr) in top_docs {
-        let doc: TantivyDocument = searcher.doc(addr).map_err(|e| e.to_string())?;
-        let turn_index = doc.get_u64_val(schema.turn_index).unwrap_or(0);
-        let block_index = doc.get_u64_val(schema.block_index).unwrap_or(0);
-        let msg = ConversationMessage {
-            role: doc.get_str(schema.role).unwrap_or("").to_string(),
-            content_type: doc.get_str(schema.content_type).unwrap_or("").to_string(),
-            text: doc.get_str(schema.content_stored).unwrap_or("").to_string(),
-            timestamp: doc
-                .get_date_val(schema.timestamp)
-                .map(format_tantivy_date),
-            truncated: true,
-        };
-        messages.push((turn_index, block_index, msg));
-    }
+        let seen = find_session_doc(&searcher, &schema, "seen").unwrap();
+        let mut map = HashMap::from([("seen".to_string(), searcher.doc(seen).unwrap())]);
+        let ids: Vec<String> = ["seen", "missed", "gone"].iter().map(|s| s.to_string()).collect();
+        let filled = fetch_missed_sessions(&searcher, &schema, &ids, &mut map).unwrap();
 
-    messages.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
-    Ok(messages.into_iter().map(|(_, _, m)| m).collect())
-}
+        assert_eq!(filled, ["missed"]);
+        assert_eq!(map["missed"].get_str(schema.session_id), Some("missed"));
+        assert!(!map.contains_key("gone"));
+    }
 
-/// Calculate total size of the index directory.
-fn get_index_dir_size() -> u64 {
-    let path = match dirs::data_local_dir() {
-        Some(p) => p.join("swarm-ui").join("tantivy"),
-        None => return 0,
-    };
-    dir_size_recursive(&path)
-}
+    #[test]
+    fn test_get_session_message_breakdown_counts_roles_and_types() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        add_session(&writer, &schema, "mix", "/p", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "other", "/p", "main", "opus", false, 1000);
+        let blocks = [
+            ("user", "text", 0),
+            ("assistant", "thinking", 1),
+            ("assistant", "text", 1),
+            ("assistant", "tool_use", 1),
+            ("user", "tool_result", 2),
+            ("assistant", "tool_use", 3),
+            ("user", "tool_result", 4),
+            ("assistant", "text", 5),
+        ];
+        for (block, (role, kind, turn)) in blocks.into_iter().enumerate() {
+            add_message(&writer, &schema, "mix", role, "x", kind, turn, block as u64, "/p", 1000);
+        }
+        add_message(&writer, &schema, "other", "user", "y", "text", 0, 0, "/p", 1000);
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
 
-fn dir_size_recursive(path: &Path) -> u64 {
-    let mut size = 0;
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries.flatten() {
-            let p = entry.path();
-            if p.is_dir() {
-                size += dir_size_recursive(&p);
-            } else {
-                size += entry.metadata().ok().map(|m| m.len()).unwrap_or(0);
+        let breakdown = get_session_message_breakdown_query(&reader, &schema, "mix").unwrap();
+        assert_eq!(
+            breakdown,
+            SessionMessageBreakdown {
+                user: 3,
+                assistant: 5,
+                tool_use: 2,
+                tool_result: 2,
+                thinking: 1,
+                text: 3,
             }
-        }
+        );
+        assert!(get_session_message_breakdown_query(&reader, &schema, "missing").is_err());
     }
-    size
-}
 
-// ---------------------------------------------------------------------------
-// Tests
-// ---------------------------------------------------------------------------
+    #[test]
+    fn test_get_context_fill_known_and_unknown_model() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        for (sid, model) in [("c-opus", "claude-opus-4-6"), ("c-other", "gpt-4o")] {
+            let mut doc = TantivyDocument::new();
+            doc.add_text(schema.session_id, sid);
+            doc.add_text(schema.doc_type, "session");
+            doc.add_text(schema.model, model);
+            doc.add_u64(schema.context_tokens, 150_000);
+            writer.add_document(doc).unwrap();
+        }
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::search::schema::IndexSchema;
-    use tantivy::{Index, IndexWriter};
+        let known = get_context_fill_query(&reader, &schema, "c-opus").unwrap();
+        assert_eq!(known.context_tokens, 150_000);
+        assert_eq!(known.window_size, Some(200_000));
+        assert_eq!(known.fill_ratio, Some(0.75));
+        assert_eq!(known.model, "claude-opus-4-6");
 
-    /// Create an in-memory index with the full schema.
-    fn test_index() -> (Index, IndexSchema) {
-        let schema = IndexSchema::new();
-        let index = Index::create_in_ram(schema.schema.clone());
-        (index, schema)
+        let unknown = get_context_fill_query(&reader, &schema, "c-other").unwrap();
+        assert_eq!(unknown.context_tokens, 150_000);
+        assert_eq!(unknown.window_size, None);
+        assert_eq!(unknown.fill_ratio, None);
+
+        assert!(get_context_fill_query(&reader, &schema, "missing").is_err());
     }
 
-    /// Add a session document to the writer.
-    fn add_session(
-        writer: &IndexWriter,
-        schema: &IndexSchema,
-        session_id: &str,
-        project: &str,
-        branch: &str,
-        model: &str,
-        archived: bool,
-        modified_secs: i64,
-    ) {
+    // -----------------------------------------------------------------------
+    // compare_sessions tests
+    // -----------------------------------------------------------------------
+
+    /// Add a session doc carrying exactly the given metrics.
+    fn add_session_metrics(writer: &IndexWriter, schema: &IndexSchema, m: &SessionMetrics) {
         let mut doc = TantivyDocument::new();
-        doc.add_text(schema.session_id, session_id);
+        doc.add_text(schema.session_id, &m.session_id);
         doc.add_text(schema.doc_type, "session");
-        doc.add_text(schema.project_path, project);
-        doc.add_text(schema.project_raw, project);
-        doc.add_text(schema.summary, &format!("Summary for {session_id}"));
-        doc.add_text(schema.first_prompt, "Hello");
-        doc.add_text(schema.git_branch, branch);
-        doc.add_text(schema.model, model);
-        doc.add_text(schema.status, "idle");
-        doc.add_text(schema.jsonl_path, &format!("/tmp/{session_id}.jsonl"));
-        doc.add_u64(schema.message_count, 10);
-        doc.add_u64(schema.input_tokens, 100);
-        doc.add_u64(schema.output_tokens, 200);
-        doc.add_u64(schema.total_tokens, 300);
-        doc.add_date(
-            schema.created_at,
-            tantivy::DateTime::from_timestamp_secs(modified_secs - 3600),
-        );
-        doc.add_date(
-            schema.modified_at,
-            tantivy::DateTime::from_timestamp_secs(modified_secs),
-        );
-        doc.add_bool(schema.archived, archived);
-        doc.add_bool(schema.file_exists, true);
-        doc.add_bool(schema.has_tool_use, true);
-        doc.add_u64(schema.turn_depth, 5);
+        doc.add_text(schema.model, &m.model);
+        doc.add_u64(schema.message_count, m.message_count);
+        doc.add_u64(schema.input_tokens, m.input_tokens);
+        doc.add_u64(schema.output_tokens, m.output_tokens);
+        doc.add_u64(schema.total_tokens, m.total_tokens);
+        doc.add_u64(schema.turn_depth, m.turn_depth);
         writer.add_document(doc).unwrap();
     }
 
-    /// Add a message document to the writer.
-    fn add_message(
-        writer: &IndexWriter,
-        schema: &IndexSchema,
-        session_id: &str,
-        role: &str,
-        content: &str,
-        content_type: &str,
-        turn_index: u64,
-        block_index: u64,
-        project: &str,
-        timestamp_secs: i64,
-    ) {
-        let stored = if content.len() > 500 {
-            &content[..500]
-        } else {
-            content
-        };
-        let mut doc = TantivyDocument::new();
-        doc.add_text(schema.session_id, session_id);
-        doc.add_text(schema.doc_type, "message");
-        doc.add_text(schema.role, role);
-        doc.add_text(schema.content, content);
-        doc.add_text(schema.content_stored, stored);
-        doc.add_text(schema.content_type, content_type);
-        doc.add_date(
-            schema.timestamp,
-            tantivy::DateTime::from_timestamp_secs(timestamp_secs),
-        );
-        doc.add_u64(schema.turn_index, turn_index);
-        doc.add_u64(schema.block_index, block_index);
-        doc.add_text(schema.msg_project, project);
-        writer.add_document(doc).unwrap();
+    fn metrics(
+        id: &str,
+        model: &str,
+        messages: u64,
+        input: u64,
+        output: u64,
+        depth: u64,
+    ) -> SessionMetrics {
+        SessionMetrics {
+            session_id: id.into(),
+            model: model.into(),
+            message_count: messages,
+            input_tokens: input,
+            output_tokens: output,
+            total_tokens: input + output,
+            turn_depth: depth,
+        }
     }
 
-    fn make_reader(index: &Index) -> IndexReader {
-        index
-            .reader_builder()
-            .reload_policy(tantivy::ReloadPolicy::Manual)
-            .try_into()
-            .unwrap()
-    }
+    #[test]
+    fn test_compare_sessions_computes_deltas() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
 
-    // -----------------------------------------------------------------------
-    // list_sessions tests
-    // -----------------------------------------------------------------------
+        add_session_metrics(&writer, &schema, &metrics("before", "opus", 40, 1_000, 500, 12));
+        add_session_metrics(&writer, &schema, &metrics("after", "sonnet", 25, 1_800, 300, 8));
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let cmp = compare_sessions_query(&reader, &schema, "before", "after").unwrap();
+
+        assert_eq!(cmp.a.model, "opus");
+        assert_eq!(cmp.b.model, "sonnet");
+        assert_eq!(cmp.a.total_tokens, 1_500);
+        assert_eq!(cmp.b.total_tokens, 2_100);
+        assert_eq!(
+            cmp.delta,
+            MetricsDelta {
+                message_count: -15,
+                input_tokens: 800,
+                output_tokens: -200,
+                total_tokens: 600,
+                turn_depth: -4,
+            }
+        );
+    }
 
     #[test]
-    fn test_list_sessions_returns_non_archived() {
+    fn test_compare_sessions_missing_id() {
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
-
-        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
-        add_session(&writer, &schema, "s2", "/proj", "main", "opus", true, 2000);
-        add_session(&writer, &schema, "s3", "/proj", "dev", "opus", false, 3000);
+        add_session_metrics(&writer, &schema, &metrics("present", "opus", 1, 1, 1, 1));
         writer.commit().unwrap();
 
         let reader = make_reader(&index);
-        let results = list_sessions_query(&reader, &schema, None).unwrap();
+        let err = compare_sessions_query(&reader, &schema, "present", "ghost").unwrap_err();
 
-        assert_eq!(results.len(), 2, "Should exclude archived sessions");
-        assert_eq!(results[0].session_id, "s3", "Most recent first");
-        assert_eq!(results[1].session_id, "s1");
+        assert!(err.contains("Session not found: ghost"), "got: {err}");
     }
 
     #[test]
-    fn test_list_sessions_include_archived() {
+    fn test_parse_date_filter_date_only_uses_offset() {
+        let tokyo = parse_utc_offset(Some("+09:00")).unwrap();
+        let bound = |s| parse_date_filter(s, tokyo).unwrap().into_timestamp_secs();
+        let utc_secs = |s| chrono::DateTime::parse_from_rfc3339(s).unwrap().timestamp();
+
+        assert_eq!(bound("2026-02-18"), utc_secs("2026-02-17T15:00:00Z"));
+        assert_eq!(bound("2026-02-19"), utc_secs("2026-02-18T15:00:00Z"));
+        // Explicit offsets win over the filter's
+        assert_eq!(bound("2026-02-18T00:00:00Z"), utc_secs("2026-02-18T00:00:00Z"));
+        assert_eq!(
+            parse_date_filter("2026-02-18", utc()).unwrap().into_timestamp_secs(),
+            utc_secs("2026-02-18T00:00:00Z")
+        );
+        assert!(parse_utc_offset(Some("JST")).is_err());
+    }
+
+    #[test]
+    fn test_list_sessions_by_branch_spans_projects() {
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        add_session(&writer, &schema, "api-1", "/work/api", "feature/login", "opus", false, 1000);
+        add_session(&writer, &schema, "web-1", "/work/web", "feature/login", "opus", false, 3000);
+        add_session(&writer, &schema, "web-2", "/work/web", "main", "opus", false, 2000);
+        add_session(&writer, &schema, "api-old", "/work/api", "feature/login", "opus", true, 500);
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
 
-        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
-        add_session(&writer, &schema, "s2", "/proj", "main", "opus", true, 2000);
+        let ids: Vec<String> = list_sessions_by_branch_query(&reader, &schema, "feature/login")
+            .unwrap()
+            .into_iter()
+            .map(|s| s.session_id)
+            .collect();
+        assert_eq!(ids, ["web-1", "api-1"], "newest first, archived left out");
+        assert!(list_sessions_by_branch_query(&reader, &schema, "gone").unwrap().is_empty());
+        assert!(list_sessions_by_branch_query(&reader, &schema, "").is_err());
+    }
+
+    #[test]
+    fn test_search_stream_emits_incrementally_then_done() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        for i in 0..25 {
+            let sid = format!("stream-{i:02}");
+            add_session(&writer, &schema, &sid, "/proj", "main", "opus", false, 1000 + i);
+            // More repeats score higher, so the ranking is deterministic
+            let content = vec!["deploy"; 1 + i as usize % 5].join(" ") + " the service";
+            add_message(&writer, &schema, &sid, "user", &content, "text", 0, 0, "/proj", 1000);
+        }
         writer.commit().unwrap();
+        let reader = make_reader(&index);
+        let filter = SearchFilter { limit: Some(20), ..Default::default() };
+
+        let mut events = Vec::new();
+        let count = search_sessions_stream_query(
+            &reader,
+            &schema,
+            "deploy",
+            Some(&filter),
+            &SearchToken::never(),
+            &mut |e| events.push(e),
+        )
+        .unwrap();
+
+        assert_eq!(count, 20);
+        assert_eq!(events.len(), 21);
+        assert!(matches!(events.last(), Some(SearchStreamEvent::Done { count: 20 })));
+        let streamed: Vec<&SearchResult> = events
+            .iter()
+            .filter_map(|e| match e {
+                SearchStreamEvent::Result { result } => Some(result),
+                _ => None,
+            })
+            .collect();
+        assert!(streamed.windows(2).all(|w| w[0].score >= w[1].score), "best first");
+        let mut streamed_ids: Vec<&str> = streamed.iter().map(|r| r.session_id.as_str()).collect();
+        let batch = search_sessions_query(&reader, &schema, "deploy", Some(&filter)).unwrap();
+        let mut batch_ids: Vec<&str> = batch.iter().map(|r| r.session_id.as_str()).collect();
+        streamed_ids.sort();
+        batch_ids.sort();
+        assert_eq!(streamed_ids, batch_ids, "same results as search_sessions");
+
+        // The consumer holds the first chunk before the search finishes: a
+        // newer search cancels it mid-stream, and a cancelled event ends it
+        let generation = Arc::new(AtomicU64::new(0));
+        let token = SearchToken::next(&generation);
+        let mut events = Vec::new();
+        let err = search_sessions_stream_query(
+            &reader,
+            &schema,
+            "deploy",
+            Some(&filter),
+            &token,
+            &mut |e| {
+                events.push(e);
+                SearchToken::next(&generation);
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, SEARCH_CANCELLED);
+        assert_eq!(events.len(), STREAM_CHUNK + 1);
+        assert!(events[..STREAM_CHUNK]
+            .iter()
+            .all(|e| matches!(e, SearchStreamEvent::Result { .. })));
+        assert!(matches!(events.last(), Some(SearchStreamEvent::Cancelled)));
+    }
 
+    #[test]
+    fn test_dashboard_summary_aggregates_and_caches() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let now = 30 * 86_400;
+        let day = 86_400;
+        add_session(&writer, &schema, "api-1", "/work/api", "main", "opus", false, now - 100);
+        add_session(&writer, &schema, "api-2", "/work/api", "main", "sonnet", false, now - 2 * day);
+        add_session(&writer, &schema, "web-1", "/work/web", "main", "opus", false, now - 20 * day);
+        // Newest and this week's, but archived: not a top project or model
+        add_session(&writer, &schema, "web-old", "/work/web", "main", "haiku", true, now - 50);
+        add_session(&writer, &schema, "web-older", "/work/web", "main", "haiku", true, 1000);
+        writer.commit().unwrap();
         let reader = make_reader(&index);
-        let filter = SessionFilter {
-            include_archived: true,
-            ..Default::default()
+
+        let summary = dashboard_summary_query(&reader.searcher(), &schema, now).unwrap();
+        assert_eq!(
+            summary,
+            DashboardSummary {
+                total_sessions: 5,
+                tokens_this_week: 900,
+                top_project: Some("/work/api".into()),
+                top_model: Some("opus".into()),
+                last_activity: Some(LastActivity {
+                    session_id: "web-old".into(),
+                    modified_at: format_tantivy_date(tantivy::DateTime::from_timestamp_secs(
+                        now - 50
+                    )),
+                }),
+            }
+        );
+
+        // Served from cache until a commit reaches the reader
+        let cache = DashboardCache::default();
+        let computed = std::cell::Cell::new(0);
+        let get = || {
+            let searcher = reader.searcher();
+            cache
+                .get_or_compute(&searcher, || {
+                    computed.set(computed.get() + 1);
+                    dashboard_summary_query(&searcher, &schema, now)
+                })
+                .unwrap()
         };
-        let results = list_sessions_query(&reader, &schema, Some(&filter)).unwrap();
+        assert_eq!(get().total_sessions, 5);
+        assert_eq!(get().total_sessions, 5);
+        assert_eq!(computed.get(), 1);
 
-        assert_eq!(results.len(), 2, "Should include archived when requested");
+        add_session(&writer, &schema, "web-2", "/work/web", "main", "opus", false, now - 10);
+        writer.commit().unwrap();
+        assert_eq!(get().total_sessions, 5, "reader not reloaded yet");
+        reader.reload().unwrap();
+        assert_eq!(get().total_sessions, 6);
+        assert_eq!(computed.get(), 2);
     }
 
     #[test]
-    fn test_list_sessions_filter_by_project() {
+    fn test_search_tight_date_filter_still_fills_limit() {
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
 
-        add_session(&writer, &schema, "s1", "/proj-a", "main", "opus", false, 1000);
-        add_session(&writer, &schema, "s2", "/proj-b", "main", "opus", false, 2000);
+        // Old sessions outrank the in-range ones, so the first page of hits
+        // is all dropped by the date filter
+        for i in 0..25 {
+            let sid = format!("old-{i}");
+            add_session(&writer, &schema, &sid, "/proj", "main", "opus", false, 1000);
+            add_message(
+                &writer, &schema, &sid, "user", "deploy deploy deploy", "text", 0, 0, "/proj",
+                1000,
+            );
+        }
+        for sid in ["new-1", "new-2"] {
+            add_session(&writer, &schema, sid, "/proj", "main", "opus", false, 90_000);
+            add_message(
+                &writer, &schema, sid, "user",
+                "notes on the quarterly release train before we deploy the widget tooling",
+                "text", 0, 0, "/proj", 90_000,
+            );
+        }
         writer.commit().unwrap();
-
         let reader = make_reader(&index);
-        let filter = SessionFilter {
-            project: Some("/proj-a".into()),
+
+        let mut filter = SearchFilter {
+            limit: Some(2),
+            date_from: Some("1970-01-02T00:00:00Z".into()),
             ..Default::default()
         };
-        let results = list_sessions_query(&reader, &schema, Some(&filter)).unwrap();
+        let ids = |filter: &SearchFilter| {
+            let mut ids: Vec<String> =
+                search_sessions_query(&reader, &schema, "deploy", Some(filter))
+                    .unwrap()
+                    .into_iter()
+                    .map(|r| r.session_id)
+                    .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(&filter), ["new-1", "new-2"]);
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].session_id, "s1");
+        // Even a 1x first round pages deep enough
+        filter.overfetch_multiplier = Some(1);
+        assert_eq!(ids(&filter), ["new-1", "new-2"]);
+
+        // An absurd multiplier is clamped instead of overflowing the page size
+        filter.overfetch_multiplier = Some(usize::MAX);
+        assert_eq!(ids(&filter), ["new-1", "new-2"]);
     }
 
     #[test]
-    fn test_list_sessions_filter_by_branch_and_model() {
+    fn test_search_date_filter_respects_utc_offset() {
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let at = |s| chrono::DateTime::parse_from_rfc3339(s).unwrap().timestamp();
 
-        add_session(&writer, &schema, "s1", "/p", "main", "opus", false, 1000);
-        add_session(&writer, &schema, "s2", "/p", "dev", "sonnet", false, 2000);
-        add_session(&writer, &schema, "s3", "/p", "main", "sonnet", false, 3000);
+        add_session(&writer, &schema, "s-late", "/proj", "main", "opus", false, 1000);
+        // 23:30 on the 18th in Tokyo, still the 18th in UTC
+        add_message(
+            &writer, &schema, "s-late", "user", "deploy notes", "text", 0, 0, "/proj",
+            at("2026-02-18T14:30:00Z"),
+        );
         writer.commit().unwrap();
-
         let reader = make_reader(&index);
-        let filter = SessionFilter {
-            git_branch: Some("main".into()),
-            model: Some("opus".into()),
+
+        let mut filter = SearchFilter {
+            date_from: Some("2026-02-19".into()),
             ..Default::default()
         };
-        let results = list_sessions_query(&reader, &schema, Some(&filter)).unwrap();
-
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].session_id, "s1");
+        let results = search_sessions_query(&reader, &schema, "deploy", Some(&filter)).unwrap();
+        assert!(results.is_empty(), "before the 19th in UTC");
+
+        filter.date_from = Some("2026-02-18".into());
+        filter.date_to = Some("2026-02-19".into());
+        filter.utc_offset = Some("+09:00".into());
+        let results = search_sessions_query(&reader, &schema, "deploy", Some(&filter)).unwrap();
+        assert_eq!(results.len(), 1, "within the Tokyo-local 18th");
     }
 
     #[test]
-    fn test_list_sessions_sorted_by_modified_at_desc() {
+    fn test_repair_index_meta_clears_mismatch() {
+        use crate::search::indexer::schema_version_mismatch;
+        use crate::search::schema::SCHEMA_VERSION;
+
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
-
-        add_session(&writer, &schema, "old", "/p", "main", "opus", false, 1000);
-        add_session(&writer, &schema, "mid", "/p", "main", "opus", false, 5000);
-        add_session(&writer, &schema, "new", "/p", "main", "opus", false, 9000);
+        add_session(&writer, &schema, "s-1", "/proj", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s-2", "/proj", "main", "opus", true, 2000);
+        add_message(&writer, &schema, "s-1", "user", "hi", "text", 0, 0, "/proj", 1000);
         writer.commit().unwrap();
-
         let reader = make_reader(&index);
-        let results = list_sessions_query(&reader, &schema, None).unwrap();
 
-        assert_eq!(results[0].session_id, "new");
-        assert_eq!(results[1].session_id, "mid");
-        assert_eq!(results[2].session_id, "old");
+        let tmp = tempfile::TempDir::new().unwrap();
+        write_index_meta(tmp.path(), 99, None).unwrap();
+        fs::remove_file(tmp.path().join("swarm-ui-meta.json")).unwrap();
+        assert!(schema_version_mismatch(tmp.path()));
+
+        let meta = repair_index_meta_query(&reader, &schema, tmp.path()).unwrap();
+
+        assert!(!schema_version_mismatch(tmp.path()));
+        assert_eq!(meta.schema_version, SCHEMA_VERSION);
+        assert_eq!(meta.session_count, 2, "archived sessions count, messages don't");
+
+        // A measured reindex throughput survives repair
+        write_index_meta(tmp.path(), 7, Some(5_000_000)).unwrap();
+        let meta = repair_index_meta_query(&reader, &schema, tmp.path()).unwrap();
+        assert_eq!((meta.session_count, meta.bytes_per_sec), (2, Some(5_000_000)));
     }
 
-    // -----------------------------------------------------------------------
-    // search_sessions tests
-    // -----------------------------------------------------------------------
+    #[test]
+    fn test_diagnose_session_file_counts_skipped_lines() {
+        let (_, schema) = test_index();
+        let projects = tempfile::TempDir::new().unwrap();
+        let proj = projects.path().join("-home-u-app");
+        fs::create_dir_all(&proj).unwrap();
+        let path = proj.join("diag-1.jsonl");
+        fs::write(
+            &path,
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"hello"},"sessionId":"diag-1","timestamp":"2026-02-18T10:00:00Z"}"#,
+                "\n",
+                "{not json\n",
+                "\n",
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"hi"}]},"sessionId":"diag-1"}"#,
+                "\n",
+                "[1, 2]\n",
+            ),
+        )
+        .unwrap();
+        let options = IndexOptions::default();
+
+        let dirs = [projects.path().to_path_buf()];
+        let diag = diagnose_session_file_query(path.to_str().unwrap(), &dirs, &schema, &options)
+            .unwrap();
+
+        assert!(diag.session_id_found);
+        assert_eq!(diag.message_doc_count, 2);
+        assert_eq!(diag.lines_total, 4, "blank lines are not counted");
+        assert_eq!(diag.lines_skipped, 2);
+        assert!(diag.first_error.as_deref().unwrap().starts_with("line 2: "));
+
+        let outside = tempfile::NamedTempFile::new().unwrap();
+        let err = diagnose_session_file_query(
+            outside.path().to_str().unwrap(),
+            &dirs,
+            &schema,
+            &options,
+        )
+        .unwrap_err();
+        assert!(err.contains("Not a session file"), "got: {err}");
+    }
 
     #[test]
-    fn test_search_finds_matching_messages() {
+    fn test_resolve_session_file_confined_to_projects_dir() {
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
-
-        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
-        add_message(
-            &writer, &schema, "s1", "user", "How do I implement authentication?",
-            "text", 0, 0, "/proj", 1000,
-        );
-        add_message(
-            &writer, &schema, "s1", "assistant", "You can use JWT tokens for auth",
-            "text", 1, 0, "/proj", 1001,
-        );
-
-        add_session(&writer, &schema, "s2", "/proj", "main", "opus", false, 2000);
-        add_message(
-            &writer, &schema, "s2", "user", "Fix the CSS layout",
-            "text", 0, 0, "/proj", 2000,
-        );
+        let projects = tempfile::TempDir::new().unwrap();
+        let proj = projects.path().join("-home-u-app");
+        fs::create_dir_all(&proj).unwrap();
+        let inside = proj.join("rev-1.jsonl");
+        fs::write(&inside, "{}\n").unwrap();
+        let extra = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(extra.path().join("-mnt-u-app")).unwrap();
+        let in_extra = extra.path().join("-mnt-u-app").join("rev-extra.jsonl");
+        fs::write(&in_extra, "{}\n").unwrap();
+        let outside = tempfile::NamedTempFile::new().unwrap();
+
+        let sessions = [
+            ("rev-1", inside.to_str().unwrap(), true),
+            ("rev-extra", in_extra.to_str().unwrap(), true),
+            ("rev-outside", outside.path().to_str().unwrap(), true),
+            ("rev-pruned", inside.to_str().unwrap(), false),
+            ("rev-deleted", "/nonexistent/rev-deleted.jsonl", true),
+        ];
+        for (sid, path, file_exists) in sessions {
+            let mut doc = TantivyDocument::new();
+            doc.add_text(schema.session_id, sid);
+            doc.add_text(schema.doc_type, "session");
+            doc.add_text(schema.jsonl_path, path);
+            doc.add_bool(schema.file_exists, file_exists);
+            writer.add_document(doc).unwrap();
+        }
         writer.commit().unwrap();
-
         let reader = make_reader(&index);
-        let results = search_sessions_query(&reader, &schema, "authentication", None).unwrap();
-
-        assert_eq!(results.len(), 1, "Only s1 should match");
-        assert_eq!(results[0].session_id, "s1");
-        assert!(!results[0].snippets.is_empty());
+        let dirs = [projects.path().to_path_buf(), extra.path().to_path_buf()];
+        let resolve = |sid| resolve_session_file_query(&reader, &schema, sid, &dirs);
+
+        assert_eq!(resolve("rev-1").unwrap(), inside.canonicalize().unwrap());
+        assert_eq!(resolve("rev-extra").unwrap(), in_extra.canonicalize().unwrap());
+        assert!(resolve("rev-outside").unwrap_err().contains("Not a session file"));
+        assert!(resolve("rev-pruned").unwrap_err().contains("no longer exists"));
+        assert!(resolve("rev-deleted").unwrap_err().contains("no longer exists"));
+        assert!(resolve("rev-missing").is_err());
     }
 
     #[test]
-    fn test_search_excludes_tool_result_by_default() {
+    fn test_daily_token_spend_sums_per_day() {
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
-
-        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
-        add_message(
-            &writer, &schema, "s1", "assistant",
-            "tool_result with secret data about tantivy search engine",
-            "tool_result", 0, 0, "/proj", 1000,
-        );
-        // No text messages matching "tantivy"
+        let session = |sid: &str, project: &str, model: &str, created: &str, input, output| {
+            let created = parse_date_filter(created, utc()).unwrap();
+            let mut doc = TantivyDocument::new();
+            doc.add_text(schema.session_id, sid);
+            doc.add_text(schema.doc_type, "session");
+            doc.add_text(schema.project_raw, project);
+            doc.add_text(schema.model, model);
+            doc.add_date(schema.created_at, created);
+            doc.add_u64(schema.input_tokens, input);
+            doc.add_u64(schema.output_tokens, output);
+            writer.add_document(doc).unwrap();
+        };
+        session("a", "/p", "claude-sonnet-4-5", "2026-02-16T09:00:00Z", 1_000_000, 0);
+        session("b", "/p", "claude-sonnet-4-5", "2026-02-16T23:30:00Z", 0, 100_000);
+        session("c", "/p", "claude-opus-4-6", "2026-02-17T12:00:00Z", 200_000, 40_000);
+        session("d", "/p", "mystery-model", "2026-02-18T08:00:00Z", 500, 500);
+        session("e", "/other", "claude-opus-4-6", "2026-02-17T12:00:00Z", 1, 1);
+        session("late", "/p", "claude-opus-4-6", "2026-02-19T12:00:00Z", 1, 1);
         writer.commit().unwrap();
-
         let reader = make_reader(&index);
-        let results = search_sessions_query(&reader, &schema, "tantivy", None).unwrap();
 
-        assert_eq!(results.len(), 0, "tool_result should be excluded by default");
+        let spend = daily_token_spend_query(
+            &reader,
+            &schema,
+            Some("2026-02-16"),
+            Some("2026-02-18"),
+            Some("/p"),
+        )
+        .unwrap();
+
+        let days: Vec<&str> = spend.iter().map(|d| d.day.as_str()).collect();
+        assert_eq!(days, ["2026-02-16", "2026-02-17", "2026-02-18"]);
+        assert_eq!(spend[0].total_tokens, 1_100_000);
+        assert!((spend[0].estimated_cost - 4.5).abs() < 1e-9, "3.00 + 1.50");
+        assert_eq!(spend[1].total_tokens, 240_000);
+        assert!((spend[1].estimated_cost - 2.0).abs() < 1e-9, "1.00 + 1.00");
+        assert_eq!(spend[2].total_tokens, 1_000);
+        assert_eq!(spend[2].estimated_cost, 0.0, "unknown model is unpriced");
+
+        assert!(daily_token_spend_query(&reader, &schema, Some("soon"), None, None).is_err());
     }
 
     #[test]
-    fn test_search_includes_tool_result_when_requested() {
+    fn test_find_sessions_with_gaps_matches_only_long_pauses() {
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let t = 1_760_000_000;
 
-        add_session(&writer, &schema, "s1", "/proj", "main", "opus", false, 1000);
-        add_message(
-            &writer, &schema, "s1", "assistant",
-            "tool_result with tantivy search data",
-            "tool_result", 0, 0, "/proj", 1000,
-        );
+        add_session(&writer, &schema, "paused", "/proj", "main", "opus", false, t);
+        add_session(&writer, &schema, "steady", "/proj", "main", "opus", false, t);
+        // Indexed out of order: the gap comes from sorted timestamps
+        for (turn, ts) in [(2, t + 60 + 5 * 3600), (0, t), (1, t + 60)] {
+            add_message(&writer, &schema, "paused", "user", "hi", "text", turn, 0, "/proj", ts);
+        }
+        for (turn, ts) in [(0, t), (1, t + 60), (2, t + 120)] {
+            add_message(&writer, &schema, "steady", "user", "hi", "text", turn, 0, "/proj", ts);
+        }
         writer.commit().unwrap();
 
         let reader = make_reader(&index);
-        let filter = SearchFilter {
-            include_tool_output: true,
-            ..Default::default()
+        let gaps = find_sessions_with_gaps_query(&reader, &schema, 3600).unwrap();
+
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].session_id, "paused");
+        assert_eq!(gaps[0].max_gap_seconds, 5 * 3600);
+        assert_eq!(
+            gaps[0].gap_started_at,
+            format_tantivy_date(tantivy::DateTime::from_timestamp_secs(t + 60))
+        );
+        let all = find_sessions_with_gaps_query(&reader, &schema, 60).unwrap();
+        let ids: Vec<&str> = all.iter().map(|g| g.session_id.as_str()).collect();
+        assert_eq!(ids, ["paused", "steady"]);
+    }
+
+    #[test]
+    fn test_find_duplicate_sessions_clusters_same_prompt_and_project() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let session = |sid: &str, project: &str, prompt: &str, count: u64, modified: i64| {
+            let mut doc = TantivyDocument::new();
+            doc.add_text(schema.session_id, sid);
+            doc.add_text(schema.doc_type, "session");
+            doc.add_text(schema.project_path, project);
+            doc.add_text(schema.project_raw, project);
+            doc.add_text(schema.first_prompt, prompt);
+            doc.add_u64(schema.message_count, count);
+            doc.add_date(schema.modified_at, tantivy::DateTime::from_timestamp_secs(modified));
+            doc.add_bool(schema.archived, false);
+            writer.add_document(doc).unwrap();
         };
-        let results =
-            search_sessions_query(&reader, &schema, "tantivy", Some(&filter)).unwrap();
+        session("dup-old", "/scratch", "try the new parser", 4, 1_000);
+        session("dup-new", "/scratch", "Try the  new parser ", 6, 3_000);
+        session("long", "/scratch", "try the new parser", 120, 2_000);
+        session("other-proj", "/work", "try the new parser", 5, 4_000);
+        session("unique", "/scratch", "something else", 4, 5_000);
+        session("blank-a", "/scratch", "", 4, 6_000);
+        session("blank-b", "/scratch", "", 4, 7_000);
+        writer.commit().unwrap();
+        let reader = make_reader(&index);
 
-        assert_eq!(results.len(), 1, "tool_result should be included when requested");
+        let clusters = find_duplicate_sessions_query(&reader, &schema).unwrap();
+
+        assert_eq!(clusters.len(), 1, "got {clusters:?}");
+        assert_eq!(clusters[0].project_path, "/scratch");
+        let ids: Vec<&str> = clusters[0].sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["dup-new", "dup-old"], "newest first");
+        assert!(clusters[0].sessions[0].modified_at.is_some());
     }
 
     #[test]
-    fn test_search_enriches_with_session_metadata() {
+    fn test_find_duplicate_sessions_includes_archived_copies() {
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
-
-        add_session(&writer, &schema, "s1", "/my/project", "feature", "claude-opus-4-6", false, 5000);
-        add_message(
-            &writer, &schema, "s1", "user", "implement the dashboard component",
-            "text", 0, 0, "/my/project", 5000,
-        );
+        for (sid, archived, modified) in [("live", false, 2_000), ("archived", true, 1_000)] {
+            let mut doc = TantivyDocument::new();
+            doc.add_text(schema.session_id, sid);
+            doc.add_text(schema.doc_type, "session");
+            doc.add_text(schema.project_path, "/scratch");
+            doc.add_text(schema.project_raw, "/scratch");
+            doc.add_text(schema.first_prompt, "try the new parser");
+            doc.add_u64(schema.message_count, 4);
+            doc.add_date(schema.modified_at, tantivy::DateTime::from_timestamp_secs(modified));
+            doc.add_bool(schema.archived, archived);
+            writer.add_document(doc).unwrap();
+        }
         writer.commit().unwrap();
-
         let reader = make_reader(&index);
-        let results = search_sessions_query(&reader, &schema, "dashboard", None).unwrap();
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].project_path.as_deref(), Some("/my/project"));
-        assert_eq!(results[0].model.as_deref(), Some("claude-opus-4-6"));
-        assert!(results[0].file_exists);
-    }
+        let clusters = find_duplicate_sessions_query(&reader, &schema).unwrap();
 
-    // -----------------------------------------------------------------------
-    // get_session_detail tests
-    // -----------------------------------------------------------------------
+        assert_eq!(clusters.len(), 1, "got {clusters:?}");
+        let ids: Vec<&str> = clusters[0].sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["live", "archived"]);
+    }
 
     #[test]
-    fn test_get_session_detail_returns_full_metadata() {
+    fn test_get_last_activity_returns_newest_session() {
         let (index, schema) = test_index();
-        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let reader = make_reader(&index);
+        assert!(get_last_activity_query(&reader, &schema).unwrap().is_none());
 
-        add_session(&writer, &schema, "detail-1", "/project", "main", "opus", false, 1000);
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        add_session(&writer, &schema, "old", "/proj", "main", "opus", false, 1_000);
+        add_session(&writer, &schema, "newest", "/proj", "main", "opus", true, 9_000);
+        add_session(&writer, &schema, "middle", "/other", "main", "opus", false, 5_000);
+        // Message docs have no modified_at and must not win
+        add_message(&writer, &schema, "old", "user", "hi", "text", 0, 0, "/proj", 20_000);
         writer.commit().unwrap();
+        reader.reload().unwrap();
+
+        let last = get_last_activity_query(&reader, &schema).unwrap().unwrap();
+        assert_eq!(last.session_id, "newest");
+        assert_eq!(last.modified_at, "1970-01-01T02:30:00+00:00");
+    }
+
+    #[test]
+    fn test_get_message_round_trips_message_id() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("perma-1.jsonl");
+        fs::write(
+            &path,
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"first question"},"sessionId":"perma-1","timestamp":"2026-02-18T10:00:00Z"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"intro"},{"type":"text","text":"the answer"}]},"sessionId":"perma-1","timestamp":"2026-02-18T10:01:00Z"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
 
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let docs = crate::search::indexer::parse_jsonl_to_documents(
+            &path,
+            &schema,
+            None,
+            &crate::search::indexer::IndexOptions::default(),
+        );
+        for doc in docs {
+            writer.add_document(doc).unwrap();
+        }
+        writer.commit().unwrap();
         let reader = make_reader(&index);
-        let detail = get_session_detail_query(&reader, &schema, "detail-1").unwrap();
 
-        assert_eq!(detail.session_id, "detail-1");
-        assert_eq!(detail.project_path, "/project");
-        assert_eq!(detail.git_branch, "main");
-        assert_eq!(detail.model, "opus");
-        assert_eq!(detail.message_count, 10);
-        assert_eq!(detail.input_tokens, 100);
-        assert_eq!(detail.output_tokens, 200);
-        assert_eq!(detail.total_tokens, 300);
-        assert_eq!(detail.turn_depth, 5);
-        assert!(!detail.archived);
-        assert!(detail.file_exists);
-        assert!(detail.has_tool_use);
-        assert_eq!(detail.jsonl_path, "/tmp/detail-1.jsonl");
+        let id = crate::search::indexer::message_id("perma-1", 1, 1);
+        let msg = get_message_query(&reader, &schema, &id).unwrap();
+
+        assert_eq!(msg.message_id, id);
+        assert_eq!(msg.session_id, "perma-1");
+        assert_eq!(msg.role, "assistant");
+        assert_eq!(msg.text, "the answer");
+        assert_eq!((msg.turn_index, msg.block_index), (1, 1));
+
+        let err = get_message_query(&reader, &schema, "0000000000000000").unwrap_err();
+        assert!(err.contains("Message not found"));
     }
 
     #[test]
-    fn test_get_session_detail_not_found() {
+    fn test_get_conversation_raw_messages_preserves_blocks() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("raw-1.jsonl");
+        fs::write(
+            &path,
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"show me /etc/hosts"},"sessionId":"raw-1","timestamp":"2026-02-18T10:00:00Z"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"id":"msg_1","role":"assistant","content":[{"type":"thinking","thinking":"read it","signature":"sig"}]},"sessionId":"raw-1","timestamp":"2026-02-18T10:00:01Z"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"id":"msg_1","role":"assistant","content":[{"type":"tool_use","id":"toolu_1","name":"Read","input":{"file_path":"/etc/hosts"}}]},"sessionId":"raw-1","timestamp":"2026-02-18T10:00:02Z"}"#,
+                "\n",
+                r#"{"type":"summary","summary":"hosts file"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"127.0.0.1 localhost"}]},"sessionId":"raw-1","timestamp":"2026-02-18T10:00:03Z"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"id":"msg_2","role":"assistant","content":[{"type":"text","text":"It maps localhost."}]},"sessionId":"raw-1","timestamp":"2026-02-18T10:00:04Z"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
         let (index, schema) = test_index();
         let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        let docs = crate::search::indexer::parse_jsonl_to_documents(
+            &path,
+            &schema,
+            None,
+            &crate::search::indexer::IndexOptions::default(),
+        );
+        for doc in docs {
+            writer.add_document(doc).unwrap();
+        }
         writer.commit().unwrap();
-
         let reader = make_reader(&index);
-        let result = get_session_detail_query(&reader, &schema, "nonexistent");
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not found"));
+        let messages = get_conversation_raw_messages_query(&reader, &schema, "raw-1").unwrap();
+
+        let roles: Vec<&str> = messages.iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(roles, ["user", "assistant", "user", "assistant"]);
+        assert_eq!(
+            messages[0]["content"],
+            serde_json::json!([{ "type": "text", "text": "show me /etc/hosts" }])
+        );
+        // Split assistant lines merge into one message, blocks untouched
+        assert_eq!(
+            messages[1]["content"],
+            serde_json::json!([
+                { "type": "thinking", "thinking": "read it", "signature": "sig" },
+                {
+                    "type": "tool_use",
+                    "id": "toolu_1",
+                    "name": "Read",
+                    "input": { "file_path": "/etc/hosts" }
+                }
+            ])
+        );
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+        assert_eq!(messages[3]["content"][0]["text"], "It maps localhost.");
+
+        fs::remove_file(&path).unwrap();
+        let err = get_conversation_raw_messages_query(&reader, &schema, "raw-1").unwrap_err();
+        assert!(err.contains("no longer exists"), "got: {err}");
     }
 
     // -----------------------------------------------------------------------
     // get_conversation tests
     // -----------------------------------------------------------------------
 
+    #[test]
+    fn test_parse_conversation_pairs_tool_use_with_result() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("pair-1.jsonl");
+        fs::write(
+            &path,
+            concat!(
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Checking."},{"type":"tool_use","id":"toolu_9","name":"Bash","input":{"command":"ls"}}]},"timestamp":"2026-02-18T10:00:01Z"}"#,
+                "\n",
+                r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_9","content":"a.txt"}]},"timestamp":"2026-02-18T10:00:02Z"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let messages = parse_conversation_from_jsonl(path.to_str().unwrap()).unwrap();
+
+        let types: Vec<&str> = messages.iter().map(|m| m.content_type.as_str()).collect();
+        assert_eq!(types, ["text", "tool_use", "tool_result"]);
+        assert_eq!(messages[0].tool_use_id, None);
+        assert_eq!(messages[1].tool_use_id.as_deref(), Some("toolu_9"));
+        assert_eq!(messages[2].tool_use_id, messages[1].tool_use_id);
+    }
+
+    #[test]
+    fn test_parse_conversation_attaches_usage_per_response() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("usage-1.jsonl");
+        let usage = r#""usage":{"input_tokens":1200,"output_tokens":80,"cache_read_input_tokens":900}"#;
+        let lines = [
+            r#"{"type":"user","message":{"role":"user","content":"list files"}}"#.to_string(),
+            // One API response split over two lines, usage repeated on both
+            format!(
+                r#"{{"type":"assistant","message":{{"id":"msg_1","role":"assistant","content":[{{"type":"thinking","thinking":"use ls"}}],{usage}}}}}"#
+            ),
+            format!(
+                r#"{{"type":"assistant","message":{{"id":"msg_1","role":"assistant","content":[{{"type":"tool_use","id":"t1","name":"Bash","input":{{}}}}],{usage}}}}}"#
+            ),
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"a.txt"}]}}"#.to_string(),
+            r#"{"type":"assistant","message":{"id":"msg_2","role":"assistant","content":[{"type":"text","text":"One file."}],"usage":{"input_tokens":1400,"output_tokens":12,"cache_creation_input_tokens":200}}}"#.to_string(),
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let messages = parse_conversation_from_jsonl(path.to_str().unwrap()).unwrap();
+
+        let usages: Vec<Option<u64>> =
+            messages.iter().map(|m| m.usage.as_ref().and_then(|u| u.input_tokens)).collect();
+        assert_eq!(usages, [None, Some(1200), None, None, Some(1400)]);
+        assert_eq!(
+            messages[1].usage,
+            Some(MessageUsage {
+                input_tokens: Some(1200),
+                output_tokens: Some(80),
+                cache_creation_input_tokens: None,
+                cache_read_input_tokens: Some(900),
+            })
+        );
+        assert_eq!(messages[4].usage.as_ref().unwrap().cache_creation_input_tokens, Some(200));
+    }
+
+    #[test]
+    fn test_parse_conversation_keeps_lines_with_null_usage_counts() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("usage-null.jsonl");
+        fs::write(
+            &path,
+            r#"{"type":"assistant","message":{"id":"msg_1","role":"assistant","content":[{"type":"text","text":"Done."}],"usage":{"input_tokens":30,"output_tokens":null,"cache_read_input_tokens":null}}}"#,
+        )
+        .unwrap();
+
+        let messages = parse_conversation_from_jsonl(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(messages.len(), 1, "a null count must not drop the line");
+        let usage = messages[0].usage.as_ref().unwrap();
+        assert_eq!(usage.input_tokens, Some(30));
+        assert_eq!(usage.output_tokens, None);
+        assert_eq!(usage.cache_read_input_tokens, None);
+    }
+
+    #[test]
+    fn test_parse_conversation_threads_branches() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("branch-1.jsonl");
+        let line = |kind: &str, uuid: &str, parent: Option<&str>, text: &str| {
+            serde_json::json!({
+                "type": kind,
+                "uuid": uuid,
+                "parentUuid": parent,
+                "message": { "role": kind, "content": text },
+            })
+            .to_string()
+        };
+        // Turn u2 was rewound and retried as u2b; the retry's reply hangs off a system line
+        let lines = [
+            line("user", "u1", None, "start"),
+            line("assistant", "a1", Some("u1"), "ok"),
+            line("user", "u2", Some("a1"), "try A"),
+            line("user", "u2b", Some("a1"), "try B"),
+            line("assistant", "a2", Some("u2"), "did A"),
+            line("system", "s1", Some("u2b"), ""),
+            line("assistant", "a2b", Some("s1"), "did B"),
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let messages = parse_conversation_from_jsonl(path.to_str().unwrap()).unwrap();
+
+        let texts: Vec<&str> = messages.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["start", "ok", "try A", "did A", "try B", "did B"]);
+        let parents: Vec<Option<&str>> =
+            messages.iter().map(|m| m.parent_id.as_deref()).collect();
+        assert_eq!(
+            parents,
+            [None, Some("u1"), Some("a1"), Some("u2"), Some("a1"), Some("u2b")],
+            "a2b links past the system line to u2b"
+        );
+        assert_eq!(messages[5].uuid.as_deref(), Some("a2b"));
+    }
+
     #[test]
     fn test_get_conversation_reconstructs_from_index() {
         let (index, schema) = test_index();
@@ -1174,6 +5272,109 @@ mod tests {
         assert!(stats.segment_count >= 1);
     }
 
+    fn meta_indexed_at(indexed_at: &str) -> IndexMeta {
+        IndexMeta {
+            schema_version: crate::search::schema::SCHEMA_VERSION,
+            indexed_at: indexed_at.into(),
+            session_count: 1,
+            bytes_per_sec: None,
+        }
+    }
+
+    #[test]
+    fn test_index_staleness_detects_newer_file_on_disk() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let proj = tmp.path().join("-home-u-proj");
+        fs::create_dir_all(&proj).unwrap();
+        fs::write(proj.join("s1.jsonl"), "{}\n").unwrap();
+
+        let meta = meta_indexed_at("2026-01-01T00:00:00Z");
+        let (is_stale, newest) = index_staleness(Some(&meta), None, &[tmp.path().to_path_buf()]);
+
+        assert!(is_stale, "file written now is newer than the index meta");
+        assert!(newest.is_some());
+    }
+
+    #[test]
+    fn test_index_staleness_fresh_when_indexed_after_mtime() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        fs::write(tmp.path().join("s1.jsonl"), "{}\n").unwrap();
+
+        let future = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+        let meta = meta_indexed_at("2026-01-01T00:00:00Z");
+        let dirs = [tmp.path().to_path_buf()];
+        let (is_stale, _) = index_staleness(Some(&meta_indexed_at(&future)), None, &dirs);
+        assert!(!is_stale);
+
+        // The watcher keeps modified_at current even though indexed_at is old
+        let recent = chrono::Utc::now().timestamp();
+        let (is_stale, _) = index_staleness(Some(&meta), Some(recent), &dirs);
+        assert!(!is_stale);
+    }
+
+    #[test]
+    fn test_index_staleness_no_files_on_disk() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        assert_eq!(index_staleness(None, None, &[tmp.path().to_path_buf()]), (false, None));
+    }
+
+    #[test]
+    fn test_index_staleness_covers_extra_dirs() {
+        let projects = tempfile::TempDir::new().unwrap();
+        let extra = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(extra.path().join("-mnt-u-proj")).unwrap();
+        fs::write(extra.path().join("-mnt-u-proj").join("s1.jsonl"), "{}\n").unwrap();
+
+        let meta = meta_indexed_at("2026-01-01T00:00:00Z");
+        let dirs = [projects.path().to_path_buf(), extra.path().to_path_buf()];
+        let (is_stale, newest) = index_staleness(Some(&meta), None, &dirs);
+
+        assert!(is_stale, "a newer file in an extra dir makes the index stale");
+        assert!(newest.is_some());
+    }
+
+    #[test]
+    fn test_warm_up_index_on_populated_index() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        add_session(&writer, &schema, "s1", "/p", "main", "opus", false, 1000);
+        add_session(&writer, &schema, "s2", "/p", "main", "opus", false, 2000);
+        add_message(&writer, &schema, "s1", "user", "hello", "text", 0, 0, "/p", 1000);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        assert_eq!(warm_up_index(&reader, &schema).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_index_size_breakdown_sums_to_total() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let schema = IndexSchema::new();
+        let index = Index::create_in_dir(tmp.path(), schema.schema.clone()).unwrap();
+        crate::search::tokenizer::register_tokenizers(&index);
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+        for i in 0..20 {
+            let sid = format!("s{i}");
+            add_session(&writer, &schema, &sid, "/p", "main", "opus", false, 1000 + i);
+            add_message(
+                &writer, &schema, &sid, "user", "index size breakdown fixture",
+                "text", 0, 0, "/p", 1000 + i,
+            );
+        }
+        writer.commit().unwrap();
+        writer.wait_merging_threads().unwrap();
+
+        let breakdown = index_size_breakdown(tmp.path());
+
+        let sum: u64 = breakdown.iter().map(|c| c.bytes).sum();
+        assert_eq!(sum, dir_size_recursive(tmp.path()));
+        let kinds: Vec<&str> = breakdown.iter().map(|c| c.kind.as_str()).collect();
+        for expected in ["store", "fast", "idx", "term", "other"] {
+            assert!(kinds.contains(&expected), "missing {expected} in {kinds:?}");
+        }
+        assert!(breakdown.windows(2).all(|w| w[0].bytes >= w[1].bytes));
+    }
+
     // -----------------------------------------------------------------------
     // search edge cases
     // -----------------------------------------------------------------------
@@ -1198,6 +5399,69 @@ mod tests {
         assert!(results[0].snippets.len() <= 3, "Should keep at most 3 snippets");
     }
 
+    #[test]
+    fn test_search_best_snippet_only_keeps_top_scoring_block() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/p", "main", "opus", false, 1000);
+        add_message(
+            &writer, &schema, "s1", "user",
+            "a long question that mentions borrowck once near the end of a verbose sentence",
+            "text", 0, 0, "/p", 1000,
+        );
+        add_message(
+            &writer, &schema, "s1", "assistant", "borrowck borrowck borrowck",
+            "text", 1, 0, "/p", 1001,
+        );
+        add_message(
+            &writer, &schema, "s1", "user", "why does borrowck complain here in this long example",
+            "text", 2, 0, "/p", 1002,
+        );
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let filter = SearchFilter { best_snippet_only: true, ..Default::default() };
+        let results = search_sessions_query(&reader, &schema, "borrowck", Some(&filter)).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].snippets.len(), 1);
+        let best = &results[0].snippets[0];
+        assert_eq!(best.snippet, "borrowck borrowck borrowck");
+        assert_eq!(best.turn_index, 1);
+        assert_eq!(best.score, results[0].score);
+
+        let all = search_sessions_query(&reader, &schema, "borrowck", None).unwrap();
+        assert!(all[0].snippets.iter().all(|s| s.score <= best.score));
+    }
+
+    #[test]
+    fn test_search_best_turn_index_is_top_scoring_block() {
+        let (index, schema) = test_index();
+        let mut writer = index.writer::<TantivyDocument>(50_000_000).unwrap();
+
+        add_session(&writer, &schema, "s1", "/p", "main", "opus", false, 1000);
+        add_message(&writer, &schema, "s1", "user", "a long request that names kubectl once somewhere in all of this text", "text", 0, 0, "/p", 1000);
+        add_message(&writer, &schema, "s1", "assistant", "some other reply", "text", 1, 0, "/p", 1001);
+        add_message(&writer, &schema, "s1", "user", "more words and kubectl again in a long sentence", "text", 2, 0, "/p", 1002);
+        add_message(&writer, &schema, "s1", "assistant", "kubectl kubectl", "text", 3, 0, "/p", 1003);
+        add_message(&writer, &schema, "s1", "user", "thanks, one last kubectl question for this long thread", "text", 4, 0, "/p", 1004);
+        writer.commit().unwrap();
+
+        let reader = make_reader(&index);
+        let results = search_sessions_query(&reader, &schema, "kubectl", None).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].best_turn_index, Some(3));
+        let top = results[0]
+            .snippets
+            .iter()
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+            .unwrap();
+        assert_eq!(top.turn_index, 3);
+        assert_eq!(top.score, results[0].score);
+    }
+
     #[test]
     fn test_search_empty_query_returns_empty() {
         let (index, schema) = test_index();
@@ -1236,4 +5500,21 @@ mod tests {
             assert_eq!(snippet.role, "user");
         }
     }
+
+    #[test]
+    fn test_days_ago_cutoff_rejects_zero() {
+        assert!(days_ago_cutoff(0).is_err());
+        let now = chrono::Utc::now().timestamp();
+        let cutoff = days_ago_cutoff(1).unwrap();
+        assert!((now - 86_400 - cutoff).abs() <= 1, "{cutoff} vs {now}");
+        assert!(days_ago_cutoff(u64::MAX).unwrap() < 0, "saturates");
+    }
+
+    #[test]
+    fn test_previewed_cutoff_accepts_only_day_old_cutoffs() {
+        let cutoff = days_ago_cutoff(3).unwrap();
+        assert_eq!(previewed_cutoff(cutoff), Ok(cutoff));
+        let hour_ago = chrono::Utc::now().timestamp() - 3_600;
+        assert!(previewed_cutoff(hour_ago).is_err());
+    }
 }
@@ -0,0 +1,71 @@
+use crate::atomic_file::write_atomic;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Most ids kept in the recents list; older opens fall off the end.
+pub const MAX_RECENT_SESSIONS: usize = 50;
+
+/// Serializes read-modify-write of the recents file across commands.
+static RECENT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Sidecar list of session ids opened in the app, most recent first.
+///
+/// Kept outside the index directory like the archive list, so it survives
+/// reindexing and schema bumps.
+pub fn recent_sessions_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("swarm-ui").join("recent-sessions.json"))
+}
+
+/// Load recent session ids, most recent first; a missing or unreadable file
+/// means none.
+pub fn load_recent_ids(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Move `session_id` to the front of the recents list, capped at
+/// `MAX_RECENT_SESSIONS`.
+pub fn record_session_open(path: &Path, session_id: &str) -> Result<(), String> {
+    if session_id.is_empty() {
+        return Err("Session id must not be empty".to_string());
+    }
+    let _guard = RECENT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ids = load_recent_ids(path);
+    ids.retain(|id| id != session_id);
+    ids.insert(0, session_id.to_string());
+    ids.truncate(MAX_RECENT_SESSIONS);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&ids).map_err(|e| e.to_string())?;
+    write_atomic(path, json).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_session_open_orders_most_recent_first() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("swarm-ui").join("recent-sessions.json");
+        assert!(load_recent_ids(&path).is_empty());
+
+        for sid in ["a", "b", "c", "a"] {
+            record_session_open(&path, sid).unwrap();
+        }
+        assert_eq!(load_recent_ids(&path), ["a", "c", "b"], "reopening moves to the front");
+        assert!(record_session_open(&path, "").is_err());
+
+        for n in 0..MAX_RECENT_SESSIONS {
+            record_session_open(&path, &format!("s{n}")).unwrap();
+        }
+        let ids = load_recent_ids(&path);
+        assert_eq!(ids.len(), MAX_RECENT_SESSIONS);
+        assert_eq!(ids[0], format!("s{}", MAX_RECENT_SESSIONS - 1));
+        assert!(!ids.contains(&"a".to_string()), "oldest fall off the end");
+    }
+}
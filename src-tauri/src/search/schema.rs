@@ -1,16 +1,17 @@
+use crate::search::tokenizer::{CASED_CONTENT_TOKENIZER, CONTENT_TOKENIZER};
 use tantivy::schema::{
     DateOptions, Field, NumericOptions, Schema, SchemaBuilder, TextFieldIndexing, TextOptions,
     FAST, STORED, STRING,
 };
 
 /// Schema version — bump forces full reindex.
-pub const SCHEMA_VERSION: u64 = 1;
+pub const SCHEMA_VERSION: u64 = 8;
 
 /// Pre-cached field handles for the Tantivy index schema.
 ///
 /// Two document types share a single index, discriminated by `doc_type`:
-/// - "session": one per JSONL file (20 fields)
-/// - "message": one per content block (10 fields)
+/// - "session": one per JSONL file (23 fields)
+/// - "message": one per content block (12 fields)
 #[derive(Debug, Clone)]
 pub struct IndexSchema {
     pub schema: Schema,
@@ -24,6 +25,7 @@ pub struct IndexSchema {
     pub project_raw: Field,
     pub summary: Field,
     pub first_prompt: Field,
+    pub first_reply: Field,
     pub git_branch: Field,
     pub model: Field,
     pub status: Field,
@@ -32,22 +34,30 @@ pub struct IndexSchema {
     pub input_tokens: Field,
     pub output_tokens: Field,
     pub total_tokens: Field,
+    /// Prompt size of the latest assistant response, cache reads and writes
+    /// included: how full the context window was when the session stopped
+    pub context_tokens: Field,
     pub created_at: Field,
     pub modified_at: Field,
     pub archived: Field,
     pub file_exists: Field,
     pub has_tool_use: Field,
+    /// Set by `prune::prune_old_messages`: message docs were dropped
+    pub messages_pruned: Field,
     pub turn_depth: Field,
 
     // -- Message fields --
     pub role: Field,
     pub content: Field,
+    /// `content` with case kept, for `SearchFilter::case_sensitive`
+    pub content_cased: Field,
     pub content_stored: Field,
     pub content_type: Field,
     pub timestamp: Field,
     pub turn_index: Field,
     pub block_index: Field,
     pub msg_project: Field,
+    pub message_id: Field,
 }
 
 impl IndexSchema {
@@ -84,6 +94,9 @@ impl IndexSchema {
         let summary = builder.add_text_field("summary", text_stored_searchable.clone());
         let first_prompt = builder.add_text_field("first_prompt", text_stored_searchable);
 
+        // first_reply: STORED only (list preview of the first assistant text)
+        let first_reply = builder.add_text_field("first_reply", STORED);
+
         let git_branch = builder.add_text_field("git_branch", STRING | FAST | STORED);
         let model = builder.add_text_field("model", STRING | FAST | STORED);
         let status = builder.add_text_field("status", STRING | FAST | STORED);
@@ -95,6 +108,7 @@ impl IndexSchema {
         let input_tokens = builder.add_u64_field("input_tokens", u64_fast_stored.clone());
         let output_tokens = builder.add_u64_field("output_tokens", u64_fast_stored.clone());
         let total_tokens = builder.add_u64_field("total_tokens", u64_fast_stored.clone());
+        let context_tokens = builder.add_u64_field("context_tokens", u64_fast_stored.clone());
 
         // Date fields: FAST STORED
         let date_fast_stored = DateOptions::default().set_fast().set_stored();
@@ -105,21 +119,32 @@ impl IndexSchema {
         let bool_fast_stored = NumericOptions::default().set_fast().set_stored();
         let archived = builder.add_bool_field("archived", bool_fast_stored.clone());
         let file_exists = builder.add_bool_field("file_exists", bool_fast_stored.clone());
-        let has_tool_use = builder.add_bool_field("has_tool_use", bool_fast_stored);
+        let has_tool_use = builder.add_bool_field("has_tool_use", bool_fast_stored.clone());
+        let messages_pruned = builder.add_bool_field("messages_pruned", bool_fast_stored);
 
         let turn_depth = builder.add_u64_field("turn_depth", u64_fast_stored);
 
         // -- Message fields --
         let role = builder.add_text_field("role", STRING | FAST | STORED);
 
-        // content: TEXT only (NOT STORED) — saves ~600MB disk
+        // content: TEXT only (NOT STORED) — saves ~600MB disk; code-aware tokenizer
         let text_only = TextOptions::default().set_indexing_options(
             TextFieldIndexing::default()
-                .set_tokenizer("default")
+                .set_tokenizer(CONTENT_TOKENIZER)
                 .set_index_option(tantivy::schema::IndexRecordOption::WithFreqsAndPositions),
         );
         let content = builder.add_text_field("content", text_only);
 
+        // content_cased: same tokens without lowercasing, so "AWS" != "aws".
+        // A second full postings list (positions included, for phrases): the
+        // index's content portion roughly doubles; nothing extra is stored.
+        let cased_only = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(CASED_CONTENT_TOKENIZER)
+                .set_index_option(tantivy::schema::IndexRecordOption::WithFreqsAndPositions),
+        );
+        let content_cased = builder.add_text_field("content_cased", cased_only);
+
         let content_stored = builder.add_text_field("content_stored", STRING | STORED);
         let content_type = builder.add_text_field("content_type", STRING | FAST | STORED);
 
@@ -131,6 +156,9 @@ impl IndexSchema {
         // msg_project: STRING FAST (denormalized for filtering, not stored)
         let msg_project = builder.add_text_field("msg_project", STRING | FAST);
 
+        // message_id: STRING STORED (stable hash of session/turn/block for permalinks)
+        let message_id = builder.add_text_field("message_id", STRING | STORED);
+
         let schema = builder.build();
 
         IndexSchema {
@@ -141,6 +169,7 @@ impl IndexSchema {
             project_raw,
             summary,
             first_prompt,
+            first_reply,
             git_branch,
             model,
             status,
@@ -149,26 +178,30 @@ impl IndexSchema {
             input_tokens,
             output_tokens,
             total_tokens,
+            context_tokens,
             created_at,
             modified_at,
             archived,
             file_exists,
             has_tool_use,
+            messages_pruned,
             turn_depth,
             role,
             content,
+            content_cased,
             content_stored,
             content_type,
             timestamp,
             turn_index,
             block_index,
             msg_project,
+            message_id,
         }
     }
 
     /// Total number of fields in the schema.
     pub fn field_count(&self) -> usize {
-        28
+        33
     }
 }
 
@@ -177,16 +210,16 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_schema_has_28_fields() {
+    fn test_schema_has_33_fields() {
         let idx = IndexSchema::new();
         // SchemaBuilder assigns sequential field IDs starting at 0
-        assert_eq!(idx.schema.num_fields(), 28);
-        assert_eq!(idx.field_count(), 28);
+        assert_eq!(idx.schema.num_fields(), 33);
+        assert_eq!(idx.field_count(), 33);
     }
 
     #[test]
-    fn test_schema_version_is_1() {
-        assert_eq!(SCHEMA_VERSION, 1);
+    fn test_schema_version_is_8() {
+        assert_eq!(SCHEMA_VERSION, 8);
     }
 
     #[test]
@@ -0,0 +1,270 @@
+use tantivy::tokenizer::{
+    LowerCaser, RemoveLongFilter, TextAnalyzer, Token, TokenStream, Tokenizer, TokenizerManager,
+};
+use tantivy::Index;
+
+/// Tokenizer name of the `content` field (see `IndexSchema::new`).
+pub const CONTENT_TOKENIZER: &str = "code";
+/// Tokenizer name of the case-preserving `content_cased` field.
+pub const CASED_CONTENT_TOKENIZER: &str = "code_cased";
+
+/// Longest token kept; compounds like short paths fit, minified blobs don't.
+const MAX_TOKEN_LEN: usize = 64;
+
+/// Code-aware analyzer for message content.
+///
+/// The default tokenizer turns `getUserName` into one opaque token and loses
+/// `snake_case`/`path/segments` identity. This one emits each compound
+/// (`getUserName`, `parse_jsonl`, `src/search/queries.rs`) as a whole token
+/// plus its parts, split on punctuation and camelCase humps, all lowercased.
+/// The compound shares the first part's position so phrase queries still line up.
+pub fn content_analyzer() -> TextAnalyzer {
+    TextAnalyzer::builder(CodeTokenizer)
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+        .filter(LowerCaser)
+        .build()
+}
+
+/// `content_analyzer` without lowercasing, for case-sensitive search.
+pub fn cased_content_analyzer() -> TextAnalyzer {
+    TextAnalyzer::builder(CodeTokenizer)
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+        .build()
+}
+
+/// Register the custom tokenizers on an index; needed before writing or querying.
+pub fn register_tokenizers(index: &Index) {
+    index.tokenizers().register(CONTENT_TOKENIZER, content_analyzer());
+    index.tokenizers().register(CASED_CONTENT_TOKENIZER, cased_content_analyzer());
+}
+
+/// The default tokenizers plus ours, for `QueryParser`s built without an `Index`.
+pub fn tokenizer_manager() -> TokenizerManager {
+    let manager = TokenizerManager::default();
+    manager.register(CONTENT_TOKENIZER, content_analyzer());
+    manager.register(CASED_CONTENT_TOKENIZER, cased_content_analyzer());
+    manager
+}
+
+#[derive(Clone, Default)]
+pub struct CodeTokenizer;
+
+pub struct CodeTokenStream {
+    tokens: std::vec::IntoIter<Token>,
+    current: Token,
+}
+
+impl Tokenizer for CodeTokenizer {
+    type TokenStream<'a> = CodeTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> CodeTokenStream {
+        CodeTokenStream {
+            tokens: code_tokens(text).into_iter(),
+            current: Token::default(),
+        }
+    }
+}
+
+impl TokenStream for CodeTokenStream {
+    fn advance(&mut self) -> bool {
+        match self.tokens.next() {
+            Some(token) => {
+                self.current = token;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.current
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.current
+    }
+}
+
+/// Characters that glue parts into one compound: `a_b`, `a/b`, `a.b`, `a-b`.
+fn is_joiner(c: char) -> bool {
+    matches!(c, '_' | '/' | '.' | '-')
+}
+
+/// Joiner classes from coarsest to finest. Each level's pieces that are
+/// still compound get their own token, so `session_store` is findable inside
+/// `src/auth/session_store.rs`.
+const JOINER_LEVELS: [&[char]; 3] = [&['/'], &['.'], &['_', '-']];
+
+fn code_tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if !(c.is_alphanumeric() || is_joiner(c)) {
+            chars.next();
+            continue;
+        }
+        let mut end = start;
+        while let Some(&(i, c)) = chars.peek() {
+            if !(c.is_alphanumeric() || is_joiner(c)) {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+
+        // Joiners at the edges are punctuation ("end.", "--flag"), not glue
+        let chunk = &text[start..end];
+        let trimmed = chunk.trim_matches(is_joiner);
+        if trimmed.is_empty() {
+            continue;
+        }
+        let from = start + (chunk.len() - chunk.trim_start_matches(is_joiner).len());
+        let to = from + trimmed.len();
+
+        let parts = split_parts(text, from, to);
+        let mut compounds = Vec::new();
+        if parts.len() > 1 {
+            compounds.push((from, to));
+        }
+        collect_compounds(text, from, to, &JOINER_LEVELS, &mut compounds);
+        // Longest first, so a compound precedes the compounds nested in it
+        compounds.sort_by_key(|&(f, t)| (f, std::cmp::Reverse(t)));
+        compounds.dedup();
+
+        for &(part_from, part_to) in &parts {
+            for &(cf, ct) in compounds.iter().filter(|(cf, _)| *cf == part_from) {
+                let covered = parts.iter().filter(|(f, t)| *f >= cf && *t <= ct).count();
+                tokens.push(Token {
+                    offset_from: cf,
+                    offset_to: ct,
+                    position,
+                    text: text[cf..ct].to_string(),
+                    position_length: covered,
+                });
+            }
+            tokens.push(Token {
+                offset_from: part_from,
+                offset_to: part_to,
+                position,
+                text: text[part_from..part_to].to_string(),
+                position_length: 1,
+            });
+            position += 1;
+        }
+    }
+    tokens
+}
+
+/// Push every multi-part piece of `text[from..to]` split at `levels[0]`, recursing finer.
+fn collect_compounds(
+    text: &str,
+    from: usize,
+    to: usize,
+    levels: &[&[char]],
+    out: &mut Vec<(usize, usize)>,
+) {
+    let Some((joiners, finer)) = levels.split_first() else {
+        return;
+    };
+    let mut pieces = Vec::new();
+    let mut piece_from = from;
+    for (i, c) in text[from..to].char_indices() {
+        if joiners.contains(&c) {
+            pieces.push((piece_from, from + i));
+            piece_from = from + i + c.len_utf8();
+        }
+    }
+    pieces.push((piece_from, to));
+    pieces.retain(|(f, t)| f < t);
+
+    for &(f, t) in &pieces {
+        if pieces.len() > 1 && split_parts(text, f, t).len() > 1 {
+            out.push((f, t));
+        }
+        collect_compounds(text, f, t, finer, out);
+    }
+}
+
+/// Byte ranges of the alphanumeric parts of `text[from..to]`, split on
+/// joiners and camelCase boundaries (`getHTTPServer` -> get, HTTP, Server).
+fn split_parts(text: &str, from: usize, to: usize) -> Vec<(usize, usize)> {
+    let mut parts = Vec::new();
+    let chars: Vec<(usize, char)> = text[from..to].char_indices().collect();
+    let mut part_start: Option<usize> = None;
+
+    for (idx, &(i, c)) in chars.iter().enumerate() {
+        if is_joiner(c) {
+            if let Some(s) = part_start.take() {
+                parts.push((from + s, from + i));
+            }
+            continue;
+        }
+        if let Some(s) = part_start {
+            let prev = chars[idx - 1].1;
+            let next = chars.get(idx + 1).map(|&(_, n)| n);
+            let hump = c.is_uppercase()
+                && (prev.is_lowercase()
+                    || (prev.is_uppercase() && next.is_some_and(|n| n.is_lowercase())));
+            if hump {
+                parts.push((from + s, from + i));
+                part_start = Some(i);
+            }
+        } else {
+            part_start = Some(i);
+        }
+    }
+    if let Some(s) = part_start {
+        parts.push((from + s, to));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(mut analyzer: TextAnalyzer, text: &str) -> Vec<(usize, String)> {
+        let mut stream = analyzer.token_stream(text);
+        let mut out = Vec::new();
+        while stream.advance() {
+            out.push((stream.token().position, stream.token().text.clone()));
+        }
+        out
+    }
+
+    #[test]
+    fn test_code_tokenizer_keeps_compounds_and_parts() {
+        let tokens = texts(content_analyzer(), "Call getHTTPServer in src/api_v2.rs.");
+        let expected: Vec<(usize, String)> = [
+            (0, "call"),
+            (1, "gethttpserver"),
+            (1, "get"),
+            (2, "http"),
+            (3, "server"),
+            (4, "in"),
+            (5, "src/api_v2.rs"),
+            (5, "src"),
+            (6, "api_v2.rs"),
+            (6, "api_v2"),
+            (6, "api"),
+            (7, "v2"),
+            (8, "rs"),
+        ]
+        .into_iter()
+        .map(|(p, t)| (p, t.to_string()))
+        .collect();
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_code_tokenizer_finds_camel_case_part_default_misses() {
+        let code = texts(content_analyzer(), "fn getUserName()");
+        let simple = TokenizerManager::default().get("default").unwrap();
+        let default = texts(simple, "fn getUserName()");
+
+        assert!(code.iter().any(|(_, t)| t == "user"));
+        assert!(!default.iter().any(|(_, t)| t == "user"));
+    }
+}
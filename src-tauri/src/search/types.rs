@@ -4,33 +4,105 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct SessionFilter {
     pub project: Option<String>,
+    /// Glob over project paths, e.g. `*/frontend-*` (see `project_glob_matcher`)
+    pub project_glob: Option<String>,
     pub git_branch: Option<String>,
     pub model: Option<String>,
     #[serde(default)]
     pub include_archived: bool,
+    /// Only sessions modified in the last N days (0 = since now)
+    pub modified_within_days: Option<u64>,
+    /// `(timestamp, window_seconds)`: only sessions last modified within
+    /// the window either side of the timestamp (see `around_query`)
+    pub around: Option<(String, u64)>,
+    /// UTC offset (e.g. `+09:00`) that an `around` timestamp without one is
+    /// local to; UTC when absent.
+    pub utc_offset: Option<String>,
 }
 
 /// Filter for `search_sessions` command.
 #[derive(Debug, Clone, Deserialize)]
 pub struct SearchFilter {
     pub project: Option<String>,
+    /// Glob over project paths, e.g. `*/frontend-*` (see `project_glob_matcher`)
+    pub project_glob: Option<String>,
+    /// Matched against the session's branch (message docs don't carry one)
+    pub git_branch: Option<String>,
     #[serde(default)]
     pub include_tool_output: bool,
     pub limit: Option<usize>,
     pub date_from: Option<String>,
     pub date_to: Option<String>,
+    /// UTC offset (e.g. `+09:00`) that date-only `date_from`/`date_to` are
+    /// local to; UTC when absent. Full RFC 3339 dates carry their own.
+    pub utc_offset: Option<String>,
     pub role: Option<String>,
+    /// Restrict to one block type: "text", "tool_use", "tool_result", "thinking"
+    pub content_type: Option<String>,
+    /// Substring of a tool_use block's JSON input (e.g. a file path); implies
+    /// content_type=tool_use. Only the first 500 chars of a block are checked.
+    pub tool_input_contains: Option<String>,
+    /// Only match assistant thinking blocks, i.e. turns that used extended
+    /// thinking (shorthand for content_type=thinking)
+    #[serde(default)]
+    pub thinking_turns_only: bool,
+    /// Half-life in days: each session's score is halved per half-life since
+    /// its modified_at, so recent-and-relevant outranks old-and-relevant
+    pub recency_boost: Option<f32>,
+    /// Literal, case-sensitive substring match instead of BM25 (slower)
+    #[serde(default)]
+    pub exact_substring: bool,
+    /// BM25 over the case-preserving `content_cased` field: "AWS" no longer
+    /// matches "aws". Ignored with `exact_substring`, which is already exact.
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Only tool_result blocks containing an error signature (see
+    /// `error_signatures`); the query text may be empty
+    #[serde(default)]
+    pub errors_only: bool,
+    /// Replaces `DEFAULT_ERROR_SIGNATURES` for `errors_only`. Each is matched
+    /// as a case-insensitive word or phrase, e.g. "exit code".
+    pub error_signatures: Option<Vec<String>>,
+    /// One snippet per session, the highest-scoring block, instead of the
+    /// first three matches
+    #[serde(default)]
+    pub best_snippet_only: bool,
+    /// `(timestamp, window_seconds)`: only messages timestamped within the
+    /// window either side of the timestamp. Unlike `date_from`/`date_to`,
+    /// this is an index range query, so untimestamped messages never match.
+    pub around: Option<(String, u64)>,
+    /// Message hits fetched per result in the first search round, overriding
+    /// the adaptive default; clamped to 1..=50 (see `overfetch_multiplier`)
+    pub overfetch_multiplier: Option<usize>,
+    /// Only messages of these sessions, as an index clause; an empty list
+    /// matches nothing (see `search_project_sessions_query`)
+    pub session_ids: Option<Vec<String>>,
 }
 
 impl Default for SearchFilter {
     fn default() -> Self {
         Self {
             project: None,
+            project_glob: None,
+            git_branch: None,
             include_tool_output: false,
             limit: None,
             date_from: None,
             date_to: None,
+            utc_offset: None,
             role: None,
+            content_type: None,
+            tool_input_contains: None,
+            thinking_turns_only: false,
+            recency_boost: None,
+            exact_substring: false,
+            case_sensitive: false,
+            errors_only: false,
+            error_signatures: None,
+            best_snippet_only: false,
+            around: None,
+            overfetch_multiplier: None,
+            session_ids: None,
         }
     }
 }
@@ -42,6 +114,8 @@ pub struct SessionListItem {
     pub project_path: String,
     pub summary: String,
     pub first_prompt: String,
+    /// First assistant text block (truncated to 500 chars)
+    pub first_reply: String,
     pub git_branch: String,
     pub model: String,
     pub status: String,
@@ -62,6 +136,8 @@ pub struct MatchSnippet {
     pub snippet: String,
     pub timestamp: Option<String>,
     pub turn_index: u64,
+    /// BM25 score of this block
+    pub score: f32,
 }
 
 /// Single result from `search_sessions`.
@@ -75,6 +151,22 @@ pub struct SearchResult {
     pub model: Option<String>,
     pub modified_at: Option<String>,
     pub file_exists: bool,
+    /// Turn of the top-scoring matched block, so the UI can open the
+    /// conversation at that position
+    pub best_turn_index: Option<u64>,
+}
+
+/// Event sent by `search_sessions_stream`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchStreamEvent {
+    Result { result: SearchResult },
+    /// Terminal: the number of results sent
+    Done { count: usize },
+    /// Terminal: a newer search superseded this one
+    Cancelled,
+    /// Terminal: the search failed partway
+    Error { message: String },
 }
 
 /// Full session metadata returned by `get_session_detail`.
@@ -100,6 +192,59 @@ pub struct SessionDetail {
     pub turn_depth: u64,
 }
 
+/// Token and size metrics for one session, read from session doc fast fields.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionMetrics {
+    pub session_id: String,
+    pub model: String,
+    pub message_count: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    pub turn_depth: u64,
+}
+
+/// Signed differences between two sessions' metrics (`b - a`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetricsDelta {
+    pub message_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub total_tokens: i64,
+    pub turn_depth: i64,
+}
+
+/// Side-by-side comparison returned by `compare_sessions`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionComparison {
+    pub a: SessionMetrics,
+    pub b: SessionMetrics,
+    pub delta: MetricsDelta,
+}
+
+/// How full a session's context window got, returned by `get_context_fill`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextFill {
+    pub context_tokens: u64,
+    /// `None` for models missing from the built-in table
+    pub window_size: Option<u64>,
+    /// `context_tokens / window_size`; `None` when the window is unknown
+    pub fill_ratio: Option<f64>,
+    pub model: String,
+}
+
+/// Message block counts of one session by role and by content type,
+/// returned by `get_session_message_breakdown`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionMessageBreakdown {
+    pub user: u64,
+    pub assistant: u64,
+    pub tool_use: u64,
+    pub tool_result: u64,
+    pub thinking: u64,
+    pub text: u64,
+}
+
 /// A single message in a conversation, returned by `get_conversation`.
 #[derive(Debug, Clone, Serialize)]
 pub struct ConversationMessage {
@@ -108,6 +253,103 @@ pub struct ConversationMessage {
     pub text: String,
     pub timestamp: Option<String>,
     pub truncated: bool,
+    /// Pairs a tool_use with its tool_result; `None` for other blocks and
+    /// for conversations rebuilt from the index
+    pub tool_use_id: Option<String>,
+    /// JSONL entry uuid, shared by all blocks of that entry
+    pub uuid: Option<String>,
+    /// uuid of the nearest preceding message in the thread; `None` for roots
+    pub parent_id: Option<String>,
+    /// Token usage of the assistant API response, on its first block only
+    /// (Claude Code repeats it on every line of a split response). `None`
+    /// for conversations rebuilt from the index.
+    pub usage: Option<MessageUsage>,
+}
+
+/// The JSONL `message.usage` object of one assistant response; a count may
+/// be missing or null.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageUsage {
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub cache_creation_input_tokens: Option<u64>,
+    pub cache_read_input_tokens: Option<u64>,
+}
+
+/// Newest session activity, returned by `get_last_activity`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LastActivity {
+    pub session_id: String,
+    /// RFC 3339 `modified_at` of that session
+    pub modified_at: String,
+}
+
+/// Home screen aggregates, returned by `get_dashboard_summary`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DashboardSummary {
+    /// Archived sessions included, as in `IndexStats`
+    pub total_sessions: u64,
+    /// total_tokens of sessions created in the last 7 days, archived included
+    pub tokens_this_week: u64,
+    /// project_path with the most non-archived sessions
+    pub top_project: Option<String>,
+    /// Model with the most non-archived sessions
+    pub top_model: Option<String>,
+    pub last_activity: Option<LastActivity>,
+}
+
+/// One day of `get_daily_token_spend`, bucketed by session created_at (UTC).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailySpend {
+    /// `YYYY-MM-DD`
+    pub day: String,
+    pub total_tokens: u64,
+    /// USD, from `search::cost`; unknown models add tokens but no cost
+    pub estimated_cost: f64,
+}
+
+/// Sessions `find_duplicate_sessions` considers likely duplicates of each other.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateCluster {
+    pub project_path: String,
+    /// First prompt of the newest session (members differ only in case/whitespace)
+    pub first_prompt: String,
+    /// Newest first, so keeping `sessions[0]` and archiving the rest is the default
+    pub sessions: Vec<DuplicateSession>,
+}
+
+/// One member of a `DuplicateCluster`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateSession {
+    pub session_id: String,
+    pub message_count: u64,
+    pub modified_at: Option<String>,
+}
+
+/// A session with a long pause between messages, from `find_sessions_with_gaps`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionGap {
+    pub session_id: String,
+    pub project_path: String,
+    pub summary: String,
+    /// Largest delta between consecutive message timestamps
+    pub max_gap_seconds: u64,
+    /// Timestamp of the message the longest pause followed
+    pub gap_started_at: String,
+}
+
+/// A single indexed message block, returned by `get_message`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageRecord {
+    pub message_id: String,
+    pub session_id: String,
+    pub role: String,
+    pub content_type: String,
+    /// Stored preview (first 500 chars); the full text lives in the JSONL
+    pub text: String,
+    pub timestamp: Option<String>,
+    pub turn_index: u64,
+    pub block_index: u64,
 }
 
 /// Index statistics returned by `get_index_stats`.
@@ -119,6 +361,97 @@ pub struct IndexStats {
     pub total_messages: u64,
     pub segment_count: u64,
     pub index_size_bytes: u64,
+    /// True when a JSONL file on disk is newer than anything the index has seen
+    pub is_stale: bool,
+    /// RFC 3339 mtime of the newest JSONL under ~/.claude/projects
+    pub newest_on_disk: Option<String>,
+    /// Active incremental-indexing watcher; None until it starts (or if it failed)
+    pub watcher_mode: Option<WatcherMode>,
+}
+
+/// Liveness of the background indexing threads, returned by `get_background_health`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackgroundHealth {
+    /// False until the watcher starts, or once it stops answering probe writes
+    pub watcher_alive: bool,
+    pub merge_thread_alive: bool,
+    /// None unless a bulk index is running; false once it stops making progress
+    pub bulk_index_alive: Option<bool>,
+    /// RFC 3339 time of the last successful periodic merge commit
+    pub last_merge_commit: Option<String>,
+    /// RFC 3339 time of the last JSONL change the watcher saw
+    pub last_watcher_event: Option<String>,
+}
+
+/// Filesystem watcher backend in use, reported by `get_index_stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatcherMode {
+    Native,
+    Poll,
+}
+
+/// Bytes on disk for one kind of index file, returned by `get_index_size_breakdown`.
+///
+/// `kind` is the Tantivy file extension: `idx` (postings), `pos` (positions),
+/// `term` (term dictionary), `store` (stored fields), `fast` (fast fields),
+/// `fieldnorm`, `del` (deletes); everything else (meta, locks) is `other`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexSizeComponent {
+    pub kind: String,
+    pub bytes: u64,
+}
+
+/// Result of `diagnose_session_file`: how the indexer sees one JSONL file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionFileDiagnosis {
+    /// False means no entry has a sessionId and the file stem is used instead
+    pub session_id_found: bool,
+    pub message_doc_count: u64,
+    /// Non-blank lines
+    pub lines_total: u64,
+    /// Unreadable or malformed lines, dropped by the indexer
+    pub lines_skipped: u64,
+    /// First skipped line, as `line N: error`
+    pub first_error: Option<String>,
+}
+
+/// Result of `prune_old_messages`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PruneReport {
+    pub sessions_pruned: u64,
+    pub messages_removed: u64,
+    /// Index directory size before pruning minus after compaction
+    pub bytes_reclaimed: u64,
+}
+
+/// What `prune_old_messages` would drop, returned by `prune_old_messages_preview`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrunePreview {
+    /// Sessions whose messages would be dropped, sorted by session id
+    pub sessions: Vec<AffectedSession>,
+    pub messages_removed: u64,
+    /// Unix seconds the preview was taken against; passing it back to
+    /// `prune_old_messages` prunes exactly these sessions
+    pub cutoff: i64,
+}
+
+/// What `archive_older_than` would archive, returned by
+/// `archive_older_than_preview`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArchivePreview {
+    pub sessions: Vec<AffectedSession>,
+    /// Unix seconds the preview was taken against; passing it back to
+    /// `archive_older_than` archives exactly these sessions
+    pub cutoff: i64,
+}
+
+/// A session a bulk archive or prune would touch, listed by its preview command.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AffectedSession {
+    pub session_id: String,
+    pub summary: String,
+    pub project_path: String,
 }
 
 /// Progress event payload emitted during bulk indexing.
@@ -135,6 +468,17 @@ pub struct IndexMeta {
     pub schema_version: u64,
     pub indexed_at: String,
     pub session_count: u64,
+    /// JSONL throughput of the last full index, for `estimate_reindex_time`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_per_sec: Option<u64>,
+}
+
+/// Result of `estimate_reindex_time`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReindexEstimate {
+    pub file_count: u64,
+    pub total_bytes: u64,
+    pub estimated_seconds: f64,
 }
 
 #[cfg(test)]
@@ -170,11 +514,13 @@ mod tests {
     fn search_filter_defaults() {
         let filter = SearchFilter::default();
         assert!(filter.project.is_none());
+        assert!(filter.project_glob.is_none());
         assert!(!filter.include_tool_output);
         assert!(filter.limit.is_none());
         assert!(filter.date_from.is_none());
         assert!(filter.date_to.is_none());
         assert!(filter.role.is_none());
+        assert!(!filter.exact_substring);
     }
 
     #[test]
@@ -203,6 +549,7 @@ mod tests {
             project_path: "/home/user/project".into(),
             summary: "Implement feature X".into(),
             first_prompt: "Help me build X".into(),
+            first_reply: "Sure, let's start with X".into(),
             git_branch: "main".into(),
             model: "claude-opus-4-6".into(),
             status: "idle".into(),
@@ -233,6 +580,7 @@ mod tests {
                     snippet: "How do I implement...".into(),
                     timestamp: Some("2026-02-18T12:00:00Z".into()),
                     turn_index: 0,
+                    score: 2.5,
                 },
                 MatchSnippet {
                     role: "assistant".into(),
@@ -240,6 +588,7 @@ mod tests {
                     snippet: "You can use the following...".into(),
                     timestamp: None,
                     turn_index: 1,
+                    score: 1.5,
                 },
             ],
             project_path: Some("/home/user/project".into()),
@@ -247,6 +596,7 @@ mod tests {
             model: Some("claude-opus-4-6".into()),
             modified_at: Some("2026-02-18T13:00:00Z".into()),
             file_exists: true,
+            best_turn_index: Some(0),
         };
         let json = serde_json::to_value(&result).unwrap();
         assert_eq!(json["snippets"].as_array().unwrap().len(), 2);
@@ -290,6 +640,10 @@ mod tests {
             text: "Here is the answer...".into(),
             timestamp: Some("2026-02-18T12:00:00Z".into()),
             truncated: false,
+            tool_use_id: None,
+            uuid: None,
+            parent_id: None,
+            usage: None,
         };
         let json = serde_json::to_value(&msg).unwrap();
         assert_eq!(json["role"], "assistant");
@@ -301,6 +655,10 @@ mod tests {
             text: "First 500 chars...".into(),
             timestamp: None,
             truncated: true,
+            tool_use_id: None,
+            uuid: None,
+            parent_id: None,
+            usage: None,
         };
         let json = serde_json::to_value(&truncated).unwrap();
         assert_eq!(json["truncated"], true);
@@ -315,6 +673,9 @@ mod tests {
             total_messages: 500000,
             segment_count: 12,
             index_size_bytes: 314_159_265,
+            is_stale: false,
+            newest_on_disk: Some("2026-02-18T13:00:00+00:00".into()),
+            watcher_mode: Some(WatcherMode::Poll),
         };
         let json = serde_json::to_value(&stats).unwrap();
         assert_eq!(json["total_sessions"], 21169);
@@ -341,6 +702,7 @@ mod tests {
             schema_version: 1,
             indexed_at: "2026-02-18T12:00:00Z".into(),
             session_count: 21169,
+            bytes_per_sec: None,
         };
         let json_str = serde_json::to_string(&meta).unwrap();
         let parsed: IndexMeta = serde_json::from_str(&json_str).unwrap();
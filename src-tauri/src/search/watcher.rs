@@ -1,9 +1,13 @@
+use crate::search::archive::{apply_archived, archived_session_doc, persisted_archived_ids};
 use crate::search::doc_ext::DocExt;
-use crate::search::indexer::{parse_jsonl_to_documents, SessionIndexEntry};
+use crate::search::health::{BackgroundHeartbeats, HEARTBEAT_INTERVAL};
+use crate::search::indexer::{
+    load_index_file, parse_jsonl_to_documents, IndexOptions, SessionIndexEntry, WatcherChoice,
+};
 use crate::search::schema::IndexSchema;
-use crate::search::types::SessionListItem;
-use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use serde::Deserialize;
+use crate::search::types::{SessionListItem, WatcherMode};
+use crate::state::lock_recover;
+use notify::{Event, EventKind, PollWatcher, RecursiveMode, Watcher};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -13,12 +17,6 @@ use tantivy::schema::Term;
 use tantivy::{IndexReader, IndexWriter, TantivyDocument};
 use tracing::{debug, warn};
 
-/// Sessions-index.json file structure (local to watcher).
-#[derive(Deserialize)]
-struct SessionsIndexFile {
-    entries: Vec<SessionIndexEntry>,
-}
-
 /// Convert a tantivy DateTime to an RFC 3339 string.
 pub fn format_tantivy_date(dt: tantivy::DateTime) -> String {
     let secs = dt.into_timestamp_secs();
@@ -34,6 +32,7 @@ pub fn session_doc_to_list_item(doc: &TantivyDocument, schema: &IndexSchema) ->
         project_path: doc.get_str(schema.project_path).unwrap_or("").to_string(),
         summary: doc.get_str(schema.summary).unwrap_or("").to_string(),
         first_prompt: doc.get_str(schema.first_prompt).unwrap_or("").to_string(),
+        first_reply: doc.get_str(schema.first_reply).unwrap_or("").to_string(),
         git_branch: doc.get_str(schema.git_branch).unwrap_or("").to_string(),
         model: doc.get_str(schema.model).unwrap_or("").to_string(),
         status: doc.get_str(schema.status).unwrap_or("").to_string(),
@@ -50,14 +49,8 @@ pub fn session_doc_to_list_item(doc: &TantivyDocument, schema: &IndexSchema) ->
 /// Load session metadata from the sessions-index.json in the same directory as the JSONL file.
 fn load_session_meta(jsonl_path: &Path) -> Option<SessionIndexEntry> {
     let parent = jsonl_path.parent()?;
-    let index_path = parent.join("sessions-index.json");
-    let content = std::fs::read_to_string(&index_path).ok()?;
-    let index_file: SessionsIndexFile = serde_json::from_str(&content).ok()?;
     let session_id = jsonl_path.file_stem()?.to_str()?;
-    index_file
-        .entries
-        .into_iter()
-        .find(|e| e.session_id == session_id)
+    load_index_file(parent).remove(session_id)
 }
 
 /// Find the session document for a given session_id via the index reader.
@@ -90,11 +83,84 @@ fn find_session_doc(
     searcher.doc(*doc_address).ok()
 }
 
-/// Emit a `session:updated` event with a full SessionListItem payload.
-fn emit_session_updated(app_handle: &Option<tauri::AppHandle>, item: &SessionListItem) {
-    if let Some(handle) = app_handle {
+/// Destination of watcher updates; the app handle in production, a recorder in tests.
+pub trait UpdateSink: Send + Sync {
+    /// One `session:updated` event.
+    fn updated(&self, item: &SessionListItem);
+    /// One `session:batch_updated` event.
+    fn batch_updated(&self, items: &[SessionListItem]);
+    /// One `session:snapshot` event: `items` replaces the UI's list.
+    fn snapshot(&self, items: &[SessionListItem]);
+}
+
+impl UpdateSink for tauri::AppHandle {
+    fn updated(&self, item: &SessionListItem) {
+        use tauri::Emitter;
+        let _ = self.emit("session:updated", item);
+    }
+
+    fn batch_updated(&self, items: &[SessionListItem]) {
+        use tauri::Emitter;
+        let _ = self.emit("session:batch_updated", items);
+    }
+
+    fn snapshot(&self, items: &[SessionListItem]) {
         use tauri::Emitter;
-        let _ = handle.emit("session:updated", item);
+        let _ = self.emit("session:snapshot", items);
+    }
+}
+
+/// How long batched updates accumulate before `session:batch_updated` fires.
+pub const UPDATE_BATCH_WINDOW: Duration = Duration::from_millis(250);
+
+/// Forwards watcher updates to a sink, either one event per update or
+/// coalesced over a window.
+///
+/// In batch mode the first update of a window schedules a flush; later ones
+/// join the pending batch, replacing any older entry for the same session, so
+/// a burst of N writes to one file reaches the frontend as a single item.
+#[derive(Clone)]
+pub struct SessionUpdates {
+    sink: Option<Arc<dyn UpdateSink>>,
+    batch_window: Option<Duration>,
+    pending: Arc<Mutex<Vec<SessionListItem>>>,
+}
+
+impl SessionUpdates {
+    pub fn new(sink: Option<Arc<dyn UpdateSink>>, batch_window: Option<Duration>) -> Self {
+        Self {
+            sink,
+            batch_window,
+            pending: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn push(&self, item: SessionListItem) {
+        let Some(ref sink) = self.sink else {
+            return;
+        };
+        let Some(window) = self.batch_window else {
+            sink.updated(&item);
+            return;
+        };
+
+        let mut pending = lock_recover(&self.pending, "pending updates");
+        let schedule = pending.is_empty();
+        pending.retain(|p| p.session_id != item.session_id);
+        pending.push(item);
+        drop(pending);
+
+        if schedule {
+            let sink = sink.clone();
+            let pending = self.pending.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(window);
+                let batch = std::mem::take(&mut *lock_recover(&pending, "pending updates"));
+                if !batch.is_empty() {
+                    sink.batch_updated(&batch);
+                }
+            });
+        }
     }
 }
 
@@ -106,9 +172,11 @@ fn reindex_session(
     jsonl_path: &Path,
     writer: &Arc<Mutex<IndexWriter>>,
     schema: &IndexSchema,
+    options: &IndexOptions,
 ) -> Option<SessionListItem> {
     let meta = load_session_meta(jsonl_path);
-    let docs = parse_jsonl_to_documents(jsonl_path, schema, meta.as_ref());
+    let mut docs = parse_jsonl_to_documents(jsonl_path, schema, meta.as_ref(), options);
+    apply_archived(&mut docs, schema, &persisted_archived_ids());
     if docs.is_empty() {
         return None;
     }
@@ -116,7 +184,7 @@ fn reindex_session(
     let item = session_doc_to_list_item(&docs[0], schema);
     let session_id = &item.session_id;
 
-    let mut w = writer.lock().ok()?;
+    let mut w = lock_recover(writer, "index writer");
     w.delete_term(Term::from_field_text(schema.session_id, session_id));
     for doc in docs {
         if let Err(e) = w.add_document(doc) {
@@ -146,58 +214,13 @@ pub fn archive_session(
     schema: &IndexSchema,
 ) -> Option<SessionListItem> {
     let existing = find_session_doc(session_id, reader, schema)?;
-
-    // Build new session doc preserving all metadata
-    let mut doc = TantivyDocument::new();
-    doc.add_text(schema.session_id, session_id);
-    doc.add_text(schema.doc_type, "session");
-
-    // Copy text fields
-    for field in [
-        schema.project_path,
-        schema.project_raw,
-        schema.summary,
-        schema.first_prompt,
-        schema.git_branch,
-        schema.model,
-        schema.status,
-        schema.jsonl_path,
-    ] {
-        doc.add_text(field, existing.get_str(field).unwrap_or(""));
-    }
-
-    // Copy numeric fields
-    for field in [
-        schema.message_count,
-        schema.input_tokens,
-        schema.output_tokens,
-        schema.total_tokens,
-        schema.turn_depth,
-    ] {
-        doc.add_u64(field, existing.get_u64_val(field).unwrap_or(0));
-    }
-
-    // Copy date fields
-    if let Some(dt) = existing.get_date_val(schema.created_at) {
-        doc.add_date(schema.created_at, dt);
-    }
-    if let Some(dt) = existing.get_date_val(schema.modified_at) {
-        doc.add_date(schema.modified_at, dt);
-    }
-
-    // Set archive flags
-    doc.add_bool(schema.archived, true);
-    doc.add_bool(schema.file_exists, false);
-    doc.add_bool(
-        schema.has_tool_use,
-        existing.get_bool_val(schema.has_tool_use).unwrap_or(false),
-    );
+    let doc = archived_session_doc(&existing, schema, false);
 
     // Build result before consuming doc
     let item = session_doc_to_list_item(&doc, schema);
 
     // Atomic: delete all docs for session, re-add archived session doc, commit
-    let mut w = writer.lock().ok()?;
+    let mut w = lock_recover(writer, "index writer");
     w.delete_term(Term::from_field_text(schema.session_id, session_id));
     if let Err(e) = w.add_document(doc) {
         warn!("Failed to add archived session doc: {e}");
@@ -213,109 +236,314 @@ pub fn archive_session(
     Some(item)
 }
 
-/// Start the filesystem watcher for incremental indexing.
-///
-/// Watches `watch_dir` recursively for JSONL file changes with 2-second debounce.
-/// Returns the watcher handle (must be kept alive) and a merge thread join handle.
-///
-/// The merge thread commits every 5 minutes using the same `Arc<Mutex<IndexWriter>>`
-/// to trigger segment compaction via the configured merge policy.
-pub fn start_index_watcher(
-    watch_dir: PathBuf,
+/// A running filesystem watcher of either backend; must be kept alive.
+pub type IndexWatcher = Box<dyn Watcher + Send>;
+
+/// How long `WatcherChoice::Auto` waits for the native watcher to see the probe file.
+const PROBE_WINDOW: Duration = Duration::from_secs(3);
+/// Probe file written into the watch dir; not `.jsonl`, so it is never indexed.
+const PROBE_FILE: &str = ".swarm-ui-watch-probe";
+
+/// How often the merge thread commits to let the merge policy compact segments.
+const MERGE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Everything the event callback needs, cloned into whichever backend runs.
+#[derive(Clone)]
+struct WatchContext {
     writer: Arc<Mutex<IndexWriter>>,
     reader: IndexReader,
     schema: IndexSchema,
     paused: Arc<AtomicBool>,
-    app_handle: Option<tauri::AppHandle>,
-) -> Result<(RecommendedWatcher, std::thread::JoinHandle<()>), notify::Error> {
-    let debounce_map: Arc<Mutex<HashMap<PathBuf, Instant>>> =
-        Arc::new(Mutex::new(HashMap::new()));
-    let debounce_dur = Duration::from_secs(2);
-
-    let watcher_writer = writer.clone();
-    let watcher_schema = schema;
-    let watcher_paused = paused.clone();
-
-    let mut watcher =
-        notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
-            // Check pause flag at top of callback
-            if watcher_paused.load(Ordering::Relaxed) {
+    options: IndexOptions,
+    updates: SessionUpdates,
+    debounce_map: Arc<Mutex<HashMap<PathBuf, Instant>>>,
+    probe_seen: Arc<AtomicBool>,
+    health: Arc<BackgroundHeartbeats>,
+}
+
+impl WatchContext {
+    fn handle_event(&self, res: Result<Event, notify::Error>) {
+        let debounce_dur = Duration::from_secs(2);
+
+        let event = match res {
+            Ok(e) => e,
+            Err(e) => {
+                warn!("Watcher error: {e}");
                 return;
             }
+        };
+        // Any delivered event, the periodic probe rewrite included, proves the backend runs
+        self.health.watcher.beat();
+
+        if event
+            .paths
+            .iter()
+            .any(|p| p.file_name().is_some_and(|n| n == PROBE_FILE))
+        {
+            self.probe_seen.store(true, Ordering::SeqCst);
+        }
 
-            let event = match res {
-                Ok(e) => e,
-                Err(e) => {
-                    warn!("Watcher error: {e}");
-                    return;
-                }
-            };
+        // Check pause flag before touching the index
+        if self.paused.load(Ordering::Relaxed) {
+            return;
+        }
 
-            for path in &event.paths {
-                if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
-                    continue;
+        for path in &event.paths {
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            self.health.record_watcher_event();
+
+            // 2s debounce: skip if last processed < 2s ago
+            {
+                let mut map = lock_recover(&self.debounce_map, "debounce map");
+                let now = Instant::now();
+                if let Some(last) = map.get(path) {
+                    if now.duration_since(*last) < debounce_dur {
+                        continue;
+                    }
                 }
+                map.insert(path.clone(), now);
+            }
 
-                // 2s debounce: skip if last processed < 2s ago
-                {
-                    let mut map = debounce_map.lock().unwrap();
-                    let now = Instant::now();
-                    if let Some(last) = map.get(path) {
-                        if now.duration_since(*last) < debounce_dur {
-                            continue;
-                        }
+            match event.kind {
+                EventKind::Create(_) | EventKind::Modify(_) => {
+                    if let Some(item) =
+                        reindex_session(path, &self.writer, &self.schema, &self.options)
+                    {
+                        self.updates.push(item);
                     }
-                    map.insert(path.clone(), now);
                 }
-
-                match event.kind {
-                    EventKind::Create(_) | EventKind::Modify(_) => {
+                EventKind::Remove(_) => {
+                    if let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) {
                         if let Some(item) =
-                            reindex_session(path, &watcher_writer, &watcher_schema)
+                            archive_session(session_id, &self.writer, &self.reader, &self.schema)
                         {
-                            emit_session_updated(&app_handle, &item);
+                            self.updates.push(item);
                         }
                     }
-                    EventKind::Remove(_) => {
-                        if let Some(session_id) =
-                            path.file_stem().and_then(|s| s.to_str())
-                        {
-                            if let Some(item) = archive_session(
-                                session_id,
-                                &watcher_writer,
-                                &reader,
-                                &watcher_schema,
-                            ) {
-                                emit_session_updated(&app_handle, &item);
-                            }
-                        }
-                    }
-                    _ => {}
                 }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Watch every directory recursively. Only the first (primary) one must
+/// succeed; an unavailable extra directory is logged and skipped.
+fn watch_all(watcher: &mut dyn Watcher, watch_dirs: &[PathBuf]) -> notify::Result<()> {
+    let (primary, extra) = watch_dirs
+        .split_first()
+        .ok_or_else(|| notify::Error::generic("No directories to watch"))?;
+    watcher.watch(primary, RecursiveMode::Recursive)?;
+    for dir in extra {
+        if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
+            warn!("Not watching {}: {e}", dir.display());
+        }
+    }
+    Ok(())
+}
+
+fn start_native_watcher(
+    ctx: &WatchContext,
+    watch_dirs: &[PathBuf],
+) -> notify::Result<IndexWatcher> {
+    let ctx = ctx.clone();
+    let mut watcher = notify::recommended_watcher(move |res| ctx.handle_event(res))?;
+    watch_all(&mut watcher, watch_dirs)?;
+    Ok(Box::new(watcher))
+}
+
+fn start_poll_watcher(
+    ctx: &WatchContext,
+    watch_dirs: &[PathBuf],
+    interval: Duration,
+) -> notify::Result<IndexWatcher> {
+    let ctx = ctx.clone();
+    let config = notify::Config::default().with_poll_interval(interval);
+    let mut watcher = PollWatcher::new(move |res| ctx.handle_event(res), config)?;
+    watch_all(&mut watcher, watch_dirs)?;
+    Ok(Box::new(watcher))
+}
+
+/// Touch a probe file and wait for the running native watcher to report it.
+///
+/// Without a probe directory, or if the probe can't be written there, there's
+/// nothing to learn, so the native watcher is trusted.
+fn native_watcher_sees_probe(ctx: &WatchContext, watch_dir: Option<&Path>) -> bool {
+    let Some(watch_dir) = watch_dir else {
+        return true;
+    };
+    let probe = watch_dir.join(PROBE_FILE);
+    if let Err(e) = std::fs::write(&probe, b"probe") {
+        debug!("Could not write watcher probe {}: {e}", probe.display());
+        return true;
+    }
+    let deadline = Instant::now() + PROBE_WINDOW;
+    while !ctx.probe_seen.load(Ordering::SeqCst) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let _ = std::fs::remove_file(&probe);
+    ctx.probe_seen.load(Ordering::SeqCst)
+}
+
+/// Rewrite the probe file in `probe_dir` every `interval` so an idle but
+/// working watcher still delivers events (and thus heartbeats).
+///
+/// The file is rewritten rather than recreated: a poll watcher misses a
+/// create/remove pair that falls between two scans. The thread ends once
+/// `stop_watcher_pinger` runs.
+fn spawn_watcher_pinger(
+    probe_dir: PathBuf,
+    health: Arc<BackgroundHeartbeats>,
+    interval: Duration,
+) {
+    let probe = probe_dir.join(PROBE_FILE);
+    let spawned = std::thread::Builder::new()
+        .name("tantivy-watch-ping".into())
+        .spawn(move || loop {
+            std::thread::sleep(interval);
+            if health.pinger_stopped() {
+                break;
             }
-        })?;
+            let stamp = chrono::Utc::now().to_rfc3339();
+            if let Err(e) = std::fs::write(&probe, stamp) {
+                debug!("Could not write watcher probe {}: {e}", probe.display());
+            }
+            // Shutdown may have removed the probe while it was being written
+            if health.pinger_stopped() {
+                let _ = std::fs::remove_file(&probe);
+                break;
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to spawn watcher ping thread: {e}");
+    }
+}
 
-    watcher.watch(&watch_dir, RecursiveMode::Recursive)?;
+/// Stop the ping thread and remove its probe file; called on app exit.
+pub fn stop_watcher_pinger(health: &BackgroundHeartbeats, probe_dir: &Path) {
+    health.stop_pinger();
+    let _ = std::fs::remove_file(probe_dir.join(PROBE_FILE));
+}
+
+/// Where `start_index_watcher` may write its probe file.
+#[derive(Debug, Clone, Default)]
+pub struct WatchProbes {
+    /// Watched directory of the one-off `Auto` probe; `~/.claude/projects` in the app
+    pub startup_dir: Option<PathBuf>,
+    /// Dedicated directory the ping thread rewrites the probe in
+    pub heartbeat_dir: Option<PathBuf>,
+}
+
+/// Start the filesystem watcher for incremental indexing.
+///
+/// Watches each of `watch_dirs` recursively for JSONL file changes with
+/// 2-second debounce. The first is the primary directory and must be
+/// watchable; the rest are best-effort. Probe files go only where `probes`
+/// says, never into an extra session directory.
+/// `options.watcher` picks the backend: the native watcher, a `PollWatcher`
+/// (every `options.poll_interval()`), or `Auto`, which starts the native one
+/// and falls back to polling if it fails to start or misses a probe file
+/// within `PROBE_WINDOW` (network mounts, some containers and WSL setups).
+///
+/// Each reindexed or archived session is emitted as `session:updated`, or
+/// coalesced into `session:batch_updated` when `options.batch_updates` is set.
+///
+/// Returns the watcher handle (must be kept alive), the backend in use, and a
+/// merge thread join handle. The merge thread commits every 5 minutes using the
+/// same `Arc<Mutex<IndexWriter>>` to trigger segment compaction via the
+/// configured merge policy.
+///
+/// Both the watcher and merge thread beat `health` (see `search::health`).
+/// With `probes.heartbeat_dir`, a ping thread rewrites a probe file there (and
+/// the watcher watches it too) so the watcher beats even when idle.
+#[allow(clippy::too_many_arguments)]
+pub fn start_index_watcher(
+    watch_dirs: Vec<PathBuf>,
+    writer: Arc<Mutex<IndexWriter>>,
+    reader: IndexReader,
+    schema: IndexSchema,
+    paused: Arc<AtomicBool>,
+    health: Arc<BackgroundHeartbeats>,
+    options: IndexOptions,
+    probes: WatchProbes,
+    app_handle: Option<tauri::AppHandle>,
+) -> Result<(IndexWatcher, WatcherMode, std::thread::JoinHandle<()>), notify::Error> {
+    let ctx = WatchContext {
+        writer: writer.clone(),
+        reader,
+        schema,
+        paused: paused.clone(),
+        options,
+        updates: SessionUpdates::new(
+            app_handle.map(|h| Arc::new(h) as Arc<dyn UpdateSink>),
+            options.batch_updates.then_some(UPDATE_BATCH_WINDOW),
+        ),
+        debounce_map: Arc::new(Mutex::new(HashMap::new())),
+        probe_seen: Arc::new(AtomicBool::new(false)),
+        health: health.clone(),
+    };
+    let interval = options.poll_interval();
+
+    let (mut watcher, mode) = match options.watcher {
+        WatcherChoice::Native => (start_native_watcher(&ctx, &watch_dirs)?, WatcherMode::Native),
+        WatcherChoice::Poll => (
+            start_poll_watcher(&ctx, &watch_dirs, interval)?,
+            WatcherMode::Poll,
+        ),
+        WatcherChoice::Auto => match start_native_watcher(&ctx, &watch_dirs) {
+            Ok(w) if native_watcher_sees_probe(&ctx, probes.startup_dir.as_deref()) => {
+                (w, WatcherMode::Native)
+            }
+            Ok(_) => {
+                warn!("Native watcher missed the probe within {PROBE_WINDOW:?}; polling instead");
+                (start_poll_watcher(&ctx, &watch_dirs, interval)?, WatcherMode::Poll)
+            }
+            Err(e) => {
+                warn!("Native watcher failed to start ({e}); polling instead");
+                (start_poll_watcher(&ctx, &watch_dirs, interval)?, WatcherMode::Poll)
+            }
+        },
+    };
+
+    health.watcher.beat();
+    if let Some(dir) = probes.heartbeat_dir {
+        let watched = std::fs::create_dir_all(&dir)
+            .map_err(notify::Error::io)
+            .and_then(|_| watcher.watch(&dir, RecursiveMode::NonRecursive));
+        match watched {
+            Ok(()) => spawn_watcher_pinger(dir, health.clone(), HEARTBEAT_INTERVAL),
+            Err(e) => warn!("Not pinging the watcher via {}: {e}", dir.display()),
+        }
+    }
 
-    // 5-minute merge thread using the SAME Arc<Mutex<IndexWriter>>
+    // 5-minute merge thread using the SAME Arc<Mutex<IndexWriter>>, beating in between
     let merge_writer = writer;
     let merge_paused = paused;
     let merge_handle = std::thread::Builder::new()
         .name("tantivy-merge".into())
-        .spawn(move || loop {
-            std::thread::sleep(Duration::from_secs(300));
-            if merge_paused.load(Ordering::Relaxed) {
-                continue;
-            }
-            if let Ok(mut w) = merge_writer.lock() {
-                if let Err(e) = w.commit() {
-                    warn!("Merge commit failed: {e}");
+        .spawn(move || {
+            let mut last_merge = Instant::now();
+            loop {
+                health.merge.beat();
+                std::thread::sleep(HEARTBEAT_INTERVAL);
+                if last_merge.elapsed() < MERGE_INTERVAL {
+                    continue;
+                }
+                last_merge = Instant::now();
+                if merge_paused.load(Ordering::Relaxed) {
+                    continue;
+                }
+                match lock_recover(&merge_writer, "index writer").commit() {
+                    Ok(_) => health.record_merge_commit(),
+                    Err(e) => warn!("Merge commit failed: {e}"),
                 }
             }
         })
         .expect("Failed to spawn merge thread");
 
-    Ok((watcher, merge_handle))
+    Ok((watcher, mode, merge_handle))
 }
 
 #[cfg(test)]
@@ -330,6 +558,7 @@ mod tests {
         let schema = IndexSchema::new();
         let tmp = TempDir::new().unwrap();
         let index = Index::create_in_dir(tmp.path(), schema.schema.clone()).unwrap();
+        crate::search::tokenizer::register_tokenizers(&index);
         (tmp, index, schema)
     }
 
@@ -379,6 +608,68 @@ mod tests {
         writer.add_document(doc).unwrap();
     }
 
+    #[derive(Default)]
+    struct RecordingSink {
+        single: Mutex<Vec<String>>,
+        batches: Mutex<Vec<Vec<String>>>,
+    }
+
+    impl UpdateSink for RecordingSink {
+        fn updated(&self, item: &SessionListItem) {
+            self.single.lock().unwrap().push(item.session_id.clone());
+        }
+
+        fn batch_updated(&self, items: &[SessionListItem]) {
+            let ids = items.iter().map(|i| i.session_id.clone()).collect();
+            self.batches.lock().unwrap().push(ids);
+        }
+
+        fn snapshot(&self, _items: &[SessionListItem]) {
+            panic!("the watcher never sends snapshots");
+        }
+    }
+
+    fn list_item(schema: &IndexSchema, session_id: &str) -> SessionListItem {
+        let mut doc = TantivyDocument::new();
+        doc.add_text(schema.session_id, session_id);
+        session_doc_to_list_item(&doc, schema)
+    }
+
+    #[test]
+    fn test_session_updates_coalesce_rapid_updates_into_one_batch() {
+        let schema = IndexSchema::new();
+        let sink = Arc::new(RecordingSink::default());
+        let updates = SessionUpdates::new(
+            Some(sink.clone() as Arc<dyn UpdateSink>),
+            Some(Duration::from_millis(100)),
+        );
+
+        for i in 0..20 {
+            let sid = if i % 2 == 0 { "busy" } else { "other" };
+            updates.push(list_item(&schema, sid));
+        }
+        updates.push(list_item(&schema, "late"));
+        std::thread::sleep(Duration::from_millis(400));
+
+        assert!(sink.single.lock().unwrap().is_empty());
+        let batches = sink.batches.lock().unwrap();
+        assert_eq!(batches.len(), 1, "got {batches:?}");
+        assert_eq!(batches[0], ["busy", "other", "late"]);
+    }
+
+    #[test]
+    fn test_session_updates_without_window_emit_each_update() {
+        let schema = IndexSchema::new();
+        let sink = Arc::new(RecordingSink::default());
+        let updates = SessionUpdates::new(Some(sink.clone() as Arc<dyn UpdateSink>), None);
+
+        updates.push(list_item(&schema, "a"));
+        updates.push(list_item(&schema, "a"));
+
+        assert_eq!(*sink.single.lock().unwrap(), ["a", "a"]);
+        assert!(sink.batches.lock().unwrap().is_empty());
+    }
+
     #[test]
     fn test_archive_session_sets_flags() {
         let (_tmp, index, schema) = create_test_index();
@@ -501,7 +792,7 @@ mod tests {
         let jsonl_path = jsonl_dir.path().join("test-uuid-1.jsonl");
         std::fs::write(&jsonl_path, jsonl_content).unwrap();
 
-        let result = reindex_session(&jsonl_path, &writer_arc, &schema);
+        let result = reindex_session(&jsonl_path, &writer_arc, &schema, &IndexOptions::default());
         assert!(result.is_some());
 
         let item = result.unwrap();
@@ -510,4 +801,137 @@ mod tests {
         assert!(!item.archived);
         assert!(item.file_exists);
     }
+
+    #[test]
+    fn test_watcher_pinger_stops_and_removes_probe() {
+        let probe_dir = TempDir::new().unwrap();
+        let probe = probe_dir.path().join(PROBE_FILE);
+        let health = Arc::new(BackgroundHeartbeats::default());
+        spawn_watcher_pinger(
+            probe_dir.path().to_path_buf(),
+            health.clone(),
+            Duration::from_millis(20),
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !probe.exists() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(probe.exists());
+
+        stop_watcher_pinger(&health, probe_dir.path());
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!probe.exists(), "no rewrite after shutdown");
+    }
+
+    #[test]
+    fn test_poll_watcher_indexes_new_file() {
+        let (_tmp, index, schema) = create_test_index();
+        let writer = index.writer(50_000_000).unwrap();
+        let reader: IndexReader = index
+            .reader_builder()
+            .reload_policy(tantivy::ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let watch_dir = TempDir::new().unwrap();
+        let options = IndexOptions {
+            watcher: WatcherChoice::Poll,
+            poll_interval_secs: Some(1),
+            ..Default::default()
+        };
+
+        let (_watcher, mode, _merge) = start_index_watcher(
+            vec![watch_dir.path().to_path_buf()],
+            Arc::new(Mutex::new(writer)),
+            reader.clone(),
+            schema.clone(),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(BackgroundHeartbeats::default()),
+            options,
+            WatchProbes::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(mode, WatcherMode::Poll);
+
+        let project = watch_dir.path().join("-home-user-proj");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(
+            project.join("polled-1.jsonl"),
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"found by polling"},"timestamp":"2026-02-18T10:00:00Z","sessionId":"polled-1","cwd":"/home/user/proj"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut found = None;
+        while found.is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(200));
+            reader.reload().unwrap();
+            found = find_session_doc("polled-1", &reader, &schema);
+        }
+        let doc = found.expect("poll watcher should index the new session");
+        assert_eq!(doc.get_str(schema.project_raw), Some("/home/user/proj"));
+    }
+
+    #[test]
+    fn test_poll_watcher_indexes_extra_dir_without_probing_it() {
+        let (_tmp, index, schema) = create_test_index();
+        let writer = index.writer(50_000_000).unwrap();
+        let reader: IndexReader = index
+            .reader_builder()
+            .reload_policy(tantivy::ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let (primary, extra, heartbeat) =
+            (TempDir::new().unwrap(), TempDir::new().unwrap(), TempDir::new().unwrap());
+        let options = IndexOptions {
+            watcher: WatcherChoice::Poll,
+            poll_interval_secs: Some(1),
+            ..Default::default()
+        };
+        let probes = WatchProbes {
+            startup_dir: Some(primary.path().to_path_buf()),
+            heartbeat_dir: Some(heartbeat.path().join("watch-probe")),
+        };
+
+        let (_watcher, mode, _merge) = start_index_watcher(
+            vec![primary.path().to_path_buf(), extra.path().to_path_buf()],
+            Arc::new(Mutex::new(writer)),
+            reader.clone(),
+            schema.clone(),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(BackgroundHeartbeats::default()),
+            options,
+            probes,
+            None,
+        )
+        .unwrap();
+        assert_eq!(mode, WatcherMode::Poll);
+        assert!(heartbeat.path().join("watch-probe").is_dir());
+
+        let project = extra.path().join("-mnt-shared-proj");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(
+            project.join("extra-1.jsonl"),
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"shared mount"},"timestamp":"2026-02-18T10:00:00Z","sessionId":"extra-1","cwd":"/mnt/shared/proj"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut found = None;
+        while found.is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(200));
+            reader.reload().unwrap();
+            found = find_session_doc("extra-1", &reader, &schema);
+        }
+        assert!(found.is_some(), "poll watcher should index the extra directory");
+        let names: Vec<_> = std::fs::read_dir(extra.path()).unwrap().flatten().collect();
+        assert_eq!(names.len(), 1, "only the project dir; no probe file in an extra dir");
+    }
 }
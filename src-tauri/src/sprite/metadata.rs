@@ -0,0 +1,139 @@
+use crate::atomic_file::write_atomic;
+use crate::error::AppError;
+use crate::sprites_api::SpriteInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// User-local nickname, color and notes for a sprite; never sent to the API.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpriteLocalMetadata {
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Any CSS color the UI accepts, e.g. `#e5484d`
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+impl SpriteLocalMetadata {
+    fn is_empty(&self) -> bool {
+        self.label.is_none() && self.color.is_none() && self.notes.is_none()
+    }
+}
+
+/// Serializes read-modify-write cycles of the metadata file.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// `~/.local/share/swarm-ui/sprite-metadata.json`, keyed by sprite name.
+pub fn metadata_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("swarm-ui").join("sprite-metadata.json"))
+}
+
+/// All stored metadata; a missing file means none. A corrupt file is an
+/// error, so `set` never overwrites the other sprites' entries with nothing.
+pub fn load_all(path: &Path) -> Result<BTreeMap<String, SpriteLocalMetadata>, AppError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::from_str(&content).map_err(|e| {
+        AppError::Internal(format!("Corrupt sprite metadata in {}: {e}", path.display()))
+    })
+}
+
+pub fn get(path: &Path, name: &str) -> Result<Option<SpriteLocalMetadata>, AppError> {
+    Ok(load_all(path)?.remove(name))
+}
+
+/// Replace a sprite's metadata. Blank fields are dropped, and a sprite left
+/// with no fields is removed from the file.
+pub fn set(
+    path: &Path,
+    name: &str,
+    metadata: SpriteLocalMetadata,
+) -> Result<SpriteLocalMetadata, AppError> {
+    let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let metadata = SpriteLocalMetadata {
+        label: clean(metadata.label),
+        color: clean(metadata.color),
+        notes: clean(metadata.notes),
+    };
+
+    let _guard = crate::state::lock_recover(&WRITE_LOCK, "sprite metadata");
+    let mut all = load_all(path)?;
+    if metadata.is_empty() {
+        all.remove(name);
+    } else {
+        all.insert(name.to_string(), metadata.clone());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(path, serde_json::to_string_pretty(&all)?)?;
+    Ok(metadata)
+}
+
+/// Attach stored metadata to each listed sprite that has some.
+pub fn merge_into(sprites: &mut [SpriteInfo], all: &BTreeMap<String, SpriteLocalMetadata>) {
+    for sprite in sprites {
+        sprite.local = all.get(&sprite.name).cloned();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sprite(name: &str) -> SpriteInfo {
+        serde_json::from_value(serde_json::json!({ "name": name, "status": "warm" })).unwrap()
+    }
+
+    #[test]
+    fn test_metadata_round_trips_and_merges_into_list() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("swarm-ui").join("sprite-metadata.json");
+        let meta = SpriteLocalMetadata {
+            label: Some(" build box ".into()),
+            color: Some("#e5484d".into()),
+            notes: Some(String::new()),
+        };
+
+        let saved = set(&path, "ci-runner", meta).unwrap();
+        assert_eq!(saved.label.as_deref(), Some("build box"));
+        assert_eq!(saved.notes, None, "blank fields are dropped");
+        assert_eq!(get(&path, "ci-runner").unwrap(), Some(saved.clone()));
+        assert_eq!(get(&path, "other").unwrap(), None);
+
+        let mut sprites = vec![sprite("ci-runner"), sprite("other")];
+        merge_into(&mut sprites, &load_all(&path).unwrap());
+        assert_eq!(sprites[0].local, Some(saved));
+        assert_eq!(sprites[1].local, None);
+        let json = serde_json::to_value(&sprites).unwrap();
+        assert_eq!(json[0]["local"]["color"], "#e5484d");
+        assert!(json[1].get("local").is_none());
+
+        set(&path, "ci-runner", SpriteLocalMetadata::default()).unwrap();
+        assert!(load_all(&path).unwrap().is_empty(), "clearing every field removes the entry");
+    }
+
+    #[test]
+    fn test_set_refuses_to_overwrite_corrupt_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("sprite-metadata.json");
+        let half_written = r#"{"ci-runner":{"label":"build box"},"gpu":{"lab"#;
+        fs::write(&path, half_written).unwrap();
+
+        let meta = SpriteLocalMetadata {
+            label: Some("new".into()),
+            ..Default::default()
+        };
+        assert!(set(&path, "other", meta).is_err());
+        assert!(get(&path, "ci-runner").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), half_written, "file left as it was");
+    }
+}
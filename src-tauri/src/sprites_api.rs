@@ -2,12 +2,23 @@ use crate::error::AppError;
 use futures_util::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::ipc::Channel;
+use tokio::sync::Semaphore;
+use urlencoding::encode;
 
 const NDJSON_MAX_LINE: usize = 64 * 1024; // 64 KB per event line
 const STREAM_TIMEOUT: Duration = Duration::from_secs(300); // checkpoints can take minutes
 const LIST_TIMEOUT: Duration = Duration::from_secs(8); // fast REST calls — fail fast
+/// Default cap on in-flight HTTP requests per client (i.e. per API host).
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 6;
+/// How long `get_sprite_metrics` reuses a sample, so dashboard polling stays cheap.
+const METRICS_CACHE_TTL: Duration = Duration::from_secs(5);
+/// One exec gathering all metrics; sections are split on `---` lines.
+const METRICS_CMD: &str =
+    "top -bn1 2>/dev/null | head -n 5; echo '---'; free -m 2>/dev/null; echo '---'; df -hP / 2>/dev/null";
 
 // ── Core sprite types ──────────────────────────────────────────────────────
 
@@ -20,6 +31,9 @@ pub struct SpriteInfo {
     pub id: Option<String>,
     #[serde(default)]
     pub region: Option<String>,
+    /// User-local label/color/notes, merged in by `sprite_list`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local: Option<crate::sprite::metadata::SpriteLocalMetadata>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,11 +59,52 @@ pub struct SpriteDetail {
     pub last_active_at: Option<String>,
 }
 
+impl From<SpriteDetail> for SpriteInfo {
+    fn from(detail: SpriteDetail) -> Self {
+        Self {
+            name: detail.name,
+            status: detail.status,
+            id: detail.id,
+            region: None,
+            local: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UrlSettings {
     pub auth: String, // "sprite" | "public"
 }
 
+/// A sprite's shareable URL and who may open it, returned by `sprite_get_url`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpriteUrl {
+    pub url: Option<String>,
+    /// `url_settings.auth`; sprites without settings use the `sprite` default
+    pub auth: String,
+    /// Reachable without a sprite token
+    pub public: bool,
+}
+
+impl SpriteDetail {
+    /// The URL and auth mode, or an error if no URL was provisioned.
+    pub fn url_info(&self) -> Result<SpriteUrl, AppError> {
+        let url = self.url.as_deref().filter(|u| !u.is_empty()).ok_or_else(|| {
+            AppError::NotFound(format!("Sprite '{}' has no URL provisioned", self.name))
+        })?;
+        let auth = self
+            .url_settings
+            .as_ref()
+            .map_or("sprite", |s| s.auth.as_str())
+            .to_string();
+        Ok(SpriteUrl {
+            url: Some(url.to_string()),
+            public: auth == "public",
+            auth,
+        })
+    }
+}
+
 // ── Checkpoint types ───────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +118,63 @@ pub struct Checkpoint {
     pub create_time: Option<String>,
     #[serde(default)]
     pub source_id: Option<String>,
+    /// Only some API versions report a size
+    #[serde(default, alias = "size")]
+    pub size_bytes: Option<u64>,
+}
+
+impl Checkpoint {
+    /// Creation time from either field spelling (`created_at` wins).
+    pub fn created(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.created_at
+            .iter()
+            .chain(self.create_time.iter())
+            .find_map(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|d| d.with_timezone(&chrono::Utc))
+    }
+}
+
+/// A checkpoint with a normalized timestamp and readable size, returned by
+/// `sprite_list_checkpoints_detailed`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointDetail {
+    #[serde(flatten)]
+    pub checkpoint: Checkpoint,
+    /// RFC 3339 UTC, from `created_at` or `create_time`
+    pub created: Option<String>,
+    /// e.g. `1.5 GB`; `None` when the API gave no size
+    pub size_human: Option<String>,
+}
+
+/// Newest first; checkpoints without a parseable time go last, in API order.
+pub fn detail_checkpoints(checkpoints: Vec<Checkpoint>) -> Vec<CheckpointDetail> {
+    let mut dated: Vec<_> = checkpoints.into_iter().map(|c| (c.created(), c)).collect();
+    // Descending on Option puts every Some before None
+    dated.sort_by(|(a, _), (b, _)| b.cmp(a));
+    dated
+        .into_iter()
+        .map(|(created, checkpoint)| CheckpointDetail {
+            created: created.map(|d| d.to_rfc3339()),
+            size_human: checkpoint.size_bytes.map(human_size),
+            checkpoint,
+        })
+        .collect()
+}
+
+/// Decimal units with one fractional digit above bytes: `512 B`, `1.5 GB`.
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
 }
 
 // ── Exec session types ─────────────────────────────────────────────────────
@@ -86,6 +198,71 @@ pub struct ExecSession {
     pub bytes_per_second: Option<f64>,
 }
 
+/// An exec session id as a string, whichever form the API used (`7` or `"7"`).
+pub fn exec_session_id(id: &serde_json::Value) -> String {
+    match id {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl ExecSession {
+    /// The session id as a string, whichever form the API used.
+    pub fn id_string(&self) -> String {
+        exec_session_id(&self.id)
+    }
+
+    /// Whether the fields `diff_exec_sessions` reports on differ. Activity
+    /// timestamps and throughput move on every poll and are ignored.
+    fn state_differs(&self, other: &ExecSession) -> bool {
+        self.command != other.command
+            || self.is_active != other.is_active
+            || self.tty != other.tty
+            || self.workdir != other.workdir
+    }
+}
+
+/// One change to a sprite's exec sessions, sent by `sprite_watch_exec_sessions`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExecSessionEvent {
+    Started { session: ExecSession },
+    Changed { session: ExecSession },
+    Ended { id: String },
+    /// A poll failed; watching continues with the last known list
+    Error { message: String },
+    /// Nothing changed for a while; sent so a closed channel is noticed
+    Heartbeat,
+}
+
+/// Events turning the `prev` session list into `next`, matching sessions by
+/// normalized id. Started/changed follow `next`'s order, ended `prev`'s.
+pub fn diff_exec_sessions(prev: &[ExecSession], next: &[ExecSession]) -> Vec<ExecSessionEvent> {
+    let before: HashMap<String, &ExecSession> =
+        prev.iter().map(|s| (s.id_string(), s)).collect();
+    let after: HashSet<String> = next.iter().map(ExecSession::id_string).collect();
+
+    let mut events = Vec::new();
+    for session in next {
+        match before.get(&session.id_string()) {
+            None => events.push(ExecSessionEvent::Started {
+                session: session.clone(),
+            }),
+            Some(old) if old.state_differs(session) => events.push(ExecSessionEvent::Changed {
+                session: session.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for session in prev {
+        let id = session.id_string();
+        if !after.contains(&id) {
+            events.push(ExecSessionEvent::Ended { id });
+        }
+    }
+    events
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecResult {
     #[serde(default)]
@@ -94,6 +271,125 @@ pub struct ExecResult {
     pub stderr: String,
     #[serde(default)]
     pub exit_code: Option<i32>,
+    /// Wall-clock time of the exec round-trip, measured client-side when the API omits it
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+impl ExecResult {
+    /// stdout followed by stderr, as one string.
+    ///
+    /// With `error_on_failure`, a non-zero exit code becomes an error carrying
+    /// the merged output. A missing exit code counts as success.
+    pub fn merged_output(self, error_on_failure: bool) -> Result<String, AppError> {
+        let mut output = self.stdout;
+        if !self.stderr.is_empty() {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            output.push_str(&self.stderr);
+        }
+        match self.exit_code {
+            Some(code) if code != 0 && error_on_failure => Err(AppError::Internal(format!(
+                "Command exited with code {code}: {output}"
+            ))),
+            _ => Ok(output),
+        }
+    }
+}
+
+/// Resource usage sampled on a sprite. A field is `None` when its tool's
+/// output was missing or unparseable.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpriteMetrics {
+    pub cpu_percent: Option<f32>,
+    pub mem_used_mb: Option<u64>,
+    pub mem_total_mb: Option<u64>,
+    pub disk_used_gb: Option<f64>,
+    pub disk_total_gb: Option<f64>,
+}
+
+/// Parse CPU busy % (100 - idle) from `top -bn1` (procps or BusyBox).
+pub fn parse_top_cpu(stdout: &str) -> Option<f32> {
+    let line = stdout.lines().find(|l| {
+        let l = l.trim_start();
+        l.starts_with("%Cpu") || l.starts_with("Cpu(s)") || l.starts_with("CPU:")
+    })?;
+    // procps: "%Cpu(s):  2.3 us,  1.0 sy, ... 96.5 id, ..."; BusyBox: "CPU: 2% usr ... 96% idle"
+    let tokens: Vec<&str> = line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let idle_at = tokens.iter().position(|t| *t == "id" || *t == "idle")?;
+    let idle: f32 = tokens.get(idle_at.checked_sub(1)?)?.trim_end_matches('%').parse().ok()?;
+    Some((100.0 - idle).clamp(0.0, 100.0))
+}
+
+/// Parse `(used_mb, total_mb)` from the `Mem:` row of `free -m`.
+pub fn parse_free_mem(stdout: &str) -> Option<(u64, u64)> {
+    let line = stdout.lines().find(|l| l.trim_start().starts_with("Mem:"))?;
+    let mut cols = line.split_whitespace().skip(1);
+    let total = cols.next()?.parse().ok()?;
+    let used = cols.next()?.parse().ok()?;
+    Some((used, total))
+}
+
+/// Parse `(used_gb, total_gb)` from `df -h` for a single filesystem.
+///
+/// Tolerates the non-POSIX layout where a long device name pushes the
+/// numbers onto the next line.
+pub fn parse_df_disk(stdout: &str) -> Option<(f64, f64)> {
+    let tokens: Vec<&str> = stdout
+        .lines()
+        .filter(|l| !l.starts_with("Filesystem"))
+        .flat_map(|l| l.split_whitespace())
+        .collect();
+    // Filesystem Size Used Avail Use% Mounted
+    let total = parse_human_size_gb(tokens.get(1)?)?;
+    let used = parse_human_size_gb(tokens.get(2)?)?;
+    Some((used, total))
+}
+
+/// Convert a `df -h` size like `20G`, `512M` or `1.5T` to GiB.
+fn parse_human_size_gb(size: &str) -> Option<f64> {
+    let (num, unit) = match size.char_indices().find(|(_, c)| c.is_ascii_alphabetic()) {
+        Some((i, _)) => size.split_at(i),
+        None => (size, "G"),
+    };
+    let value: f64 = num.parse().ok()?;
+    let factor = match unit.chars().next()?.to_ascii_uppercase() {
+        'K' => 1.0 / (1024.0 * 1024.0),
+        'M' => 1.0 / 1024.0,
+        'G' => 1.0,
+        'T' => 1024.0,
+        'P' => 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(value * factor)
+}
+
+/// Parse the combined `METRICS_CMD` output. Sections are split on `---`
+/// lines, so a command that printed nothing still leaves its section empty.
+pub fn parse_sprite_metrics(stdout: &str) -> SpriteMetrics {
+    let mut sections = vec![String::new()];
+    for line in stdout.lines() {
+        if line.trim() == "---" {
+            sections.push(String::new());
+        } else if let Some(section) = sections.last_mut() {
+            section.push_str(line);
+            section.push('\n');
+        }
+    }
+    let section = |i: usize| sections.get(i).map_or("", String::as_str);
+    let mem = parse_free_mem(section(1));
+    let disk = parse_df_disk(section(2));
+    SpriteMetrics {
+        cpu_percent: parse_top_cpu(section(0)),
+        mem_used_mb: mem.map(|m| m.0),
+        mem_total_mb: mem.map(|m| m.1),
+        disk_used_gb: disk.map(|d| d.0),
+        disk_total_gb: disk.map(|d| d.1),
+    }
 }
 
 // ── Service types ──────────────────────────────────────────────────────────
@@ -207,6 +503,28 @@ pub enum ExecKillEvent {
     },
 }
 
+/// Output of a running exec session, streamed by `sprite_read_exec_session`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExecOutputEvent {
+    Stdout {
+        data: Option<String>,
+        timestamp: Option<i64>,
+    },
+    Stderr {
+        data: Option<String>,
+        timestamp: Option<i64>,
+    },
+    Exit {
+        exit_code: Option<i32>,
+        timestamp: Option<i64>,
+    },
+    Error {
+        data: Option<String>,
+        timestamp: Option<i64>,
+    },
+}
+
 // ── Terminal predicates ────────────────────────────────────────────────────
 
 impl StreamEvent {
@@ -230,6 +548,12 @@ impl ServiceStreamEvent {
     }
 }
 
+impl ExecOutputEvent {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, ExecOutputEvent::Exit { .. } | ExecOutputEvent::Error { .. })
+    }
+}
+
 impl ExecKillEvent {
     pub fn is_terminal(&self) -> bool {
         matches!(
@@ -252,18 +576,43 @@ pub async fn pipe_ndjson_stream<T>(
 where
     T: for<'de> serde::Deserialize<'de> + serde::Serialize + Clone + Send + 'static,
 {
-    let mut stream = response.bytes_stream();
+    pump_ndjson(
+        response.bytes_stream(),
+        |event| {
+            on_event
+                .send(event)
+                .map_err(|e| AppError::Internal(format!("channel send error: {e}")))
+        },
+        is_terminal,
+    )
+    .await
+}
+
+/// Drive an NDJSON byte stream, handing each parsed event to `on_event`.
+/// Returns as soon as a terminal event has been handed off, or at EOF.
+async fn pump_ndjson<T, S, B, E>(
+    stream: S,
+    mut on_event: impl FnMut(T) -> Result<(), AppError>,
+    is_terminal: impl Fn(&T) -> bool,
+) -> Result<(), AppError>
+where
+    T: for<'de> serde::Deserialize<'de>,
+    S: futures_util::Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: std::fmt::Display,
+{
+    let mut stream = std::pin::pin!(stream);
     let mut buffer = String::new();
     let mut received_terminal = false;
 
     loop {
         match stream.next().await {
             Some(Ok(chunk)) => {
-                buffer.push_str(&String::from_utf8_lossy(&chunk));
+                buffer.push_str(&String::from_utf8_lossy(chunk.as_ref()));
             }
             Some(Err(e)) => {
                 // Flush buffer before deciding whether this error matters
-                let flushed = flush_buffer::<T>(&mut buffer, on_event, &is_terminal)?;
+                let flushed = flush_buffer::<T>(&mut buffer, &mut on_event, &is_terminal)?;
                 if flushed || received_terminal {
                     // Connection closed after terminal event — this is normal
                     return Ok(());
@@ -272,7 +621,7 @@ where
             }
             None => {
                 // Clean EOF — flush remaining buffer
-                flush_buffer::<T>(&mut buffer, on_event, &is_terminal)?;
+                flush_buffer::<T>(&mut buffer, &mut on_event, &is_terminal)?;
                 return Ok(());
             }
         }
@@ -300,9 +649,7 @@ where
             })?;
 
             let terminal = is_terminal(&event);
-            on_event
-                .send(event)
-                .map_err(|e| AppError::Internal(format!("channel send error: {e}")))?;
+            on_event(event)?;
 
             if terminal {
                 received_terminal = true;
@@ -323,11 +670,11 @@ where
 /// Returns true if a terminal event was found and sent.
 fn flush_buffer<T>(
     buffer: &mut String,
-    on_event: &Channel<T>,
+    on_event: &mut impl FnMut(T) -> Result<(), AppError>,
     is_terminal: &impl Fn(&T) -> bool,
 ) -> Result<bool, AppError>
 where
-    T: for<'de> serde::Deserialize<'de> + serde::Serialize + Clone + Send + 'static,
+    T: for<'de> serde::Deserialize<'de>,
 {
     let remaining = buffer.trim().to_string();
     buffer.clear();
@@ -338,21 +685,162 @@ where
     match serde_json::from_str::<T>(&remaining) {
         Ok(event) => {
             let terminal = is_terminal(&event);
-            on_event
-                .send(event)
-                .map_err(|e| AppError::Internal(format!("channel send error: {e}")))?;
+            on_event(event)?;
             Ok(terminal)
         }
         Err(_) => Ok(false), // Partial line — silently discard
     }
 }
 
+// ── Multi-service log merging ──────────────────────────────────────────────
+
+/// A service stream event tagged with the service it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaggedServiceEvent {
+    pub service: String,
+    #[serde(flatten)]
+    pub event: ServiceStreamEvent,
+}
+
+/// Follow several services' NDJSON log streams concurrently, multiplexing
+/// their events into one channel tagged by service name.
+///
+/// A terminal event only ends that service's stream; the merged stream
+/// completes once every service has finished. Per-service read/parse failures
+/// are reported as a tagged `error` event instead of aborting the others.
+pub async fn merge_service_log_streams<S, B, E>(
+    streams: Vec<(String, S)>,
+    on_event: &Channel<TaggedServiceEvent>,
+) -> Result<(), AppError>
+where
+    S: futures_util::Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: std::fmt::Display,
+{
+    let send = |service: &str, event: ServiceStreamEvent| {
+        on_event
+            .send(TaggedServiceEvent {
+                service: service.to_string(),
+                event,
+            })
+            .map_err(|e| AppError::Internal(format!("channel send error: {e}")))
+    };
+
+    let followers = streams.into_iter().map(|(service, stream)| async move {
+        let result = pump_ndjson(
+            stream,
+            |event| send(&service, event),
+            ServiceStreamEvent::is_terminal,
+        )
+        .await;
+        if let Err(e) = result {
+            let _ = send(
+                &service,
+                ServiceStreamEvent::Error {
+                    data: Some(e.to_string()),
+                    timestamp: None,
+                },
+            );
+        }
+    });
+    futures_util::future::join_all(followers).await;
+    Ok(())
+}
+
 // ── HTTP Client ────────────────────────────────────────────────────────────
 
+/// A canonical Sprites API base URL and what was changed to get it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NormalizedBaseUrl {
+    /// `scheme://host[:port][/path]`, no trailing slash or `/v1`
+    pub url: String,
+    /// Human-readable fixes applied to the input, in order
+    pub fixups: Vec<String>,
+}
+
+/// Validate a pasted API base URL and reduce it to the form `SpritesClient`
+/// expects: the client appends `/v1` itself and derives `ws(s)://` for exec,
+/// so both are rejected or stripped here.
+pub fn normalize_sprites_base_url(input: &str) -> Result<NormalizedBaseUrl, AppError> {
+    let invalid = |msg: String| AppError::Internal(format!("Invalid Sprites API URL: {msg}"));
+    let mut fixups = Vec::new();
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(invalid("it is empty".into()));
+    }
+    if trimmed != input {
+        fixups.push("Removed surrounding whitespace".to_string());
+    }
+
+    let parsed = url::Url::parse(trimmed).map_err(|e| match e {
+        url::ParseError::RelativeUrlWithoutBase => {
+            invalid(format!("'{trimmed}' has no scheme; start it with https://"))
+        }
+        e => invalid(format!("'{trimmed}': {e}")),
+    })?;
+    match parsed.scheme() {
+        "http" | "https" => {}
+        "ws" | "wss" => {
+            return Err(invalid(format!(
+                "'{trimmed}' is a WebSocket URL; use http(s):// instead, the exec \
+                 WebSocket URL is derived from it"
+            )))
+        }
+        other => return Err(invalid(format!("unsupported scheme '{other}://'"))),
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(invalid(format!("'{trimmed}' has no host")));
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        fixups.push("Removed query string and fragment".to_string());
+    }
+
+    let mut path = parsed.path();
+    if path.len() > 1 && path.ends_with('/') {
+        fixups.push("Removed trailing slash".to_string());
+    }
+    path = path.trim_end_matches('/');
+    if let Some(prefix) = path.strip_suffix("/v1") {
+        fixups.push("Removed /v1 (added to every request)".to_string());
+        path = prefix.trim_end_matches('/');
+    }
+
+    Ok(NormalizedBaseUrl {
+        url: format!("{}{path}", parsed.origin().ascii_serialization()),
+        fixups,
+    })
+}
+
+/// Clones share the HTTP connection pool and the concurrency limiter.
+#[derive(Clone)]
 pub struct SpritesClient {
     base_url: String,
     token: String,
     http: Client,
+    limiter: Arc<Semaphore>,
+    /// Recent `get_sprite_metrics` samples per sprite
+    metrics_cache: Arc<Mutex<HashMap<String, (Instant, SpriteMetrics)>>>,
+}
+
+/// API path for a sprite resource: `/sprites/{name}{suffix}`.
+///
+/// The name is percent-encoded as a single path segment, so an org prefix
+/// (`acme/dev`) or a space can't split or break the URL. `suffix` is appended
+/// as-is and must encode its own dynamic segments.
+fn sprite_path(name: &str, suffix: &str) -> String {
+    format!("/sprites/{}{suffix}", encode(name))
+}
+
+/// API path of an exec session's buffered output.
+fn exec_output_path(name: &str, session_id: &serde_json::Value) -> String {
+    let id = exec_session_id(session_id);
+    sprite_path(name, &format!("/exec/{}/output", encode(&id)))
+}
+
+/// Whether a failed create means the sprite is already there: a 409, or an
+/// error body saying so (some API versions answer 400/422).
+fn is_already_exists(status: reqwest::StatusCode, body: &str) -> bool {
+    status == reqwest::StatusCode::CONFLICT || body.to_ascii_lowercase().contains("already exists")
 }
 
 /// Convert a reqwest error into a user-friendly message with sprite context.
@@ -374,6 +862,25 @@ fn reqwest_err(e: &reqwest::Error, context: &str) -> AppError {
     }
 }
 
+/// Command that resumes a Claude session inside a sprite terminal.
+///
+/// Session ids are UUIDs; anything else is rejected so the id can't smuggle
+/// extra shell arguments into the command.
+pub fn claude_resume_command(session_id: &str) -> Result<String, AppError> {
+    let valid = !session_id.is_empty()
+        && session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(AppError::Internal(format!(
+            "Invalid session id: {session_id:?}"
+        )));
+    }
+    Ok(format!(
+        "claude --resume {session_id} --dangerously-skip-permissions"
+    ))
+}
+
 impl SpritesClient {
     pub fn new(base_url: String, token: String) -> Self {
         let http = Client::builder()
@@ -381,6 +888,9 @@ impl SpritesClient {
             .tcp_keepalive(Duration::from_secs(15))
             .pool_max_idle_per_host(4)
             .connection_verbose(cfg!(debug_assertions))
+            // Sends `Accept-Encoding: gzip` and decodes before the body stream,
+            // so NDJSON logs and exec output transfer compressed
+            .gzip(true)
             .build()
             .unwrap_or_else(|_| Client::new());
 
@@ -388,9 +898,37 @@ impl SpritesClient {
             base_url: base_url.trim_end_matches('/').to_string(),
             token,
             http,
+            limiter: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+            metrics_cache: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Replace the concurrency limit (permits shared by all clones made after).
+    pub fn with_max_concurrent_requests(mut self, permits: usize) -> Self {
+        self.limiter = Arc::new(Semaphore::new(permits.max(1)));
+        self
+    }
+
+    /// Drop cached responses; clones share the caches, so this clears theirs too.
+    pub fn clear_caches(&self) {
+        self.metrics_cache.lock().unwrap().clear();
+    }
+
+    /// Send a request once a limiter permit is free.
+    ///
+    /// Excess requests queue here instead of hitting the API's rate limit. The
+    /// permit is held until response headers arrive, so long NDJSON streams
+    /// don't block other calls while their body is being read.
+    async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, reqwest::Error> {
+        let _permit = self
+            .limiter
+            .acquire()
+            .await
+            .expect("sprites limiter semaphore is never closed");
+        req.send().await
+    }
+
+    /// `path` must already be encoded; build sprite paths with `sprite_path`.
     fn api_url(&self, path: &str) -> String {
         format!("{}/v1{}", self.base_url, path)
     }
@@ -398,13 +936,11 @@ impl SpritesClient {
     // ── Sprites CRUD ─────────────────────────────────────────────────────
 
     pub async fn list_sprites(&self) -> Result<Vec<SpriteInfo>, AppError> {
-        let resp = self
+        let req = self
             .http
             .get(self.api_url("/sprites"))
-            .bearer_auth(&self.token)
-            .send()
-            .await
-            .map_err(|e| reqwest_err(&e, "list sprites"))?;
+            .bearer_auth(&self.token);
+        let resp = self.send(req).await.map_err(|e| reqwest_err(&e, "list sprites"))?;
 
         if !resp.status().is_success() {
             let status = resp.status();
@@ -447,12 +983,13 @@ impl SpritesClient {
     }
 
     pub async fn get_sprite(&self, name: &str) -> Result<SpriteDetail, AppError> {
-        let resp = self
+        let req = self
             .http
-            .get(self.api_url(&format!("/sprites/{name}")))
+            .get(self.api_url(&sprite_path(name, "")))
             .bearer_auth(&self.token)
-            .timeout(LIST_TIMEOUT)
-            .send()
+            .timeout(LIST_TIMEOUT);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("get sprite '{name}'")))?;
 
@@ -472,18 +1009,32 @@ impl SpritesClient {
     }
 
     pub async fn create_sprite(&self, name: &str) -> Result<SpriteInfo, AppError> {
-        let resp = self
+        self.post_sprite(name, false).await
+    }
+
+    /// "Ensure it exists": like `create_sprite`, but a sprite that already
+    /// exists is success and is returned as-is (fetched via `get_sprite`).
+    pub async fn create_sprite_if_absent(&self, name: &str) -> Result<SpriteInfo, AppError> {
+        self.post_sprite(name, true).await
+    }
+
+    async fn post_sprite(&self, name: &str, tolerate_existing: bool) -> Result<SpriteInfo, AppError> {
+        let req = self
             .http
             .post(self.api_url("/sprites"))
             .bearer_auth(&self.token)
-            .json(&serde_json::json!({ "name": name }))
-            .send()
+            .json(&serde_json::json!({ "name": name }));
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("create sprite '{name}'")))?;
 
         if !resp.status().is_success() {
             let status = resp.status();
             let body = resp.text().await.unwrap_or_default();
+            if tolerate_existing && is_already_exists(status, &body) {
+                return self.get_sprite(name).await.map(SpriteInfo::from);
+            }
             return Err(AppError::Internal(format!(
                 "create sprite '{name}' returned {status}: {body}"
             )));
@@ -499,12 +1050,13 @@ impl SpritesClient {
         name: &str,
         url_auth: &str,
     ) -> Result<SpriteDetail, AppError> {
-        let resp = self
+        let req = self
             .http
-            .put(self.api_url(&format!("/sprites/{name}")))
+            .put(self.api_url(&sprite_path(name, "")))
             .bearer_auth(&self.token)
-            .json(&serde_json::json!({ "url_settings": { "auth": url_auth } }))
-            .send()
+            .json(&serde_json::json!({ "url_settings": { "auth": url_auth } }));
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("update sprite '{name}'")))?;
 
@@ -522,11 +1074,12 @@ impl SpritesClient {
     }
 
     pub async fn delete_sprite(&self, name: &str) -> Result<(), AppError> {
-        let resp = self
+        let req = self
             .http
-            .delete(self.api_url(&format!("/sprites/{name}")))
-            .bearer_auth(&self.token)
-            .send()
+            .delete(self.api_url(&sprite_path(name, "")))
+            .bearer_auth(&self.token);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("delete sprite '{name}'")))?;
 
@@ -543,14 +1096,13 @@ impl SpritesClient {
     // ── Exec ──────────────────────────────────────────────────────────────
 
     pub async fn exec_http(&self, name: &str, cmd: &str) -> Result<ExecResult, AppError> {
-        let resp = self
+        let started = Instant::now();
+        let req = self
             .http
-            .post(self.api_url(&format!("/sprites/{name}/exec")))
+            .post(self.api_url(&sprite_path(name, "/exec")))
             .bearer_auth(&self.token)
-            .json(&serde_json::json!({ "command": cmd }))
-            .send()
-            .await
-            .map_err(|e| reqwest_err(&e, &format!("exec on '{name}'")))?;
+            .json(&serde_json::json!({ "command": cmd }));
+        let resp = self.send(req).await.map_err(|e| reqwest_err(&e, &format!("exec on '{name}'")))?;
 
         if !resp.status().is_success() {
             let status = resp.status();
@@ -560,23 +1112,46 @@ impl SpritesClient {
             )));
         }
 
-        resp.json::<ExecResult>()
+        let mut result = resp
+            .json::<ExecResult>()
             .await
-            .map_err(|e| AppError::Internal(format!("Failed to parse exec result: {e}")))
+            .map_err(|e| AppError::Internal(format!("Failed to parse exec result: {e}")))?;
+        if result.duration_ms.is_none() {
+            result.duration_ms = Some(started.elapsed().as_millis() as u64);
+        }
+        Ok(result)
+    }
+
+    /// Sample CPU, memory and root-disk usage on a sprite.
+    ///
+    /// The Sprites API has no metrics endpoint, so this runs `top`, `free` and
+    /// `df` in one exec. Samples are cached for `METRICS_CACHE_TTL` per sprite.
+    pub async fn get_sprite_metrics(&self, name: &str) -> Result<SpriteMetrics, AppError> {
+        if let Some((at, metrics)) = self.metrics_cache.lock().unwrap().get(name) {
+            if at.elapsed() < METRICS_CACHE_TTL {
+                return Ok(metrics.clone());
+            }
+        }
+
+        let result = self.exec_http(name, METRICS_CMD).await?;
+        let metrics = parse_sprite_metrics(&result.stdout);
+        self.metrics_cache
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), (Instant::now(), metrics.clone()));
+        Ok(metrics)
     }
 
     /// Execute a shell command on a sprite via query-param API.
     /// Commands go as repeated `cmd` query params: ?cmd=sh&cmd=-c&cmd=<shell_cmd>
     pub async fn exec_command(&self, name: &str, cmd: &str) -> Result<String, AppError> {
-        let resp = self
+        let req = self
             .http
-            .post(self.api_url(&format!("/sprites/{name}/exec")))
+            .post(self.api_url(&sprite_path(name, "/exec")))
             .bearer_auth(&self.token)
             .timeout(Duration::from_secs(30))
-            .query(&[("cmd", "sh"), ("cmd", "-c"), ("cmd", cmd)])
-            .send()
-            .await
-            .map_err(|e| reqwest_err(&e, &format!("exec on '{name}'")))?;
+            .query(&[("cmd", "sh"), ("cmd", "-c"), ("cmd", cmd)]);
+        let resp = self.send(req).await.map_err(|e| reqwest_err(&e, &format!("exec on '{name}'")))?;
 
         if !resp.status().is_success() {
             let status = resp.status();
@@ -592,12 +1167,13 @@ impl SpritesClient {
     }
 
     pub async fn list_exec_sessions(&self, name: &str) -> Result<Vec<ExecSession>, AppError> {
-        let resp = self
+        let req = self
             .http
-            .get(self.api_url(&format!("/sprites/{name}/exec")))
+            .get(self.api_url(&sprite_path(name, "/exec")))
             .bearer_auth(&self.token)
-            .timeout(LIST_TIMEOUT)
-            .send()
+            .timeout(LIST_TIMEOUT);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("list sessions for '{name}'")))?;
 
@@ -637,15 +1213,19 @@ impl SpritesClient {
         session_id: &str,
         signal: &str,
     ) -> Result<reqwest::Response, AppError> {
-        let mut url = self.api_url(&format!("/sprites/{name}/exec/{session_id}/kill"));
-        url = format!("{url}?signal={signal}");
+        let mut url = self.api_url(&sprite_path(
+            name,
+            &format!("/exec/{}/kill", encode(session_id)),
+        ));
+        url = format!("{url}?signal={}", encode(signal));
 
-        let resp = self
+        let req = self
             .http
             .post(&url)
             .bearer_auth(&self.token)
-            .timeout(STREAM_TIMEOUT)
-            .send()
+            .timeout(STREAM_TIMEOUT);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("kill session on '{name}'")))?;
 
@@ -659,15 +1239,43 @@ impl SpritesClient {
         Ok(resp)
     }
 
+    /// Read an exec session's buffered output and follow it — returns raw
+    /// Response for NDJSON streaming
+    pub async fn exec_session_output_stream(
+        &self,
+        name: &str,
+        session_id: &serde_json::Value,
+    ) -> Result<reqwest::Response, AppError> {
+        let req = self
+            .http
+            .get(self.api_url(&exec_output_path(name, session_id)))
+            .bearer_auth(&self.token)
+            .timeout(STREAM_TIMEOUT);
+        let resp = self
+            .send(req)
+            .await
+            .map_err(|e| reqwest_err(&e, &format!("read exec session on '{name}'")))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(AppError::Internal(format!(
+                "Exec session output returned {status}: {body}"
+            )));
+        }
+        Ok(resp)
+    }
+
     // ── Checkpoints ──────────────────────────────────────────────────────
 
     pub async fn list_checkpoints(&self, name: &str) -> Result<Vec<Checkpoint>, AppError> {
-        let resp = self
+        let req = self
             .http
-            .get(self.api_url(&format!("/sprites/{name}/checkpoints")))
+            .get(self.api_url(&sprite_path(name, "/checkpoints")))
             .bearer_auth(&self.token)
-            .timeout(LIST_TIMEOUT)
-            .send()
+            .timeout(LIST_TIMEOUT);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("list checkpoints for '{name}'")))?;
 
@@ -697,13 +1305,14 @@ impl SpritesClient {
             None => serde_json::json!({}),
         };
 
-        let resp = self
+        let req = self
             .http
-            .post(self.api_url(&format!("/sprites/{name}/checkpoint")))
+            .post(self.api_url(&sprite_path(name, "/checkpoint")))
             .bearer_auth(&self.token)
             .timeout(STREAM_TIMEOUT)
-            .json(&body)
-            .send()
+            .json(&body);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("create checkpoint for '{name}'")))?;
 
@@ -723,14 +1332,16 @@ impl SpritesClient {
         name: &str,
         checkpoint_id: &str,
     ) -> Result<reqwest::Response, AppError> {
-        let resp = self
+        let req = self
             .http
-            .post(self.api_url(&format!(
-                "/sprites/{name}/checkpoints/{checkpoint_id}/restore"
+            .post(self.api_url(&sprite_path(
+                name,
+                &format!("/checkpoints/{}/restore", encode(checkpoint_id)),
             )))
             .bearer_auth(&self.token)
-            .timeout(STREAM_TIMEOUT)
-            .send()
+            .timeout(STREAM_TIMEOUT);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("restore checkpoint on '{name}'")))?;
 
@@ -747,12 +1358,13 @@ impl SpritesClient {
     // ── Services ─────────────────────────────────────────────────────────
 
     pub async fn list_services(&self, name: &str) -> Result<Vec<Service>, AppError> {
-        let resp = self
+        let req = self
             .http
-            .get(self.api_url(&format!("/sprites/{name}/services")))
+            .get(self.api_url(&sprite_path(name, "/services")))
             .bearer_auth(&self.token)
-            .timeout(LIST_TIMEOUT)
-            .send()
+            .timeout(LIST_TIMEOUT);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("list services for '{name}'")))?;
 
@@ -777,14 +1389,16 @@ impl SpritesClient {
         name: &str,
         service_name: &str,
     ) -> Result<reqwest::Response, AppError> {
-        let resp = self
+        let req = self
             .http
-            .post(self.api_url(&format!(
-                "/sprites/{name}/services/{service_name}/start"
+            .post(self.api_url(&sprite_path(
+                name,
+                &format!("/services/{}/start", encode(service_name)),
             )))
             .bearer_auth(&self.token)
-            .timeout(STREAM_TIMEOUT)
-            .send()
+            .timeout(STREAM_TIMEOUT);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("start service '{service_name}' on '{name}'")))?;
 
@@ -804,14 +1418,16 @@ impl SpritesClient {
         name: &str,
         service_name: &str,
     ) -> Result<reqwest::Response, AppError> {
-        let resp = self
+        let req = self
             .http
-            .post(self.api_url(&format!(
-                "/sprites/{name}/services/{service_name}/stop"
+            .post(self.api_url(&sprite_path(
+                name,
+                &format!("/services/{}/stop", encode(service_name)),
             )))
             .bearer_auth(&self.token)
-            .timeout(STREAM_TIMEOUT)
-            .send()
+            .timeout(STREAM_TIMEOUT);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("stop service '{service_name}' on '{name}'")))?;
 
@@ -832,19 +1448,21 @@ impl SpritesClient {
         service_name: &str,
         lines: Option<u32>,
     ) -> Result<reqwest::Response, AppError> {
-        let mut url = self.api_url(&format!(
-            "/sprites/{name}/services/{service_name}/logs"
+        let mut url = self.api_url(&sprite_path(
+            name,
+            &format!("/services/{}/logs", encode(service_name)),
         ));
         if let Some(n) = lines {
             url = format!("{url}?lines={n}");
         }
 
-        let resp = self
+        let req = self
             .http
             .get(&url)
             .bearer_auth(&self.token)
-            .timeout(STREAM_TIMEOUT)
-            .send()
+            .timeout(STREAM_TIMEOUT);
+        let resp = self
+            .send(req)
             .await
             .map_err(|e| reqwest_err(&e, &format!("get logs for '{service_name}' on '{name}'")))?;
 
@@ -861,20 +1479,29 @@ impl SpritesClient {
     // ── WebSocket + Config ───────────────────────────────────────────────
 
     pub fn ws_exec_url(&self, name: &str, cols: u16, rows: u16) -> String {
+        self.ws_exec_url_with_cmd(name, "/bin/bash", cols, rows)
+    }
+
+    /// WebSocket exec URL that runs `cmd` instead of the default shell.
+    ///
+    /// The sprite name and every query value are percent-encoded, so names
+    /// with spaces or `#`/`?` and multi-word commands stay well-formed.
+    pub fn ws_exec_url_with_cmd(&self, name: &str, cmd: &str, cols: u16, rows: u16) -> String {
         let base = self
             .base_url
             .replace("https://", "wss://")
             .replace("http://", "ws://");
         format!(
-            "{}/v1/sprites/{}/exec?tty=true&cmd=/bin/bash&cols={}&rows={}",
-            base, name, cols, rows
+            "{}/v1{}?tty={}&cmd={}&cols={}&rows={}",
+            base,
+            sprite_path(name, "/exec"),
+            encode("true"),
+            encode(cmd),
+            encode(&cols.to_string()),
+            encode(&rows.to_string())
         )
     }
 
-    pub fn base_url(&self) -> &str {
-        &self.base_url
-    }
-
     pub fn token(&self) -> &str {
         &self.token
     }
@@ -885,3 +1512,616 @@ impl SpritesClient {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minimal HTTP/1.1 server answering every request with `respond(request_line)`
+    /// after `delay`. Returns the base URL to hand to `SpritesClient::new`.
+    async fn mock_server<F>(delay: Duration, respond: F) -> String
+    where
+        F: Fn(&str) -> (u16, String) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let respond = Arc::new(respond);
+
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(s) => s,
+                    Err(_) => break,
+                };
+                let respond = respond.clone();
+                tokio::spawn(async move {
+                    let request = read_request(&mut socket).await;
+                    let request_line = request.lines().next().unwrap_or("").to_string();
+                    tokio::time::sleep(delay).await;
+                    let (status, body) = respond(&request_line);
+                    let response = format!(
+                        "HTTP/1.1 {status} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        format!("http://{addr}")
+    }
+
+    /// Read request head plus any Content-Length body so the client never sees a reset.
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = match socket.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            data.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&data);
+            if let Some(head_end) = text.find("\r\n\r\n") {
+                let content_length = text[..head_end]
+                    .lines()
+                    .find_map(|l| {
+                        let (k, v) = l.split_once(':')?;
+                        k.eq_ignore_ascii_case("content-length")
+                            .then(|| v.trim().parse::<usize>().ok())
+                            .flatten()
+                    })
+                    .unwrap_or(0);
+                if data.len() >= head_end + 4 + content_length {
+                    break;
+                }
+            }
+        }
+        String::from_utf8_lossy(&data).to_string()
+    }
+
+    /// Channel that records every sent payload as a JSON value.
+    fn collecting_channel<T>() -> (Channel<T>, Arc<std::sync::Mutex<Vec<serde_json::Value>>>) {
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = sent.clone();
+        let channel = Channel::new(move |body| {
+            if let tauri::ipc::InvokeResponseBody::Json(json) = body {
+                sink.lock().unwrap().push(serde_json::from_str(&json).unwrap());
+            }
+            Ok(())
+        });
+        (channel, sent)
+    }
+
+    fn fixture_stream(
+        chunks: &[&str],
+    ) -> impl futures_util::Stream<Item = Result<Vec<u8>, std::io::Error>> {
+        let chunks: Vec<_> = chunks.iter().map(|c| Ok(c.as_bytes().to_vec())).collect();
+        futures_util::stream::iter(chunks)
+    }
+
+    #[tokio::test]
+    async fn test_merge_service_log_streams_tags_and_survives_terminal() {
+        let web = fixture_stream(&[
+            "{\"type\":\"stdout\",\"data\":\"web up\"}\n{\"type\":\"exit\",",
+            "\"exit_code\":1}\n{\"type\":\"stdout\",\"data\":\"after exit\"}\n",
+        ]);
+        let worker = fixture_stream(&[
+            "{\"type\":\"stdout\",\"data\":\"job 1\"}\n",
+            "{\"type\":\"stderr\",\"data\":\"job 2 failed\"}\n{\"type\":\"complete\"}\n",
+        ]);
+        let (channel, sent) = collecting_channel();
+
+        merge_service_log_streams(
+            vec![("web".to_string(), web), ("worker".to_string(), worker)],
+            &channel,
+        )
+        .await
+        .unwrap();
+
+        let sent = sent.lock().unwrap();
+        let for_service = |svc: &str| -> Vec<String> {
+            sent.iter()
+                .filter(|e| e["service"] == svc)
+                .map(|e| e["type"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(for_service("web"), vec!["stdout", "exit"], "web stops at its exit");
+        assert_eq!(
+            for_service("worker"),
+            vec!["stdout", "stderr", "complete"],
+            "web's terminal event must not end the worker stream"
+        );
+        assert!(sent.iter().any(|e| e["data"] == "job 2 failed"));
+    }
+
+    #[tokio::test]
+    async fn test_merge_service_log_streams_reports_parse_error_per_service() {
+        let bad = fixture_stream(&["not json\n"]);
+        let good = fixture_stream(&["{\"type\":\"stdout\",\"data\":\"ok\"}\n"]);
+        let (channel, sent) = collecting_channel();
+
+        merge_service_log_streams(
+            vec![("bad".to_string(), bad), ("good".to_string(), good)],
+            &channel,
+        )
+        .await
+        .unwrap();
+
+        let sent = sent.lock().unwrap();
+        assert!(sent
+            .iter()
+            .any(|e| e["service"] == "bad" && e["type"] == "error"));
+        assert!(sent
+            .iter()
+            .any(|e| e["service"] == "good" && e["data"] == "ok"));
+    }
+
+    #[tokio::test]
+    async fn test_exec_http_populates_duration_ms() {
+        let base = mock_server(Duration::from_millis(50), |_| {
+            (200, r#"{"stdout":"hi","stderr":"","exit_code":0}"#.to_string())
+        })
+        .await;
+        let client = SpritesClient::new(base, "token".into());
+
+        let result = client.exec_http("sprite-a", "echo hi").await.unwrap();
+
+        assert_eq!(result.stdout, "hi");
+        let duration = result.duration_ms.expect("duration_ms should be measured");
+        assert!(duration >= 50, "expected >= 50ms, got {duration}");
+    }
+
+    #[tokio::test]
+    async fn test_exec_http_keeps_api_duration() {
+        let base = mock_server(Duration::ZERO, |_| {
+            (200, r#"{"stdout":"","exit_code":0,"duration_ms":1234}"#.to_string())
+        })
+        .await;
+        let client = SpritesClient::new(base, "token".into());
+
+        let result = client.exec_http("sprite-a", "true").await.unwrap();
+
+        assert_eq!(result.duration_ms, Some(1234));
+    }
+
+    #[test]
+    fn test_normalize_sprites_base_url_fixups() {
+        let normalized = |input: &str| normalize_sprites_base_url(input).unwrap();
+
+        let clean = normalized("https://api.sprites.dev");
+        assert_eq!(clean.url, "https://api.sprites.dev");
+        assert!(clean.fixups.is_empty());
+
+        let slash = normalized("https://api.sprites.dev/");
+        assert_eq!(slash.url, "https://api.sprites.dev");
+        assert!(slash.fixups.is_empty(), "the root path is not a trailing slash");
+        let nested = normalized("https://api.sprites.dev/proxy//");
+        assert_eq!(nested.url, "https://api.sprites.dev/proxy");
+        assert_eq!(nested.fixups, ["Removed trailing slash"]);
+
+        let v1 = normalized("  https://api.sprites.dev/v1/  ");
+        assert_eq!(v1.url, "https://api.sprites.dev");
+        assert_eq!(
+            v1.fixups,
+            [
+                "Removed surrounding whitespace",
+                "Removed trailing slash",
+                "Removed /v1 (added to every request)"
+            ]
+        );
+        assert_eq!(normalized("http://localhost:8080/gw/v1").url, "http://localhost:8080/gw");
+        assert_eq!(normalized("https://api.sprites.dev:443/v1").url, "https://api.sprites.dev");
+
+        let query = normalized("https://api.sprites.dev/?org=acme#top");
+        assert_eq!(query.url, "https://api.sprites.dev");
+        assert_eq!(query.fixups, ["Removed query string and fragment"]);
+    }
+
+    #[test]
+    fn test_normalize_sprites_base_url_rejects_invalid() {
+        let err = |input: &str| normalize_sprites_base_url(input).unwrap_err().to_string();
+
+        assert!(err("wss://api.sprites.dev/v1").contains("use http(s)://"));
+        assert!(err("ws://localhost:8080").contains("WebSocket URL"));
+        assert!(err("api.sprites.dev").contains("no scheme"));
+        assert!(err("ftp://api.sprites.dev").contains("unsupported scheme"));
+        assert!(err("https://").contains("Invalid Sprites API URL"));
+        assert!(err("   ").contains("empty"));
+    }
+
+    #[test]
+    fn test_sprite_url_info_with_and_without_url_settings() {
+        let detail: SpriteDetail = serde_json::from_str(
+            r#"{"name":"web","url":"https://web.sprites.app","url_settings":{"auth":"public"}}"#,
+        )
+        .unwrap();
+        let info = detail.url_info().unwrap();
+        assert_eq!(info.url.as_deref(), Some("https://web.sprites.app"));
+        assert_eq!(info.auth, "public");
+        assert!(info.public);
+
+        let detail: SpriteDetail =
+            serde_json::from_str(r#"{"name":"api","url":"https://api.sprites.app"}"#).unwrap();
+        let info = detail.url_info().unwrap();
+        assert_eq!(info.auth, "sprite", "missing url_settings means sprite auth");
+        assert!(!info.public);
+
+        let detail: SpriteDetail = serde_json::from_str(r#"{"name":"bare"}"#).unwrap();
+        let err = detail.url_info().unwrap_err();
+        assert!(err.to_string().contains("'bare' has no URL provisioned"));
+    }
+
+    #[test]
+    fn test_detail_checkpoints_sorts_mixed_time_fields_newest_first() {
+        let checkpoints: Vec<Checkpoint> = serde_json::from_value(serde_json::json!([
+            { "id": "v1", "create_time": "2026-02-16T08:00:00Z" },
+            { "id": "undated" },
+            { "id": "v3", "created_at": "2026-02-18T09:00:00+09:00", "size": 1_500_000_000u64 },
+            { "id": "v2", "created_at": "2026-02-17T12:00:00Z", "size_bytes": 512 },
+        ]))
+        .unwrap();
+
+        let detailed = detail_checkpoints(checkpoints);
+
+        let ids: Vec<&str> = detailed.iter().map(|c| c.checkpoint.id.as_str()).collect();
+        assert_eq!(ids, ["v3", "v2", "v1", "undated"]);
+        assert_eq!(detailed[0].created.as_deref(), Some("2026-02-18T00:00:00+00:00"));
+        assert_eq!(detailed[0].size_human.as_deref(), Some("1.5 GB"));
+        assert_eq!(detailed[1].size_human.as_deref(), Some("512 B"));
+        assert_eq!(detailed[3].created, None);
+    }
+
+    fn exec_session(json: serde_json::Value) -> ExecSession {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_diff_exec_sessions_reports_started_ended_changed() {
+        let prev = vec![
+            exec_session(serde_json::json!({ "id": 7, "command": "bash", "is_active": true })),
+            exec_session(serde_json::json!({ "id": "abc", "command": "top", "is_active": true })),
+            exec_session(serde_json::json!({ "id": "gone", "command": "sleep 1" })),
+        ];
+        let next = vec![
+            // Same session, id now a string; only throughput moved
+            exec_session(serde_json::json!({
+                "id": "7", "command": "bash", "is_active": true, "bytes_per_second": 12.5
+            })),
+            exec_session(serde_json::json!({ "id": "abc", "command": "top", "is_active": false })),
+            exec_session(serde_json::json!({ "id": 9, "command": "claude" })),
+        ];
+
+        let events = serde_json::to_value(diff_exec_sessions(&prev, &next)).unwrap();
+
+        assert_eq!(events.as_array().unwrap().len(), 3, "got {events}");
+        assert_eq!(events[0]["type"], "changed");
+        assert_eq!(events[0]["session"]["id"], "abc");
+        assert_eq!(events[1]["type"], "started");
+        assert_eq!(events[1]["session"]["id"], 9);
+        assert_eq!(events[2], serde_json::json!({ "type": "ended", "id": "gone" }));
+        assert!(diff_exec_sessions(&next, &next).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_exec_http_nonzero_exit_fails_merged_output() {
+        let base = mock_server(Duration::ZERO, |_| {
+            let body = r#"{"stdout":"partial","stderr":"ls: missing: No such file","exit_code":1}"#;
+            (200, body.to_string())
+        })
+        .await;
+        let client = SpritesClient::new(base, "token".into());
+
+        let result = client.exec_http("sprite-a", "ls missing").await.unwrap();
+        let err = result.clone().merged_output(true).unwrap_err().to_string();
+        assert!(err.contains("exited with code 1"), "got {err}");
+        assert!(err.contains("partial\nls: missing: No such file"), "got {err}");
+
+        let output = result.merged_output(false).unwrap();
+        assert_eq!(output, "partial\nls: missing: No such file");
+    }
+
+    #[test]
+    fn test_ws_exec_url_encodes_resume_command() {
+        let client = SpritesClient::new("https://api.sprites.dev".into(), "token".into());
+        let cmd = claude_resume_command("0b5e7c2a-1f3d-4e6b-9a8c-2d4f6e8a0b1c").unwrap();
+
+        let url = client.ws_exec_url_with_cmd("sprite-a", &cmd, 120, 40);
+
+        assert_eq!(
+            url,
+            "wss://api.sprites.dev/v1/sprites/sprite-a/exec?tty=true\
+             &cmd=claude%20--resume%200b5e7c2a-1f3d-4e6b-9a8c-2d4f6e8a0b1c%20--dangerously-skip-permissions\
+             &cols=120&rows=40"
+        );
+    }
+
+    #[test]
+    fn test_claude_resume_command_rejects_shell_metacharacters() {
+        assert!(claude_resume_command("abc; rm -rf ~").is_err());
+        assert!(claude_resume_command("").is_err());
+    }
+
+    #[test]
+    fn test_ws_exec_url_encodes_sprite_name() {
+        let client = SpritesClient::new("https://api.sprites.dev".into(), "token".into());
+
+        let url = client.ws_exec_url("my sprite#1/?", 80, 24);
+
+        assert_eq!(
+            url,
+            "wss://api.sprites.dev/v1/sprites/my%20sprite%231%2F%3F/exec\
+             ?tty=true&cmd=%2Fbin%2Fbash&cols=80&rows=24"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_sprite_if_absent_returns_existing_on_conflict() {
+        let base = mock_server(Duration::ZERO, |request_line| {
+            if request_line.starts_with("POST /v1/sprites ") {
+                (409, r#"{"error":"sprite already exists"}"#.to_string())
+            } else if request_line.starts_with("GET /v1/sprites/dev ") {
+                (200, r#"{"name":"dev","status":"running","id":"spr_1"}"#.to_string())
+            } else {
+                (404, "{}".to_string())
+            }
+        })
+        .await;
+        let client = SpritesClient::new(base, "token".into());
+
+        let info = client.create_sprite_if_absent("dev").await.unwrap();
+        assert_eq!(info.name, "dev");
+        assert_eq!(info.status, "running");
+        assert_eq!(info.id.as_deref(), Some("spr_1"));
+
+        // Plain create still reports the conflict
+        let err = client.create_sprite("dev").await.unwrap_err().to_string();
+        assert!(err.contains("409"), "got: {err}");
+    }
+
+    #[test]
+    fn test_sprite_path_encodes_name_segment() {
+        assert_eq!(sprite_path("sprite-a", ""), "/sprites/sprite-a");
+        assert_eq!(
+            sprite_path("acme/dev box", "/exec"),
+            "/sprites/acme%2Fdev%20box/exec"
+        );
+    }
+
+    #[test]
+    fn test_exec_output_path_normalizes_id_shapes() {
+        assert_eq!(
+            exec_output_path("dev", &serde_json::json!(42)),
+            "/sprites/dev/exec/42/output"
+        );
+        assert_eq!(
+            exec_output_path("dev", &serde_json::json!("42")),
+            "/sprites/dev/exec/42/output"
+        );
+        assert_eq!(
+            exec_output_path("dev", &serde_json::json!("sess/a b")),
+            "/sprites/dev/exec/sess%2Fa%20b/output"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_sprite_requests_encoded_path() {
+        let base = mock_server(Duration::ZERO, |request_line| {
+            if request_line.starts_with("GET /v1/sprites/acme%2Fdev%20box ") {
+                (200, r#"{"name":"acme/dev box","organization":"acme"}"#.to_string())
+            } else {
+                (404, format!(r#"{{"error":"unexpected {request_line}"}}"#))
+            }
+        })
+        .await;
+        let client = SpritesClient::new(base, "token".into());
+
+        let detail = client.get_sprite("acme/dev box").await.unwrap();
+
+        assert_eq!(detail.name, "acme/dev box");
+        assert_eq!(detail.organization.as_deref(), Some("acme"));
+    }
+
+    #[test]
+    fn test_ws_exec_url_with_spaced_command_round_trips() {
+        let client = SpritesClient::new("https://api.sprites.dev".into(), "token".into());
+        let cmd = "tail -f /var/log/app.log && echo 'done=1'";
+
+        let raw = client.ws_exec_url_with_cmd("sprite-a", cmd, 100, 30);
+        let parsed = url::Url::parse(&raw).expect("ws_exec_url should produce a valid URL");
+
+        assert_eq!(parsed.scheme(), "wss");
+        assert_eq!(parsed.path(), "/v1/sprites/sprite-a/exec");
+        let query: HashMap<String, String> = parsed.query_pairs().into_owned().collect();
+        assert_eq!(query.get("cmd").map(String::as_str), Some(cmd));
+        assert_eq!(query.get("cols").map(String::as_str), Some("100"));
+        assert_eq!(query.get("rows").map(String::as_str), Some("30"));
+        assert_eq!(query.get("tty").map(String::as_str), Some("true"));
+    }
+
+    /// Like `mock_server`, but records the peak number of requests in flight.
+    async fn counting_server(delay: Duration) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let peak_out = peak.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let in_flight = in_flight.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    read_request(&mut socket).await;
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(delay).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let body = r#"{"stdout":"ok","exit_code":0}"#;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        (format!("http://{addr}"), peak_out)
+    }
+
+    /// Serves `body` gzip-encoded, but only to requests that accept gzip.
+    async fn gzip_server(body: &'static str) -> String {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let request = read_request(&mut socket).await.to_ascii_lowercase();
+                let response = if request.contains("accept-encoding: gzip") {
+                    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                    encoder.write_all(body.as_bytes()).unwrap();
+                    let gzipped = encoder.finish().unwrap();
+                    let mut response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        gzipped.len()
+                    )
+                    .into_bytes();
+                    response.extend_from_slice(&gzipped);
+                    response
+                } else {
+                    b"HTTP/1.1 406 Not Acceptable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_vec()
+                };
+                let _ = socket.write_all(&response).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_gzipped_ndjson_stream_parses_events() {
+        let body = concat!(
+            "{\"type\":\"stdout\",\"data\":\"line one\"}\n",
+            "{\"type\":\"stderr\",\"data\":\"line two\"}\n",
+            "{\"type\":\"complete\"}\n",
+        );
+        let base = gzip_server(body).await;
+        let client = SpritesClient::new(base, "token".into());
+        let (channel, sent) = collecting_channel::<ServiceStreamEvent>();
+
+        let response = client.get_service_logs_stream("sprite-a", "web", None).await.unwrap();
+        pipe_ndjson_stream(response, &channel, ServiceStreamEvent::is_terminal)
+            .await
+            .unwrap();
+
+        let sent = sent.lock().unwrap();
+        let types: Vec<&str> = sent.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["stdout", "stderr", "complete"]);
+        assert_eq!(sent[1]["data"], "line two");
+    }
+
+    #[tokio::test]
+    async fn test_limiter_queues_requests_beyond_permits() {
+        let (base, peak) = counting_server(Duration::from_millis(40)).await;
+        let client = SpritesClient::new(base, "token".into()).with_max_concurrent_requests(2);
+
+        let calls = (0..6).map(|_| {
+            let client = client.clone();
+            async move { client.exec_http("sprite-a", "true").await }
+        });
+        let results = futures_util::future::join_all(calls).await;
+
+        assert!(results.iter().all(|r| r.is_ok()), "all queued calls should succeed");
+        assert_eq!(peak.load(Ordering::SeqCst), 2, "never more than 2 requests in flight");
+    }
+
+    const TOP_FIXTURE: &str = "\
+top - 10:15:01 up 3 days,  2:04,  0 users,  load average: 0.15, 0.10, 0.05
+Tasks:  42 total,   1 running,  41 sleeping,   0 stopped,   0 zombie
+%Cpu(s):  7.5 us,  2.5 sy,  0.0 ni, 88.0 id,  2.0 wa,  0.0 hi,  0.0 si,  0.0 st
+MiB Mem :   7976.2 total,   5120.0 free,   1234.5 used,   1621.7 buff/cache
+";
+    const FREE_FIXTURE: &str = "\
+               total        used        free      shared  buff/cache   available
+Mem:            7976        1234        5120          12        1621        6500
+Swap:              0           0           0
+";
+    const DF_FIXTURE: &str = "\
+Filesystem      Size  Used Avail Use% Mounted on
+/dev/root        20G  5.5G   14G  28% /
+";
+
+    #[test]
+    fn test_parse_top_cpu_procps_and_busybox() {
+        assert_eq!(parse_top_cpu(TOP_FIXTURE), Some(12.0));
+        let busybox = "Mem: 1000K used\nCPU:  3% usr  1% sys  0% nic 95% idle  0% io  0% irq  1% sirq\n";
+        assert_eq!(parse_top_cpu(busybox), Some(5.0));
+        assert_eq!(parse_top_cpu("no cpu line here"), None);
+    }
+
+    #[test]
+    fn test_parse_free_mem() {
+        assert_eq!(parse_free_mem(FREE_FIXTURE), Some((1234, 7976)));
+        assert_eq!(parse_free_mem(""), None);
+    }
+
+    #[test]
+    fn test_parse_df_disk_handles_units_and_wrapped_rows() {
+        assert_eq!(parse_df_disk(DF_FIXTURE), Some((5.5, 20.0)));
+        let wrapped = "Filesystem      Size  Used Avail Use% Mounted on\n\
+/dev/mapper/very-long-volume-name\n                1.5T  512M  1.4T   1% /\n";
+        assert_eq!(parse_df_disk(wrapped), Some((0.5, 1536.0)));
+        assert_eq!(parse_df_disk("Filesystem Size Used\n"), None);
+    }
+
+    #[test]
+    fn test_parse_sprite_metrics_keeps_partial_results() {
+        let stdout = format!("{TOP_FIXTURE}---\n{FREE_FIXTURE}---\n");
+        let metrics = parse_sprite_metrics(&stdout);
+        assert_eq!(metrics.cpu_percent, Some(12.0));
+        assert_eq!(metrics.mem_used_mb, Some(1234));
+        assert_eq!(metrics.mem_total_mb, Some(7976));
+        assert_eq!(metrics.disk_used_gb, None, "df section missing");
+        assert_eq!(metrics.disk_total_gb, None);
+    }
+
+    #[test]
+    fn test_parse_sprite_metrics_with_empty_top_output() {
+        let stdout = format!("---\n{FREE_FIXTURE}---\n{DF_FIXTURE}");
+        let metrics = parse_sprite_metrics(&stdout);
+        assert_eq!(metrics.cpu_percent, None, "no top installed");
+        assert_eq!(metrics.mem_used_mb, Some(1234));
+        assert_eq!(metrics.mem_total_mb, Some(7976));
+        assert_eq!(metrics.disk_used_gb, Some(5.5));
+        assert_eq!(metrics.disk_total_gb, Some(20.0));
+    }
+
+    #[tokio::test]
+    async fn test_get_sprite_metrics_caches_briefly() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_in_server = calls.clone();
+        let stdout = format!("{TOP_FIXTURE}---\n{FREE_FIXTURE}---\n{DF_FIXTURE}");
+        let body = serde_json::json!({ "stdout": stdout, "exit_code": 0 }).to_string();
+        let base = mock_server(Duration::ZERO, move |_| {
+            calls_in_server.fetch_add(1, Ordering::SeqCst);
+            (200, body.clone())
+        })
+        .await;
+        let client = SpritesClient::new(base, "token".into());
+
+        let first = client.get_sprite_metrics("sprite-a").await.unwrap();
+        let second = client.clone().get_sprite_metrics("sprite-a").await.unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first.disk_total_gb, Some(20.0));
+        assert_eq!(calls.load(Ordering::SeqCst), 1, "second call served from cache");
+    }
+}
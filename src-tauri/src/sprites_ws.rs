@@ -3,6 +3,7 @@ use crate::state::PtyInfo;
 use futures_util::{SinkExt, StreamExt};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
 use tokio_tungstenite::tungstenite;
@@ -23,7 +24,7 @@ pub struct WsSession {
     >,
     pub cols: u16,
     pub rows: u16,
-    _abort: tokio::task::AbortHandle,
+    abort: tokio::task::AbortHandle,
 }
 
 /// Shared state for WebSocket sessions
@@ -37,6 +38,36 @@ impl WsState {
             sessions: Mutex::new(HashMap::new()),
         }
     }
+
+    /// Close every session on app exit: send each a Close frame and abort
+    /// its reader task.
+    ///
+    /// Returns once all Close frames are sent or `timeout` elapses, whichever
+    /// comes first; the map is empty either way.
+    pub async fn shutdown_all(&self, timeout: Duration) {
+        let sessions: Vec<WsSession> =
+            self.sessions.lock().await.drain().map(|(_, s)| s).collect();
+        if sessions.is_empty() {
+            return;
+        }
+
+        let closes = sessions.iter().map(|session| async move {
+            let mut tx = session.tx.lock().await;
+            if let Err(e) = tx.send(tungstenite::Message::Close(None)).await {
+                tracing::debug!("Close frame for WS session {} failed: {}", session.id, e);
+            }
+        });
+        if tokio::time::timeout(timeout, futures_util::future::join_all(closes))
+            .await
+            .is_err()
+        {
+            tracing::warn!("Timed out closing {} WS sessions on shutdown", sessions.len());
+        }
+
+        for session in &sessions {
+            session.abort.abort();
+        }
+    }
 }
 
 /// Connect to a sprite's exec WebSocket for interactive terminal
@@ -113,7 +144,7 @@ pub async fn sprite_ws_connect(
         tx: write,
         cols,
         rows,
-        _abort: abort_handle,
+        abort: abort_handle,
     };
 
     ws_state
@@ -182,7 +213,7 @@ pub async fn ws_kill(session_id: &str, ws_state: &WsState) -> Result<(), AppErro
         // Close the WebSocket
         let mut tx = session.tx.lock().await;
         let _ = tx.send(tungstenite::Message::Close(None)).await;
-        // Abort handle drops automatically, killing the reader task
+        session.abort.abort();
     }
     Ok(())
 }
@@ -196,3 +227,53 @@ fn extract_host(url: &str) -> String {
         .unwrap_or("api.sprites.dev")
         .to_string()
 }
+
+/// Connect a `WsSession` to a throwaway local WebSocket server, for tests that
+/// need a live session in `WsState`.
+#[cfg(test)]
+pub(crate) async fn connect_test_session(id: &str, sprite_name: &str) -> WsSession {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+
+    let (ws_stream, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+        .await
+        .unwrap();
+    let (write, _read) = ws_stream.split();
+    WsSession {
+        id: id.to_string(),
+        sprite_name: sprite_name.to_string(),
+        tx: Arc::new(Mutex::new(write)),
+        cols: 120,
+        rows: 40,
+        abort: server.abort_handle(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_all_empties_sessions() {
+        let state = WsState::new();
+        for id in ["ws-1", "ws-2", "ws-3"] {
+            let session = connect_test_session(id, "sprite-a").await;
+            state.sessions.lock().await.insert(id.to_string(), session);
+        }
+        let abort = state.sessions.lock().await["ws-1"].abort.clone();
+
+        state.shutdown_all(Duration::from_secs(2)).await;
+
+        assert!(state.sessions.lock().await.is_empty());
+        while !abort.is_finished() {
+            tokio::task::yield_now().await;
+        }
+        // A second call (e.g. ExitRequested then Exit) is a no-op
+        state.shutdown_all(Duration::from_secs(2)).await;
+    }
+}
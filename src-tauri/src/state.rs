@@ -1,17 +1,27 @@
 use portable_pty::MasterPty;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs::File;
 use std::io::Write;
-use std::sync::atomic::AtomicBool;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use tantivy::merge_policy::LogMergePolicy;
 use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy};
 
+use crate::search::health::BackgroundHeartbeats;
+use crate::search::indexer::IndexOptions;
+use crate::search::queries::DashboardCache;
 use crate::search::schema::IndexSchema;
+use crate::search::tokenizer::register_tokenizers;
+use crate::search::types::WatcherMode;
 use crate::sprites_api::SpritesClient;
 use crate::sprites_ws::WsState;
 
+/// Log file a PTY's raw output is tee'd to while `pty_start_logging` is
+/// active; shared between the `PtyInstance` and its reader thread.
+pub type PtyLog = Arc<Mutex<Option<File>>>;
+
 /// Represents a single PTY instance
 pub struct PtyInstance {
     pub id: String,
@@ -20,6 +30,23 @@ pub struct PtyInstance {
     pub child: Box<dyn portable_pty::Child + Send>,
     pub cols: u16,
     pub rows: u16,
+    /// Working directory requested at spawn (None = inherited)
+    pub cwd: Option<String>,
+    pub log: PtyLog,
+}
+
+/// Lock `mutex`, taking over the guard if a previous holder panicked.
+///
+/// Index state is only ever mutated through commits, so a panic mid-batch
+/// leaves uncommitted docs at worst; failing every later index operation
+/// until restart would be far worse. Clears the poison flag so the warning
+/// is logged once per panic.
+pub fn lock_recover<'a, T>(mutex: &'a Mutex<T>, what: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        tracing::warn!("Recovering {what} lock poisoned by a panicked thread");
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
 }
 
 /// Handle to the Tantivy search index, shared across watcher and query threads.
@@ -29,15 +56,32 @@ pub struct IndexHandle {
     pub schema: IndexSchema,
     pub writer: Arc<Mutex<IndexWriter>>,
     pub paused: Arc<AtomicBool>,
+    pub options: IndexOptions,
+    /// Bumped by every `search_sessions` call; older searches see it and bail.
+    pub search_generation: Arc<AtomicU64>,
+    /// Set once the index watcher starts, for `get_index_stats`.
+    pub watcher_mode: Arc<Mutex<Option<WatcherMode>>>,
+    /// Heartbeats of the watcher and merge thread, for `get_background_health`.
+    pub health: Arc<BackgroundHeartbeats>,
+    /// Last `get_dashboard_summary`, reused until the next commit or its TTL.
+    pub dashboard_cache: Arc<DashboardCache>,
 }
 
 impl IndexHandle {
     /// Create a new IndexHandle from an existing Tantivy `Index`.
     ///
+    /// - Registers the custom tokenizers (see `search::tokenizer`).
     /// - Sets `LogMergePolicy` on the writer immediately after creation.
     /// - Creates a reader with `ReloadPolicy::OnCommitWithDelay`.
     /// - `heap_bytes`: writer buffer size (512MB for bulk, 50MB for watcher).
-    pub fn new(index: Index, schema: IndexSchema, heap_bytes: usize) -> tantivy::Result<Self> {
+    /// - `options`: indexing options reused by `reindex_all`.
+    pub fn new(
+        index: Index,
+        schema: IndexSchema,
+        heap_bytes: usize,
+        options: IndexOptions,
+    ) -> tantivy::Result<Self> {
+        register_tokenizers(&index);
         let writer: IndexWriter = index.writer(heap_bytes)?;
         writer.set_merge_policy(Box::new(LogMergePolicy::default()));
 
@@ -52,6 +96,11 @@ impl IndexHandle {
             schema,
             writer: Arc::new(Mutex::new(writer)),
             paused: Arc::new(AtomicBool::new(false)),
+            options,
+            search_generation: Arc::new(AtomicU64::new(0)),
+            watcher_mode: Arc::new(Mutex::new(None)),
+            health: Arc::new(BackgroundHeartbeats::new(options.poll_interval())),
+            dashboard_cache: Arc::new(DashboardCache::default()),
         })
     }
 
@@ -82,22 +131,33 @@ impl AppState {
     }
 
     /// Update the sprites client when settings change
-    pub fn set_sprites_client(&self, base_url: String, token: String) {
-        let client = SpritesClient::new(base_url, token);
+    pub fn set_sprites_client(
+        &self,
+        base_url: String,
+        token: String,
+        max_concurrent_requests: Option<usize>,
+    ) {
+        let mut client = SpritesClient::new(base_url, token);
+        if let Some(permits) = max_concurrent_requests {
+            client = client.with_max_concurrent_requests(permits);
+        }
         *self.sprites_client.lock().unwrap() = Some(client);
     }
 
+    /// Forget the sprites client and its caches, e.g. after a token rotation;
+    /// sprite commands report "not configured" until it is set again
+    pub fn clear_sprites_client(&self) {
+        if let Some(client) = self.sprites_client.lock().unwrap().take() {
+            client.clear_caches();
+        }
+    }
+
     /// Get a reference to the sprites client, returning error if not configured
     pub fn get_sprites_client(&self) -> Result<SpritesClient, crate::error::AppError> {
         let guard = self.sprites_client.lock().unwrap();
         match &*guard {
-            Some(client) => {
-                // Clone the client data to create a new one (SpritesClient is cheap to recreate)
-                Ok(SpritesClient::new(
-                    client.base_url().to_string(),
-                    client.token().to_string(),
-                ))
-            }
+            // Clones share the connection pool and request limiter
+            Some(client) => Ok(client.clone()),
             None => Err(crate::error::AppError::Internal(
                 "Sprites API not configured. Go to Settings to enter your API token.".to_string(),
             )),
@@ -134,7 +194,8 @@ mod tests {
     fn create_test_index_handle(heap_bytes: usize) -> IndexHandle {
         let schema = IndexSchema::new();
         let index = Index::create_in_ram(schema.schema.clone());
-        IndexHandle::new(index, schema, heap_bytes).expect("failed to create IndexHandle")
+        IndexHandle::new(index, schema, heap_bytes, IndexOptions::default())
+            .expect("failed to create IndexHandle")
     }
 
     #[test]
@@ -176,6 +237,48 @@ mod tests {
         assert!(paused_clone.load(Ordering::Relaxed));
     }
 
+    #[test]
+    fn test_clear_sprites_client_requires_reconfigure() {
+        let state = AppState::new();
+        state.set_sprites_client("https://api.sprites.dev".into(), "old-token".into(), None);
+        assert!(state.get_sprites_client().is_ok());
+
+        state.clear_sprites_client();
+        let err = state.get_sprites_client().err().expect("client was cleared");
+        assert!(err.to_string().contains("not configured"), "{err}");
+
+        state.set_sprites_client("https://api.sprites.dev".into(), "new-token".into(), None);
+        assert!(state.get_sprites_client().is_ok());
+    }
+
+    #[test]
+    fn test_lock_recover_survives_poisoned_writer() {
+        let handle = create_test_index_handle(50_000_000);
+        let writer = Arc::clone(&handle.writer);
+        let result = std::thread::spawn(move || {
+            let _guard = writer.lock().unwrap();
+            panic!("indexing thread panicked while holding the writer");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(handle.writer.is_poisoned());
+
+        // Indexing still proceeds through the recovered guard
+        {
+            let mut w = lock_recover(&handle.writer, "index writer");
+            let mut doc = tantivy::TantivyDocument::new();
+            doc.add_text(handle.schema.session_id, "after-panic");
+            w.add_document(doc).unwrap();
+            w.commit().unwrap();
+        }
+        handle.reader.reload().unwrap();
+        assert_eq!(handle.searcher().num_docs(), 1);
+
+        // Poison is cleared, so plain lock() works again
+        assert!(!handle.writer.is_poisoned());
+        assert!(handle.writer.lock().is_ok());
+    }
+
     #[test]
     fn test_app_state_new_creates_empty_ptys() {
         let state = AppState::new();
@@ -1,23 +1,30 @@
+use crate::claude_paths::{tasks_dir, teams_dir};
 use crate::commands::team::{TaskSummary, TeamConfig, TeamInfo, TeamTask};
 use crate::error::AppError;
-use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
+/// Quiet period after a team dir or config.json appears or disappears before
+/// "teams:changed" fires, so a team being created emits once.
+const TEAMS_CHANGED_DEBOUNCE: Duration = Duration::from_millis(500);
+
 /// Start watching ~/.claude/teams/ and ~/.claude/tasks/ for changes.
-/// Emits "team:updated" events when team configs or tasks change.
+/// Emits "team:updated" events when team configs or tasks change, and
+/// "teams:changed" with the team names when a team is created or deleted.
 #[tauri::command]
 pub async fn start_team_watcher(app: AppHandle) -> Result<(), AppError> {
-    let home = dirs::home_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
-
-    let teams_dir = home.join(".claude").join("teams");
-    let tasks_dir = home.join(".claude").join("tasks");
+    let no_home = || AppError::Internal("No home dir".into());
+    // Watched and compared canonically, so event paths match through symlinks
+    let teams_dir = canonical_or_raw(teams_dir().ok_or_else(no_home)?);
+    let tasks_dir = canonical_or_raw(tasks_dir().ok_or_else(no_home)?);
 
     // Create dirs if they don't exist (they might not exist yet)
     if !teams_dir.exists() {
-        tracing::info!("~/.claude/teams/ not found, team watcher will wait");
+        tracing::info!("{} not found, team watcher will wait", teams_dir.display());
     }
 
     std::thread::spawn(move || {
@@ -56,9 +63,15 @@ pub async fn start_team_watcher(app: AppHandle) -> Result<(), AppError> {
             }
         }
 
+        let mut team_list = TeamListTracker::new(teams_dir.clone());
         loop {
-            match rx.recv_timeout(Duration::from_secs(5)) {
+            team_list.flush(Instant::now(), |names| {
+                let _ = app.emit("teams:changed", names);
+            });
+            let timeout = team_list.next_timeout(Instant::now(), Duration::from_secs(5));
+            match rx.recv_timeout(timeout) {
                 Ok(event) => {
+                    team_list.observe(&event, Instant::now());
                     for path in &event.paths {
                         let path_str = path.to_string_lossy();
 
@@ -68,7 +81,7 @@ pub async fn start_team_watcher(app: AppHandle) -> Result<(), AppError> {
                         }
 
                         // Determine which team was affected
-                        let team_name = extract_team_name(&path_str);
+                        let team_name = extract_team_name(&path_str, &teams_dir, &tasks_dir);
                         if let Some(name) = team_name {
                             // Re-read the full team info and emit.
                             // Silently skip teams whose config was deleted.
@@ -93,40 +106,106 @@ pub async fn start_team_watcher(app: AppHandle) -> Result<(), AppError> {
     Ok(())
 }
 
-/// Extract team name from a path like ~/.claude/teams/{name}/config.json
-/// or ~/.claude/tasks/{name}/1.json
+/// `dir` with symlinks resolved, or as given if it doesn't exist (yet).
+fn canonical_or_raw(dir: PathBuf) -> PathBuf {
+    dir.canonicalize().unwrap_or(dir)
+}
+
+/// Names of the teams under `teams_dir` (dirs holding a config.json), sorted.
+fn team_names(teams_dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(teams_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join("config.json").is_file())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Debounces creation and deletion of teams into "teams:changed" emits
+/// carrying the new team list, skipping emits that wouldn't change it.
+struct TeamListTracker {
+    teams_dir: PathBuf,
+    last: Vec<String>,
+    /// When the pending change settles enough to re-list
+    due: Option<Instant>,
+}
+
+impl TeamListTracker {
+    fn new(teams_dir: PathBuf) -> Self {
+        let last = team_names(&teams_dir);
+        Self { teams_dir, last, due: None }
+    }
+
+    /// Note an event that may add or remove a team: a team dir itself, or a
+    /// config.json directly inside one, being created, removed or renamed.
+    fn observe(&mut self, event: &Event, now: Instant) {
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(_))
+        {
+            return;
+        }
+        let affects_list = event.paths.iter().any(|path| {
+            let parent = path.parent();
+            parent == Some(self.teams_dir.as_path())
+                || (path.file_name().is_some_and(|n| n == "config.json")
+                    && parent.and_then(Path::parent) == Some(self.teams_dir.as_path()))
+        });
+        if affects_list {
+            self.due = Some(now + TEAMS_CHANGED_DEBOUNCE);
+        }
+    }
+
+    /// How long to wait for the next event: at most `idle`, less while a
+    /// change is pending.
+    fn next_timeout(&self, now: Instant, idle: Duration) -> Duration {
+        self.due
+            .map(|due| due.saturating_duration_since(now).min(idle))
+            .unwrap_or(idle)
+    }
+
+    /// Re-list the teams once a pending change has settled, handing the list
+    /// to `emit` if it differs from the last one.
+    fn flush(&mut self, now: Instant, emit: impl FnOnce(&[String])) {
+        if self.due.is_none_or(|due| now < due) {
+            return;
+        }
+        self.due = None;
+        let names = team_names(&self.teams_dir);
+        if names != self.last {
+            emit(&names);
+            self.last = names;
+        }
+    }
+}
+
+/// Extract team name from a path like {teams_dir}/{name}/config.json
+/// or {tasks_dir}/{name}/1.json
 /// Works with both `/` (Unix) and `\` (Windows) separators.
-fn extract_team_name(path: &str) -> Option<String> {
+fn extract_team_name(path: &str, teams_dir: &Path, tasks_dir: &Path) -> Option<String> {
     // Normalize to forward slashes for consistent matching
-    let normalized = path.replace('\\', "/");
-    if let Some(idx) = normalized.find("/.claude/teams/") {
-        let after = &normalized[idx + "/.claude/teams/".len()..];
-        return after
-            .split('/')
-            .next()
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string());
-    }
-    if let Some(idx) = normalized.find("/.claude/tasks/") {
-        let after = &normalized[idx + "/.claude/tasks/".len()..];
-        return after
-            .split('/')
-            .next()
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string());
+    let normalize = |p: &str| p.replace('\\', "/").trim_end_matches('/').to_string();
+    let normalized = normalize(path);
+    for dir in [teams_dir, tasks_dir] {
+        let prefix = format!("{}/", normalize(&dir.to_string_lossy()));
+        if let Some(after) = normalized.strip_prefix(&prefix) {
+            return after
+                .split('/')
+                .next()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string());
+        }
     }
     None
 }
 
 /// Read team info for emitting events (same logic as commands::team but standalone)
 fn read_team_for_event(team_name: &str) -> Result<TeamInfo, AppError> {
-    let home = dirs::home_dir().ok_or_else(|| AppError::Internal("No home dir".into()))?;
+    let no_home = || AppError::Internal("No home dir".into());
+    let team_dir = teams_dir().ok_or_else(no_home)?.join(team_name);
 
-    let config_path = home
-        .join(".claude")
-        .join("teams")
-        .join(team_name)
-        .join("config.json");
+    let config_path = team_dir.join("config.json");
 
     if !config_path.exists() {
         return Err(AppError::NotFound(format!(
@@ -138,7 +217,7 @@ fn read_team_for_event(team_name: &str) -> Result<TeamInfo, AppError> {
     let config: TeamConfig = serde_json::from_str(&content)?;
 
     // Read tasks
-    let tasks_dir = home.join(".claude").join("tasks").join(team_name);
+    let tasks_dir = tasks_dir().ok_or_else(no_home)?.join(team_name);
     let mut tasks = Vec::new();
 
     if tasks_dir.exists() {
@@ -149,7 +228,7 @@ fn read_team_for_event(team_name: &str) -> Result<TeamInfo, AppError> {
                     continue;
                 }
                 if let Ok(content) = fs::read_to_string(&path) {
-                    if let Ok(task) = serde_json::from_str::<TeamTask>(&content) {
+                    if let Ok(task) = TeamTask::from_json(&content) {
                         tasks.push(task);
                     }
                 }
@@ -163,27 +242,8 @@ fn read_team_for_event(team_name: &str) -> Result<TeamInfo, AppError> {
         a_num.cmp(&b_num)
     });
 
-    let total = tasks.len();
-    let pending = tasks
-        .iter()
-        .filter(|t| t.status.as_deref() == Some("pending"))
-        .count();
-    let in_progress = tasks
-        .iter()
-        .filter(|t| {
-            t.status.as_deref() == Some("in_progress") || t.status.as_deref() == Some("in-progress")
-        })
-        .count();
-    let completed = tasks
-        .iter()
-        .filter(|t| t.status.as_deref() == Some("completed"))
-        .count();
-
-    let inboxes_dir = home
-        .join(".claude")
-        .join("teams")
-        .join(team_name)
-        .join("inboxes");
+    let task_summary = TaskSummary::from_tasks(&tasks);
+    let inboxes_dir = team_dir.join("inboxes");
 
     Ok(TeamInfo {
         name: config.name,
@@ -193,12 +253,72 @@ fn read_team_for_event(team_name: &str) -> Result<TeamInfo, AppError> {
         lead_session_id: config.lead_session_id,
         members: config.members,
         tasks,
-        task_summary: TaskSummary {
-            total,
-            pending,
-            in_progress,
-            completed,
-        },
+        task_summary,
         has_inboxes: inboxes_dir.exists(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, RemoveKind};
+
+    fn event(kind: EventKind, path: PathBuf) -> Event {
+        Event::new(kind).add_path(path)
+    }
+
+    #[test]
+    fn test_team_list_tracker_emits_on_team_create_and_delete() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let teams_dir = tmp.path().join("teams");
+        fs::create_dir_all(teams_dir.join("existing")).unwrap();
+        fs::write(teams_dir.join("existing").join("config.json"), "{}").unwrap();
+        let mut tracker = TeamListTracker::new(teams_dir.clone());
+        let mut emitted: Vec<Vec<String>> = Vec::new();
+        let start = Instant::now();
+        let settled = start + TEAMS_CHANGED_DEBOUNCE;
+
+        let team = teams_dir.join("alpha");
+        fs::create_dir_all(&team).unwrap();
+        fs::write(team.join("config.json"), "{}").unwrap();
+        tracker.observe(&event(EventKind::Create(CreateKind::Folder), team.clone()), start);
+        tracker.observe(
+            &event(EventKind::Create(CreateKind::File), team.join("config.json")),
+            start,
+        );
+        tracker.flush(start, |names| emitted.push(names.to_vec()));
+        assert!(emitted.is_empty(), "debounced until the change settles");
+        tracker.flush(settled, |names| emitted.push(names.to_vec()));
+        assert_eq!(emitted, [vec!["alpha".to_string(), "existing".to_string()]]);
+
+        // Task files and in-place edits leave the list alone
+        let inbox = team.join("inboxes").join("lead.json");
+        tracker.observe(&event(EventKind::Create(CreateKind::File), inbox), settled);
+        assert_eq!(tracker.next_timeout(settled, Duration::from_secs(5)), Duration::from_secs(5));
+
+        fs::remove_dir_all(&team).unwrap();
+        tracker.observe(&event(EventKind::Remove(RemoveKind::Folder), team), settled);
+        tracker.flush(settled + TEAMS_CHANGED_DEBOUNCE, |names| emitted.push(names.to_vec()));
+        assert_eq!(emitted.len(), 2);
+        assert_eq!(emitted[1], ["existing"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_canonical_teams_dir_matches_events_through_symlink() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let real = tmp.path().join("real-claude").join("teams");
+        fs::create_dir_all(&real).unwrap();
+        let link = tmp.path().join("claude");
+        std::os::unix::fs::symlink(tmp.path().join("real-claude"), &link).unwrap();
+
+        let teams_dir = canonical_or_raw(link.join("teams"));
+        assert_eq!(teams_dir, real.canonicalize().unwrap());
+        let event_path = real.canonicalize().unwrap().join("alpha").join("config.json");
+        let name = extract_team_name(&event_path.to_string_lossy(), &teams_dir, Path::new("/x"));
+        assert_eq!(name.as_deref(), Some("alpha"));
+
+        let missing = tmp.path().join("missing");
+        assert_eq!(canonical_or_raw(missing.clone()), missing);
+    }
+}
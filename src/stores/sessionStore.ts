@@ -50,19 +50,22 @@ export const useSessionStore = create<SessionState>((set) => ({
         try {
             // Surgical upsert on session:updated
             await listen<SessionListItem>("session:updated", (event) => {
-                const updated = event.payload;
-                set((state) => {
-                    const idx = state.sessions.findIndex(
-                        (s) => s.session_id === updated.session_id,
-                    );
-                    if (idx >= 0) {
-                        const sessions = [...state.sessions];
-                        sessions[idx] = updated;
-                        return { sessions };
-                    } else {
-                        return { sessions: [updated, ...state.sessions] };
-                    }
-                });
+                set((state) => ({
+                    sessions: upsertSessions(state.sessions, [event.payload]),
+                }));
+            });
+
+            // Coalesced updates (SWARM_UI_BATCH_UPDATES=1): one render per batch
+            await listen<SessionListItem[]>("session:batch_updated", (event) => {
+                set((state) => ({
+                    sessions: upsertSessions(state.sessions, event.payload),
+                }));
+            });
+
+            // Full resync: the snapshot replaces the list, dropping sessions
+            // deleted or archived since the last one
+            await listen<SessionListItem[]>("session:snapshot", (event) => {
+                set({ sessions: event.payload });
             });
 
             // Index progress events
@@ -75,3 +78,20 @@ export const useSessionStore = create<SessionState>((set) => ({
         }
     },
 }));
+
+/** Replace sessions in place by id; unseen ones are prepended. */
+function upsertSessions(
+    sessions: SessionListItem[],
+    updates: SessionListItem[],
+): SessionListItem[] {
+    const next = [...sessions];
+    for (const updated of updates) {
+        const idx = next.findIndex((s) => s.session_id === updated.session_id);
+        if (idx >= 0) {
+            next[idx] = updated;
+        } else {
+            next.unshift(updated);
+        }
+    }
+    return next;
+}
@@ -2,6 +2,7 @@ import { create } from "zustand";
 import { invoke, Channel } from "@tauri-apps/api/core";
 import type {
     SpriteInfo,
+    SpriteLocalMetadata,
     SpriteDetail,
     Checkpoint,
     ExecSession,
@@ -41,9 +42,12 @@ interface SpriteState {
     createSprite: (name: string) => Promise<void>;
     updateSprite: (name: string, urlAuth: string) => Promise<void>;
     deleteSprite: (name: string) => Promise<void>;
+    /** Save a sprite's local label/color/notes and merge them into `sprites` */
+    setSpriteMetadata: (name: string, metadata: SpriteLocalMetadata) => Promise<void>;
 
     // ── Exec ──────────────────────────────────────────────────
-    execOnSprite: (name: string, command: string) => Promise<string>;
+    /** Rejects on a non-zero exit code unless errorOnFailure is false */
+    execOnSprite: (name: string, command: string, errorOnFailure?: boolean) => Promise<string>;
     listExecSessions: (name: string) => Promise<void>;
     killExecSession: (
         name: string,
@@ -122,6 +126,19 @@ export const useSpriteStore = create<SpriteState>((set, get) => ({
         }
     },
 
+    setSpriteMetadata: async (name: string, metadata: SpriteLocalMetadata) => {
+        const saved = await invoke<SpriteLocalMetadata>("sprite_set_metadata", {
+            name,
+            ...metadata,
+        });
+        const empty = !saved.label && !saved.color && !saved.notes;
+        set((s) => ({
+            sprites: s.sprites.map((sp) =>
+                sp.name === name ? { ...sp, local: empty ? undefined : saved } : sp,
+            ),
+        }));
+    },
+
     createSprite: async (name: string) => {
         const sprite = await invoke<SpriteInfo>("sprite_create", { name });
         set((s) => ({ sprites: [...s.sprites, sprite] }));
@@ -150,8 +167,8 @@ export const useSpriteStore = create<SpriteState>((set, get) => ({
 
     // ── Exec ──────────────────────────────────────────────────────────
 
-    execOnSprite: async (name: string, command: string) => {
-        return await invoke<string>("sprite_exec", { name, command });
+    execOnSprite: async (name: string, command: string, errorOnFailure?: boolean) => {
+        return await invoke<string>("sprite_exec", { name, command, errorOnFailure });
     },
 
     listExecSessions: async (name: string) => {
@@ -1,7 +1,7 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import type { TeamInfo } from "../types/team";
+import type { OrphanTaskDir, TeamInfo } from "../types/team";
 
 interface TeamState {
     teams: TeamInfo[];
@@ -12,6 +12,18 @@ interface TeamState {
     fetchTeams: () => Promise<void>;
     selectTeam: (name: string | null) => void;
     startWatcher: () => Promise<void>;
+    /** Copy source's tasks into dest; resolves to old id -> new id */
+    mergeTeamTasks: (
+        sourceTeam: string,
+        destTeam: string,
+        renumber: boolean,
+    ) => Promise<Record<string, string>>;
+    /** Write config + tasks to destPath as one JSON bundle */
+    exportTeam: (teamName: string, destPath: string) => Promise<void>;
+    /** Recreate a team from an exported bundle, optionally renamed */
+    importTeam: (bundlePath: string, newName?: string) => Promise<TeamInfo>;
+    /** Task dirs without a team config; removed too when `remove` is set */
+    findOrphanTasks: (remove: boolean) => Promise<OrphanTaskDir[]>;
 }
 
 export const useTeamStore = create<TeamState>((set) => ({
@@ -61,4 +73,32 @@ export const useTeamStore = create<TeamState>((set) => ({
             console.error("Failed to start team watcher:", e);
         }
     },
+
+    mergeTeamTasks: async (sourceTeam, destTeam, renumber) => {
+        // dest's refreshed TeamInfo arrives via team:updated
+        return invoke<Record<string, string>>("merge_team_tasks", {
+            sourceTeam,
+            destTeam,
+            renumber,
+        });
+    },
+
+    exportTeam: async (teamName, destPath) => {
+        await invoke("export_team", { teamName, destPath });
+    },
+
+    importTeam: async (bundlePath, newName) => {
+        const team = await invoke<TeamInfo>("import_team", {
+            bundlePath,
+            newName: newName ?? null,
+        });
+        set((state) => ({
+            teams: [team, ...state.teams.filter((t) => t.name !== team.name)],
+        }));
+        return team;
+    },
+
+    findOrphanTasks: async (remove) => {
+        return invoke<OrphanTaskDir[]>("find_orphan_tasks", { delete: remove });
+    },
 }));
@@ -19,6 +19,9 @@ interface TerminalState {
     writeToTerminal: (id: string, data: string) => Promise<void>;
     resizeTerminal: (id: string, cols: number, rows: number) => Promise<void>;
     isSpriteTerminal: (id: string) => boolean;
+    /** Tee a local terminal's raw output to logPath (under $HOME, appended) */
+    startLogging: (id: string, logPath: string) => Promise<void>;
+    stopLogging: (id: string) => Promise<void>;
 }
 
 export const useTerminalStore = create<TerminalState>((set, get) => ({
@@ -105,4 +108,12 @@ export const useTerminalStore = create<TerminalState>((set, get) => ({
     isSpriteTerminal: (id: string) => {
         return get().spriteTerminals.has(id);
     },
+
+    startLogging: async (id: string, logPath: string) => {
+        await invoke("pty_start_logging", { id, logPath });
+    },
+
+    stopLogging: async (id: string) => {
+        await invoke("pty_stop_logging", { id });
+    },
 }));
@@ -6,6 +6,8 @@ export interface SessionListItem {
     project_path: string;
     summary: string;
     first_prompt: string;
+    /** First assistant text block, truncated to 500 chars */
+    first_reply: string;
     git_branch: string;
     model: string;
     status: string;
@@ -28,14 +30,25 @@ export interface SearchResult {
     model?: string;
     modified_at?: string;
     file_exists: boolean;
+    /** Turn of the top-scoring matched block, for deep-linking */
+    best_turn_index?: number;
 }
 
+/** search_sessions_stream channel event; "done", "cancelled" or "error" is always last */
+export type SearchStreamEvent =
+    | { type: "result"; result: SearchResult }
+    | { type: "done"; count: number }
+    | { type: "cancelled" }
+    | { type: "error"; message: string };
+
 export interface MatchSnippet {
     role: string;
     content_type: string;
     snippet: string;
     timestamp?: string;
     turn_index: number;
+    /** BM25 score of this block */
+    score: number;
 }
 
 /** get_conversation response */
@@ -45,6 +58,133 @@ export interface ConversationMessage {
     text: string;
     timestamp?: string;
     truncated: boolean;
+    /** Shared by a tool_use and its tool_result */
+    tool_use_id?: string;
+    /** JSONL entry uuid, shared by all blocks of that entry */
+    uuid?: string;
+    /** uuid of the parent message in the thread; absent for roots */
+    parent_id?: string;
+    /** Usage of the assistant response, on its first block; absent when rebuilt from the index */
+    usage?: MessageUsage;
+}
+
+/** JSONL usage object of one assistant response */
+export interface MessageUsage {
+    input_tokens?: number;
+    output_tokens?: number;
+    cache_creation_input_tokens?: number;
+    cache_read_input_tokens?: number;
+}
+
+/** One get_conversation_raw_messages entry, Anthropic Messages API shaped */
+export interface RawMessage {
+    role: "user" | "assistant";
+    content: Array<{ type: string; [key: string]: unknown }>;
+}
+
+/** get_last_activity response (null on an empty index) */
+export interface LastActivity {
+    session_id: string;
+    modified_at: string;
+}
+
+/** get_dashboard_summary response (cached briefly, refreshed after index writes) */
+export interface DashboardSummary {
+    /** Archived sessions included */
+    total_sessions: number;
+    /** Of sessions created in the last 7 days, archived included */
+    tokens_this_week: number;
+    /** Most non-archived sessions */
+    top_project?: string;
+    /** Most non-archived sessions */
+    top_model?: string;
+    last_activity?: LastActivity;
+}
+
+/** get_message response */
+export interface MessageRecord {
+    message_id: string;
+    session_id: string;
+    role: string;
+    content_type: string;
+    text: string;
+    timestamp?: string;
+    turn_index: number;
+    block_index: number;
+}
+
+/** get_context_fill response */
+export interface ContextFill {
+    context_tokens: number;
+    /** Absent for models missing from the built-in table */
+    window_size?: number;
+    /** context_tokens / window_size; absent when the window is unknown */
+    fill_ratio?: number;
+    model: string;
+}
+
+/** get_session_message_breakdown response: block counts by role and by type */
+export interface SessionMessageBreakdown {
+    user: number;
+    assistant: number;
+    tool_use: number;
+    tool_result: number;
+    thinking: number;
+    text: number;
+}
+
+/** get_daily_token_spend response entry (UTC day, oldest first) */
+export interface DailySpend {
+    day: string;
+    total_tokens: number;
+    estimated_cost: number;
+}
+
+/** find_duplicate_sessions response entry; sessions are newest first */
+export interface DuplicateCluster {
+    project_path: string;
+    first_prompt: string;
+    sessions: Array<{
+        session_id: string;
+        message_count: number;
+        modified_at?: string;
+    }>;
+}
+
+/** find_sessions_with_gaps response entry, longest pause first */
+export interface SessionGap {
+    session_id: string;
+    project_path: string;
+    summary: string;
+    max_gap_seconds: number;
+    /** RFC 3339 timestamp of the message the pause followed */
+    gap_started_at: string;
+}
+
+/** get_background_health response; timestamps are RFC 3339 */
+export interface BackgroundHealth {
+    watcher_alive: boolean;
+    merge_thread_alive: boolean;
+    /** set only while a bulk index runs */
+    bulk_index_alive?: boolean;
+    last_merge_commit?: string;
+    last_watcher_event?: string;
+}
+
+/** repair_index_meta response (the rewritten swarm-ui-meta.json) */
+export interface IndexMeta {
+    schema_version: number;
+    indexed_at: string;
+    session_count: number;
+    /** JSONL throughput of the last full index */
+    bytes_per_sec?: number;
+}
+
+/** estimate_reindex_time response */
+export interface ReindexEstimate {
+    file_count: number;
+    total_bytes: number;
+    estimated_seconds: number;
 }
 
 /** get_index_stats response */
@@ -55,6 +195,60 @@ export interface IndexStats {
     total_messages: number;
     segment_count: number;
     index_size_bytes: number;
+    is_stale: boolean;
+    newest_on_disk?: string;
+    /** Watcher backing incremental indexing; absent until it starts */
+    watcher_mode?: "native" | "poll";
+}
+
+/** get_index_size_breakdown entry */
+export interface IndexSizeComponent {
+    kind: string;
+    bytes: number;
+}
+
+/** diagnose_session_file response */
+export interface SessionFileDiagnosis {
+    /** False when no entry has a sessionId and the file stem stands in */
+    session_id_found: boolean;
+    message_doc_count: number;
+    /** Non-blank lines */
+    lines_total: number;
+    /** Unreadable or malformed lines, dropped by the indexer */
+    lines_skipped: number;
+    /** First skipped line, as `line N: error` */
+    first_error?: string;
+}
+
+/** prune_old_messages response */
+export interface PruneReport {
+    sessions_pruned: number;
+    messages_removed: number;
+    /** Index size before pruning minus after compaction */
+    bytes_reclaimed: number;
+}
+
+/** prune_old_messages_preview response */
+export interface PrunePreview {
+    /** sorted by session_id */
+    sessions: AffectedSession[];
+    messages_removed: number;
+    /** Unix seconds; pass back to prune_old_messages to prune exactly these */
+    cutoff: number;
+}
+
+/** archive_older_than_preview response */
+export interface ArchivePreview {
+    sessions: AffectedSession[];
+    /** Unix seconds; pass back to archive_older_than to archive exactly these */
+    cutoff: number;
+}
+
+/** archive_project_preview / archive_older_than_preview entry, by session_id */
+export interface AffectedSession {
+    session_id: string;
+    summary: string;
+    project_path: string;
 }
 
 /** index:progress event payload */
@@ -67,19 +261,56 @@ export interface IndexProgress {
 /** list_sessions filter */
 export interface SessionFilter {
     project?: string;
+    /** Glob over project paths, e.g. "/home/me/frontend-*" */
+    project_glob?: string;
     git_branch?: string;
     model?: string;
     include_archived: boolean;
+    /** Only sessions modified in the last N days */
+    modified_within_days?: number;
+    /** [timestamp, window_seconds]: last modified within the window either side */
+    around?: [string, number];
+    /** e.g. "+09:00": offset an `around` timestamp without one is local to (UTC if absent) */
+    utc_offset?: string;
 }
 
 /** search_sessions filter */
 export interface SearchFilter {
     project?: string;
+    /** Glob over project paths, e.g. "/home/me/frontend-*" */
+    project_glob?: string;
+    git_branch?: string;
     include_tool_output: boolean;
     limit?: number;
     date_from?: string;
     date_to?: string;
+    /** Offset date-only bounds are local to, e.g. "+09:00" (default UTC) */
+    utc_offset?: string;
     role?: string;
+    /** Restrict to one block type: "text", "tool_use", "tool_result", "thinking" */
+    content_type?: string;
+    /** Substring of a tool_use block's input JSON, e.g. a file path */
+    tool_input_contains?: string;
+    /** Only match assistant thinking blocks */
+    thinking_turns_only?: boolean;
+    /** Half-life in days for decaying scores of older sessions */
+    recency_boost?: number;
+    /** Literal, case-sensitive substring match instead of BM25 (slower) */
+    exact_substring?: boolean;
+    /** BM25 that keeps case, so "AWS" doesn't match "aws" */
+    case_sensitive?: boolean;
+    /** Only tool_result blocks matching an error signature; query may be empty */
+    errors_only?: boolean;
+    /** Overrides the default error signatures, e.g. ["Traceback", "exit code"] */
+    error_signatures?: string[];
+    /** One snippet per session: the highest-scoring block, not the first three */
+    best_snippet_only?: boolean;
+    /** [timestamp, window_seconds]: messages within the window either side */
+    around?: [string, number];
+    /** Hits per result in the first round (default 3, or 10 with post-filters; 1-50) */
+    overfetch_multiplier?: number;
+    /** Only messages of these sessions; an empty list matches nothing */
+    session_ids?: string[];
 }
 
 // --- Status helpers (status is a plain string from Tantivy) ---
@@ -5,6 +5,15 @@ export interface SpriteInfo {
     status: "cold" | "warm" | "running" | string;
     id?: string;
     region?: string;
+    /** User-local label/color/notes (sprite_set_metadata) */
+    local?: SpriteLocalMetadata;
+}
+
+/** Stored locally per sprite name, never sent to the Sprites API */
+export interface SpriteLocalMetadata {
+    label?: string;
+    color?: string;
+    notes?: string;
 }
 
 export interface SpriteDetail {
@@ -20,6 +29,31 @@ export interface SpriteDetail {
     last_active_at?: string;
 }
 
+/** normalize_sprites_base_url response */
+export interface NormalizedBaseUrl {
+    /** scheme://host[:port][/path], without a trailing slash or /v1 */
+    url: string;
+    /** What was changed, e.g. "Removed /v1 (added to every request)" */
+    fixups: string[];
+}
+
+/** sprite_get_url response; errors when the sprite has no URL */
+export interface SpriteUrl {
+    url?: string;
+    /** url_settings.auth, "sprite" when unset */
+    auth: "sprite" | "public" | string;
+    public: boolean;
+}
+
+/** sprite_get_metrics response; null fields mean the tool output was unavailable */
+export interface SpriteMetrics {
+    cpu_percent: number | null;
+    mem_used_mb: number | null;
+    mem_total_mb: number | null;
+    disk_used_gb: number | null;
+    disk_total_gb: number | null;
+}
+
 // --- Checkpoint types ---
 
 export interface Checkpoint {
@@ -28,6 +62,14 @@ export interface Checkpoint {
     created_at?: string;
     create_time?: string;
     source_id?: string;
+    size_bytes?: number;
+}
+
+/** sprite_list_checkpoints_detailed entry (newest first) */
+export interface CheckpointDetail extends Checkpoint {
+    /** RFC 3339 UTC, from created_at or create_time */
+    created?: string;
+    size_human?: string;
 }
 
 // --- Exec session types ---
@@ -43,6 +85,13 @@ export interface ExecSession {
     bytes_per_second?: number;
 }
 
+/** sprite_watch_exec_sessions channel events (diffs against the previous poll) */
+export type ExecSessionEvent =
+    | { type: "started" | "changed"; session: ExecSession }
+    | { type: "ended"; id: string }
+    | { type: "error"; message: string }
+    | { type: "heartbeat" };
+
 // --- Service types ---
 
 export interface ServiceState {
@@ -87,6 +136,14 @@ export interface ServiceStreamEvent {
     log_files?: Record<string, string>;
 }
 
+/** sprite_read_exec_session channel events */
+export interface ExecOutputEvent {
+    type: "stdout" | "stderr" | "exit" | "error";
+    data?: string;
+    exit_code?: number;
+    timestamp?: number;
+}
+
 export interface ExecKillEvent {
     type: "signal" | "timeout" | "exited" | "killed" | "error" | "complete";
     message?: string;
@@ -106,7 +163,12 @@ export interface OperationState {
 // --- Introspection types (existing, used for ps aux / file scanning) ---
 
 export interface SpriteSessionInfo {
-    pid: string;
+    user: string;
+    pid: number;
+    cpu: number;
+    mem: number;
+    start: string;
+    time: string;
     command: string;
     status: string;
 }
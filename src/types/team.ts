@@ -17,13 +17,25 @@ export interface TeamTask {
     status?: string;
     blocks: string[];
     blockedBy: string[];
+    /** `status` normalized; any spelling the backend doesn't know is "unknown" */
+    normalizedStatus: TaskStatus;
 }
 
+export type TaskStatus =
+    | "pending"
+    | "in_progress"
+    | "completed"
+    | "blocked"
+    | "cancelled"
+    | "unknown";
+
 export interface TaskSummary {
     total: number;
     pending: number;
     in_progress: number;
     completed: number;
+    blocked: number;
+    cancelled: number;
 }
 
 export interface TeamInfo {
@@ -38,6 +50,14 @@ export interface TeamInfo {
     hasInboxes: boolean;
 }
 
+/** find_orphan_tasks entry: a task dir whose team config is gone */
+export interface OrphanTaskDir {
+    name: string;
+    taskCount: number;
+    bytes: number;
+    deleted: boolean;
+}
+
 export function taskStatusColor(status?: string): string {
     switch (status) {
         case "completed":
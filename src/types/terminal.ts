@@ -28,3 +28,13 @@ export interface PaneConfig {
   sessionId?: string;
   spriteName?: string;
 }
+
+/** list_all_terminals entry: a local PTY or a sprite WebSocket session */
+export type TerminalInfo = {
+  id: string;
+  cols: number;
+  rows: number;
+} & (
+  | { kind: "Local"; pid: number; cwd: string | null }
+  | { kind: "Remote"; sprite_name: string }
+);